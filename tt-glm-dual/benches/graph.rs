@@ -0,0 +1,107 @@
+//! Benchmarks for the graph engine on generated 10k and 100k task databases.
+//!
+//! Run with `cargo bench --bench graph`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use tempfile::NamedTempFile;
+use tt::core::db::{Db, TaskSet};
+use tt::core::graph::TaskGraph;
+use tt::core::task::TaskManager;
+
+const SIZES: [i64; 2] = [10_000, 100_000];
+
+/// Create `n` tasks where each task depends on the previous one and on up to
+/// two earlier tasks, every tenth task is the child of a later epic, and the
+/// first third is completed. The last task is the target.
+fn generate(n: i64) -> (TaskManager, NamedTempFile) {
+    let temp = NamedTempFile::new().unwrap();
+    let mut db = Db::open(temp.path()).unwrap();
+    db.init_schema().unwrap();
+
+    let tx = db.transaction().unwrap();
+    {
+        let now = Db::now();
+        let mut task = tx
+            .prepare(
+                "INSERT INTO tasks (id, title, status, manual_order, created_at, last_touched_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?5)",
+            )
+            .unwrap();
+        let mut dependency = tx
+            .prepare("INSERT OR IGNORE INTO dependencies (task_id, depends_on) VALUES (?1, ?2)")
+            .unwrap();
+
+        // Deterministic pseudo-random numbers (64-bit LCG)
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = |bound: i64| {
+            seed = seed
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            ((seed >> 33) % bound as u64) as i64
+        };
+
+        for id in 1..=n {
+            let status = if id <= n / 3 { "completed" } else { "pending" };
+            // Shuffle manual order locally so the heap has work to do
+            let order = (id * 10 + next(50) - 25) as f64;
+            task.execute((id, format!("Task {id}"), status, order, now.as_str()))
+                .unwrap();
+            if id > 1 {
+                dependency.execute((id, id - 1)).unwrap();
+            }
+            for _ in 0..2 {
+                if id > 2 {
+                    let depends_on = id - 2 - next((id - 2).min(1000));
+                    dependency.execute((id, depends_on)).unwrap();
+                }
+            }
+        }
+        tx.execute(
+            "UPDATE tasks SET parent_id = id + 5 WHERE id % 10 = 0 AND id + 5 <= ?1",
+            [n],
+        )
+        .unwrap();
+    }
+    tx.commit().unwrap();
+
+    let mut mgr = TaskManager::new(db);
+    mgr.set_target(n).unwrap();
+    (mgr, temp)
+}
+
+fn bench_graph(c: &mut Criterion) {
+    let mut group = c.benchmark_group("graph");
+    group.sample_size(10);
+
+    for n in SIZES {
+        let (mgr, _temp) = generate(n);
+
+        group.bench_with_input(BenchmarkId::new("load", n), &n, |b, &n| {
+            b.iter(|| TaskGraph::load_incomplete(mgr.db(), TaskSet::Subgraph(n)).unwrap())
+        });
+
+        let graph = TaskGraph::load_incomplete(mgr.db(), TaskSet::Subgraph(n)).unwrap();
+        group.bench_with_input(
+            BenchmarkId::new("topological_sort", n),
+            &graph,
+            |b, graph| b.iter(|| graph.topological_sort().unwrap()),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("order_conflicts", n),
+            &graph,
+            |b, graph| b.iter(|| graph.order_conflicts()),
+        );
+
+        group.bench_function(BenchmarkId::new("next", n), |b| {
+            b.iter(|| mgr.next_task().unwrap())
+        });
+        group.bench_function(BenchmarkId::new("list", n), |b| {
+            b.iter(|| mgr.list_tasks(false).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_graph);
+criterion_main!(benches);
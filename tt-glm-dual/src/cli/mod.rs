@@ -37,6 +37,9 @@ enum Command {
         /// Position before this task ID
         #[arg(long)]
         before: Option<i64>,
+        /// Parent task (epic) ID
+        #[arg(long)]
+        parent: Option<i64>,
     },
 
     /// Edit a task
@@ -52,6 +55,12 @@ enum Command {
         /// New Definition of Done
         #[arg(long)]
         dod: Option<String>,
+        /// New parent task (epic) ID
+        #[arg(long, conflicts_with = "no_parent")]
+        parent: Option<i64>,
+        /// Detach the task from its parent
+        #[arg(long)]
+        no_parent: bool,
     },
 
     /// Show task details
@@ -67,6 +76,12 @@ enum Command {
         all: bool,
     },
 
+    /// Show the parent/child hierarchy with rolled-up progress
+    Tree {
+        /// Root task ID (defaults to all top-level tasks)
+        id: Option<i64>,
+    },
+
     /// Set the target task
     Target {
         /// Task ID
@@ -150,8 +165,45 @@ enum Command {
     /// Reindex all task orders
     Reindex,
 
+    /// Manage projects
+    Project {
+        #[command(subcommand)]
+        action: ProjectCommand,
+    },
+
     /// Start MCP server
-    Mcp,
+    Mcp {
+        /// Only expose tools that do not modify the database
+        #[arg(long, conflicts_with = "tools")]
+        read_only: bool,
+        /// Only expose these tools (comma-separated tool names or commands, e.g. next,current,log_artifact)
+        #[arg(long, value_name = "TOOLS")]
+        tools: Option<String>,
+        /// Work in this project for the whole session instead of the active one
+        #[arg(long, env = "TT_PROJECT", value_name = "NAME")]
+        project: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ProjectCommand {
+    /// Create a project
+    Create {
+        /// Project name
+        name: String,
+        /// Also make it the active project
+        #[arg(long = "use")]
+        use_it: bool,
+    },
+
+    /// Make a project active
+    Use {
+        /// Project name
+        name: String,
+    },
+
+    /// List projects, marking the active one
+    List,
 }
 
 /// Get the database path.
@@ -210,9 +262,16 @@ pub fn run() -> Result<()> {
             dod,
             after,
             before,
+            parent,
         } => {
             let mut mgr = open_manager()?;
+            if let Some(parent_id) = parent {
+                mgr.db().get_task(parent_id)?; // Verify parent exists before creating
+            }
             let id = mgr.add_task(&title, desc.as_deref(), dod.as_deref(), after, before)?;
+            if parent.is_some() {
+                mgr.set_parent(id, parent)?;
+            }
             println!("{}", id);
         }
 
@@ -221,9 +280,18 @@ pub fn run() -> Result<()> {
             title,
             desc,
             dod,
+            parent,
+            no_parent,
         } => {
             let mut mgr = open_manager()?;
+            let change_parent = parent.is_some() || no_parent;
+            if change_parent {
+                mgr.check_parent(id, parent)?; // Fail before editing anything
+            }
             mgr.edit_task(id, title.as_deref(), desc.as_deref(), dod.as_deref())?;
+            if change_parent {
+                mgr.set_parent(id, parent)?;
+            }
             println!("Task #{} updated", id);
         }
 
@@ -245,6 +313,12 @@ pub fn run() -> Result<()> {
             }
         }
 
+        Command::Tree { id } => {
+            let mgr = open_manager()?;
+            let tree = mgr.tree(id)?;
+            output::format_tree(&tree);
+        }
+
         Command::Target { id } => {
             let mut mgr = open_manager()?;
             mgr.set_target(id)?;
@@ -337,8 +411,40 @@ pub fn run() -> Result<()> {
             println!("Reindexed all task orders");
         }
 
-        Command::Mcp => {
-            return crate::run_mcp();
+        Command::Project { action } => {
+            let mut mgr = open_manager()?;
+            match action {
+                ProjectCommand::Create { name, use_it } => {
+                    let project = mgr.create_project(&name)?;
+                    if use_it {
+                        mgr.use_project(&project.name)?;
+                        println!("Created and switched to project '{}'", project.name);
+                    } else {
+                        println!("Created project '{}'", project.name);
+                    }
+                }
+                ProjectCommand::Use { name } => {
+                    let project = mgr.use_project(&name)?;
+                    println!("Switched to project '{}'", project.name);
+                }
+                ProjectCommand::List => {
+                    let active = mgr.current_project()?;
+                    output::format_projects(&mgr.list_projects()?, active.id);
+                }
+            }
+        }
+
+        Command::Mcp {
+            read_only,
+            tools,
+            project,
+        } => {
+            let tools = match tools {
+                Some(list) => crate::ToolSet::parse(&list)?,
+                None if read_only => crate::ToolSet::read_only(),
+                None => crate::ToolSet::all(),
+            };
+            return crate::run_mcp(tools, project.as_deref());
         }
     }
 
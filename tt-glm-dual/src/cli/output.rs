@@ -1,7 +1,9 @@
 //! Output formatting for the CLI.
 
-use crate::core::db::{Artifact, Db, Task, TaskWithDeps};
+use crate::core::db::{Artifact, Db, Progress, Project, Task, TaskWithDeps};
 use crate::core::error::TTError;
+use crate::core::task::TreeNode;
+use std::collections::HashMap;
 
 /// Format a single task for display.
 fn format_task_id(id: i64) -> String {
@@ -32,14 +34,18 @@ pub fn format_list(tasks: &[TaskWithDeps], target_id: i64) {
     println!("Target: {}", format_task_with_status(&target_task.task));
     println!();
 
+    let status: HashMap<i64, &str> = tasks
+        .iter()
+        .map(|t| (t.task.id, t.task.status.as_str()))
+        .collect();
     for task_with_deps in tasks {
         let task = &task_with_deps.task;
         let deps_str = if !task_with_deps.dependencies.is_empty() {
             let parts: Vec<String> = task_with_deps
                 .dependencies
                 .iter()
-                .map(|&id| match tasks.iter().find(|t| t.task.id == id) {
-                    Some(t) => format!("{id} {}", format_status(&t.task.status)),
+                .map(|&id| match status.get(&id) {
+                    Some(status) => format!("{id} {}", format_status(status)),
                     None => format!("{id} ?"),
                 })
                 .collect();
@@ -60,33 +66,125 @@ pub fn format_list(tasks: &[TaskWithDeps], target_id: i64) {
     println!("Legend: ✓ completed  ● in_progress  ○ pending  ✗ blocked");
 }
 
-/// Format the list all output.
+/// Format the list all output, with tasks grouped under their epics.
 pub fn format_list_all(tasks: &[TaskWithDeps]) {
+    let by_id: HashMap<i64, &TaskWithDeps> = tasks.iter().map(|t| (t.task.id, t)).collect();
+
     for task_with_deps in tasks {
-        let task = &task_with_deps.task;
-        let deps_str = if !task_with_deps.dependencies.is_empty() {
-            let parts: Vec<String> = task_with_deps
-                .dependencies
-                .iter()
-                .map(|&id| format!("{id}"))
-                .collect();
-            format!("(deps: {})", parts.join(", "))
-        } else {
-            String::new()
+        let is_root = match task_with_deps.task.parent_id {
+            Some(parent_id) => !by_id.contains_key(&parent_id),
+            None => true,
         };
+        if is_root {
+            format_list_all_entry(task_with_deps, &by_id, 0);
+        }
+    }
 
-        println!(
-            "[{}] {} {} {deps_str:<30}",
-            format_task_id(task.id),
-            format_status(&task.status),
-            task.title,
-        );
+    println!();
+    println!("Legend: ✓ completed  ● in_progress  ○ pending  ✗ blocked");
+}
+
+/// Print one `list --all` entry followed by its children.
+fn format_list_all_entry(
+    task_with_deps: &TaskWithDeps,
+    by_id: &HashMap<i64, &TaskWithDeps>,
+    depth: usize,
+) {
+    let task = &task_with_deps.task;
+    let deps_str = if !task_with_deps.dependencies.is_empty() {
+        let parts: Vec<String> = task_with_deps
+            .dependencies
+            .iter()
+            .map(|&id| format!("{id}"))
+            .collect();
+        format!("(deps: {})", parts.join(", "))
+    } else {
+        String::new()
+    };
+    let progress_str = match list_progress(task.id, by_id) {
+        Some(progress) => format!("{} ", format_progress(&progress)),
+        None => String::new(),
+    };
+
+    println!(
+        "{}[{}] {} {} {progress_str}{deps_str:<30}",
+        "  ".repeat(depth),
+        format_task_id(task.id),
+        format_status(&task.status),
+        task.title,
+    );
+
+    for child_id in &task_with_deps.children {
+        if let Some(child) = by_id.get(child_id) {
+            format_list_all_entry(child, by_id, depth + 1);
+        }
+    }
+}
+
+/// Roll up progress over the descendants present in the listing.
+fn list_progress(id: i64, by_id: &HashMap<i64, &TaskWithDeps>) -> Option<Progress> {
+    let children = &by_id.get(&id)?.children;
+    if children.is_empty() {
+        return None;
+    }
+
+    let mut progress = Progress {
+        completed: 0,
+        total: 0,
+    };
+    let mut stack = children.clone();
+    while let Some(child_id) = stack.pop() {
+        if let Some(child) = by_id.get(&child_id) {
+            progress.total += 1;
+            if child.task.status == "completed" {
+                progress.completed += 1;
+            }
+            stack.extend(&child.children);
+        }
+    }
+    Some(progress)
+}
+
+/// Format rolled-up progress as `[done/total]`.
+fn format_progress(progress: &Progress) -> String {
+    format!("[{}/{}]", progress.completed, progress.total)
+}
+
+/// Format the tree output.
+pub fn format_tree(nodes: &[TreeNode]) {
+    if nodes.is_empty() {
+        println!("No tasks");
+        return;
+    }
+
+    for node in nodes {
+        format_tree_node(node, 0);
     }
 
     println!();
     println!("Legend: ✓ completed  ● in_progress  ○ pending  ✗ blocked");
 }
 
+fn format_tree_node(node: &TreeNode, depth: usize) {
+    let task = &node.task;
+    let progress_str = match &node.progress {
+        Some(progress) => format!(" {}", format_progress(progress)),
+        None => String::new(),
+    };
+
+    println!(
+        "{}[{}] {} {}{progress_str}",
+        "  ".repeat(depth),
+        format_task_id(task.id),
+        format_status(&task.status),
+        task.title,
+    );
+
+    for child in &node.children {
+        format_tree_node(child, depth + 1);
+    }
+}
+
 /// Format task with status.
 fn format_task_with_status(task: &Task) -> String {
     format!(
@@ -159,6 +257,19 @@ pub fn format_show(task_with_deps: &TaskWithDeps) {
         }
     );
 
+    if let Some(parent_id) = task.parent_id {
+        println!("Parent:       #{}", parent_id);
+    }
+
+    if !task_with_deps.children.is_empty() {
+        let parts: Vec<String> = task_with_deps
+            .children
+            .iter()
+            .map(|&id| format!("#{}", id))
+            .collect();
+        println!("Children:     {}", parts.join(", "));
+    }
+
     println!(
         "Artifacts:    {}",
         if task_with_deps.artifacts.is_empty() {
@@ -281,6 +392,14 @@ pub fn format_artifacts(artifacts: &[Artifact]) {
     }
 }
 
+/// Format the projects list, marking the active one.
+pub fn format_projects(projects: &[Project], active_id: i64) {
+    for project in projects {
+        let marker = if project.id == active_id { "*" } else { " " };
+        println!("{} {}", marker, project.name);
+    }
+}
+
 /// Format timestamp for display.
 fn format_timestamp(ts: &str) -> String {
     // Parse ISO 8601 and format nicely
@@ -4,12 +4,22 @@
 
 use crate::core::error::Result;
 use chrono::Utc;
-use rusqlite::{params_from_iter, Connection, Transaction};
+use rusqlite::{params_from_iter, Connection, OptionalExtension, Transaction};
+use std::collections::HashMap;
 use std::path::Path;
 
+/// The project every database starts with, and that older databases'
+/// tasks are migrated into.
+pub const DEFAULT_PROJECT_ID: i64 = 1;
+
 /// Database connection and operations.
+///
+/// Project-level queries (task listings, the active task, config) are scoped
+/// to the active project. Task IDs are global, so lookups by ID and
+/// dependency edges cross projects.
 pub struct Db {
     conn: Connection,
+    project_id: i64,
 }
 
 impl Db {
@@ -20,15 +30,94 @@ impl Db {
         // Enable WAL mode for better concurrency
         conn.pragma_update(None, "journal_mode", "WAL")?;
 
+        let mut db = Self {
+            conn,
+            project_id: DEFAULT_PROJECT_ID,
+        };
+        // Migrate with foreign keys off: SQLite refuses to add a REFERENCES
+        // column with a non-NULL default while they are on
+        db.conn.pragma_update(None, "foreign_keys", "off")?;
+        db.migrate()?;
+
         // Enable foreign keys
-        conn.pragma_update(None, "foreign_keys", "on")?;
+        db.conn.pragma_update(None, "foreign_keys", "on")?;
 
-        Ok(Self { conn })
+        db.project_id = db.stored_project_id()?;
+        Ok(db)
+    }
+
+    /// Bring databases created by older versions up to the current schema.
+    fn migrate(&self) -> Result<()> {
+        let has_tasks: bool = self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'tasks')",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_tasks {
+            return Ok(());
+        }
+
+        let has_parent: bool = self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM pragma_table_info('tasks') WHERE name = 'parent_id')",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_parent {
+            self.conn.execute(
+                "ALTER TABLE tasks ADD COLUMN parent_id INTEGER REFERENCES tasks(id) ON DELETE SET NULL",
+                [],
+            )?;
+            self.conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_tasks_parent_id ON tasks(parent_id)",
+                [],
+            )?;
+        }
+
+        let has_project: bool = self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM pragma_table_info('tasks') WHERE name = 'project_id')",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_project {
+            // Existing tasks and config all belong to the default project
+            let tx = self.conn.unchecked_transaction()?;
+            self.create_projects_table()?;
+            self.create_settings_table()?;
+            tx.execute(
+                "ALTER TABLE tasks ADD COLUMN project_id INTEGER NOT NULL DEFAULT 1 REFERENCES projects(id)",
+                [],
+            )?;
+            tx.execute(
+                "CREATE INDEX IF NOT EXISTS idx_tasks_project_id ON tasks(project_id)",
+                [],
+            )?;
+
+            let has_config: bool = tx.query_row(
+                "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'config')",
+                [],
+                |row| row.get(0),
+            )?;
+            if has_config {
+                tx.execute("ALTER TABLE config RENAME TO config_unscoped", [])?;
+            }
+            self.create_config_table()?;
+            if has_config {
+                tx.execute_batch(
+                    "INSERT INTO config (project_id, key, value) SELECT 1, key, value FROM config_unscoped;
+                     DROP TABLE config_unscoped;",
+                )?;
+            }
+            tx.commit()?;
+        }
+
+        Ok(())
     }
 
     /// Initialize the database schema.
     /// Creates all tables and indexes.
     pub fn init_schema(&self) -> Result<()> {
+        self.create_projects_table()?;
+        self.create_settings_table()?;
         self.create_tasks_table()?;
         self.create_dependencies_table()?;
         self.create_artifacts_table()?;
@@ -50,7 +139,10 @@ impl Db {
                 started_at TEXT,
                 completed_at TEXT,
                 last_touched_at TEXT NOT NULL,
-                CHECK(status IN ('pending', 'in_progress', 'completed', 'blocked'))
+                parent_id INTEGER REFERENCES tasks(id) ON DELETE SET NULL,
+                project_id INTEGER NOT NULL DEFAULT 1 REFERENCES projects(id),
+                CHECK(status IN ('pending', 'in_progress', 'completed', 'blocked')),
+                CHECK(parent_id IS NULL OR parent_id != id)
             )",
             [],
         )?;
@@ -87,9 +179,26 @@ impl Db {
         Ok(())
     }
 
-    fn create_config_table(&self) -> Result<()> {
+    fn create_projects_table(&self) -> Result<()> {
         self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS config (
+            "CREATE TABLE IF NOT EXISTS projects (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL UNIQUE,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+        self.conn.execute(
+            "INSERT OR IGNORE INTO projects (id, name, created_at) VALUES (?1, 'default', ?2)",
+            (DEFAULT_PROJECT_ID, Self::now()),
+        )?;
+        Ok(())
+    }
+
+    /// Database-wide settings, such as the active project.
+    fn create_settings_table(&self) -> Result<()> {
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS settings (
                 key TEXT PRIMARY KEY,
                 value TEXT NOT NULL
             )",
@@ -98,6 +207,20 @@ impl Db {
         Ok(())
     }
 
+    /// Per-project settings, such as the target.
+    fn create_config_table(&self) -> Result<()> {
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS config (
+                project_id INTEGER NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+                key TEXT NOT NULL,
+                value TEXT NOT NULL,
+                PRIMARY KEY (project_id, key)
+            )",
+            [],
+        )?;
+        Ok(())
+    }
+
     fn create_indexes(&self) -> Result<()> {
         self.conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_tasks_status ON tasks(status)",
@@ -119,6 +242,14 @@ impl Db {
             "CREATE INDEX IF NOT EXISTS idx_artifacts_task_id ON artifacts(task_id)",
             [],
         )?;
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_tasks_parent_id ON tasks(parent_id)",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_tasks_project_id ON tasks(project_id)",
+            [],
+        )?;
         Ok(())
     }
 
@@ -128,7 +259,7 @@ impl Db {
     }
 
     /// Begin a new transaction.
-    pub fn transaction(&mut self) -> Result<Transaction> {
+    pub fn transaction(&mut self) -> Result<Transaction<'_>> {
         Ok(self.conn.transaction()?)
     }
 
@@ -136,6 +267,24 @@ impl Db {
     pub fn now() -> String {
         Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
     }
+
+    /// Map a row selected with the standard task columns to a Task.
+    fn task_from_row(row: &rusqlite::Row) -> rusqlite::Result<Task> {
+        Ok(Task {
+            id: row.get(0)?,
+            title: row.get(1)?,
+            description: row.get(2)?,
+            dod: row.get(3)?,
+            status: row.get(4)?,
+            manual_order: row.get(5)?,
+            created_at: row.get(6)?,
+            started_at: row.get(7)?,
+            completed_at: row.get(8)?,
+            last_touched_at: row.get(9)?,
+            parent_id: row.get(10)?,
+            project_id: row.get(11)?,
+        })
+    }
 }
 
 /// Task data structure.
@@ -151,6 +300,16 @@ pub struct Task {
     pub started_at: Option<String>,
     pub completed_at: Option<String>,
     pub last_touched_at: String,
+    pub parent_id: Option<i64>,
+    pub project_id: i64,
+}
+
+/// A named group of tasks with its own target.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Project {
+    pub id: i64,
+    pub name: String,
+    pub created_at: String,
 }
 
 /// Dependency data structure.
@@ -176,9 +335,17 @@ pub struct TaskWithDeps {
     pub task: Task,
     pub dependencies: Vec<i64>,
     pub dependents: Vec<i64>,
+    pub children: Vec<i64>,
     pub artifacts: Vec<Artifact>,
 }
 
+/// Completed and total counts over all descendants of a parent task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Progress {
+    pub completed: i64,
+    pub total: i64,
+}
+
 /// Tasks in topological order with their ready status.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct TaskInOrder {
@@ -186,6 +353,57 @@ pub struct TaskInOrder {
     pub all_deps_completed: bool,
 }
 
+/// A prerequisite edge: `task_id` cannot be finished before `depends_on`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edge {
+    pub task_id: i64,
+    pub depends_on: i64,
+    /// `depends_on` is a child of `task_id` rather than a dependency.
+    pub child: bool,
+    pub depends_on_completed: bool,
+}
+
+/// The tasks covered by a set-based query.
+#[derive(Debug, Clone, Copy)]
+pub enum TaskSet<'a> {
+    All,
+    Ids(&'a [i64]),
+    /// A target and its transitive prerequisites.
+    Subgraph(i64),
+}
+
+impl TaskSet<'_> {
+    /// A `scope(id)` table expression selecting the set, with its parameters.
+    /// `All` means all tasks of `project_id`.
+    fn scope(&self, project_id: i64) -> (&'static str, Vec<rusqlite::types::Value>) {
+        use rusqlite::types::Value;
+
+        match self {
+            TaskSet::All => (
+                "scope(id) AS (SELECT id FROM tasks WHERE project_id = ?1)",
+                vec![Value::Integer(project_id)],
+            ),
+            TaskSet::Ids(ids) => (
+                "scope(id) AS (SELECT value FROM json_each(?1))",
+                vec![Value::Text(
+                    serde_json::to_string(ids).expect("IDs serialize to JSON"),
+                )],
+            ),
+            TaskSet::Subgraph(target_id) => (
+                "scope(id) AS (
+                    SELECT ?1
+                    UNION
+                    SELECT d.depends_on FROM dependencies d JOIN scope s ON d.task_id = s.id
+                    UNION
+                    -- A parent cannot finish before its children
+                    SELECT t.id FROM tasks t JOIN scope s ON t.parent_id = s.id
+                )",
+                vec![Value::Integer(*target_id)],
+            ),
+        }
+    }
+}
+
 /// Database operations for tasks.
 impl Db {
     /// Create a new task.
@@ -198,9 +416,9 @@ impl Db {
     ) -> Result<i64> {
         let now = Self::now();
         self.conn.execute(
-            "INSERT INTO tasks (title, description, dod, status, manual_order, created_at, last_touched_at)
-             VALUES (?1, ?2, ?3, 'pending', ?4, ?5, ?5)",
-            (title, description, dod, manual_order, now.as_str()),
+            "INSERT INTO tasks (title, description, dod, status, manual_order, created_at, last_touched_at, project_id)
+             VALUES (?1, ?2, ?3, 'pending', ?4, ?5, ?5, ?6)",
+            (title, description, dod, manual_order, now.as_str(), self.project_id),
         )?;
         Ok(self.conn.last_insert_rowid())
     }
@@ -208,24 +426,11 @@ impl Db {
     /// Get a task by ID.
     pub fn get_task(&self, id: i64) -> Result<Task> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, title, description, dod, status, manual_order, created_at, started_at, completed_at, last_touched_at
+            "SELECT id, title, description, dod, status, manual_order, created_at, started_at, completed_at, last_touched_at, parent_id, project_id
              FROM tasks WHERE id = ?1"
         )?;
 
-        let task = stmt.query_row(params_from_iter([&id]), |row| {
-            Ok(Task {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                description: row.get(2)?,
-                dod: row.get(3)?,
-                status: row.get(4)?,
-                manual_order: row.get(5)?,
-                created_at: row.get(6)?,
-                started_at: row.get(7)?,
-                completed_at: row.get(8)?,
-                last_touched_at: row.get(9)?,
-            })
-        })?;
+        let task = stmt.query_row(params_from_iter([&id]), Self::task_from_row)?;
 
         Ok(task)
     }
@@ -308,82 +513,43 @@ impl Db {
         Ok(())
     }
 
-    /// Get all tasks.
+    /// Get all tasks of the active project.
     pub fn get_all_tasks(&self) -> Result<Vec<Task>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, title, description, dod, status, manual_order, created_at, started_at, completed_at, last_touched_at
-             FROM tasks"
+            "SELECT id, title, description, dod, status, manual_order, created_at, started_at, completed_at, last_touched_at, parent_id, project_id
+             FROM tasks WHERE project_id = ?1"
         )?;
 
         let tasks = stmt
-            .query_map([], |row| {
-                Ok(Task {
-                    id: row.get(0)?,
-                    title: row.get(1)?,
-                    description: row.get(2)?,
-                    dod: row.get(3)?,
-                    status: row.get(4)?,
-                    manual_order: row.get(5)?,
-                    created_at: row.get(6)?,
-                    started_at: row.get(7)?,
-                    completed_at: row.get(8)?,
-                    last_touched_at: row.get(9)?,
-                })
-            })?
+            .query_map([self.project_id], Self::task_from_row)?
             .collect::<std::result::Result<Vec<_>, _>>()?;
 
         Ok(tasks)
     }
 
-    /// Get tasks with a specific status.
+    /// Get tasks of the active project with a specific status.
     pub fn get_tasks_by_status(&self, status: &str) -> Result<Vec<Task>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, title, description, dod, status, manual_order, created_at, started_at, completed_at, last_touched_at
-             FROM tasks WHERE status = ?1"
+            "SELECT id, title, description, dod, status, manual_order, created_at, started_at, completed_at, last_touched_at, parent_id, project_id
+             FROM tasks WHERE status = ?1 AND project_id = ?2"
         )?;
 
         let tasks = stmt
-            .query_map(params_from_iter([status]), |row| {
-                Ok(Task {
-                    id: row.get(0)?,
-                    title: row.get(1)?,
-                    description: row.get(2)?,
-                    dod: row.get(3)?,
-                    status: row.get(4)?,
-                    manual_order: row.get(5)?,
-                    created_at: row.get(6)?,
-                    started_at: row.get(7)?,
-                    completed_at: row.get(8)?,
-                    last_touched_at: row.get(9)?,
-                })
-            })?
+            .query_map((status, self.project_id), Self::task_from_row)?
             .collect::<std::result::Result<Vec<_>, _>>()?;
 
         Ok(tasks)
     }
 
-    /// Get the currently active task (status = in_progress).
+    /// Get the active project's in-progress task.
     pub fn get_active_task(&self) -> Result<Option<Task>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, title, description, dod, status, manual_order, created_at, started_at, completed_at, last_touched_at
-             FROM tasks WHERE status = 'in_progress' LIMIT 1"
+            "SELECT id, title, description, dod, status, manual_order, created_at, started_at, completed_at, last_touched_at, parent_id, project_id
+             FROM tasks WHERE status = 'in_progress' AND project_id = ?1 LIMIT 1"
         )?;
 
         let tasks = stmt
-            .query_map([], |row| {
-                Ok(Task {
-                    id: row.get(0)?,
-                    title: row.get(1)?,
-                    description: row.get(2)?,
-                    dod: row.get(3)?,
-                    status: row.get(4)?,
-                    manual_order: row.get(5)?,
-                    created_at: row.get(6)?,
-                    started_at: row.get(7)?,
-                    completed_at: row.get(8)?,
-                    last_touched_at: row.get(9)?,
-                })
-            })?
+            .query_map([self.project_id], Self::task_from_row)?
             .collect::<std::result::Result<Vec<_>, _>>()?;
 
         Ok(tasks.into_iter().next())
@@ -433,6 +599,61 @@ impl Db {
         Ok(())
     }
 
+    /// Set or clear the parent of a task.
+    pub fn set_parent(&self, id: i64, parent_id: Option<i64>) -> Result<()> {
+        self.conn.execute(
+            "UPDATE tasks SET parent_id = ?1, last_touched_at = ?2 WHERE id = ?3",
+            (parent_id, Self::now(), id),
+        )?;
+        Ok(())
+    }
+
+    /// Get the direct children of a task, in manual order.
+    pub fn get_children(&self, parent_id: i64) -> Result<Vec<i64>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id FROM tasks WHERE parent_id = ?1 ORDER BY manual_order, id")?;
+
+        let children = stmt
+            .query_map(params_from_iter([&parent_id]), |row| row.get(0))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(children)
+    }
+
+    /// Get the prerequisites of a task: its dependencies plus its children.
+    pub fn get_prerequisites(&self, task_id: i64) -> Result<Vec<i64>> {
+        let mut prereqs = self.get_dependencies(task_id)?;
+        for child in self.get_children(task_id)? {
+            if !prereqs.contains(&child) {
+                prereqs.push(child);
+            }
+        }
+        Ok(prereqs)
+    }
+
+    /// Count completed and total tasks among all descendants of a task.
+    pub fn get_progress(&self, parent_id: i64) -> Result<Progress> {
+        let mut stmt = self.conn.prepare(
+            "WITH RECURSIVE descendants(id) AS (
+                SELECT id FROM tasks WHERE parent_id = ?1
+                UNION
+                SELECT t.id FROM tasks t JOIN descendants d ON t.parent_id = d.id
+            )
+            SELECT COALESCE(SUM(t.status = 'completed'), 0), COUNT(*)
+            FROM tasks t JOIN descendants d ON t.id = d.id",
+        )?;
+
+        let progress = stmt.query_row(params_from_iter([&parent_id]), |row| {
+            Ok(Progress {
+                completed: row.get(0)?,
+                total: row.get(1)?,
+            })
+        })?;
+
+        Ok(progress)
+    }
+
     /// Get artifacts for a task.
     pub fn get_artifacts(&self, task_id: i64) -> Result<Vec<Artifact>> {
         let mut stmt = self.conn.prepare(
@@ -466,13 +687,13 @@ impl Db {
         Ok(self.conn.last_insert_rowid())
     }
 
-    /// Get config value.
+    /// Get a config value of the active project.
     pub fn get_config(&self, key: &str) -> Result<Option<String>> {
         let mut stmt = self
             .conn
-            .prepare("SELECT value FROM config WHERE key = ?1")?;
+            .prepare("SELECT value FROM config WHERE project_id = ?1 AND key = ?2")?;
 
-        let mut rows = stmt.query(params_from_iter([key]))?;
+        let mut rows = stmt.query((self.project_id, key))?;
 
         match rows.next() {
             Ok(Some(row)) => Ok(Some(row.get(0)?)),
@@ -481,57 +702,168 @@ impl Db {
         }
     }
 
-    /// Set config value.
+    /// Set a config value of the active project.
     pub fn set_config(&self, key: &str, value: &str) -> Result<()> {
         self.conn.execute(
-            "INSERT OR REPLACE INTO config (key, value) VALUES (?1, ?2)",
-            params_from_iter([key, value]),
+            "INSERT OR REPLACE INTO config (project_id, key, value) VALUES (?1, ?2, ?3)",
+            (self.project_id, key, value),
         )?;
         Ok(())
     }
 
     /// Get all tasks with their dependencies and dependents.
     pub fn get_tasks_with_deps(&self) -> Result<Vec<TaskWithDeps>> {
-        let tasks = self.get_all_tasks()?;
-        let mut result = Vec::new();
-
-        for task in tasks {
-            let deps = self.get_dependencies(task.id)?;
-            let dependents = self.get_dependents(task.id)?;
-            let artifacts = self.get_artifacts(task.id)?;
+        self.get_tasks_with_deps_in(TaskSet::All)
+    }
 
-            result.push(TaskWithDeps {
+    /// Get the tasks in `set` with their dependencies, dependents, children
+    /// and artifacts, in ID order, using one query for each.
+    pub fn get_tasks_with_deps_in(&self, set: TaskSet) -> Result<Vec<TaskWithDeps>> {
+        let (scope, params) = set.scope(self.project_id);
+        let mut result: Vec<TaskWithDeps> = self
+            .get_task_set(set)?
+            .into_iter()
+            .map(|task| TaskWithDeps {
                 task,
-                dependencies: deps,
-                dependents,
-                artifacts,
-            });
+                dependencies: Vec::new(),
+                dependents: Vec::new(),
+                children: Vec::new(),
+                artifacts: Vec::new(),
+            })
+            .collect();
+        let index: HashMap<i64, usize> = result
+            .iter()
+            .enumerate()
+            .map(|(i, t)| (t.task.id, i))
+            .collect();
+
+        let mut stmt = self.conn.prepare(&format!(
+            "WITH RECURSIVE {scope}
+             SELECT task_id, depends_on FROM dependencies
+             WHERE task_id IN (SELECT id FROM scope) OR depends_on IN (SELECT id FROM scope)
+             ORDER BY task_id, depends_on"
+        ))?;
+        let mut rows = stmt.query(params_from_iter(&params))?;
+        while let Some(row) = rows.next()? {
+            let (task_id, depends_on): (i64, i64) = (row.get(0)?, row.get(1)?);
+            if let Some(&i) = index.get(&task_id) {
+                result[i].dependencies.push(depends_on);
+            }
+            if let Some(&i) = index.get(&depends_on) {
+                result[i].dependents.push(task_id);
+            }
+        }
+
+        let mut stmt = self.conn.prepare(&format!(
+            "WITH RECURSIVE {scope}
+             SELECT id, parent_id FROM tasks
+             WHERE parent_id IN (SELECT id FROM scope)
+             ORDER BY manual_order, id"
+        ))?;
+        let mut rows = stmt.query(params_from_iter(&params))?;
+        while let Some(row) = rows.next()? {
+            let (id, parent_id): (i64, i64) = (row.get(0)?, row.get(1)?);
+            if let Some(&i) = index.get(&parent_id) {
+                result[i].children.push(id);
+            }
+        }
+
+        let mut stmt = self.conn.prepare(&format!(
+            "WITH RECURSIVE {scope}
+             SELECT id, task_id, name, file_path, created_at FROM artifacts
+             WHERE task_id IN (SELECT id FROM scope)
+             ORDER BY id"
+        ))?;
+        let mut rows = stmt.query(params_from_iter(&params))?;
+        while let Some(row) = rows.next()? {
+            let artifact = Artifact {
+                id: row.get(0)?,
+                task_id: row.get(1)?,
+                name: row.get(2)?,
+                file_path: row.get(3)?,
+                created_at: row.get(4)?,
+            };
+            if let Some(&i) = index.get(&artifact.task_id) {
+                result[i].artifacts.push(artifact);
+            }
         }
 
         Ok(result)
     }
 
-    /// Get the maximum manual_order value.
+    /// Get the tasks in `set`, in ID order.
+    pub fn get_task_set(&self, set: TaskSet) -> Result<Vec<Task>> {
+        let (scope, params) = set.scope(self.project_id);
+        let mut stmt = self.conn.prepare(&format!(
+            "WITH RECURSIVE {scope}
+             SELECT id, title, description, dod, status, manual_order, created_at, started_at, completed_at, last_touched_at, parent_id, project_id
+             FROM tasks WHERE id IN (SELECT id FROM scope)
+             ORDER BY id"
+        ))?;
+
+        let tasks = stmt
+            .query_map(params_from_iter(&params), Self::task_from_row)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(tasks)
+    }
+
+    /// Get the prerequisite edges leaving the tasks in `set`: their
+    /// dependencies and their children.
+    pub fn get_prerequisite_edges(&self, set: TaskSet) -> Result<Vec<Edge>> {
+        let (scope, params) = set.scope(self.project_id);
+        let mut stmt = self.conn.prepare(&format!(
+            "WITH RECURSIVE {scope}
+             SELECT d.task_id, d.depends_on, 0, t.status = 'completed'
+             FROM dependencies d JOIN tasks t ON t.id = d.depends_on
+             WHERE d.task_id IN (SELECT id FROM scope)
+             UNION ALL
+             SELECT t.parent_id, t.id, 1, t.status = 'completed'
+             FROM tasks t
+             WHERE t.parent_id IN (SELECT id FROM scope)"
+        ))?;
+
+        let edges = stmt
+            .query_map(params_from_iter(&params), |row| {
+                Ok(Edge {
+                    task_id: row.get(0)?,
+                    depends_on: row.get(1)?,
+                    child: row.get(2)?,
+                    depends_on_completed: row.get(3)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(edges)
+    }
+
+    /// Get the maximum manual_order value in the active project.
     pub fn get_max_manual_order(&self) -> Result<f64> {
         let mut stmt = self
             .conn
-            .prepare("SELECT COALESCE(MAX(manual_order), 0) FROM tasks")?;
+            .prepare("SELECT COALESCE(MAX(manual_order), 0) FROM tasks WHERE project_id = ?1")?;
 
-        let max = stmt.query_row([], |row| row.get(0))?;
+        let max = stmt.query_row([self.project_id], |row| row.get(0))?;
         Ok(max)
     }
 
     /// Get tasks in the target subgraph (transitive dependencies).
     pub fn get_target_subgraph(&self, target_id: i64) -> Result<Vec<i64>> {
         let mut stmt = self.conn.prepare(
-            "WITH RECURSIVE subgraph(id) AS (
+            "WITH RECURSIVE edges(task_id, depends_on) AS (
+                SELECT task_id, depends_on FROM dependencies
+                UNION ALL
+                -- A parent cannot finish before its children
+                SELECT parent_id, id FROM tasks WHERE parent_id IS NOT NULL
+            ),
+            subgraph(id) AS (
                 -- Start with the target
                 SELECT ?1
-                UNION ALL
-                -- Add all dependencies
+                UNION
+                -- Add all dependencies and children
                 SELECT d.depends_on
                 FROM subgraph s
-                JOIN dependencies d ON d.task_id = s.id
+                JOIN edges d ON d.task_id = s.id
             )
             SELECT DISTINCT id FROM subgraph",
         )?;
@@ -545,61 +877,25 @@ impl Db {
 
     /// Get incomplete tasks in the target subgraph.
     pub fn get_incomplete_in_subgraph(&self, target_id: i64) -> Result<Vec<Task>> {
-        let subgraph_ids = self.get_target_subgraph(target_id)?;
-
-        if subgraph_ids.is_empty() {
-            return Ok(Vec::new());
-        }
-
-        let placeholders = subgraph_ids
-            .iter()
-            .map(|_| "?")
-            .collect::<Vec<_>>()
-            .join(",");
-
-        let query = format!(
-            "SELECT id, title, description, dod, status, manual_order, created_at, started_at, completed_at, last_touched_at
-             FROM tasks
-             WHERE id IN ({}) AND status != 'completed'",
-            placeholders
-        );
-
-        let mut stmt = self.conn.prepare(&query)?;
-
-        let params: Vec<&dyn rusqlite::ToSql> = subgraph_ids
-            .iter()
-            .map(|id| id as &dyn rusqlite::ToSql)
-            .collect();
-
-        let tasks = stmt
-            .query_map(params.as_slice(), |row| {
-                Ok(Task {
-                    id: row.get(0)?,
-                    title: row.get(1)?,
-                    description: row.get(2)?,
-                    dod: row.get(3)?,
-                    status: row.get(4)?,
-                    manual_order: row.get(5)?,
-                    created_at: row.get(6)?,
-                    started_at: row.get(7)?,
-                    completed_at: row.get(8)?,
-                    last_touched_at: row.get(9)?,
-                })
-            })?
-            .collect::<std::result::Result<Vec<_>, _>>()?;
-
+        let mut tasks = self.get_task_set(TaskSet::Subgraph(target_id))?;
+        tasks.retain(|t| t.status != "completed");
         Ok(tasks)
     }
 
     /// Check if a dependency path exists (for cycle detection).
     pub fn path_exists(&self, from: i64, to: i64) -> Result<bool> {
         let mut stmt = self.conn.prepare(
-            "WITH RECURSIVE path(curr) AS (
-                SELECT ?1
+            "WITH RECURSIVE edges(task_id, depends_on) AS (
+                SELECT task_id, depends_on FROM dependencies
                 UNION ALL
+                SELECT parent_id, id FROM tasks WHERE parent_id IS NOT NULL
+            ),
+            path(curr) AS (
+                SELECT ?1
+                UNION
                 SELECT d.depends_on
                 FROM path p
-                JOIN dependencies d ON d.task_id = p.curr
+                JOIN edges d ON d.task_id = p.curr
             )
             SELECT EXISTS(SELECT 1 FROM path WHERE curr = ?2)",
         )?;
@@ -612,12 +908,17 @@ impl Db {
     /// Get the path from one task to another (for cycle detection error messages).
     pub fn get_path(&self, from: i64, to: i64) -> Result<Vec<i64>> {
         let mut stmt = self.conn.prepare(
-            "WITH RECURSIVE path(curr, path_str) AS (
+            "WITH RECURSIVE edges(task_id, depends_on) AS (
+                SELECT task_id, depends_on FROM dependencies
+                UNION ALL
+                SELECT parent_id, id FROM tasks WHERE parent_id IS NOT NULL
+            ),
+            path(curr, path_str) AS (
                 SELECT ?1, ?1 || ','
                 UNION ALL
                 SELECT d.depends_on, p.path_str || d.depends_on || ','
                 FROM path p
-                JOIN dependencies d ON d.task_id = p.curr
+                JOIN edges d ON d.task_id = p.curr
                 WHERE p.path_str NOT LIKE '%,' || d.depends_on || ',%'
             )
             SELECT path_str FROM path WHERE curr = ?2 LIMIT 1",
@@ -637,7 +938,7 @@ impl Db {
         Ok(ids)
     }
 
-    /// Reindex all manual_order values to clean integers.
+    /// Reindex the active project's manual_order values to clean integers.
     pub fn reindex_orders(&mut self) -> Result<()> {
         let tasks = self.get_all_tasks()?;
         let mut sorted: Vec<_> = tasks.iter().collect();
@@ -658,6 +959,107 @@ impl Db {
     }
 }
 
+/// Database operations for projects.
+impl Db {
+    /// The project that project-level queries are scoped to.
+    pub fn project_id(&self) -> i64 {
+        self.project_id
+    }
+
+    /// The active project as recorded in the settings table.
+    fn stored_project_id(&self) -> Result<i64> {
+        let has_settings: bool = self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'settings')",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_settings {
+            return Ok(DEFAULT_PROJECT_ID);
+        }
+
+        let value: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT value FROM settings WHERE key = 'project_id'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(value
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_PROJECT_ID))
+    }
+
+    /// Scope this connection to a project without changing the one that
+    /// later connections start in.
+    pub fn pin_project(&mut self, id: i64) {
+        self.project_id = id;
+    }
+
+    /// Make a project active for this connection and for later ones.
+    pub fn set_project(&mut self, id: i64) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO settings (key, value) VALUES ('project_id', ?1)",
+            [id.to_string()],
+        )?;
+        self.project_id = id;
+        Ok(())
+    }
+
+    /// Create a project.
+    pub fn create_project(&self, name: &str) -> Result<i64> {
+        self.conn.execute(
+            "INSERT INTO projects (name, created_at) VALUES (?1, ?2)",
+            (name, Self::now()),
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Get a project by ID.
+    pub fn get_project(&self, id: i64) -> Result<Project> {
+        let project = self.conn.query_row(
+            "SELECT id, name, created_at FROM projects WHERE id = ?1",
+            [id],
+            Self::project_from_row,
+        )?;
+        Ok(project)
+    }
+
+    /// Find a project by name.
+    pub fn find_project(&self, name: &str) -> Result<Option<Project>> {
+        let project = self
+            .conn
+            .query_row(
+                "SELECT id, name, created_at FROM projects WHERE name = ?1",
+                [name],
+                Self::project_from_row,
+            )
+            .optional()?;
+        Ok(project)
+    }
+
+    /// Get all projects, in creation order.
+    pub fn get_projects(&self) -> Result<Vec<Project>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, name, created_at FROM projects ORDER BY id")?;
+
+        let projects = stmt
+            .query_map([], Self::project_from_row)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(projects)
+    }
+
+    fn project_from_row(row: &rusqlite::Row) -> rusqlite::Result<Project> {
+        Ok(Project {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            created_at: row.get(2)?,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -685,6 +1087,95 @@ mod tests {
         assert!(tables.contains(&"config".to_string()));
     }
 
+    #[test]
+    fn test_migrate_adds_parent_id() {
+        let temp = NamedTempFile::new().unwrap();
+        {
+            let conn = Connection::open(temp.path()).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE tasks (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    title TEXT NOT NULL,
+                    description TEXT,
+                    dod TEXT,
+                    status TEXT NOT NULL DEFAULT 'pending',
+                    manual_order REAL NOT NULL DEFAULT 0.0,
+                    created_at TEXT NOT NULL,
+                    started_at TEXT,
+                    completed_at TEXT,
+                    last_touched_at TEXT NOT NULL
+                );
+                INSERT INTO tasks (title, created_at, last_touched_at) VALUES ('Old', 'now', 'now');",
+            )
+            .unwrap();
+        }
+
+        let db = Db::open(temp.path()).unwrap();
+        db.init_schema().unwrap();
+        let task = db.get_task(1).unwrap();
+        assert_eq!(task.title, "Old");
+        assert_eq!(task.parent_id, None);
+    }
+
+    #[test]
+    fn test_migrate_moves_data_into_default_project() {
+        let temp = NamedTempFile::new().unwrap();
+        {
+            let db = Db::open(temp.path()).unwrap();
+            db.init_schema().unwrap();
+        }
+        {
+            // Rebuild the tables as they were before projects existed
+            let conn = Connection::open(temp.path()).unwrap();
+            conn.execute_batch(
+                "DROP TABLE config;
+                 DROP TABLE settings;
+                 DROP TABLE tasks;
+                 DROP TABLE projects;
+                 CREATE TABLE tasks (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    title TEXT NOT NULL,
+                    description TEXT,
+                    dod TEXT,
+                    status TEXT NOT NULL DEFAULT 'pending',
+                    manual_order REAL NOT NULL DEFAULT 0.0,
+                    created_at TEXT NOT NULL,
+                    started_at TEXT,
+                    completed_at TEXT,
+                    last_touched_at TEXT NOT NULL,
+                    parent_id INTEGER REFERENCES tasks(id) ON DELETE SET NULL
+                 );
+                 CREATE TABLE config (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+                 INSERT INTO tasks (title, created_at, last_touched_at) VALUES ('Old', 'now', 'now');
+                 INSERT INTO config (key, value) VALUES ('target_id', '1');",
+            )
+            .unwrap();
+        }
+
+        let mut db = Db::open(temp.path()).unwrap();
+        db.init_schema().unwrap();
+        assert_eq!(db.project_id(), DEFAULT_PROJECT_ID);
+        assert_eq!(db.get_task(1).unwrap().project_id, DEFAULT_PROJECT_ID);
+        assert_eq!(db.get_config("target_id").unwrap(), Some("1".to_string()));
+
+        let other = db.create_project("other").unwrap();
+        db.set_project(other).unwrap();
+        assert_eq!(db.get_config("target_id").unwrap(), None);
+        assert!(db.get_all_tasks().unwrap().is_empty());
+
+        // The choice of project outlives the connection, but open ones keep theirs
+        let mut open = Db::open(temp.path()).unwrap();
+        assert_eq!(open.project_id(), other);
+        db.set_project(DEFAULT_PROJECT_ID).unwrap();
+        assert_eq!(open.project_id(), other);
+
+        // Pinning a project leaves the stored choice alone
+        open.pin_project(DEFAULT_PROJECT_ID);
+        db.set_project(other).unwrap();
+        assert_eq!(open.project_id(), DEFAULT_PROJECT_ID);
+        assert_eq!(Db::open(temp.path()).unwrap().project_id(), other);
+    }
+
     #[test]
     fn test_create_task() {
         let temp = NamedTempFile::new().unwrap();
@@ -782,6 +1273,46 @@ mod tests {
         assert_eq!(active.unwrap().id, id);
     }
 
+    #[test]
+    fn test_tasks_with_deps_in_set() {
+        let temp = NamedTempFile::new().unwrap();
+        let db = Db::open(temp.path()).unwrap();
+        db.init_schema().unwrap();
+
+        let epic = db.create_task("Epic", None, None, 10.0).unwrap();
+        let child = db.create_task("Child", None, None, 20.0).unwrap();
+        let dep = db.create_task("Dep", None, None, 30.0).unwrap();
+        let other = db.create_task("Other", None, None, 40.0).unwrap();
+        db.set_parent(child, Some(epic)).unwrap();
+        db.add_dependency(child, dep).unwrap();
+        db.add_dependency(other, epic).unwrap();
+        db.add_artifact(child, "notes", "notes.md").unwrap();
+
+        let subgraph = db.get_task_set(TaskSet::Subgraph(epic)).unwrap();
+        let ids: Vec<i64> = subgraph.iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![epic, child, dep]);
+
+        let details = db
+            .get_tasks_with_deps_in(TaskSet::Ids(&[epic, child]))
+            .unwrap();
+        assert_eq!(details.len(), 2);
+        assert_eq!(details[0].children, vec![child]);
+        assert_eq!(details[0].dependents, vec![other]);
+        assert_eq!(details[1].dependencies, vec![dep]);
+        assert_eq!(details[1].artifacts[0].name, "notes");
+
+        let edges = db.get_prerequisite_edges(TaskSet::Ids(&[epic])).unwrap();
+        assert_eq!(
+            edges,
+            vec![Edge {
+                task_id: epic,
+                depends_on: child,
+                child: true,
+                depends_on_completed: false,
+            }]
+        );
+    }
+
     #[test]
     fn test_target_subgraph() {
         let temp = NamedTempFile::new().unwrap();
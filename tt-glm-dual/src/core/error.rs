@@ -43,6 +43,14 @@ pub enum TTError {
     #[error("Target reached. All tasks for #{0} are completed.")]
     TargetReached(i64),
 
+    /// Parent task has children that are not completed yet.
+    #[error("Cannot complete #{0}: children not completed: {1:?}")]
+    IncompleteChildren(i64, Vec<i64>),
+
+    /// Parent task is already completed, so it cannot take an unfinished child.
+    #[error("Cannot attach #{0} to #{1}: parent is already completed")]
+    ParentCompleted(i64, i64),
+
     /// Task has no definition of done.
     #[error("Task #{0} has no definition of done. Set one with `tt edit {0} --dod`")]
     NoDod(i64),
@@ -83,6 +91,22 @@ pub enum TTError {
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
 
+    /// No project has this name.
+    #[error("Project '{0}' not found. Create it with `tt project create {0}`")]
+    ProjectNotFound(String),
+
+    /// A project with this name already exists.
+    #[error("Project '{0}' already exists")]
+    ProjectExists(String),
+
+    /// Project names must be non-empty and contain no whitespace.
+    #[error("Invalid project name: '{0}'")]
+    InvalidProjectName(String),
+
+    /// The task belongs to a project other than the active one.
+    #[error("Task #{0} belongs to project '{1}'. Switch with `tt project use {1}` first.")]
+    TaskInOtherProject(i64, String),
+
     /// Invalid state transition.
     #[error("Invalid state transition from {0} to {1}")]
     InvalidTransition(String, String),
@@ -90,6 +114,14 @@ pub enum TTError {
     /// MCP error.
     #[error("MCP error: {0}")]
     Mcp(String),
+
+    /// No MCP tool has this name.
+    #[error("Unknown tool: {0}")]
+    UnknownTool(String),
+
+    /// The tool is not enabled for this MCP session.
+    #[error("Tool {0} is not available in this session")]
+    ToolNotAllowed(String),
 }
 
 impl TTError {
@@ -104,6 +136,8 @@ impl TTError {
             TTError::CycleDetected(_, _, _) => "CycleDetected",
             TTError::NoTarget => "NoTarget",
             TTError::TargetReached(_) => "TargetReached",
+            TTError::IncompleteChildren(_, _) => "IncompleteChildren",
+            TTError::ParentCompleted(_, _) => "ParentCompleted",
             TTError::NoDod(_) => "NoDod",
             TTError::OrderConflict(_, _, _, _) => "OrderConflict",
             TTError::InvalidStatus(_) => "InvalidStatus",
@@ -111,11 +145,17 @@ impl TTError {
             TTError::DeletionNotSupported => "DeletionNotSupported",
             TTError::FloatPrecisionExhausted => "FloatPrecisionExhausted",
             TTError::AfterOrBeforeRequired => "AfterOrBeforeRequired",
+            TTError::ProjectNotFound(_) => "ProjectNotFound",
+            TTError::ProjectExists(_) => "ProjectExists",
+            TTError::InvalidProjectName(_) => "InvalidProjectName",
+            TTError::TaskInOtherProject(_, _) => "TaskInOtherProject",
             TTError::InvalidTransition(_, _) => "InvalidTransition",
             TTError::Db(_) => "DatabaseError",
             TTError::Io(_) => "IoError",
             TTError::Json(_) => "JsonError",
             TTError::Mcp(_) => "McpError",
+            TTError::UnknownTool(_) => "UnknownTool",
+            TTError::ToolNotAllowed(_) => "ToolNotAllowed",
         }
     }
 }
@@ -134,5 +174,8 @@ mod tests {
 
         let err = TTError::NoTarget;
         assert_eq!(err.error_code(), "NoTarget");
+
+        let err = TTError::ToolNotAllowed("edit_task".to_string());
+        assert_eq!(err.error_code(), "ToolNotAllowed");
     }
 }
@@ -1,17 +1,21 @@
 //! Graph engine for the tt task tracker.
 //!
 //! Handles topological sorting, cycle detection, and target subgraph operations.
+//! Sorting and conflict checks run on a [`TaskGraph`] held in memory, which is
+//! loaded with two set-based queries.
 
-use crate::core::db::{Db, Task};
+use crate::core::db::{Db, Edge, Task, TaskSet};
 use crate::core::error::{Result, TTError};
 use std::cmp::Ordering;
-use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::collections::{BinaryHeap, HashMap};
 
 /// Wrapper for Task to use in min-heap based on manual_order.
 #[derive(Debug, Clone, PartialEq)]
 struct MinHeapTask {
     task_id: i64,
     manual_order: f64,
+    /// Position of the task in its graph.
+    index: usize,
 }
 
 impl Eq for MinHeapTask {}
@@ -33,103 +37,170 @@ impl PartialOrd for MinHeapTask {
     }
 }
 
-/// Perform topological sort using Kahn's algorithm with manual_order tiebreaking.
-pub fn topological_sort(db: &Db, task_ids: &[i64]) -> Result<Vec<TaskInOrder>> {
-    if task_ids.is_empty() {
-        return Ok(Vec::new());
-    }
+/// A set of tasks with the prerequisite edges between them, as adjacency
+/// lists indexed by position in `tasks`.
+#[derive(Debug, Clone, Default)]
+pub struct TaskGraph {
+    tasks: Vec<Task>,
+    /// Tasks each task waits for: its dependencies and children.
+    prerequisites: Vec<Vec<usize>>,
+    /// Tasks waiting for each task.
+    dependents: Vec<Vec<usize>>,
+    /// Whether each task waits for an incomplete task outside the graph.
+    waits_outside: Vec<bool>,
+    /// Dependency edges, without parent-child links, as (task, depends on).
+    dependencies: Vec<(usize, usize)>,
+}
 
-    // Fetch all tasks and build maps
-    let mut tasks_by_id: HashMap<i64, Task> = HashMap::new();
-    let mut in_degree: HashMap<i64, usize> = HashMap::new();
-    let mut adj_list: HashMap<i64, Vec<i64>> = HashMap::new();
+impl TaskGraph {
+    /// Load the tasks in `set` and the edges between them.
+    pub fn load(db: &Db, set: TaskSet) -> Result<Self> {
+        Self::load_tasks(db, db.get_task_set(set)?)
+    }
 
-    // Initialize in-degree for all tasks
-    for &id in task_ids {
-        in_degree.insert(id, 0);
-        adj_list.insert(id, Vec::new());
+    /// Load the incomplete tasks in `set` and the edges between them.
+    pub fn load_incomplete(db: &Db, set: TaskSet) -> Result<Self> {
+        let mut tasks = db.get_task_set(set)?;
+        tasks.retain(|t| t.status != "completed");
+        Self::load_tasks(db, tasks)
     }
 
-    // Build the graph
-    for &id in task_ids {
-        let task = db.get_task(id)?;
-        tasks_by_id.insert(id, task);
+    /// Fetch the edges of already loaded tasks, so the set is not
+    /// recomputed by a second recursive query.
+    fn load_tasks(db: &Db, tasks: Vec<Task>) -> Result<Self> {
+        let ids: Vec<i64> = tasks.iter().map(|t| t.id).collect();
+        let edges = db.get_prerequisite_edges(TaskSet::Ids(&ids))?;
+        Ok(Self::new(tasks, &edges))
+    }
 
-        let deps = db.get_dependencies(id)?;
-        for &dep_id in &deps {
-            if task_ids.contains(&dep_id) {
-                *in_degree.entry(id).or_insert(0) += 1;
-                adj_list.entry(dep_id).or_default().push(id);
+    /// Build a graph from tasks and the edges leaving them. Edges to tasks
+    /// outside the graph only count towards readiness.
+    pub fn new(tasks: Vec<Task>, edges: &[Edge]) -> Self {
+        let index: HashMap<i64, usize> = tasks
+            .iter()
+            .enumerate()
+            .map(|(i, task)| (task.id, i))
+            .collect();
+        let mut prerequisites = vec![Vec::new(); tasks.len()];
+        let mut dependents = vec![Vec::new(); tasks.len()];
+        let mut waits_outside = vec![false; tasks.len()];
+        let mut dependencies = Vec::new();
+
+        for edge in edges {
+            let Some(&from) = index.get(&edge.task_id) else {
+                continue;
+            };
+            match index.get(&edge.depends_on) {
+                Some(&to) => {
+                    prerequisites[from].push(to);
+                    dependents[to].push(from);
+                    if !edge.child {
+                        dependencies.push((from, to));
+                    }
+                }
+                None => waits_outside[from] |= !edge.depends_on_completed,
             }
         }
-    }
 
-    // Seed the heap with tasks that have in-degree 0
-    let mut heap: BinaryHeap<MinHeapTask> = BinaryHeap::new();
-    for (&id, degree) in &in_degree {
-        if *degree == 0 {
-            if let Some(task) = tasks_by_id.get(&id) {
-                heap.push(MinHeapTask {
-                    task_id: id,
-                    manual_order: task.manual_order,
-                });
-            }
+        // A task may both depend on a child and contain it
+        for list in prerequisites.iter_mut().chain(dependents.iter_mut()) {
+            list.sort_unstable();
+            list.dedup();
+        }
+
+        Self {
+            tasks,
+            prerequisites,
+            dependents,
+            waits_outside,
+            dependencies,
         }
     }
 
-    let mut result = Vec::new();
-    let mut completed: HashSet<i64> = HashSet::new();
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
 
-    while let Some(min_task) = heap.pop() {
-        let id = min_task.task_id;
-        let task = tasks_by_id.get(&id).unwrap().clone();
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
 
-        // Check if all dependencies are completed
-        let deps = db.get_dependencies(id)?;
-        let all_deps_completed = deps.iter().all(|&dep_id| {
-            if let Ok(dep_task) = db.get_task(dep_id) {
-                dep_task.status == "completed"
-            } else {
-                false
-            }
-        });
-
-        result.push(TaskInOrder {
-            task,
-            all_deps_completed,
-        });
-
-        completed.insert(id);
-
-        // Decrement in-degree of dependents
-        if let Some(dependents) = adj_list.get(&id) {
-            for &dep_id in dependents {
-                if let Some(degree) = in_degree.get_mut(&dep_id) {
-                    *degree -= 1;
-                    if *degree == 0 {
-                        if let Some(task) = tasks_by_id.get(&dep_id) {
-                            heap.push(MinHeapTask {
-                                task_id: dep_id,
-                                manual_order: task.manual_order,
-                            });
-                        }
-                    }
+    /// Perform topological sort using Kahn's algorithm with manual_order tiebreaking.
+    pub fn topological_sort(&self) -> Result<Vec<TaskInOrder>> {
+        let mut in_degree: Vec<usize> = self.prerequisites.iter().map(Vec::len).collect();
+        let mut heap: BinaryHeap<MinHeapTask> = (0..self.len())
+            .filter(|&i| in_degree[i] == 0)
+            .map(|i| self.heap_entry(i))
+            .collect();
+
+        let mut result = Vec::with_capacity(self.len());
+        while let Some(MinHeapTask { index, .. }) = heap.pop() {
+            result.push(TaskInOrder {
+                task: self.tasks[index].clone(),
+                all_deps_completed: self.prerequisites_completed(index),
+            });
+
+            for &dependent in &self.dependents[index] {
+                in_degree[dependent] -= 1;
+                if in_degree[dependent] == 0 {
+                    heap.push(self.heap_entry(dependent));
                 }
             }
         }
+
+        // Check for cycles
+        if result.len() != self.len() {
+            return Err(TTError::InvalidStatus(
+                "Cycle detected in task graph".to_string(),
+            ));
+        }
+
+        Ok(result)
     }
 
-    // Check for cycles
-    if result.len() != task_ids.len() {
-        return Err(TTError::InvalidStatus(
-            "Cycle detected in task graph".to_string(),
-        ));
+    /// Warnings for tasks ordered before one of their dependencies.
+    pub fn order_conflicts(&self) -> Vec<String> {
+        self.dependencies
+            .iter()
+            .filter_map(|&(from, to)| {
+                let (task, dep_task) = (&self.tasks[from], &self.tasks[to]);
+                (task.manual_order < dep_task.manual_order).then(|| {
+                    format!(
+                        "Warning: #{} (order {}) depends on #{} (order {}) which has higher manual_order",
+                        task.id, task.manual_order, dep_task.id, dep_task.manual_order
+                    )
+                })
+            })
+            .collect()
     }
 
-    Ok(result)
+    fn heap_entry(&self, index: usize) -> MinHeapTask {
+        MinHeapTask {
+            task_id: self.tasks[index].id,
+            manual_order: self.tasks[index].manual_order,
+            index,
+        }
+    }
+
+    /// Whether all dependencies and children of a task are completed.
+    fn prerequisites_completed(&self, index: usize) -> bool {
+        !self.waits_outside[index]
+            && self.prerequisites[index]
+                .iter()
+                .all(|&p| self.tasks[p].status == "completed")
+    }
 }
 
-/// Check for cycles when adding a dependency edge.
+/// Topologically sort the given tasks.
+pub fn topological_sort(db: &Db, task_ids: &[i64]) -> Result<Vec<TaskInOrder>> {
+    TaskGraph::load(db, TaskSet::Ids(task_ids))?.topological_sort()
+}
+
+/// Check for cycles when adding an edge where `from` must wait for `to`.
+///
+/// Both dependency edges and parent-child links are followed, since a parent
+/// waits for its children just like a task waits for its dependencies. Use
+/// `check_cycle(db, parent, child)` before linking a child to a parent.
 pub fn check_cycle(db: &Db, from: i64, to: i64) -> Result<()> {
     if db.path_exists(from, to)? {
         let path = db.get_path(from, to)?;
@@ -174,26 +245,7 @@ pub fn calculate_midpoint(db: &Db, after_id: Option<i64>, before_id: Option<i64>
 
 /// Check for order conflicts and return warnings.
 pub fn check_order_conflicts(db: &Db, task_ids: &[i64]) -> Result<Vec<String>> {
-    let mut warnings = Vec::new();
-
-    for &id in task_ids {
-        let task = db.get_task(id)?;
-        let deps = db.get_dependencies(id)?;
-
-        for &dep_id in &deps {
-            if task_ids.contains(&dep_id) {
-                let dep_task = db.get_task(dep_id)?;
-                if task.manual_order < dep_task.manual_order {
-                    warnings.push(format!(
-                        "Warning: #{} (order {}) depends on #{} (order {}) which has higher manual_order",
-                        id, task.manual_order, dep_id, dep_task.manual_order
-                    ));
-                }
-            }
-        }
-    }
-
-    Ok(warnings)
+    Ok(TaskGraph::load(db, TaskSet::Ids(task_ids))?.order_conflicts())
 }
 
 /// Task in topological order with completion status.
@@ -227,6 +279,98 @@ mod tests {
         assert_eq!(sorted[2].task.id, id3);
     }
 
+    #[test]
+    fn test_check_cycle_follows_parent_links() {
+        let temp = NamedTempFile::new().unwrap();
+        let db = Db::open(temp.path()).unwrap();
+        db.init_schema().unwrap();
+
+        let epic = db.create_task("Epic", None, None, 10.0).unwrap();
+        let child = db.create_task("Child", None, None, 20.0).unwrap();
+        let other = db.create_task("Other", None, None, 30.0).unwrap();
+        db.set_parent(child, Some(epic)).unwrap();
+
+        // A child cannot depend on its own parent
+        assert!(matches!(
+            check_cycle(&db, child, epic),
+            Err(TTError::CycleDetected(_, _, _))
+        ));
+
+        // Anything waiting on the epic also waits on its children
+        db.add_dependency(other, epic).unwrap();
+        assert!(check_cycle(&db, other, child).is_ok());
+        assert!(matches!(
+            check_cycle(&db, epic, other),
+            Err(TTError::CycleDetected(_, _, _))
+        ));
+        assert!(check_cycle(&db, child, other).is_err());
+    }
+
+    #[test]
+    fn test_topological_sort_orders_children_first() {
+        let temp = NamedTempFile::new().unwrap();
+        let db = Db::open(temp.path()).unwrap();
+        db.init_schema().unwrap();
+
+        let epic = db.create_task("Epic", None, None, 10.0).unwrap();
+        let child = db.create_task("Child", None, None, 20.0).unwrap();
+        db.set_parent(child, Some(epic)).unwrap();
+
+        let sorted = topological_sort(&db, &[epic, child]).unwrap();
+        assert_eq!(sorted[0].task.id, child);
+        assert_eq!(sorted[1].task.id, epic);
+        assert!(!sorted[1].all_deps_completed);
+    }
+
+    #[test]
+    fn test_graph_readiness_and_conflicts() {
+        let temp = NamedTempFile::new().unwrap();
+        let db = Db::open(temp.path()).unwrap();
+        db.init_schema().unwrap();
+
+        let done = db.create_task("Done", None, None, 10.0).unwrap();
+        let open = db.create_task("Open", None, None, 40.0).unwrap();
+        let ready = db.create_task("Ready", None, None, 30.0).unwrap();
+        let waiting = db.create_task("Waiting", None, None, 20.0).unwrap();
+        db.update_task_status(done, "completed").unwrap();
+        db.add_dependency(ready, done).unwrap();
+        db.add_dependency(waiting, open).unwrap();
+        db.add_dependency(waiting, ready).unwrap();
+
+        let graph = TaskGraph::load_incomplete(&db, TaskSet::Subgraph(waiting)).unwrap();
+        assert_eq!(graph.len(), 3);
+
+        let sorted = graph.topological_sort().unwrap();
+        let order: Vec<(i64, bool)> = sorted
+            .iter()
+            .map(|t| (t.task.id, t.all_deps_completed))
+            .collect();
+        assert_eq!(order, vec![(ready, true), (open, true), (waiting, false)]);
+
+        let conflicts = graph.order_conflicts();
+        assert_eq!(conflicts.len(), 2);
+        assert!(conflicts
+            .iter()
+            .any(|w| w.starts_with(&format!("Warning: #{} (order 20)", waiting))));
+    }
+
+    #[test]
+    fn test_topological_sort_detects_cycles() {
+        let temp = NamedTempFile::new().unwrap();
+        let db = Db::open(temp.path()).unwrap();
+        db.init_schema().unwrap();
+
+        let id1 = db.create_task("Task 1", None, None, 10.0).unwrap();
+        let id2 = db.create_task("Task 2", None, None, 20.0).unwrap();
+        db.add_dependency(id1, id2).unwrap();
+        db.add_dependency(id2, id1).unwrap();
+
+        assert!(matches!(
+            topological_sort(&db, &[id1, id2]),
+            Err(TTError::InvalidStatus(_))
+        ));
+    }
+
     #[test]
     fn test_midpoint_calculation() {
         let temp = NamedTempFile::new().unwrap();
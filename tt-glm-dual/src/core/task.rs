@@ -3,9 +3,18 @@
 //! Implements all business logic for task management, workflow, dependencies,
 //! artifacts, and ordering.
 
-use crate::core::db::{Artifact, Db, Task, TaskWithDeps};
+use crate::core::db::{Artifact, Db, Progress, Project, Task, TaskSet, TaskWithDeps};
 use crate::core::error::{Result, TTError};
-use crate::core::graph;
+use crate::core::graph::{self, TaskGraph};
+
+/// A task and its descendants, for the tree view.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TreeNode {
+    pub task: Task,
+    /// Rolled-up progress over all descendants; `None` for leaf tasks.
+    pub progress: Option<Progress>,
+    pub children: Vec<TreeNode>,
+}
 
 /// Task manager for high-level operations.
 pub struct TaskManager {
@@ -68,12 +77,14 @@ impl TaskManager {
         let task = self.db.get_task(id)?;
         let dependencies = self.db.get_dependencies(id)?;
         let dependents = self.db.get_dependents(id)?;
+        let children = self.db.get_children(id)?;
         let artifacts = self.db.get_artifacts(id)?;
 
         Ok(TaskWithDeps {
             task,
             dependencies,
             dependents,
+            children,
             artifacts,
         })
     }
@@ -81,27 +92,15 @@ impl TaskManager {
     /// List tasks (either all or in target subgraph).
     pub fn list_tasks(&self, all: bool) -> Result<Vec<TaskWithDeps>> {
         if all {
-            self.db.get_tasks_with_deps()
-        } else {
-            let target_id = self.get_target()?;
-            let tasks = self.db.get_incomplete_in_subgraph(target_id)?;
-            let mut result = Vec::new();
-
-            for task in tasks {
-                let deps = self.db.get_dependencies(task.id)?;
-                let dependents = self.db.get_dependents(task.id)?;
-                let artifacts = self.db.get_artifacts(task.id)?;
-
-                result.push(TaskWithDeps {
-                    task,
-                    dependencies: deps,
-                    dependents,
-                    artifacts,
-                });
-            }
-
-            Ok(result)
+            return self.db.get_tasks_with_deps();
         }
+
+        let target_id = self.get_target()?;
+        let mut tasks = self
+            .db
+            .get_tasks_with_deps_in(TaskSet::Subgraph(target_id))?;
+        tasks.retain(|t| t.task.status != "completed");
+        Ok(tasks)
     }
 
     /// Set the target.
@@ -119,22 +118,13 @@ impl TaskManager {
     /// Get the next task to work on.
     pub fn next_task(&self) -> Result<TaskWithDeps> {
         let target_id = self.get_target()?;
-        let subgraph_ids = self.db.get_target_subgraph(target_id)?;
+        let graph = TaskGraph::load_incomplete(&self.db, TaskSet::Subgraph(target_id))?;
 
-        // Filter out completed tasks
-        let incomplete: Vec<i64> = subgraph_ids
-            .into_iter()
-            .filter(|&id| match self.db.get_task(id) {
-                Ok(task) => task.status != "completed",
-                Err(_) => false,
-            })
-            .collect();
-
-        if incomplete.is_empty() {
+        if graph.is_empty() {
             return Err(TTError::TargetReached(target_id));
         }
 
-        let sorted = graph::topological_sort(&self.db, &incomplete)?;
+        let sorted = graph.topological_sort()?;
 
         // Find the first pending task with all deps completed
         for task_in_order in &sorted {
@@ -157,6 +147,14 @@ impl TaskManager {
     pub fn start_task(&mut self, id: i64) -> Result<Task> {
         // Verify task exists and is pending
         let task = self.db.get_task(id)?;
+
+        // The one-active-task rule and `current` are per project, so only
+        // the active project's tasks can be started
+        if task.project_id != self.db.project_id() {
+            let project = self.db.get_project(task.project_id)?;
+            return Err(TTError::TaskInOtherProject(id, project.name));
+        }
+
         if task.status != "pending" && task.status != "in_progress" {
             return Err(TTError::TaskNotPending(id));
         }
@@ -203,10 +201,32 @@ impl TaskManager {
         let active = self.db.get_active_task()?.ok_or(TTError::NoActiveTask)?;
 
         // Check DoD
-        if active.dod.is_none() || active.dod.as_ref().map(|s| s.trim()).unwrap_or("") == "" {
+        if active.dod.is_none()
+            || active
+                .dod
+                .as_ref()
+                .map(|s| s.trim())
+                .unwrap_or("")
+                .is_empty()
+        {
             return Err(TTError::NoDod(active.id));
         }
 
+        // A parent is done only when all of its children are
+        let incomplete: Vec<i64> = self
+            .db
+            .get_children(active.id)?
+            .into_iter()
+            .filter(|&child_id| match self.db.get_task(child_id) {
+                Ok(child) => child.status != "completed",
+                Err(_) => true,
+            })
+            .collect();
+
+        if !incomplete.is_empty() {
+            return Err(TTError::IncompleteChildren(active.id, incomplete));
+        }
+
         self.db.update_task_status(active.id, "completed")?;
         self.db.get_task(active.id)
     }
@@ -266,6 +286,86 @@ impl TaskManager {
         self.db.remove_dependency(task_id, depends_on)
     }
 
+    /// Set or clear the parent of a task.
+    pub fn set_parent(&mut self, id: i64, parent_id: Option<i64>) -> Result<()> {
+        self.check_parent(id, parent_id)?;
+        self.db.set_parent(id, parent_id)
+    }
+
+    /// Check that `set_parent(id, parent_id)` would succeed, without changing
+    /// anything.
+    pub fn check_parent(&self, id: i64, parent_id: Option<i64>) -> Result<()> {
+        let task = self.db.get_task(id)?;
+
+        if let Some(parent_id) = parent_id {
+            let parent = self.db.get_task(parent_id)?;
+            if parent_id == id {
+                return Err(TTError::CycleDetected(id, id, vec![id]));
+            }
+
+            // A completed parent implies completed children
+            if parent.status == "completed" && task.status != "completed" {
+                return Err(TTError::ParentCompleted(id, parent_id));
+            }
+
+            // The parent waits on the child, so check it like a dependency
+            graph::check_cycle(&self.db, parent_id, id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Get rolled-up progress over all descendants of a task.
+    pub fn progress(&self, id: i64) -> Result<Progress> {
+        self.db.get_task(id)?; // Verify task exists
+        self.db.get_progress(id)
+    }
+
+    /// Build the parent/child tree, rooted at `root` or at all top-level tasks.
+    pub fn tree(&self, root: Option<i64>) -> Result<Vec<TreeNode>> {
+        let roots = match root {
+            Some(id) => vec![self.db.get_task(id)?],
+            None => {
+                let mut tasks: Vec<Task> = self
+                    .db
+                    .get_all_tasks()?
+                    .into_iter()
+                    .filter(|t| t.parent_id.is_none())
+                    .collect();
+                tasks.sort_by(|a, b| {
+                    a.manual_order
+                        .partial_cmp(&b.manual_order)
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                        .then(a.id.cmp(&b.id))
+                });
+                tasks
+            }
+        };
+
+        roots.into_iter().map(|task| self.tree_node(task)).collect()
+    }
+
+    fn tree_node(&self, task: Task) -> Result<TreeNode> {
+        let children = self
+            .db
+            .get_children(task.id)?
+            .into_iter()
+            .map(|id| self.tree_node(self.db.get_task(id)?))
+            .collect::<Result<Vec<_>>>()?;
+
+        let progress = if children.is_empty() {
+            None
+        } else {
+            Some(self.db.get_progress(task.id)?)
+        };
+
+        Ok(TreeNode {
+            task,
+            progress,
+            children,
+        })
+    }
+
     /// Log an artifact for the active task.
     pub fn log_artifact(&mut self, name: &str, file_path: &str) -> Result<Artifact> {
         let active = self.db.get_active_task()?.ok_or(TTError::NoActiveTask)?;
@@ -306,6 +406,51 @@ impl TaskManager {
     pub fn reindex(&mut self) -> Result<()> {
         self.db.reindex_orders()
     }
+
+    /// Create a project.
+    pub fn create_project(&mut self, name: &str) -> Result<Project> {
+        if name.is_empty() || name.chars().any(char::is_whitespace) {
+            return Err(TTError::InvalidProjectName(name.to_string()));
+        }
+        if self.db.find_project(name)?.is_some() {
+            return Err(TTError::ProjectExists(name.to_string()));
+        }
+
+        let id = self.db.create_project(name)?;
+        self.db.get_project(id)
+    }
+
+    /// Make a project active: new tasks go into it, and listings, the
+    /// target and the current task are its own.
+    pub fn use_project(&mut self, name: &str) -> Result<Project> {
+        let project = self
+            .db
+            .find_project(name)?
+            .ok_or_else(|| TTError::ProjectNotFound(name.to_string()))?;
+        self.db.set_project(project.id)?;
+        Ok(project)
+    }
+
+    /// Work in a project for this session only, leaving the active project
+    /// of other sessions as it is.
+    pub fn pin_project(&mut self, name: &str) -> Result<Project> {
+        let project = self
+            .db
+            .find_project(name)?
+            .ok_or_else(|| TTError::ProjectNotFound(name.to_string()))?;
+        self.db.pin_project(project.id);
+        Ok(project)
+    }
+
+    /// Get the active project.
+    pub fn current_project(&self) -> Result<Project> {
+        self.db.get_project(self.db.project_id())
+    }
+
+    /// List all projects.
+    pub fn list_projects(&self) -> Result<Vec<Project>> {
+        self.db.get_projects()
+    }
 }
 
 #[cfg(test)]
@@ -368,6 +513,191 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_parent_requires_children_completed() {
+        let (mut mgr, _) = setup_manager();
+        let epic = mgr.add_task("Epic", None, Some("DoD"), None, None).unwrap();
+        let child = mgr
+            .add_task("Child", None, Some("DoD"), None, None)
+            .unwrap();
+        mgr.set_parent(child, Some(epic)).unwrap();
+
+        mgr.start_task(epic).unwrap();
+        let result = mgr.complete_task();
+        assert!(
+            matches!(result, Err(TTError::IncompleteChildren(id, ref ids)) if id == epic && ids == &vec![child])
+        );
+
+        mgr.stop_task().unwrap();
+        mgr.start_task(child).unwrap();
+        mgr.complete_task().unwrap();
+
+        mgr.start_task(epic).unwrap();
+        assert_eq!(mgr.complete_task().unwrap().status, "completed");
+    }
+
+    #[test]
+    fn test_set_parent_rejects_cycles() {
+        let (mut mgr, _) = setup_manager();
+        let a = mgr.add_task("A", None, None, None, None).unwrap();
+        let b = mgr.add_task("B", None, None, None, None).unwrap();
+
+        mgr.set_parent(b, Some(a)).unwrap();
+        assert!(mgr.set_parent(a, Some(b)).is_err());
+        assert!(mgr.set_parent(a, Some(a)).is_err());
+
+        // A child cannot depend on its parent, and vice versa for nesting
+        assert!(mgr.add_dependency(b, a).is_err());
+        mgr.set_parent(b, None).unwrap();
+        mgr.add_dependency(b, a).unwrap();
+        assert!(mgr.set_parent(b, Some(a)).is_err());
+    }
+
+    #[test]
+    fn test_set_parent_rejects_completed_parent() {
+        let (mut mgr, _) = setup_manager();
+        let done = mgr.add_task("Done", None, Some("DoD"), None, None).unwrap();
+        let child = mgr
+            .add_task("Child", None, Some("DoD"), None, None)
+            .unwrap();
+        mgr.start_task(done).unwrap();
+        mgr.complete_task().unwrap();
+
+        assert!(matches!(
+            mgr.set_parent(child, Some(done)),
+            Err(TTError::ParentCompleted(c, p)) if c == child && p == done
+        ));
+
+        // A finished child may still be filed under a finished parent
+        mgr.start_task(child).unwrap();
+        mgr.complete_task().unwrap();
+        mgr.set_parent(child, Some(done)).unwrap();
+    }
+
+    #[test]
+    fn test_tree_and_progress() {
+        let (mut mgr, _) = setup_manager();
+        let epic = mgr.add_task("Epic", None, None, None, None).unwrap();
+        let story = mgr.add_task("Story", None, None, None, None).unwrap();
+        let leaf1 = mgr
+            .add_task("Leaf 1", None, Some("DoD"), None, None)
+            .unwrap();
+        let leaf2 = mgr.add_task("Leaf 2", None, None, None, None).unwrap();
+        let loose = mgr.add_task("Loose", None, None, None, None).unwrap();
+        mgr.set_parent(story, Some(epic)).unwrap();
+        mgr.set_parent(leaf1, Some(story)).unwrap();
+        mgr.set_parent(leaf2, Some(story)).unwrap();
+
+        mgr.start_task(leaf1).unwrap();
+        mgr.complete_task().unwrap();
+
+        let tree = mgr.tree(None).unwrap();
+        let roots: Vec<i64> = tree.iter().map(|n| n.task.id).collect();
+        assert_eq!(roots, vec![epic, loose]);
+        assert_eq!(
+            tree[0].progress,
+            Some(Progress {
+                completed: 1,
+                total: 3
+            })
+        );
+        assert_eq!(tree[0].children[0].children.len(), 2);
+        assert_eq!(tree[1].progress, None);
+
+        let story_progress = mgr.progress(story).unwrap();
+        assert_eq!(story_progress.completed, 1);
+        assert_eq!(story_progress.total, 2);
+    }
+
+    #[test]
+    fn test_target_epic_includes_children() {
+        let (mut mgr, _) = setup_manager();
+        let epic = mgr.add_task("Epic", None, Some("DoD"), None, None).unwrap();
+        let child = mgr
+            .add_task("Child", None, Some("DoD"), None, None)
+            .unwrap();
+        mgr.set_parent(child, Some(epic)).unwrap();
+        mgr.set_target(epic).unwrap();
+
+        assert_eq!(mgr.next_task().unwrap().task.id, child);
+    }
+
+    #[test]
+    fn test_projects_scope_listing_and_target() {
+        let (mut mgr, _) = setup_manager();
+        let shared = mgr
+            .add_task("Shared API", None, Some("DoD"), None, None)
+            .unwrap();
+        mgr.set_target(shared).unwrap();
+
+        mgr.create_project("web").unwrap();
+        assert!(matches!(
+            mgr.create_project("web"),
+            Err(TTError::ProjectExists(_))
+        ));
+        assert!(matches!(
+            mgr.use_project("mobile"),
+            Err(TTError::ProjectNotFound(_))
+        ));
+        let web = mgr.use_project("web").unwrap();
+        assert_eq!(mgr.current_project().unwrap().id, web.id);
+
+        // The new project starts empty, with no target of its own
+        assert!(mgr.list_tasks(true).unwrap().is_empty());
+        assert!(matches!(mgr.get_target(), Err(TTError::NoTarget)));
+
+        let page = mgr.add_task("Page", None, Some("DoD"), None, None).unwrap();
+        assert_eq!(mgr.db().get_task(page).unwrap().project_id, web.id);
+        let ids: Vec<i64> = mgr
+            .list_tasks(true)
+            .unwrap()
+            .iter()
+            .map(|t| t.task.id)
+            .collect();
+        assert_eq!(ids, vec![page]);
+
+        // Dependencies may cross projects
+        mgr.add_dependency(page, shared).unwrap();
+        mgr.set_target(page).unwrap();
+        assert_eq!(mgr.next_task().unwrap().task.id, shared);
+
+        mgr.use_project("default").unwrap();
+        assert_eq!(mgr.get_target().unwrap(), shared);
+        let ids: Vec<i64> = mgr
+            .list_tasks(true)
+            .unwrap()
+            .iter()
+            .map(|t| t.task.id)
+            .collect();
+        assert_eq!(ids, vec![shared]);
+    }
+
+    #[test]
+    fn test_start_task_refuses_other_projects() {
+        let (mut mgr, _) = setup_manager();
+        let busy = mgr.add_task("Busy", None, None, None, None).unwrap();
+        let idle = mgr.add_task("Idle", None, None, None, None).unwrap();
+        mgr.start_task(busy).unwrap();
+
+        mgr.create_project("web").unwrap();
+        mgr.use_project("web").unwrap();
+        let page = mgr.add_task("Page", None, None, None, None).unwrap();
+
+        // The default project already has a task in progress
+        assert!(matches!(
+            mgr.start_task(idle),
+            Err(TTError::TaskInOtherProject(id, ref name)) if id == idle && name == "default"
+        ));
+        assert_eq!(mgr.db().get_task(idle).unwrap().status, "pending");
+
+        // What gets started is always what `current` shows
+        mgr.start_task(page).unwrap();
+        assert_eq!(mgr.get_current_task().unwrap().task.id, page);
+
+        mgr.use_project("default").unwrap();
+        assert_eq!(mgr.get_current_task().unwrap().task.id, busy);
+    }
+
     #[test]
     fn test_workflow() {
         let (mut mgr, _) = setup_manager();
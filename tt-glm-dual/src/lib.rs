@@ -7,4 +7,7 @@ pub mod core;
 
 // MCP server module (private to library, used by main)
 mod mcp_impl;
+mod mcp_prompts;
+mod mcp_tools;
 pub use mcp_impl::run_mcp;
+pub use mcp_tools::ToolSet;
@@ -1,150 +1,434 @@
 //! MCP server for the tt task tracker.
 //!
-//! Provides a stdio-based MCP server exposing all tt operations as tools.
+//! Speaks MCP over stdio as newline-delimited JSON-RPC 2.0: the `initialize`
+//! handshake, `tools/list` and `tools/call` for every tt operation, workflow
+//! prompts (see `mcp_prompts`), `ping`, `logging/setLevel` and request
+//! cancellation. Notifications never get a reply.
 
-use crate::core::error::Result;
+use crate::core::db::Db;
+use crate::core::error::{Result, TTError};
 use crate::core::task::TaskManager;
-use serde_json::Value;
-use std::sync::{Arc, Mutex};
+use crate::mcp_prompts;
+use crate::mcp_tools::{self, ToolSet};
+use serde_json::{Map, Value};
+use std::collections::{HashSet, VecDeque};
+use std::sync::mpsc;
+
+/// Protocol versions this server speaks, newest first.
+const PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+/// Levels accepted by `logging/setLevel`, least severe first.
+const LOG_LEVELS: &[&str] = &[
+    "debug",
+    "info",
+    "notice",
+    "warning",
+    "error",
+    "critical",
+    "alert",
+    "emergency",
+];
+
+// JSON-RPC 2.0 error codes.
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+
+/// Run the MCP server over stdio, exposing only the given tools.
+///
+/// The session stays in `project`, or the active project at startup, so a
+/// `tt project use` elsewhere does not move it mid-task.
+pub fn run_mcp(tools: ToolSet, project: Option<&str>) -> Result<()> {
+    use std::io::{self, BufRead, Write};
 
-/// Run the MCP server over stdio.
-pub fn run_mcp() -> Result<()> {
     eprintln!("tt MCP server starting...");
 
-    // For now, we'll use a simple manual JSON-RPC implementation
-    // since rmcp API is still evolving
-    run_simple_mcp()
-}
-
-/// Simple MCP server using manual JSON-RPC over stdio.
-fn run_simple_mcp() -> Result<()> {
-    use std::io::{self, BufRead, Write};
-
     let db_path = std::path::PathBuf::from("tt.db");
     if !db_path.exists() {
-        return Err(crate::core::error::TTError::Io(std::io::Error::new(
+        return Err(TTError::Io(std::io::Error::new(
             io::ErrorKind::NotFound,
             "tt.db not found",
         )));
     }
-
-    let db = crate::core::db::Db::open(&db_path)?;
-    let mgr = Arc::new(Mutex::new(TaskManager::new(db)));
-
-    let stdin = io::stdin();
-    let stdout = io::stdout();
-    let mut reader = stdin.lock();
-    let mut writer = stdout.lock();
-
-    // MCP protocol: read JSON-RPC messages line by line
-    let mut buffer = String::new();
-
-    loop {
-        buffer.clear();
-        match reader.read_line(&mut buffer) {
-            Ok(0) => break, // EOF
-            Ok(_) => {
-                let response = match handle_mcp_request(buffer.trim(), mgr.clone()) {
-                    Ok(resp) => resp,
-                    Err(e) => format_mcp_error(&e.to_string(), e.error_code()),
-                };
-
-                if let Err(e) = writeln!(writer, "{}", response) {
-                    eprintln!("Error writing response: {}", e);
+    let mut mgr = TaskManager::new(Db::open(&db_path)?);
+    if let Some(name) = project {
+        mgr.pin_project(name)?;
+    }
+    let mut session = Session::new(mgr, tools);
+
+    // Read on a separate thread so that lines which arrived while a request
+    // was running are visible, and cancellations among them take effect.
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            match line {
+                Ok(line) => {
+                    if tx.send(line).is_err() {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Error reading request: {}", e);
                     break;
                 }
-                let _ = writer.flush();
             }
-            Err(e) => {
-                eprintln!("Error reading request: {}", e);
-                break;
+        }
+    });
+
+    let mut writer = io::stdout().lock();
+    let mut pending = VecDeque::new();
+    loop {
+        if pending.is_empty() {
+            match rx.recv() {
+                Ok(line) => pending.push_back(line),
+                Err(_) => break, // EOF
+            }
+        }
+        for line in rx.try_iter() {
+            session.note_cancellation(&line);
+            pending.push_back(line);
+        }
+
+        let Some(line) = pending.pop_front() else {
+            continue;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        for message in session.handle_line(&line) {
+            if let Err(e) = writeln!(writer, "{}", message) {
+                eprintln!("Error writing response: {}", e);
+                return Ok(());
             }
         }
+        let _ = writer.flush();
     }
 
     Ok(())
 }
 
-/// Handle an MCP request.
-fn handle_mcp_request(request: &str, mgr: Arc<Mutex<TaskManager>>) -> Result<String> {
-    let json: Value = serde_json::from_str(request)
-        .map_err(|e| crate::core::error::TTError::Mcp(format!("Invalid JSON: {}", e)))?;
-
-    let method = json
-        .get("method")
-        .and_then(|m| m.as_str())
-        .ok_or_else(|| crate::core::error::TTError::Mcp("Missing method".to_string()))?;
-
-    let params = json.get("params").and_then(|p| p.as_object());
-    let id = json.get("id").cloned().unwrap_or(Value::Null);
-
-    let (status, data) = match handle_method(method, params, mgr) {
-        Ok(result) => ("ok", result),
-        Err(e) => {
-            return Ok(format_mcp_error_response(
-                &e.to_string(),
-                e.error_code(),
-                &id,
-            ))
-        }
-    };
-
-    let response = serde_json::json!({
-        "jsonrpc": "2.0",
-        "id": id,
-        "result": {
-            "status": status,
-            "data": data
+/// A JSON-RPC error object.
+#[derive(Debug)]
+struct RpcError {
+    code: i64,
+    message: String,
+    data: Option<Value>,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
         }
-    });
+    }
+}
 
-    Ok(serde_json::to_string(&response)?)
+impl From<TTError> for RpcError {
+    /// Bad arguments are invalid params; anything else is an internal error.
+    /// The tt error code is kept in `data.error_code`.
+    fn from(e: TTError) -> Self {
+        let code = match e {
+            TTError::Mcp(_)
+            | TTError::UnknownTool(_)
+            | TTError::ToolNotAllowed(_)
+            | TTError::TaskNotFound(_) => INVALID_PARAMS,
+            _ => INTERNAL_ERROR,
+        };
+        Self {
+            code,
+            message: e.to_string(),
+            data: Some(serde_json::json!({ "error_code": e.error_code() })),
+        }
+    }
 }
 
-/// Handle a specific method call.
-fn handle_method(
-    method: &str,
-    params: Option<&serde_json::Map<String, Value>>,
-    mgr: Arc<Mutex<TaskManager>>,
-) -> Result<Value> {
-    match method {
-        "get_next_task" => {
-            let mgr = mgr.lock().unwrap();
-            match mgr.next_task() {
-                Ok(task) => Ok(serde_json::to_value(task_with_deps_to_json(&task))?),
-                Err(e) => match &e {
-                    crate::core::error::TTError::TargetReached(id) => {
-                        Ok(serde_json::json!({"type": "target_reached", "id": id}))
-                    }
-                    crate::core::error::TTError::AllBlocked(ids) => {
-                        Ok(serde_json::json!({"type": "all_blocked", "ids": ids}))
-                    }
-                    _ => Err(e),
-                },
+/// The state of one client connection.
+pub(crate) struct Session {
+    mgr: TaskManager,
+    tools: ToolSet,
+    /// Set once `initialize` has been answered.
+    initialized: bool,
+    /// Index into `LOG_LEVELS` of the least severe message to send. No log
+    /// messages are sent until the client sets a level.
+    log_level: Option<usize>,
+    /// Ids of requests the client cancelled before they were handled.
+    cancelled: HashSet<String>,
+    /// Notifications to send before the response to the current message.
+    outbox: Vec<Value>,
+}
+
+impl Session {
+    pub(crate) fn new(mgr: TaskManager, tools: ToolSet) -> Self {
+        Self {
+            mgr,
+            tools,
+            initialized: false,
+            log_level: None,
+            cancelled: HashSet::new(),
+            outbox: Vec::new(),
+        }
+    }
+
+    /// Record a `notifications/cancelled` that arrived ahead of its turn, so
+    /// the request it names is skipped if it has not been handled yet.
+    pub(crate) fn note_cancellation(&mut self, line: &str) {
+        if let Some(id) = cancelled_request(line) {
+            self.cancelled.insert(id);
+        }
+    }
+
+    /// Handle one line from the client, returning the messages to send back.
+    pub(crate) fn handle_line(&mut self, line: &str) -> Vec<Value> {
+        let response = match serde_json::from_str::<Value>(line) {
+            Ok(message) => self.handle_message(&message),
+            Err(e) => Some(error_response(
+                &Value::Null,
+                RpcError::new(PARSE_ERROR, format!("Parse error: {}", e)),
+            )),
+        };
+
+        let mut messages = std::mem::take(&mut self.outbox);
+        messages.extend(response);
+        messages
+    }
+
+    /// Handle a parsed message; only requests get a response.
+    fn handle_message(&mut self, message: &Value) -> Option<Value> {
+        let Some(message) = message.as_object() else {
+            return Some(error_response(
+                &Value::Null,
+                RpcError::new(INVALID_REQUEST, "Invalid Request: expected an object"),
+            ));
+        };
+        let method = message.get("method").and_then(|m| m.as_str());
+
+        let Some(id) = message.get("id") else {
+            if let Some(method) = method {
+                self.handle_notification(method, message.get("params"));
+            }
+            return None;
+        };
+
+        let Some(method) = method else {
+            // A response to a request we never send.
+            if message.contains_key("result") || message.contains_key("error") {
+                return None;
+            }
+            return Some(error_response(
+                id,
+                RpcError::new(INVALID_REQUEST, "Invalid Request: missing method"),
+            ));
+        };
+        if message.get("jsonrpc").and_then(|v| v.as_str()) != Some("2.0") {
+            return Some(error_response(
+                id,
+                RpcError::new(INVALID_REQUEST, "Invalid Request: jsonrpc must be \"2.0\""),
+            ));
+        }
+        if self.cancelled.remove(&id.to_string()) {
+            return None;
+        }
+
+        let params = match message.get("params") {
+            None | Some(Value::Null) => None,
+            Some(Value::Object(params)) => Some(params),
+            Some(_) => {
+                return Some(error_response(
+                    id,
+                    RpcError::new(INVALID_PARAMS, "Invalid params: expected an object"),
+                ))
+            }
+        };
+
+        Some(match self.handle_request(method, params) {
+            Ok(result) => serde_json::json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(e) => error_response(id, e),
+        })
+    }
+
+    fn handle_notification(&mut self, method: &str, params: Option<&Value>) {
+        if method == "notifications/cancelled" {
+            // The request came before this notification, so it has been
+            // handled by now, whether or not it was skipped.
+            let id = params.and_then(|p| p.get("requestId"));
+            if let Some(id) = id {
+                self.cancelled.remove(&id.to_string());
+            }
+        }
+        // `notifications/initialized` and anything unknown need no action.
+    }
+
+    fn handle_request(
+        &mut self,
+        method: &str,
+        params: Option<&Map<String, Value>>,
+    ) -> std::result::Result<Value, RpcError> {
+        match method {
+            "initialize" => return Ok(self.initialize(params)),
+            "ping" => return Ok(serde_json::json!({})),
+            _ if !self.initialized => {
+                return Err(RpcError::new(INVALID_REQUEST, "Server not initialized"))
+            }
+            _ => {}
+        }
+
+        match method {
+            "tools/list" => Ok(self.tools.list()),
+            "tools/call" => self.call_tool(params),
+            "prompts/list" => Ok(mcp_prompts::list()),
+            "prompts/get" => {
+                let name = get_param_str(params, "name")?;
+                let arguments = params
+                    .and_then(|p| p.get("arguments"))
+                    .and_then(|v| v.as_object());
+                Ok(mcp_prompts::get(&self.mgr, &name, arguments)?)
             }
+            "logging/setLevel" => {
+                let level = get_param_str(params, "level")?;
+                let index = LOG_LEVELS.iter().position(|l| *l == level).ok_or_else(|| {
+                    RpcError::new(INVALID_PARAMS, format!("Unknown log level: {}", level))
+                })?;
+                self.log_level = Some(index);
+                Ok(serde_json::json!({}))
+            }
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("Method not found: {}", method),
+            )),
+        }
+    }
+
+    /// Answer `initialize`, agreeing on the client's protocol version if we
+    /// speak it and offering our newest one otherwise.
+    fn initialize(&mut self, params: Option<&Map<String, Value>>) -> Value {
+        let requested = params
+            .and_then(|p| p.get("protocolVersion"))
+            .and_then(|v| v.as_str());
+        let version = requested
+            .filter(|v| PROTOCOL_VERSIONS.contains(v))
+            .unwrap_or(PROTOCOL_VERSIONS[0]);
+        self.initialized = true;
+
+        serde_json::json!({
+            "protocolVersion": version,
+            "capabilities": {
+                "tools": { "listChanged": false },
+                "prompts": { "listChanged": false },
+                "logging": {},
+            },
+            "serverInfo": {
+                "name": "tt",
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+            "instructions": "Work toward the target task: call get_next_task, start_task, \
+                do the work, log_artifact for files you create, then complete_task.",
+        })
+    }
+
+    /// Run a tool. Failures of the tool itself are reported in the result
+    /// with `isError`, so the model can see and react to them.
+    fn call_tool(
+        &mut self,
+        params: Option<&Map<String, Value>>,
+    ) -> std::result::Result<Value, RpcError> {
+        let name = get_param_str(params, "name")?;
+        if !mcp_tools::is_tool(&name) {
+            return Err(TTError::UnknownTool(name).into());
+        }
+        if !self.tools.allows(&name) {
+            return Err(TTError::ToolNotAllowed(name).into());
+        }
+        let arguments = params
+            .and_then(|p| p.get("arguments"))
+            .and_then(|v| v.as_object());
+
+        self.log("debug", serde_json::json!({ "tool": name }));
+        let (content, is_error) = match run_tool(&name, arguments, &mut self.mgr) {
+            Ok(data) => (serde_json::json!({ "status": "ok", "data": data }), false),
+            Err(e) => {
+                let content = serde_json::json!({
+                    "status": "error",
+                    "error_code": e.error_code(),
+                    "message": e.to_string(),
+                });
+                self.log(
+                    "warning",
+                    serde_json::json!({ "tool": name, "error": content }),
+                );
+                (content, true)
+            }
+        };
+
+        Ok(serde_json::json!({
+            "content": [{ "type": "text", "text": content.to_string() }],
+            "structuredContent": content,
+            "isError": is_error,
+        }))
+    }
+
+    /// Queue a `notifications/message` if the client asked for `level`.
+    fn log(&mut self, level: &str, data: Value) {
+        let index = LOG_LEVELS.iter().position(|l| *l == level);
+        if self.log_level.is_some_and(|min| index >= Some(min)) {
+            self.outbox.push(serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "notifications/message",
+                "params": { "level": level, "logger": "tt", "data": data },
+            }));
         }
+    }
+}
+
+/// The request id named by a `notifications/cancelled` line, as JSON text.
+fn cancelled_request(line: &str) -> Option<String> {
+    let message: Value = serde_json::from_str(line).ok()?;
+    if message.get("method")?.as_str()? != "notifications/cancelled" {
+        return None;
+    }
+    Some(message.get("params")?.get("requestId")?.to_string())
+}
+
+/// Run a tool against the task manager.
+fn run_tool(
+    name: &str,
+    params: Option<&Map<String, Value>>,
+    mgr: &mut TaskManager,
+) -> Result<Value> {
+    match name {
+        "get_next_task" => match mgr.next_task() {
+            Ok(task) => Ok(serde_json::to_value(task_with_deps_to_json(&task))?),
+            Err(e) => match &e {
+                TTError::TargetReached(id) => {
+                    Ok(serde_json::json!({"type": "target_reached", "id": id}))
+                }
+                TTError::AllBlocked(ids) => {
+                    Ok(serde_json::json!({"type": "all_blocked", "ids": ids}))
+                }
+                _ => Err(e),
+            },
+        },
 
         "get_current_task" => {
-            let mgr = mgr.lock().unwrap();
             let task = mgr.get_current_task()?;
             Ok(serde_json::to_value(task_with_deps_to_json(&task))?)
         }
 
         "start_task" => {
             let id = get_param(params, "id")?;
-            let mut mgr = mgr.lock().unwrap();
             let task = mgr.start_task(id)?;
             Ok(serde_json::to_value(task_to_json(&task))?)
         }
 
         "complete_task" => {
-            let mut mgr = mgr.lock().unwrap();
             let task = mgr.complete_task()?;
             Ok(serde_json::to_value(task_to_json(&task))?)
         }
 
         "stop_task" => {
-            let mut mgr = mgr.lock().unwrap();
             let task = mgr.stop_task()?;
             Ok(serde_json::to_value(task_to_json(&task))?)
         }
@@ -161,9 +445,17 @@ fn handle_method(
             let before_id = params
                 .and_then(|p| p.get("before_id"))
                 .and_then(|v| v.as_i64());
+            let parent_id = params
+                .and_then(|p| p.get("parent_id"))
+                .and_then(|v| v.as_i64());
 
-            let mut mgr = mgr.lock().unwrap();
+            if let Some(parent_id) = parent_id {
+                mgr.db().get_task(parent_id)?; // Verify parent exists before creating
+            }
             let id = mgr.add_task(&title, description, dod, after_id, before_id)?;
+            if parent_id.is_some() {
+                mgr.set_parent(id, parent_id)?;
+            }
             let task = mgr.show_task(id)?;
             Ok(serde_json::to_value(task_with_deps_to_json(&task))?)
         }
@@ -176,7 +468,6 @@ fn handle_method(
                 .and_then(|v| v.as_str());
             let dod = params.and_then(|p| p.get("dod")).and_then(|v| v.as_str());
 
-            let mut mgr = mgr.lock().unwrap();
             mgr.edit_task(id, title, description, dod)?;
             let task = mgr.show_task(id)?;
             Ok(serde_json::to_value(task_with_deps_to_json(&task))?)
@@ -185,7 +476,6 @@ fn handle_method(
         "add_dependency" => {
             let task_id = get_param(params, "task_id")?;
             let depends_on = get_param(params, "depends_on")?;
-            let mut mgr = mgr.lock().unwrap();
             mgr.add_dependency(task_id, depends_on)?;
             Ok(serde_json::json!({"success": true}))
         }
@@ -193,21 +483,34 @@ fn handle_method(
         "remove_dependency" => {
             let task_id = get_param(params, "task_id")?;
             let depends_on = get_param(params, "depends_on")?;
-            let mut mgr = mgr.lock().unwrap();
             mgr.remove_dependency(task_id, depends_on)?;
             Ok(serde_json::json!({"success": true}))
         }
 
+        "set_parent" => {
+            let id = get_param(params, "id")?;
+            let parent_id = params
+                .and_then(|p| p.get("parent_id"))
+                .and_then(|v| v.as_i64());
+            mgr.set_parent(id, parent_id)?;
+            let task = mgr.show_task(id)?;
+            Ok(serde_json::to_value(task_with_deps_to_json(&task))?)
+        }
+
+        "get_tree" => {
+            let id = params.and_then(|p| p.get("id")).and_then(|v| v.as_i64());
+            let tree = mgr.tree(id)?;
+            Ok(serde_json::to_value(tree)?)
+        }
+
         "block_task" => {
             let id = get_param(params, "id")?;
-            let mut mgr = mgr.lock().unwrap();
             let task = mgr.block_task(id)?;
             Ok(serde_json::to_value(task_to_json(&task))?)
         }
 
         "unblock_task" => {
             let id = get_param(params, "id")?;
-            let mut mgr = mgr.lock().unwrap();
             let task = mgr.unblock_task(id)?;
             Ok(serde_json::to_value(task_to_json(&task))?)
         }
@@ -217,7 +520,6 @@ fn handle_method(
                 .and_then(|p| p.get("all"))
                 .and_then(|v| v.as_bool())
                 .unwrap_or(false);
-            let mgr = mgr.lock().unwrap();
             let tasks = mgr.list_tasks(all)?;
             let json: Vec<Value> = tasks.iter().map(task_with_deps_to_json).collect();
             Ok(serde_json::to_value(json)?)
@@ -225,7 +527,6 @@ fn handle_method(
 
         "show_task" => {
             let id = get_param(params, "id")?;
-            let mgr = mgr.lock().unwrap();
             let task = mgr.show_task(id)?;
             Ok(serde_json::to_value(task_with_deps_to_json(&task))?)
         }
@@ -233,7 +534,6 @@ fn handle_method(
         "log_artifact" => {
             let name = get_param_str(params, "name")?;
             let file_path = get_param_str(params, "file_path")?;
-            let mut mgr = mgr.lock().unwrap();
             let artifact = mgr.log_artifact(&name, &file_path)?;
             Ok(serde_json::to_value(artifact_to_json(&artifact))?)
         }
@@ -242,7 +542,6 @@ fn handle_method(
             let task_id = params
                 .and_then(|p| p.get("task_id"))
                 .and_then(|v| v.as_i64());
-            let mgr = mgr.lock().unwrap();
             let artifacts = mgr.get_artifacts(task_id)?;
             let json: Vec<Value> = artifacts.iter().map(artifact_to_json).collect();
             Ok(serde_json::to_value(json)?)
@@ -250,7 +549,6 @@ fn handle_method(
 
         "set_target" => {
             let id = get_param(params, "id")?;
-            let mut mgr = mgr.lock().unwrap();
             mgr.set_target(id)?;
             Ok(serde_json::json!({"success": true, "target_id": id}))
         }
@@ -263,37 +561,29 @@ fn handle_method(
             let before_id = params
                 .and_then(|p| p.get("before_id"))
                 .and_then(|v| v.as_i64());
-            let mut mgr = mgr.lock().unwrap();
             mgr.reorder_task(id, after_id, before_id)?;
             Ok(serde_json::json!({"success": true}))
         }
 
-        _ => Err(crate::core::error::TTError::Mcp(format!(
-            "Unknown method: {}",
-            method
-        ))),
+        _ => Err(TTError::UnknownTool(name.to_string())),
     }
 }
 
 /// Get a parameter as i64.
-fn get_param(params: Option<&serde_json::Map<String, Value>>, key: &str) -> Result<i64> {
+fn get_param(params: Option<&Map<String, Value>>, key: &str) -> Result<i64> {
     params
         .and_then(|p| p.get(key))
         .and_then(|v| v.as_i64())
-        .ok_or_else(|| {
-            crate::core::error::TTError::Mcp(format!("Missing or invalid parameter: {}", key))
-        })
+        .ok_or_else(|| TTError::Mcp(format!("Missing or invalid parameter: {}", key)))
 }
 
 /// Get a parameter as String.
-fn get_param_str(params: Option<&serde_json::Map<String, Value>>, key: &str) -> Result<String> {
+fn get_param_str(params: Option<&Map<String, Value>>, key: &str) -> Result<String> {
     params
         .and_then(|p| p.get(key))
         .and_then(|v| v.as_str())
         .map(|s| s.to_string())
-        .ok_or_else(|| {
-            crate::core::error::TTError::Mcp(format!("Missing or invalid parameter: {}", key))
-        })
+        .ok_or_else(|| TTError::Mcp(format!("Missing or invalid parameter: {}", key)))
 }
 
 /// Convert TaskWithDeps to JSON.
@@ -309,8 +599,11 @@ fn task_with_deps_to_json(t: &crate::core::db::TaskWithDeps) -> Value {
         "started_at": t.task.started_at,
         "completed_at": t.task.completed_at,
         "last_touched_at": t.task.last_touched_at,
+        "parent_id": t.task.parent_id,
+        "project_id": t.task.project_id,
         "dependencies": t.dependencies,
         "dependents": t.dependents,
+        "children": t.children,
         "artifacts": t.artifacts.iter().map(artifact_to_json).collect::<Vec<_>>(),
     })
 }
@@ -328,6 +621,8 @@ fn task_to_json(t: &crate::core::db::Task) -> Value {
         "started_at": t.started_at,
         "completed_at": t.completed_at,
         "last_touched_at": t.last_touched_at,
+        "parent_id": t.parent_id,
+        "project_id": t.project_id,
     })
 }
 
@@ -342,26 +637,91 @@ fn artifact_to_json(a: &crate::core::db::Artifact) -> Value {
     })
 }
 
-/// Format an MCP error response.
-fn format_mcp_error_response(message: &str, code: &str, id: &Value) -> String {
-    let response = serde_json::json!({
-        "jsonrpc": "2.0",
-        "id": id,
-        "error": {
-            "code": code,
-            "message": message
-        }
-    });
-
-    serde_json::to_string(&response).unwrap_or_else(|_| {
-        format!(
-            r#"{{"jsonrpc":"2.0","id":{},"error":{{"code":"{}","message":"{}"}}}}"#,
-            id, code, message
-        )
-    })
+/// Format a JSON-RPC error response.
+fn error_response(id: &Value, error: RpcError) -> Value {
+    let mut body = serde_json::json!({ "code": error.code, "message": error.message });
+    if let Some(data) = error.data {
+        body["data"] = data;
+    }
+    serde_json::json!({ "jsonrpc": "2.0", "id": id, "error": body })
 }
 
-/// Format an MCP error (without request id).
-fn format_mcp_error(message: &str, code: &str) -> String {
-    format_mcp_error_response(message, code, &Value::Null)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::db::DEFAULT_PROJECT_ID;
+    use tempfile::NamedTempFile;
+
+    fn setup_session() -> (Session, NamedTempFile) {
+        let temp = NamedTempFile::new().unwrap();
+        let db = Db::open(temp.path()).unwrap();
+        db.init_schema().unwrap();
+        let mut session = Session::new(TaskManager::new(db), ToolSet::all());
+        session.handle_line(r#"{"jsonrpc":"2.0","id":0,"method":"initialize","params":{}}"#);
+        (session, temp)
+    }
+
+    const CREATE: &str = r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"create_task","arguments":{"title":"A"}}}"#;
+    const CANCEL: &str =
+        r#"{"jsonrpc":"2.0","method":"notifications/cancelled","params":{"requestId":1}}"#;
+
+    #[test]
+    fn test_cancelled_request_is_skipped() {
+        let (mut session, _temp) = setup_session();
+
+        // The cancellation was read ahead of the request it names.
+        session.note_cancellation(CANCEL);
+        assert!(session.handle_line(CREATE).is_empty());
+        assert!(session.handle_line(CANCEL).is_empty());
+        assert!(session.mgr.list_tasks(true).unwrap().is_empty());
+
+        // Once handled, the id may be reused.
+        let responses = session.handle_line(CREATE);
+        assert_eq!(responses.len(), 1);
+        assert_eq!(responses[0]["result"]["isError"], false);
+    }
+
+    #[test]
+    fn test_late_cancellation_is_ignored() {
+        let (mut session, _temp) = setup_session();
+
+        assert_eq!(session.handle_line(CREATE).len(), 1);
+        session.note_cancellation(CANCEL);
+        assert!(session.handle_line(CANCEL).is_empty());
+        assert!(session.cancelled.is_empty());
+    }
+
+    #[test]
+    fn test_project_switch_elsewhere_is_ignored() {
+        let (mut session, temp) = setup_session();
+
+        let mut other = Db::open(temp.path()).unwrap();
+        let project = other.create_project("other").unwrap();
+        other.set_project(project).unwrap();
+
+        assert_eq!(session.handle_line(CREATE).len(), 1);
+        let tasks = session.mgr.list_tasks(true).unwrap();
+        assert_eq!(tasks[0].task.project_id, DEFAULT_PROJECT_ID);
+    }
+
+    #[test]
+    fn test_log_level_filters_messages() {
+        let (mut session, _temp) = setup_session();
+
+        session.handle_line(
+            r#"{"jsonrpc":"2.0","id":1,"method":"logging/setLevel","params":{"level":"error"}}"#,
+        );
+        let stop =
+            r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"stop_task"}}"#;
+        assert_eq!(session.handle_line(stop).len(), 1);
+
+        session.handle_line(
+            r#"{"jsonrpc":"2.0","id":3,"method":"logging/setLevel","params":{"level":"info"}}"#,
+        );
+        let messages = session.handle_line(stop);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0]["method"], "notifications/message");
+        assert_eq!(messages[0]["params"]["level"], "warning");
+        assert_eq!(messages[1]["id"], 2);
+    }
 }
@@ -0,0 +1,436 @@
+//! MCP prompts for the tt workflow.
+//!
+//! Prompts are rendered from live task data so that the agent starts with
+//! the current state of the target, task and artifacts.
+
+use crate::core::db::{Artifact, TaskSet, TaskWithDeps};
+use crate::core::error::{Result, TTError};
+use crate::core::graph::TaskGraph;
+use crate::core::task::TaskManager;
+use serde_json::{Map, Value};
+use std::fmt::Write;
+
+/// A prompt argument as (name, description, required).
+type PromptArg = (&'static str, &'static str, bool);
+
+/// Prompt names and descriptions, with their arguments.
+const PROMPTS: &[(&str, &str, &[PromptArg])] = &[
+    (
+        "work-on-target",
+        "Work through the target's tasks until the target is reached",
+        &[(
+            "target_id",
+            "Target task ID (defaults to the current target)",
+            false,
+        )],
+    ),
+    (
+        "decompose-task",
+        "Split a task into subtasks, each with a definition of done",
+        &[("id", "ID of the task to decompose", true)],
+    ),
+    (
+        "write-dod",
+        "Draft acceptance criteria for a task",
+        &[("id", "ID of the task", true)],
+    ),
+];
+
+/// List the available prompts.
+pub fn list() -> Value {
+    let prompts: Vec<Value> = PROMPTS
+        .iter()
+        .map(|(name, description, args)| {
+            let arguments: Vec<Value> = args
+                .iter()
+                .map(|(name, description, required)| {
+                    serde_json::json!({
+                        "name": name,
+                        "description": description,
+                        "required": required,
+                    })
+                })
+                .collect();
+            serde_json::json!({
+                "name": name,
+                "description": description,
+                "arguments": arguments,
+            })
+        })
+        .collect();
+    serde_json::json!({ "prompts": prompts })
+}
+
+/// Render a prompt with the given arguments.
+pub fn get(mgr: &TaskManager, name: &str, args: Option<&Map<String, Value>>) -> Result<Value> {
+    let (_, description, _) = PROMPTS
+        .iter()
+        .find(|(n, _, _)| *n == name)
+        .ok_or_else(|| TTError::Mcp(format!("Unknown prompt: {}", name)))?;
+
+    let text = match name {
+        "work-on-target" => {
+            let target_id = match get_arg(args, "target_id")? {
+                Some(id) => id,
+                None => mgr.get_target()?,
+            };
+            work_on_target(mgr, target_id)?
+        }
+        "decompose-task" => decompose_task(mgr, require_arg(args, "id")?)?,
+        _ => write_dod(mgr, require_arg(args, "id")?)?,
+    };
+
+    Ok(serde_json::json!({
+        "description": description,
+        "messages": [{
+            "role": "user",
+            "content": { "type": "text", "text": text },
+        }],
+    }))
+}
+
+/// Prompt arguments are strings per the MCP spec, but numbers are accepted too.
+fn get_arg(args: Option<&Map<String, Value>>, key: &str) -> Result<Option<i64>> {
+    let invalid = || TTError::Mcp(format!("Invalid argument: {}", key));
+    match args.and_then(|a| a.get(key)) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(s)) => s.trim().parse().map(Some).map_err(|_| invalid()),
+        Some(v) => v.as_i64().map(Some).ok_or_else(invalid),
+    }
+}
+
+fn require_arg(args: Option<&Map<String, Value>>, key: &str) -> Result<i64> {
+    get_arg(args, key)?.ok_or_else(|| TTError::Mcp(format!("Missing argument: {}", key)))
+}
+
+fn work_on_target(mgr: &TaskManager, target_id: i64) -> Result<String> {
+    let target = mgr.show_task(target_id)?;
+    let current_target = mgr.get_target().ok();
+
+    let sorted =
+        TaskGraph::load_incomplete(mgr.db(), TaskSet::Subgraph(target_id))?.topological_sort()?;
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "Work on target #{}: {}\n",
+        target.task.id, target.task.title
+    );
+    write_details(&mut out, &target);
+
+    if current_target != Some(target_id) {
+        let _ = writeln!(
+            out,
+            "\nThis is not the current target. Call `set_target` with id {} first.",
+            target_id
+        );
+    }
+
+    if sorted.is_empty() {
+        let _ = writeln!(out, "\nAll tasks for this target are completed.");
+    } else {
+        let _ = writeln!(out, "\nRemaining tasks in dependency order:");
+        for t in &sorted {
+            let deps = mgr.db().get_dependencies(t.task.id)?;
+            let _ = write!(out, "- #{} [{}] {}", t.task.id, t.task.status, t.task.title);
+            if !deps.is_empty() {
+                let _ = write!(out, " (depends on {})", id_list(&deps));
+            }
+            if t.task.dod.is_none() {
+                let _ = write!(out, " — no DoD");
+            }
+            let _ = writeln!(out);
+        }
+    }
+
+    if let Ok(current) = mgr.get_current_task() {
+        let _ = writeln!(
+            out,
+            "\nTask #{} ({}) is already in progress. Finish it before starting another.",
+            current.task.id, current.task.title
+        );
+    }
+
+    out.push_str(
+        "\nRepeat this loop until `get_next_task` reports that the target is reached:\n\
+         1. Call `get_next_task` to get the next unblocked task.\n\
+         2. Call `start_task` with its id.\n\
+         3. Research, plan and implement the task until its definition of done is met.\n\
+         4. Write notes to `.tt/artifacts/<id>-<name>.md` and record them with `log_artifact`.\n\
+         5. Call `complete_task`.\n\
+         If a task cannot proceed, call `block_task` and move on. \
+         If a task is too large, decompose it into subtasks first.\n",
+    );
+    Ok(out)
+}
+
+fn decompose_task(mgr: &TaskManager, id: i64) -> Result<String> {
+    let task = mgr.show_task(id)?;
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "Decompose task #{}: {}\n",
+        task.task.id, task.task.title
+    );
+    write_details(&mut out, &task);
+    write_related(&mut out, mgr, &task)?;
+
+    let _ = write!(
+        out,
+        "\nSplit #{id} into small subtasks that can each be completed in one session.\n\
+         For every subtask:\n\
+         1. Call `create_task` with a title, a description and a concrete, verifiable `dod`, \
+         and `parent_id` set to {id}.\n\
+         2. Call `add_dependency` between subtasks that must happen in order.\n\
+         Do not duplicate existing children. The subtasks together must satisfy the \
+         definition of done of #{id}.\n"
+    );
+    Ok(out)
+}
+
+fn write_dod(mgr: &TaskManager, id: i64) -> Result<String> {
+    let task = mgr.show_task(id)?;
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "Write a definition of done for task #{}: {}\n",
+        task.task.id, task.task.title
+    );
+    write_details(&mut out, &task);
+    write_related(&mut out, mgr, &task)?;
+
+    let action = if task.task.dod.is_some() {
+        "Refine the existing definition of done"
+    } else {
+        "Draft a definition of done"
+    };
+    let _ = write!(
+        out,
+        "\n{action} as 3-7 acceptance criteria. Each criterion must be observable \
+         and verifiable, e.g. a command that succeeds or a behaviour that can be tested. \
+         Keep it within the scope of the title and description.\n\
+         Then save it with `edit_task` using id {id} and the criteria as `dod`.\n"
+    );
+    Ok(out)
+}
+
+/// Status, description and DoD of a task.
+fn write_details(out: &mut String, task: &TaskWithDeps) {
+    let _ = writeln!(out, "Status: {}", task.task.status);
+    if let Some(description) = &task.task.description {
+        let _ = writeln!(out, "Description: {}", description);
+    }
+    match &task.task.dod {
+        Some(dod) => {
+            let _ = writeln!(out, "Definition of done: {}", dod);
+        }
+        None => {
+            let _ = writeln!(out, "Definition of done: (none)");
+        }
+    }
+}
+
+/// Dependencies, children and artifacts of a task.
+fn write_related(out: &mut String, mgr: &TaskManager, task: &TaskWithDeps) -> Result<()> {
+    for (label, ids) in [
+        ("Dependencies", &task.dependencies),
+        ("Children", &task.children),
+    ] {
+        if ids.is_empty() {
+            continue;
+        }
+        let _ = writeln!(out, "\n{}:", label);
+        for &id in ids {
+            let t = mgr.db().get_task(id)?;
+            let _ = writeln!(out, "- #{} [{}] {}", t.id, t.status, t.title);
+        }
+    }
+
+    let mut artifacts: Vec<&Artifact> = task.artifacts.iter().collect();
+    let dep_artifacts: Vec<Artifact> = task
+        .dependencies
+        .iter()
+        .map(|&id| mgr.db().get_artifacts(id))
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .flatten()
+        .collect();
+    artifacts.extend(&dep_artifacts);
+
+    if !artifacts.is_empty() {
+        let _ = writeln!(out, "\nArtifacts (read them for context):");
+        for a in artifacts {
+            let _ = writeln!(out, "- #{} {}: {}", a.task_id, a.name, a.file_path);
+        }
+    }
+    Ok(())
+}
+
+fn id_list(ids: &[i64]) -> String {
+    ids.iter()
+        .map(|id| format!("#{}", id))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::db::Db;
+    use tempfile::NamedTempFile;
+
+    fn setup_manager() -> (TaskManager, NamedTempFile) {
+        let temp = NamedTempFile::new().unwrap();
+        let db = Db::open(temp.path()).unwrap();
+        db.init_schema().unwrap();
+        (TaskManager::new(db), temp)
+    }
+
+    fn args(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    fn text(prompt: &Value) -> &str {
+        prompt["messages"][0]["content"]["text"].as_str().unwrap()
+    }
+
+    fn text_contains(prompt: &Value, needle: &str) -> bool {
+        text(prompt).contains(needle)
+    }
+
+    #[test]
+    fn test_list_prompts() {
+        let prompts = list();
+        let names: Vec<&str> = prompts["prompts"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["work-on-target", "decompose-task", "write-dod"]);
+    }
+
+    #[test]
+    fn test_work_on_target() {
+        let (mut mgr, _temp) = setup_manager();
+        let a = mgr
+            .add_task("Schema", None, Some("Tables exist"), None, None)
+            .unwrap();
+        let b = mgr.add_task("API", None, None, None, None).unwrap();
+        mgr.add_dependency(b, a).unwrap();
+
+        assert!(matches!(
+            get(&mgr, "work-on-target", None),
+            Err(TTError::NoTarget)
+        ));
+
+        mgr.set_target(b).unwrap();
+        let prompt = get(&mgr, "work-on-target", None).unwrap();
+        let text = text(&prompt);
+        assert!(text.contains(&format!("Work on target #{}: API", b)));
+        assert!(text.contains(&format!("- #{} [pending] Schema\n", a)));
+        assert!(text.contains(&format!("(depends on #{}) — no DoD", a)));
+        assert!(text.find("Schema").unwrap() < text.find("[pending] API").unwrap());
+        assert!(!text.contains("set_target"));
+
+        // A different target is rendered but not switched to
+        let prompt = get(
+            &mgr,
+            "work-on-target",
+            Some(&args(serde_json::json!({"target_id": a.to_string()}))),
+        )
+        .unwrap();
+        assert!(text_contains(
+            &prompt,
+            &format!("`set_target` with id {}", a)
+        ));
+        assert_eq!(mgr.get_target().unwrap(), b);
+    }
+
+    #[test]
+    fn test_decompose_task_includes_deps_and_artifacts() {
+        let (mut mgr, _temp) = setup_manager();
+        let research = mgr
+            .add_task("Research", None, Some("Notes"), None, None)
+            .unwrap();
+        let build = mgr
+            .add_task("Build", Some("The feature"), Some("Works"), None, None)
+            .unwrap();
+        mgr.add_dependency(build, research).unwrap();
+        mgr.start_task(research).unwrap();
+        mgr.log_artifact("research", ".tt/artifacts/1-research.md")
+            .unwrap();
+
+        let prompt = get(
+            &mgr,
+            "decompose-task",
+            Some(&args(serde_json::json!({"id": build}))),
+        )
+        .unwrap();
+        assert_eq!(prompt["messages"][0]["role"], "user");
+        assert!(text_contains(&prompt, "Description: The feature"));
+        assert!(text_contains(
+            &prompt,
+            &format!("- #{} [in_progress] Research", research)
+        ));
+        assert!(text_contains(
+            &prompt,
+            "research: .tt/artifacts/1-research.md"
+        ));
+        assert!(text_contains(
+            &prompt,
+            &format!("`parent_id` set to {}", build)
+        ));
+    }
+
+    #[test]
+    fn test_write_dod() {
+        let (mut mgr, _temp) = setup_manager();
+        let id = mgr.add_task("Login", None, None, None, None).unwrap();
+
+        let prompt = get(
+            &mgr,
+            "write-dod",
+            Some(&args(serde_json::json!({"id": id.to_string()}))),
+        )
+        .unwrap();
+        assert!(text_contains(&prompt, "Definition of done: (none)"));
+        assert!(text_contains(&prompt, "Draft a definition of done"));
+
+        mgr.edit_task(id, None, None, Some("Users can log in"))
+            .unwrap();
+        let prompt = get(
+            &mgr,
+            "write-dod",
+            Some(&args(serde_json::json!({"id": id}))),
+        )
+        .unwrap();
+        assert!(text_contains(
+            &prompt,
+            "Refine the existing definition of done"
+        ));
+    }
+
+    #[test]
+    fn test_invalid_requests() {
+        let (mgr, _temp) = setup_manager();
+        for (name, a) in [
+            ("unknown", serde_json::json!({})),
+            ("write-dod", serde_json::json!({})),
+            ("write-dod", serde_json::json!({"id": "abc"})),
+        ] {
+            assert!(matches!(
+                get(&mgr, name, Some(&args(a))),
+                Err(TTError::Mcp(_))
+            ));
+        }
+        assert!(get(
+            &mgr,
+            "write-dod",
+            Some(&args(serde_json::json!({"id": "99"})))
+        )
+        .is_err());
+    }
+}
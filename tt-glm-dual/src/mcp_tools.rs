@@ -0,0 +1,403 @@
+//! MCP tool catalogue with annotations, and per-session tool allowlists.
+
+use crate::core::error::{Result, TTError};
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+
+/// A tool parameter as (name, JSON type, required, description).
+type Param = (&'static str, &'static str, bool, &'static str);
+
+/// A tool exposed over MCP.
+#[derive(Debug)]
+pub struct ToolSpec {
+    pub name: &'static str,
+    /// The matching CLI command, accepted by `tt mcp --tools`.
+    pub alias: &'static str,
+    pub description: &'static str,
+    /// Does not modify the database.
+    pub read_only: bool,
+    /// May overwrite or remove existing data rather than only adding to it.
+    pub destructive: bool,
+    /// Repeating the call with the same arguments has no further effect.
+    pub idempotent: bool,
+    pub params: &'static [Param],
+}
+
+const ID: Param = ("id", "integer", true, "Task ID");
+const DEPENDENCY: [Param; 2] = [
+    ("task_id", "integer", true, "The dependent task ID"),
+    ("depends_on", "integer", true, "The prerequisite task ID"),
+];
+const POSITION: [Param; 2] = [
+    ("after_id", "integer", false, "Place after this task ID"),
+    ("before_id", "integer", false, "Place before this task ID"),
+];
+
+/// Every tool, in `tools/list` order.
+pub const TOOLS: &[ToolSpec] = &[
+    ToolSpec {
+        name: "get_next_task",
+        alias: "next",
+        description: "Returns the next task to work on toward the current target. Call this after completing a task. If the response is TargetReached, stop working and report to the user.",
+        read_only: true,
+        destructive: false,
+        idempotent: true,
+        params: &[],
+    },
+    ToolSpec {
+        name: "get_current_task",
+        alias: "current",
+        description: "Returns the task you are working on, with its dependencies and artifacts. Call this when resuming work to see where you left off.",
+        read_only: true,
+        destructive: false,
+        idempotent: true,
+        params: &[],
+    },
+    ToolSpec {
+        name: "list_tasks",
+        alias: "list",
+        description: "Lists the incomplete tasks toward the current target. Pass all to see every task, including other work and completed tasks.",
+        read_only: true,
+        destructive: false,
+        idempotent: true,
+        params: &[("all", "boolean", false, "List every task")],
+    },
+    ToolSpec {
+        name: "show_task",
+        alias: "show",
+        description: "Returns a task with its dependencies, children and artifacts. Read a task this way before starting it.",
+        read_only: true,
+        destructive: false,
+        idempotent: true,
+        params: &[ID],
+    },
+    ToolSpec {
+        name: "get_tree",
+        alias: "tree",
+        description: "Returns the parent/child hierarchy with rolled-up progress. Use this to see how far along an epic is.",
+        read_only: true,
+        destructive: false,
+        idempotent: true,
+        params: &[("id", "integer", false, "Root task ID")],
+    },
+    ToolSpec {
+        name: "get_artifacts",
+        alias: "artifacts",
+        description: "Lists the artifacts recorded for a task, or for the current task if task_id is omitted. Read them to pick up earlier research and plans.",
+        read_only: true,
+        destructive: false,
+        idempotent: true,
+        params: &[("task_id", "integer", false, "Task ID")],
+    },
+    ToolSpec {
+        name: "start_task",
+        alias: "start",
+        description: "Starts working on a task. Only one task can be in progress; if AnotherTaskActive is returned, complete or stop that task first. Its dependencies and children must be completed.",
+        read_only: false,
+        destructive: false,
+        idempotent: true,
+        params: &[ID],
+    },
+    ToolSpec {
+        name: "stop_task",
+        alias: "stop",
+        description: "Stops working on the current task without completing it. Call this before switching to a different task.",
+        read_only: false,
+        destructive: false,
+        idempotent: false,
+        params: &[],
+    },
+    ToolSpec {
+        name: "complete_task",
+        alias: "done",
+        description: "Marks the current task as completed. Only call this once its definition of done is met, then call get_next_task.",
+        read_only: false,
+        destructive: false,
+        idempotent: false,
+        params: &[],
+    },
+    ToolSpec {
+        name: "block_task",
+        alias: "block",
+        description: "Marks a task as blocked on something outside the task graph, such as a question for the user. Blocked tasks are skipped until unblocked.",
+        read_only: false,
+        destructive: false,
+        idempotent: false,
+        params: &[ID],
+    },
+    ToolSpec {
+        name: "unblock_task",
+        alias: "unblock",
+        description: "Marks a blocked task as ready to work on again. Call this once whatever blocked it is resolved.",
+        read_only: false,
+        destructive: false,
+        idempotent: false,
+        params: &[ID],
+    },
+    ToolSpec {
+        name: "create_task",
+        alias: "add",
+        description: "Creates a new task. If you discover during implementation that a task needs to be broken into smaller pieces, create subtasks and add dependencies.",
+        read_only: false,
+        destructive: false,
+        idempotent: false,
+        params: &[
+            ("title", "string", true, "Task title"),
+            ("description", "string", false, "Task description"),
+            ("dod", "string", false, "Definition of done"),
+            POSITION[0],
+            POSITION[1],
+            ("parent_id", "integer", false, "Parent task (epic) ID"),
+        ],
+    },
+    ToolSpec {
+        name: "edit_task",
+        alias: "edit",
+        description: "Changes a task's title, description or definition of done. Omitted fields are left as they are.",
+        read_only: false,
+        destructive: true,
+        idempotent: true,
+        params: &[
+            ID,
+            ("title", "string", false, "New title"),
+            ("description", "string", false, "New description"),
+            ("dod", "string", false, "New definition of done"),
+        ],
+    },
+    ToolSpec {
+        name: "add_dependency",
+        alias: "depend",
+        description: "Records that task_id cannot start until depends_on is completed. Fails with CycleDetected if that would create a cycle.",
+        read_only: false,
+        destructive: false,
+        idempotent: false,
+        params: &DEPENDENCY,
+    },
+    ToolSpec {
+        name: "remove_dependency",
+        alias: "undepend",
+        description: "Removes a dependency added with add_dependency. Only do this when the ordering is no longer needed.",
+        read_only: false,
+        destructive: true,
+        idempotent: true,
+        params: &DEPENDENCY,
+    },
+    ToolSpec {
+        name: "set_parent",
+        alias: "parent",
+        description: "Moves a task under a parent task (epic), or clears its parent if parent_id is omitted. The parent cannot start or complete until all its children are completed.",
+        read_only: false,
+        destructive: true,
+        idempotent: true,
+        params: &[
+            ID,
+            (
+                "parent_id",
+                "integer",
+                false,
+                "Parent task ID; omit to clear",
+            ),
+        ],
+    },
+    ToolSpec {
+        name: "set_target",
+        alias: "target",
+        description: "Sets the task that get_next_task works toward. Only change the target when the user asks for a new goal.",
+        read_only: false,
+        destructive: true,
+        idempotent: true,
+        params: &[ID],
+    },
+    ToolSpec {
+        name: "reorder_task",
+        alias: "reorder",
+        description: "Moves a task after or before another task in the work order. Dependencies still take precedence over this order.",
+        read_only: false,
+        destructive: true,
+        idempotent: false,
+        params: &[ID, POSITION[0], POSITION[1]],
+    },
+    ToolSpec {
+        name: "log_artifact",
+        alias: "log",
+        description: "Records a file you have created as an artifact of the current task. Create the file first, then call this. Use descriptive names like 'research', 'plan', 'implementation-notes', 'test-report'.",
+        read_only: false,
+        destructive: false,
+        idempotent: false,
+        params: &[
+            ("name", "string", true, "Artifact name"),
+            ("file_path", "string", true, "Path to the artifact file"),
+        ],
+    },
+];
+
+/// Whether `method` is a tool call, as opposed to e.g. `tools/list`.
+pub fn is_tool(method: &str) -> bool {
+    TOOLS.iter().any(|t| t.name == method)
+}
+
+/// Look up a tool by name or CLI alias.
+pub fn find(name: &str) -> Option<&'static ToolSpec> {
+    TOOLS.iter().find(|t| t.name == name || t.alias == name)
+}
+
+impl ToolSpec {
+    /// The tool as a `tools/list` entry.
+    pub fn to_json(&self) -> Value {
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+        for (name, kind, is_required, description) in self.params {
+            properties.insert(
+                name.to_string(),
+                serde_json::json!({ "type": kind, "description": description }),
+            );
+            if *is_required {
+                required.push(*name);
+            }
+        }
+
+        serde_json::json!({
+            "name": self.name,
+            "description": self.description,
+            "inputSchema": {
+                "type": "object",
+                "properties": properties,
+                "required": required,
+            },
+            "annotations": {
+                "readOnlyHint": self.read_only,
+                "destructiveHint": self.destructive,
+                "idempotentHint": self.idempotent,
+                "openWorldHint": false,
+            },
+        })
+    }
+}
+
+/// The tools a session may list and call.
+#[derive(Debug, Clone, Default)]
+pub struct ToolSet {
+    /// `None` allows every tool.
+    allowed: Option<BTreeSet<&'static str>>,
+}
+
+impl ToolSet {
+    /// Every tool.
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Only tools that do not modify the database.
+    pub fn read_only() -> Self {
+        Self {
+            allowed: Some(
+                TOOLS
+                    .iter()
+                    .filter(|t| t.read_only)
+                    .map(|t| t.name)
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Parse a comma-separated list of tool names or CLI aliases.
+    pub fn parse(list: &str) -> Result<Self> {
+        let allowed = list
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| {
+                find(name)
+                    .map(|t| t.name)
+                    .ok_or_else(|| TTError::UnknownTool(name.to_string()))
+            })
+            .collect::<Result<BTreeSet<_>>>()?;
+        Ok(Self {
+            allowed: Some(allowed),
+        })
+    }
+
+    pub fn allows(&self, name: &str) -> bool {
+        self.allowed.as_ref().is_none_or(|a| a.contains(name))
+    }
+
+    /// The `tools/list` result for this session.
+    pub fn list(&self) -> Value {
+        let tools: Vec<Value> = TOOLS
+            .iter()
+            .filter(|t| self.allows(t.name))
+            .map(ToolSpec::to_json)
+            .collect();
+        serde_json::json!({ "tools": tools })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(set: &ToolSet) -> Vec<String> {
+        set.list()["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_annotations() {
+        let hints = |name: &str| find(name).unwrap().to_json()["annotations"].clone();
+        assert_eq!(hints("list_tasks")["readOnlyHint"], true);
+        assert_eq!(hints("show_task")["readOnlyHint"], true);
+        assert_eq!(hints("edit_task")["destructiveHint"], true);
+        assert_eq!(hints("start_task")["idempotentHint"], true);
+        assert_eq!(hints("start_task")["readOnlyHint"], false);
+        assert_eq!(hints("create_task")["destructiveHint"], false);
+    }
+
+    #[test]
+    fn test_input_schema() {
+        let tool = find("log_artifact").unwrap().to_json();
+        assert_eq!(tool["inputSchema"]["type"], "object");
+        assert_eq!(
+            tool["inputSchema"]["required"],
+            serde_json::json!(["name", "file_path"])
+        );
+        assert_eq!(tool["inputSchema"]["properties"]["name"]["type"], "string");
+    }
+
+    #[test]
+    fn test_read_only_set() {
+        let set = ToolSet::read_only();
+        assert!(set.allows("show_task"));
+        assert!(!set.allows("edit_task"));
+        assert_eq!(
+            names(&set),
+            vec![
+                "get_next_task",
+                "get_current_task",
+                "list_tasks",
+                "show_task",
+                "get_tree",
+                "get_artifacts"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_aliases() {
+        let set = ToolSet::parse("next, current,log_artifact").unwrap();
+        assert_eq!(
+            names(&set),
+            vec!["get_next_task", "get_current_task", "log_artifact"]
+        );
+        assert!(!set.allows("start_task"));
+
+        assert!(matches!(
+            ToolSet::parse("next,nope"),
+            Err(TTError::UnknownTool(name)) if name == "nope"
+        ));
+        assert_eq!(names(&ToolSet::all()).len(), TOOLS.len());
+    }
+}
@@ -48,7 +48,7 @@ fn test_workflow() {
     assert!(id_b > 0);
 
     // Add dependency
-    let (stdout, _, status) = run_tt(&["depend", &id_b.to_string(), &id_a.to_string()], dir);
+    let (_stdout, _, status) = run_tt(&["depend", &id_b.to_string(), &id_a.to_string()], dir);
     assert_eq!(status, 0);
 
     // Set target
@@ -216,3 +216,302 @@ fn test_reorder() {
     );
     assert_eq!(status, 0);
 }
+
+#[test]
+fn test_epic_hierarchy() {
+    let temp = TempDir::new().unwrap();
+    let dir = temp.path();
+
+    run_tt(&["init"], dir);
+
+    let (stdout, _, _) = run_tt(&["add", "Epic", "--dod", "All stories shipped"], dir);
+    let epic: i64 = stdout.trim().parse().unwrap();
+
+    let (stdout, _, status) = run_tt(
+        &[
+            "add",
+            "Story",
+            "--dod",
+            "Done",
+            "--parent",
+            &epic.to_string(),
+        ],
+        dir,
+    );
+    assert_eq!(status, 0);
+    let story: i64 = stdout.trim().parse().unwrap();
+
+    run_tt(&["add", "Loose task"], dir);
+
+    // A child cannot depend on its parent
+    let (_, stderr, status) = run_tt(&["depend", &story.to_string(), &epic.to_string()], dir);
+    assert_ne!(status, 0);
+    assert!(stderr.contains("cycle"));
+
+    // A rejected parent leaves the other edits unapplied
+    let (_, stderr, status) = run_tt(
+        &[
+            "edit",
+            &epic.to_string(),
+            "--title",
+            "Renamed",
+            "--parent",
+            &story.to_string(),
+        ],
+        dir,
+    );
+    assert_ne!(status, 0);
+    assert!(stderr.contains("cycle"));
+    let (stdout, _, _) = run_tt(&["show", &epic.to_string()], dir);
+    assert!(!stdout.contains("Renamed"));
+
+    let (stdout, _, _) = run_tt(&["tree"], dir);
+    assert!(stdout.contains("Epic [0/1]"));
+    assert!(stdout.contains("  [2] ○ Story"));
+
+    let (stdout, _, _) = run_tt(&["list", "--all"], dir);
+    assert!(stdout.contains("  [2] ○ Story"));
+
+    // The epic cannot be completed before its story
+    run_tt(&["start", &epic.to_string()], dir);
+    let (_, stderr, status) = run_tt(&["done"], dir);
+    assert_ne!(status, 0);
+    assert!(stderr.contains("children not completed"));
+    run_tt(&["stop"], dir);
+
+    run_tt(&["start", &story.to_string()], dir);
+    run_tt(&["done"], dir);
+
+    let (stdout, _, _) = run_tt(&["tree", &epic.to_string()], dir);
+    assert!(stdout.contains("Epic [1/1]"));
+}
+
+fn run_mcp(args: &[&str], requests: &[&str], dir: &Path) -> Vec<serde_json::Value> {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_tt"))
+        .current_dir(dir)
+        .arg("mcp")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("Failed to execute tt mcp");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(requests.join("\n").as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_projects() {
+    let temp = TempDir::new().unwrap();
+    let dir = temp.path();
+    run_tt(&["init"], dir);
+
+    let (stdout, _, _) = run_tt(&["add", "Backend"], dir);
+    let backend: i64 = stdout.trim().parse().unwrap();
+
+    let (stdout, _, status) = run_tt(&["project", "create", "web", "--use"], dir);
+    assert_eq!(status, 0);
+    assert!(stdout.contains("web"));
+
+    let (stdout, _, _) = run_tt(&["project", "list"], dir);
+    assert_eq!(stdout, "  default\n* web\n");
+
+    let (stdout, _, _) = run_tt(&["add", "Frontend"], dir);
+    let frontend: i64 = stdout.trim().parse().unwrap();
+    let (_, _, status) = run_tt(
+        &["depend", &frontend.to_string(), &backend.to_string()],
+        dir,
+    );
+    assert_eq!(status, 0);
+
+    let (stdout, _, _) = run_tt(&["list", "--all"], dir);
+    assert!(stdout.contains("Frontend"));
+    assert!(!stdout.contains("Backend"));
+
+    let (_, stderr, status) = run_tt(&["project", "use", "mobile"], dir);
+    assert_ne!(status, 0);
+    assert!(stderr.contains("not found"));
+
+    run_tt(&["project", "use", "default"], dir);
+    let (stdout, _, _) = run_tt(&["list", "--all"], dir);
+    assert!(stdout.contains("Backend"));
+    assert!(!stdout.contains("Frontend"));
+}
+
+#[test]
+fn test_mcp_project_is_pinned() {
+    let temp = TempDir::new().unwrap();
+    let dir = temp.path();
+    run_tt(&["init"], dir);
+    run_tt(&["add", "Backend"], dir);
+    run_tt(&["project", "create", "web"], dir);
+
+    let requests = [
+        r#"{"jsonrpc":"2.0","id":0,"method":"initialize","params":{"protocolVersion":"2025-06-18"}}"#,
+        r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"create_task","arguments":{"title":"Frontend"}}}"#,
+    ];
+    let responses = run_mcp(&["--project", "web"], &requests, dir);
+    assert_eq!(responses[1]["result"]["isError"], false);
+
+    // The server worked in "web" while "default" stayed active
+    let (stdout, _, _) = run_tt(&["list", "--all"], dir);
+    assert!(stdout.contains("Backend"));
+    assert!(!stdout.contains("Frontend"));
+    run_tt(&["project", "use", "web"], dir);
+    let (stdout, _, _) = run_tt(&["list", "--all"], dir);
+    assert!(stdout.contains("Frontend"));
+
+    // An unknown project stops the server before it answers anything
+    assert!(run_mcp(&["--project", "mobile"], &requests, dir).is_empty());
+}
+
+#[test]
+fn test_mcp_tool_allowlist() {
+    let temp = TempDir::new().unwrap();
+    let dir = temp.path();
+    run_tt(&["init"], dir);
+    run_tt(&["add", "Review", "--dod", "Reviewed"], dir);
+
+    let requests = [
+        r#"{"jsonrpc":"2.0","id":0,"method":"initialize","params":{"protocolVersion":"2025-06-18"}}"#,
+        r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#,
+        r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"edit_task","arguments":{"id":1,"title":"Changed"}}}"#,
+        r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"show_task","arguments":{"id":1}}}"#,
+    ];
+
+    let responses = run_mcp(&["--read-only"], &requests, dir);
+    let tools = responses[1]["result"]["tools"].as_array().unwrap();
+    assert!(tools
+        .iter()
+        .all(|t| t["annotations"]["readOnlyHint"] == true));
+    assert_eq!(responses[2]["error"]["code"], -32602);
+    assert_eq!(
+        responses[2]["error"]["data"]["error_code"],
+        "ToolNotAllowed"
+    );
+    assert_eq!(
+        responses[3]["result"]["structuredContent"]["data"]["title"],
+        "Review"
+    );
+
+    let responses = run_mcp(&["--tools", "next,current,log_artifact"], &requests, dir);
+    let names: Vec<&str> = responses[1]["result"]["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["get_next_task", "get_current_task", "log_artifact"]);
+    assert_eq!(
+        responses[3]["error"]["data"]["error_code"],
+        "ToolNotAllowed"
+    );
+
+    // Without restrictions every tool is listed and callable
+    let responses = run_mcp(&[], &requests, dir);
+    assert_eq!(
+        responses[2]["result"]["structuredContent"]["data"]["title"],
+        "Changed"
+    );
+
+    let (_, stderr, status) = run_tt(&["mcp", "--tools", "next,nope"], dir);
+    assert_ne!(status, 0);
+    assert!(stderr.contains("Unknown tool: nope"));
+}
+
+/// Check that `expected` matches `actual`, where the string `"<any>"` in
+/// `expected` stands for any value.
+fn matches_recorded(expected: &serde_json::Value, actual: &serde_json::Value) -> bool {
+    use serde_json::Value;
+
+    match (expected, actual) {
+        (Value::String(s), _) if s == "<any>" => true,
+        (Value::Object(e), Value::Object(a)) => {
+            e.len() == a.len()
+                && e.iter()
+                    .all(|(k, v)| a.get(k).is_some_and(|av| matches_recorded(v, av)))
+        }
+        (Value::Array(e), Value::Array(a)) => {
+            e.len() == a.len() && e.iter().zip(a).all(|(ev, av)| matches_recorded(ev, av))
+        }
+        _ => expected == actual,
+    }
+}
+
+/// Replay a recorded MCP session from `tests/transcripts/<name>.txt`
+/// against a fresh database.
+///
+/// Lines starting with `>` are sent to the server and lines starting with
+/// `<` are the messages it must send back, in order.
+fn run_transcript(name: &str) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/transcripts")
+        .join(format!("{}.txt", name));
+    let transcript = std::fs::read_to_string(&path).unwrap();
+
+    let mut requests = Vec::new();
+    let mut expected = Vec::new();
+    for line in transcript.lines() {
+        if let Some(request) = line.strip_prefix("> ") {
+            requests.push(request);
+        } else if let Some(response) = line.strip_prefix("< ") {
+            let value: serde_json::Value = serde_json::from_str(response)
+                .unwrap_or_else(|e| panic!("{}: bad expected line {}: {}", name, response, e));
+            expected.push(value);
+        }
+    }
+
+    let temp = TempDir::new().unwrap();
+    run_tt(&["init"], temp.path());
+    let actual = run_mcp(&[], &requests, temp.path());
+
+    for (i, (expected, actual)) in expected.iter().zip(&actual).enumerate() {
+        assert!(
+            matches_recorded(expected, actual),
+            "{}: message {} differs\nexpected: {}\n  actual: {}",
+            name,
+            i + 1,
+            expected,
+            actual
+        );
+    }
+    assert_eq!(
+        expected.len(),
+        actual.len(),
+        "{}: wrong number of messages",
+        name
+    );
+}
+
+#[test]
+fn test_mcp_transcript_handshake() {
+    run_transcript("handshake");
+}
+
+#[test]
+fn test_mcp_transcript_errors() {
+    run_transcript("errors");
+}
+
+#[test]
+fn test_mcp_transcript_tools() {
+    run_transcript("tools");
+}
+
+#[test]
+fn test_mcp_transcript_logging() {
+    run_transcript("logging");
+}
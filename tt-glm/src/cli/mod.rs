@@ -40,13 +40,10 @@ pub fn run() -> Result<()> {
         "artifacts" => cmd_artifacts(&args[2..]),
         "reorder" => cmd_reorder(&args[2..]),
         "reindex" => cmd_reindex(),
-        "mcp" => {
-            eprintln!("Error: Use 'tt mcp' in a context where MCP stdio is available");
-            Err(crate::error::Error::Io(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                "mcp command not available",
-            )))
-        }
+        "forecast" => cmd_forecast(&args[2..]),
+        "undo" => cmd_undo(&args[2..]),
+        "redo" => cmd_redo(),
+        "mcp" => crate::mcp::run(ensure_initialized()?),
         _ => {
             eprintln!("Unknown command: {command}");
             print_help();
@@ -66,12 +63,14 @@ fn print_help() {
     println!("  add \"<title>\"                  Create a new task");
     println!("    --desc <description>           Set description");
     println!("    --dod <definition>             Set definition of done");
+    println!("    --estimate <n>                 Set effort estimate (points or hours)");
     println!("    --after <id>                   Insert after task");
     println!("    --before <id>                  Insert before task");
     println!("  edit <id>                       Update a task");
     println!("    --title <title>                New title");
     println!("    --desc <description>           New description");
     println!("    --dod <definition>             New DoD");
+    println!("    --estimate <n>                 New effort estimate");
     println!("  show <id>                       Show task details");
     println!("  list [--all]                    List tasks in target subgraph");
     println!("  target <id>                     Set the target task");
@@ -90,6 +89,11 @@ fn print_help() {
     println!("    --after <id>                  Move after task");
     println!("    --before <id>                 Move before task");
     println!("  reindex                         Reindex all manual_order values");
+    println!("  forecast [<id>]                 Forecast remaining effort for target");
+    println!("    --window <days>               Throughput window (default 14)");
+    println!("  undo [<n>]                      Undo the last n commands (default 1)");
+    println!("  redo                            Redo the last undone command");
+    println!("  mcp                             Serve MCP tools over stdio");
 }
 
 fn ensure_initialized() -> Result<TaskRepository> {
@@ -136,6 +140,7 @@ fn cmd_add(args: &[String]) -> Result<()> {
 
     let mut description = None;
     let mut dod = None;
+    let mut estimate = None;
     let mut after = None;
     let mut before = None;
 
@@ -150,6 +155,10 @@ fn cmd_add(args: &[String]) -> Result<()> {
                 dod = args.get(i + 1).cloned();
                 i += 2;
             }
+            "--estimate" => {
+                estimate = Some(parse_estimate(args.get(i + 1))?);
+                i += 2;
+            }
             "--after" => {
                 after = args.get(i + 1).and_then(|s| s.parse().ok());
                 i += 2;
@@ -168,11 +177,31 @@ fn cmd_add(args: &[String]) -> Result<()> {
     // Calculate manual_order
     let manual_order = calculate_manual_order(&mut repo, after, before)?;
 
-    let task = repo.create_task(title, description, dod, manual_order)?;
+    let task = repo.grouped(|repo| {
+        let task = repo.create_task(title, description, dod, manual_order)?;
+        match estimate {
+            Some(_) => repo.update_task(task.id, None, None, None, estimate),
+            None => Ok(task),
+        }
+    })?;
     println!("#{}", task.id);
     Ok(())
 }
 
+fn parse_estimate(value: Option<&String>) -> Result<f64> {
+    let value = value.ok_or_else(|| {
+        crate::error::Error::Io(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "--estimate requires a value",
+        ))
+    })?;
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|e| e.is_finite() && *e >= 0.0)
+        .ok_or_else(|| crate::error::Error::InvalidEstimate(value.clone()))
+}
+
 fn calculate_manual_order(
     repo: &mut TaskRepository,
     after: Option<i64>,
@@ -211,6 +240,7 @@ fn cmd_edit(args: &[String]) -> Result<()> {
     let mut title = None;
     let mut description = None;
     let mut dod = None;
+    let mut estimate = None;
 
     let mut i = 1;
     while i < args.len() {
@@ -227,6 +257,10 @@ fn cmd_edit(args: &[String]) -> Result<()> {
                 dod = args.get(i + 1).cloned();
                 i += 2;
             }
+            "--estimate" => {
+                estimate = Some(parse_estimate(args.get(i + 1))?);
+                i += 2;
+            }
             _ => {
                 eprintln!("Unknown flag: {}", args[i]);
                 i += 1;
@@ -234,7 +268,7 @@ fn cmd_edit(args: &[String]) -> Result<()> {
         }
     }
 
-    repo.update_task(id, title, description, dod)?;
+    repo.update_task(id, title, description, dod, estimate)?;
     println!("Updated task #{id}");
     Ok(())
 }
@@ -257,6 +291,9 @@ fn print_task_detail(repo: &mut TaskRepository, task: &Task) -> Result<()> {
     if let Some(dod) = &task.dod {
         println!("DoD:          {dod}");
     }
+    if let Some(estimate) = task.estimate {
+        println!("Estimate:     {estimate}");
+    }
 
     let deps = dependency::get_dependencies(repo.conn(), task.id)?;
     if !deps.is_empty() {
@@ -302,10 +339,8 @@ fn cmd_list(args: &[String]) -> Result<()> {
     let all = args.iter().any(|a| a == "--all");
     let tasks = repo.list_tasks(all)?;
 
-    if let Ok(target_id) = repo.get_target() {
-        if let Some(tid) = target_id {
-            println!("Target: #{tid}");
-        }
+    if let Ok(Some(tid)) = repo.get_target() {
+        println!("Target: #{tid}");
     }
 
     for task in tasks {
@@ -449,7 +484,7 @@ fn cmd_depend(args: &[String]) -> Result<()> {
 
     let on_id = get_required_id(&args[1..], "depends_on")?;
 
-    dependency::add_dependency(repo.conn(), id, on_id)?;
+    repo.add_dependency(id, on_id)?;
     println!("Added dependency: #{id} depends on #{on_id}");
     Ok(())
 }
@@ -461,10 +496,8 @@ fn cmd_undepend(args: &[String]) -> Result<()> {
 
     let on_id = get_required_id(&args[1..], "depends_on")?;
 
-    dependency::remove_dependency(repo.conn(), id, on_id)?;
-    println!(
-        "Removed dependency: #{id} no longer depends on #{on_id}"
-    );
+    repo.remove_dependency(id, on_id)?;
+    println!("Removed dependency: #{id} no longer depends on #{on_id}");
     Ok(())
 }
 
@@ -505,7 +538,7 @@ fn cmd_log(args: &[String]) -> Result<()> {
     })?;
 
     let (task_id, _) = artifact_ops::get_active_task_artifacts(repo.conn())?;
-    artifact_ops::add_artifact(repo.conn(), task_id, name.clone(), file_path.clone())?;
+    repo.log_artifact(task_id, name.clone(), file_path)?;
 
     println!("Logged artifact '{name}' for task #{task_id}");
     Ok(())
@@ -576,10 +609,7 @@ fn cmd_reorder(args: &[String]) -> Result<()> {
 
     let new_order = calculate_manual_order(&mut repo, after, before)?;
 
-    repo.conn().execute(
-        "UPDATE tasks SET manual_order = ?, last_touched_at = strftime('%Y-%m-%dT%H:%M:%S', 'now') WHERE id = ?",
-        &[&new_order as &dyn rusqlite::ToSql, &id as &dyn rusqlite::ToSql],
-    )?;
+    repo.reorder_task(id, new_order)?;
 
     println!("Reordered task #{id} to order {new_order}");
     Ok(())
@@ -593,6 +623,104 @@ fn cmd_reindex() -> Result<()> {
     Ok(())
 }
 
+fn cmd_undo(args: &[String]) -> Result<()> {
+    let mut repo = ensure_initialized()?;
+
+    let n = match args.first() {
+        Some(value) => value.parse().ok().filter(|&n| n > 0).ok_or_else(|| {
+            crate::error::Error::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("invalid count: {value}"),
+            ))
+        })?,
+        None => 1,
+    };
+
+    for command in repo.undo(n)? {
+        println!("Undid: {command}");
+    }
+    Ok(())
+}
+
+fn cmd_redo() -> Result<()> {
+    let mut repo = ensure_initialized()?;
+
+    let command = repo.redo()?;
+    println!("Redid: {command}");
+    Ok(())
+}
+
+fn cmd_forecast(args: &[String]) -> Result<()> {
+    let mut repo = ensure_initialized()?;
+
+    let mut target_id = None;
+    let mut window_days = 14;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--window" => {
+                window_days = args
+                    .get(i + 1)
+                    .and_then(|s| s.parse().ok())
+                    .filter(|&d: &i64| d > 0)
+                    .ok_or_else(|| {
+                        crate::error::Error::Io(std::io::Error::new(
+                            std::io::ErrorKind::InvalidInput,
+                            "--window requires a positive number of days",
+                        ))
+                    })?;
+                i += 2;
+            }
+            _ => {
+                if target_id.is_none() {
+                    target_id = args[i].parse().ok();
+                }
+                i += 1;
+            }
+        }
+    }
+
+    let target_id = match target_id {
+        Some(id) => id,
+        None => repo.get_target()?.ok_or(crate::error::Error::NoTarget)?,
+    };
+
+    let forecast = repo.forecast(target_id, window_days)?;
+
+    println!("Forecast for target #{}", forecast.target_id);
+    println!(
+        "  Remaining:   {:.1} of {:.1} ({} tasks)",
+        forecast.remaining, forecast.scope, forecast.remaining_tasks
+    );
+    if !forecast.unestimated.is_empty() {
+        println!(
+            "  Unestimated: {} (counted as 0)",
+            crate::error::format_task_ids(&forecast.unestimated)
+        );
+    }
+    match forecast.throughput {
+        Some(rate) => println!(
+            "  Throughput:  {rate:.2}/day over the last {} days",
+            forecast.window_days
+        ),
+        None => println!(
+            "  Throughput:  (no estimated tasks completed in the last {} days)",
+            forecast.window_days
+        ),
+    }
+    match forecast.projected_date {
+        Some(date) => println!("  Projected:   {date}"),
+        None => println!("  Projected:   (unknown)"),
+    }
+
+    println!();
+    print!("{}", forecast.render_burndown());
+    println!();
+    println!("Legend: █ actual  ░ projected");
+    Ok(())
+}
+
 fn format_datetime(dt: &str) -> String {
     // Basic formatting - just return the first 19 characters (YYYY-MM-DD HH:MM:SS)
     dt.chars().take(19).collect()
@@ -0,0 +1,339 @@
+//! Effort forecasting and burndown charts for a target.
+
+use crate::core::{Task, TaskStatus};
+use chrono::{Days, NaiveDate};
+use std::fmt::Write;
+
+/// Width of a full burndown bar in characters.
+const CHART_WIDTH: usize = 40;
+
+/// Maximum number of rows rendered in the burndown chart.
+const MAX_CHART_ROWS: usize = 30;
+
+/// Projections further out than this are not charted.
+const MAX_PROJECTION_DAYS: u64 = 3650;
+
+/// A single day in the burndown chart.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BurndownPoint {
+    pub date: NaiveDate,
+    pub remaining: f64,
+    /// True when extrapolated from throughput rather than recorded completions.
+    pub projected: bool,
+}
+
+/// Remaining effort and projected completion for a target subgraph.
+#[derive(Debug, Clone)]
+pub struct Forecast {
+    pub target_id: i64,
+    /// Number of tasks in the subgraph that are not completed.
+    pub remaining_tasks: usize,
+    /// Sum of estimates over the whole subgraph, including completed tasks.
+    pub scope: f64,
+    /// Sum of estimates over tasks that are not completed.
+    pub remaining: f64,
+    /// Remaining tasks without an estimate. They count as zero.
+    pub unestimated: Vec<i64>,
+    /// Estimate completed per day over the throughput window.
+    pub throughput: Option<f64>,
+    pub window_days: i64,
+    pub projected_date: Option<NaiveDate>,
+    pub burndown: Vec<BurndownPoint>,
+}
+
+impl Forecast {
+    /// Build a forecast from the target subgraph and the completion history.
+    ///
+    /// `subgraph` holds the target and all of its transitive dependencies.
+    /// `history` holds completed tasks project-wide; only those completed in
+    /// the last `window_days` days (including `today`) count toward throughput.
+    pub fn compute(
+        target_id: i64,
+        subgraph: &[Task],
+        history: &[Task],
+        today: NaiveDate,
+        window_days: i64,
+    ) -> Self {
+        let window_days = window_days.max(1);
+
+        let scope: f64 = subgraph.iter().filter_map(|t| t.estimate).sum();
+        let open: Vec<&Task> = subgraph
+            .iter()
+            .filter(|t| t.status != TaskStatus::Completed)
+            .collect();
+        let remaining: f64 = open.iter().filter_map(|t| t.estimate).sum();
+        let mut unestimated: Vec<i64> = open
+            .iter()
+            .filter(|t| t.estimate.is_none())
+            .map(|t| t.id)
+            .collect();
+        unestimated.sort_unstable();
+
+        let window_start = today - Days::new(window_days as u64 - 1);
+        let done_in_window: f64 = history
+            .iter()
+            .filter_map(|t| Some((completed_date(t)?, t.estimate?)))
+            .filter(|(date, _)| *date >= window_start && *date <= today)
+            .map(|(_, estimate)| estimate)
+            .sum();
+        let throughput = (done_in_window > 0.0).then(|| done_in_window / window_days as f64);
+
+        let projected_date = if open.is_empty() {
+            Some(today)
+        } else if remaining <= 0.0 {
+            None
+        } else {
+            throughput.map(|rate| today + Days::new(projection_days(remaining, rate)))
+        };
+
+        let burndown = burndown(subgraph, scope, remaining, throughput, today);
+
+        Self {
+            target_id,
+            remaining_tasks: open.len(),
+            scope,
+            remaining,
+            unestimated,
+            throughput,
+            window_days,
+            projected_date,
+            burndown,
+        }
+    }
+
+    /// Render the burndown as a text bar chart, one row per day.
+    pub fn render_burndown(&self) -> String {
+        let mut out = String::new();
+        for point in &self.burndown {
+            let filled = if self.scope > 0.0 {
+                ((point.remaining / self.scope) * CHART_WIDTH as f64).round() as usize
+            } else {
+                0
+            }
+            .min(CHART_WIDTH);
+            let bar = if point.projected { "░" } else { "█" };
+            let _ = writeln!(
+                out,
+                "{} |{}{} {:.1}",
+                point.date,
+                bar.repeat(filled),
+                " ".repeat(CHART_WIDTH - filled),
+                point.remaining
+            );
+        }
+        out
+    }
+}
+
+/// Whole days needed to burn `remaining` at `rate` per day.
+fn projection_days(remaining: f64, rate: f64) -> u64 {
+    ((remaining / rate).ceil() as u64).min(MAX_PROJECTION_DAYS)
+}
+
+/// Parse the date part of a task's `completed_at` timestamp.
+fn completed_date(task: &Task) -> Option<NaiveDate> {
+    let completed_at = task.completed_at.as_deref()?;
+    NaiveDate::parse_from_str(completed_at.get(..10)?, "%Y-%m-%d").ok()
+}
+
+/// Daily remaining estimate from the first completion to today, then projected to zero.
+fn burndown(
+    subgraph: &[Task],
+    scope: f64,
+    remaining: f64,
+    throughput: Option<f64>,
+    today: NaiveDate,
+) -> Vec<BurndownPoint> {
+    let completions: Vec<(NaiveDate, f64)> = subgraph
+        .iter()
+        .filter(|t| t.status == TaskStatus::Completed)
+        .filter_map(|t| Some((completed_date(t)?, t.estimate?)))
+        .collect();
+
+    let start = completions
+        .iter()
+        .map(|(date, _)| *date)
+        .min()
+        .unwrap_or(today)
+        .min(today);
+
+    let mut points = Vec::new();
+    for date in start.iter_days().take_while(|d| *d < today) {
+        let done: f64 = completions
+            .iter()
+            .filter(|(completed, _)| *completed <= date)
+            .map(|(_, estimate)| estimate)
+            .sum();
+        points.push(BurndownPoint {
+            date,
+            remaining: scope - done,
+            projected: false,
+        });
+    }
+    points.push(BurndownPoint {
+        date: today,
+        remaining,
+        projected: false,
+    });
+
+    if let Some(rate) = throughput.filter(|_| remaining > 0.0) {
+        for day in 1..=projection_days(remaining, rate) {
+            points.push(BurndownPoint {
+                date: today + Days::new(day),
+                remaining: (remaining - rate * day as f64).max(0.0),
+                projected: true,
+            });
+        }
+    }
+
+    downsample(points)
+}
+
+/// Keep at most `MAX_CHART_ROWS` evenly spaced rows, always including the last one.
+fn downsample(points: Vec<BurndownPoint>) -> Vec<BurndownPoint> {
+    if points.len() <= MAX_CHART_ROWS {
+        return points;
+    }
+    let step = (points.len() - 1).div_ceil(MAX_CHART_ROWS - 1);
+    let last = points.len() - 1;
+    points
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| i % step == 0 || *i == last)
+        .map(|(_, p)| p)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn make_task(id: i64, estimate: Option<f64>, completed_at: Option<&str>) -> Task {
+        Task {
+            id,
+            title: format!("Task {id}"),
+            description: None,
+            dod: None,
+            status: if completed_at.is_some() {
+                TaskStatus::Completed
+            } else {
+                TaskStatus::Pending
+            },
+            manual_order: id as f64 * 10.0,
+            estimate,
+            created_at: String::new(),
+            started_at: None,
+            completed_at: completed_at.map(|s| format!("{s}T12:00:00")),
+            last_touched_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_remaining_and_unestimated() {
+        let subgraph = vec![
+            make_task(1, Some(3.0), Some("2026-10-01")),
+            make_task(2, Some(5.0), None),
+            make_task(3, None, None),
+            make_task(4, Some(2.0), None),
+        ];
+
+        let forecast = Forecast::compute(4, &subgraph, &[], date("2026-10-10"), 14);
+        assert_eq!(forecast.scope, 10.0);
+        assert_eq!(forecast.remaining, 7.0);
+        assert_eq!(forecast.remaining_tasks, 3);
+        assert_eq!(forecast.unestimated, vec![3]);
+        // No history means no throughput and no projection
+        assert_eq!(forecast.throughput, None);
+        assert_eq!(forecast.projected_date, None);
+    }
+
+    #[test]
+    fn test_projection_from_throughput() {
+        let history = vec![
+            make_task(10, Some(4.0), Some("2026-10-08")),
+            make_task(11, Some(6.0), Some("2026-10-10")),
+            // Outside the 5-day window
+            make_task(12, Some(100.0), Some("2026-09-01")),
+        ];
+        let subgraph = vec![make_task(1, Some(5.0), None)];
+
+        let forecast = Forecast::compute(1, &subgraph, &history, date("2026-10-10"), 5);
+        assert_eq!(forecast.throughput, Some(2.0));
+        // 5.0 remaining at 2.0/day takes 3 days
+        assert_eq!(forecast.projected_date, Some(date("2026-10-13")));
+
+        let projected: Vec<f64> = forecast
+            .burndown
+            .iter()
+            .filter(|p| p.projected)
+            .map(|p| p.remaining)
+            .collect();
+        assert_eq!(projected, vec![3.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_target_reached() {
+        let subgraph = vec![make_task(1, Some(2.0), Some("2026-10-01"))];
+        let forecast = Forecast::compute(1, &subgraph, &subgraph, date("2026-10-03"), 14);
+        assert_eq!(forecast.remaining_tasks, 0);
+        assert_eq!(forecast.projected_date, Some(date("2026-10-03")));
+    }
+
+    #[test]
+    fn test_burndown_history() {
+        let subgraph = vec![
+            make_task(1, Some(2.0), Some("2026-10-01")),
+            make_task(2, Some(3.0), Some("2026-10-03")),
+            make_task(3, Some(5.0), None),
+        ];
+
+        let forecast = Forecast::compute(3, &subgraph, &[], date("2026-10-04"), 14);
+        let history: Vec<(NaiveDate, f64)> = forecast
+            .burndown
+            .iter()
+            .map(|p| (p.date, p.remaining))
+            .collect();
+        assert_eq!(
+            history,
+            vec![
+                (date("2026-10-01"), 8.0),
+                (date("2026-10-02"), 8.0),
+                (date("2026-10-03"), 5.0),
+                (date("2026-10-04"), 5.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_burndown_is_downsampled() {
+        let history = vec![make_task(10, Some(1.0), Some("2026-10-10"))];
+        let subgraph = vec![make_task(1, Some(100.0), None)];
+
+        let forecast = Forecast::compute(1, &subgraph, &history, date("2026-10-10"), 10);
+        assert!(forecast.burndown.len() <= MAX_CHART_ROWS);
+        assert_eq!(forecast.burndown.last().unwrap().remaining, 0.0);
+        assert_eq!(forecast.projected_date, Some(date("2029-07-06")));
+    }
+
+    #[test]
+    fn test_render_burndown() {
+        let subgraph = vec![
+            make_task(1, Some(2.0), Some("2026-10-01")),
+            make_task(2, Some(2.0), None),
+        ];
+        let forecast = Forecast::compute(2, &subgraph, &subgraph, date("2026-10-02"), 2);
+
+        let chart = forecast.render_burndown();
+        let lines: Vec<&str> = chart.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("2026-10-01 |"));
+        assert!(lines[0].contains(&"█".repeat(20)));
+        assert!(lines[0].ends_with(" 2.0"));
+        assert!(lines[2].contains('░'));
+        assert!(lines[3].ends_with(" 0.0"));
+    }
+}
@@ -3,9 +3,12 @@
 pub mod artifact;
 pub mod config;
 pub mod dependency;
+pub mod forecast;
 pub mod repository;
 pub mod task;
+pub mod undo;
 
 pub use artifact::Artifact;
+pub use forecast::Forecast;
 pub use repository::TaskRepository;
 pub use task::{Task, TaskStatus};
@@ -1,29 +1,50 @@
 //! Task repository - high-level task operations.
 
+use crate::core::artifact::{self, Artifact};
 use crate::core::config::{get_target, set_target};
 use crate::core::dependency;
+use crate::core::forecast::Forecast;
+use crate::core::undo::{self, Change};
 use crate::core::{Task, TaskStatus};
-use crate::db::{schema::TaskRow, Connection};
+use crate::db::{
+    schema::{ArtifactRow, Schema, TaskRow},
+    Connection,
+};
 use crate::error::{Error, Result};
 use std::collections::{BinaryHeap, HashMap};
 
 /// Task repository.
+///
+/// Mutating methods record their changes in the undo log.
 pub struct TaskRepository {
     conn: Connection,
+    /// Changes collected by [`TaskRepository::grouped`], logged as one command.
+    group: Option<Group>,
+}
+
+#[derive(Default)]
+struct Group {
+    command: Option<String>,
+    changes: Vec<Change>,
 }
 
 impl TaskRepository {
     /// Open a new repository connection.
     pub fn open() -> Result<Self> {
-        let conn = Connection::open_default()?;
-        Ok(Self { conn })
+        let mut conn = Connection::open_default()?;
+        Schema::migrate(&mut conn)?;
+        Ok(Self::with_conn(conn))
     }
 
     /// Open an in-memory repository for testing.
     #[cfg(test)]
     pub fn open_in_memory() -> Result<Self> {
         let conn = Connection::open_in_memory()?;
-        Ok(Self { conn })
+        Ok(Self::with_conn(conn))
+    }
+
+    pub(crate) fn with_conn(conn: Connection) -> Self {
+        Self { conn, group: None }
     }
 
     /// Get the underlying connection.
@@ -31,6 +52,74 @@ impl TaskRepository {
         &mut self.conn
     }
 
+    /// Run `f` in a savepoint, rolling back everything it wrote if it fails.
+    fn atomically<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        self.conn.execute("SAVEPOINT repository", &[])?;
+        match f(self) {
+            Ok(value) => {
+                self.conn.execute("RELEASE repository", &[])?;
+                Ok(value)
+            }
+            Err(e) => {
+                self.conn.execute("ROLLBACK TO repository", &[])?;
+                self.conn.execute("RELEASE repository", &[])?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Log `changes` as `command`, or add them to the open group.
+    fn record(&mut self, command: String, changes: Vec<Change>) -> Result<()> {
+        match &mut self.group {
+            Some(group) => {
+                group.command.get_or_insert(command);
+                group.changes.extend(changes);
+                Ok(())
+            }
+            None => undo::record(&mut self.conn, &command, &changes),
+        }
+    }
+
+    /// Run several mutations atomically and log them as a single command,
+    /// named after the first one.
+    pub fn grouped<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        self.atomically(|repo| {
+            repo.group = Some(Group::default());
+            let result = f(repo);
+            let group = repo.group.take().unwrap_or_default();
+            let value = result?;
+
+            if let Some(command) = group.command {
+                undo::record(&mut repo.conn, &command, &group.changes)?;
+            }
+            Ok(value)
+        })
+    }
+
+    /// Run `f` atomically and log how it changed task `id`.
+    fn track_task<T>(
+        &mut self,
+        command: String,
+        id: i64,
+        f: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> Result<T> {
+        self.atomically(|repo| {
+            let before = undo::task_image(&mut repo.conn, id)?;
+            let value = f(repo)?;
+            let after = undo::task_image(&mut repo.conn, id)?;
+
+            if before != after {
+                let change = Change::Task {
+                    id,
+                    before: before.map(Box::new),
+                    after: after.map(Box::new),
+                };
+                repo.record(command, vec![change])?;
+            }
+            Ok(value)
+        })
+    }
+
     /// Create a new task.
     pub fn create_task(
         &mut self,
@@ -39,18 +128,29 @@ impl TaskRepository {
         dod: Option<String>,
         manual_order: f64,
     ) -> Result<Task> {
-        self.conn.execute(
-            "INSERT INTO tasks (title, description, dod, manual_order) VALUES (?, ?, ?, ?)",
-            &[
-                &title as &dyn rusqlite::ToSql,
-                &description as &dyn rusqlite::ToSql,
-                &dod as &dyn rusqlite::ToSql,
-                &manual_order as &dyn rusqlite::ToSql,
-            ],
-        )?;
+        self.atomically(|repo| {
+            repo.conn.execute(
+                "INSERT INTO tasks (title, description, dod, manual_order) VALUES (?, ?, ?, ?)",
+                &[
+                    &title as &dyn rusqlite::ToSql,
+                    &description as &dyn rusqlite::ToSql,
+                    &dod as &dyn rusqlite::ToSql,
+                    &manual_order as &dyn rusqlite::ToSql,
+                ],
+            )?;
 
-        let id = self.conn.last_insert_rowid();
-        self.get_task(id)
+            let id = repo.conn.last_insert_rowid();
+            let after = undo::task_image(&mut repo.conn, id)?;
+            repo.record(
+                format!("add #{id}"),
+                vec![Change::Task {
+                    id,
+                    before: None,
+                    after: after.map(Box::new),
+                }],
+            )?;
+            repo.get_task(id)
+        })
     }
 
     /// Get a task by ID.
@@ -96,93 +196,112 @@ impl TaskRepository {
         title: Option<String>,
         description: Option<String>,
         dod: Option<String>,
+        estimate: Option<f64>,
     ) -> Result<Task> {
-        let task = self.get_task(id)?;
+        self.track_task(format!("edit #{id}"), id, |repo| {
+            let task = repo.get_task(id)?;
 
-        if task.status == TaskStatus::Completed {
-            return Err(Error::TaskCompleted(id));
-        }
+            if task.status == TaskStatus::Completed {
+                return Err(Error::TaskCompleted(id));
+            }
 
-        // Build the UPDATE dynamically based on what's provided
-        if let Some(t) = title {
-            self.conn.execute(
-                "UPDATE tasks SET title = ?, last_touched_at = strftime('%Y-%m-%dT%H:%M:%S', 'now') WHERE id = ?",
-                &[&t as &dyn rusqlite::ToSql, &id as &dyn rusqlite::ToSql],
-            )?;
-        }
-        if description.is_some() {
-            self.conn.execute(
-                "UPDATE tasks SET description = ?, last_touched_at = strftime('%Y-%m-%dT%H:%M:%S', 'now') WHERE id = ?",
-                &[&description as &dyn rusqlite::ToSql, &id as &dyn rusqlite::ToSql],
-            )?;
-        }
-        if dod.is_some() {
-            self.conn.execute(
-                "UPDATE tasks SET dod = ?, last_touched_at = strftime('%Y-%m-%dT%H:%M:%S', 'now') WHERE id = ?",
-                &[&dod as &dyn rusqlite::ToSql, &id as &dyn rusqlite::ToSql],
-            )?;
-        }
+            if let Some(e) = estimate {
+                if !e.is_finite() || e < 0.0 {
+                    return Err(Error::InvalidEstimate(e.to_string()));
+                }
+            }
 
-        self.get_task(id)
+            // Build the UPDATE dynamically based on what's provided
+            if let Some(t) = title {
+                repo.conn.execute(
+                    "UPDATE tasks SET title = ?, last_touched_at = strftime('%Y-%m-%dT%H:%M:%S', 'now') WHERE id = ?",
+                    &[&t as &dyn rusqlite::ToSql, &id as &dyn rusqlite::ToSql],
+                )?;
+            }
+            if description.is_some() {
+                repo.conn.execute(
+                    "UPDATE tasks SET description = ?, last_touched_at = strftime('%Y-%m-%dT%H:%M:%S', 'now') WHERE id = ?",
+                    &[&description as &dyn rusqlite::ToSql, &id as &dyn rusqlite::ToSql],
+                )?;
+            }
+            if dod.is_some() {
+                repo.conn.execute(
+                    "UPDATE tasks SET dod = ?, last_touched_at = strftime('%Y-%m-%dT%H:%M:%S', 'now') WHERE id = ?",
+                    &[&dod as &dyn rusqlite::ToSql, &id as &dyn rusqlite::ToSql],
+                )?;
+            }
+            if let Some(e) = estimate {
+                repo.conn.execute(
+                    "UPDATE tasks SET estimate = ?, last_touched_at = strftime('%Y-%m-%dT%H:%M:%S', 'now') WHERE id = ?",
+                    &[&e as &dyn rusqlite::ToSql, &id as &dyn rusqlite::ToSql],
+                )?;
+            }
+
+            repo.get_task(id)
+        })
     }
 
     /// Start a task (move to in_progress).
     pub fn start_task(&mut self, id: i64) -> Result<Task> {
-        // Check if another task is already active
-        if let Ok(active) = self.get_active_task() {
-            if active.id == id {
-                // Already active, return as no-op
-                return self.get_task(id);
+        self.track_task(format!("start #{id}"), id, |repo| {
+            // Check if another task is already active
+            if let Ok(active) = repo.get_active_task() {
+                if active.id == id {
+                    // Already active, return as no-op
+                    return repo.get_task(id);
+                }
+                return Err(Error::AnotherTaskActive(active.id));
             }
-            return Err(Error::AnotherTaskActive(active.id));
-        }
 
-        let task = self.get_task(id)?;
+            let task = repo.get_task(id)?;
 
-        // Check if task is in pending status
-        if task.status != TaskStatus::Pending {
-            return Err(Error::TaskNotPending(id));
-        }
-
-        // Check all dependencies are completed
-        let deps = dependency::get_dependencies(&mut self.conn, id)?;
-        let unmet: Vec<i64> = deps
-            .into_iter()
-            .filter(|&dep_id| {
-                if let Ok(dep_task) = self.get_task(dep_id) {
-                    dep_task.status != TaskStatus::Completed
-                } else {
-                    true
-                }
-            })
-            .collect();
+            // Check if task is in pending status
+            if task.status != TaskStatus::Pending {
+                return Err(Error::TaskNotPending(id));
+            }
 
-        if !unmet.is_empty() {
-            return Err(Error::UnmetDependencies(
-                id,
-                crate::error::format_task_ids(&unmet),
-            ));
-        }
+            // Check all dependencies are completed
+            let deps = dependency::get_dependencies(&mut repo.conn, id)?;
+            let unmet: Vec<i64> = deps
+                .into_iter()
+                .filter(|&dep_id| {
+                    if let Ok(dep_task) = repo.get_task(dep_id) {
+                        dep_task.status != TaskStatus::Completed
+                    } else {
+                        true
+                    }
+                })
+                .collect();
+
+            if !unmet.is_empty() {
+                return Err(Error::UnmetDependencies(
+                    id,
+                    crate::error::format_task_ids(&unmet),
+                ));
+            }
 
-        // Update the task
-        self.conn.execute(
-            "UPDATE tasks SET status = 'in_progress', started_at = strftime('%Y-%m-%dT%H:%M:%S', 'now'), last_touched_at = strftime('%Y-%m-%dT%H:%M:%S', 'now') WHERE id = ?",
-            &[&id as &dyn rusqlite::ToSql],
-        )?;
+            // Update the task
+            repo.conn.execute(
+                "UPDATE tasks SET status = 'in_progress', started_at = strftime('%Y-%m-%dT%H:%M:%S', 'now'), last_touched_at = strftime('%Y-%m-%dT%H:%M:%S', 'now') WHERE id = ?",
+                &[&id as &dyn rusqlite::ToSql],
+            )?;
 
-        self.get_task(id)
+            repo.get_task(id)
+        })
     }
 
     /// Stop the active task (move back to pending).
     pub fn stop_task(&mut self) -> Result<Task> {
         let task = self.get_active_task()?;
 
-        self.conn.execute(
-            "UPDATE tasks SET status = 'pending', last_touched_at = strftime('%Y-%m-%dT%H:%M:%S', 'now') WHERE id = ?",
-            &[&task.id as &dyn rusqlite::ToSql],
-        )?;
+        self.track_task(format!("stop #{}", task.id), task.id, |repo| {
+            repo.conn.execute(
+                "UPDATE tasks SET status = 'pending', last_touched_at = strftime('%Y-%m-%dT%H:%M:%S', 'now') WHERE id = ?",
+                &[&task.id as &dyn rusqlite::ToSql],
+            )?;
 
-        self.get_task(task.id)
+            repo.get_task(task.id)
+        })
     }
 
     /// Complete the active task.
@@ -194,55 +313,75 @@ impl TaskRepository {
             return Err(Error::NoDod(task.id));
         }
 
-        self.conn.execute(
-            "UPDATE tasks SET status = 'completed', completed_at = strftime('%Y-%m-%dT%H:%M:%S', 'now'), last_touched_at = strftime('%Y-%m-%dT%H:%M:%S', 'now') WHERE id = ?",
-            &[&task.id as &dyn rusqlite::ToSql],
-        )?;
+        self.track_task(format!("done #{}", task.id), task.id, |repo| {
+            repo.conn.execute(
+                "UPDATE tasks SET status = 'completed', completed_at = strftime('%Y-%m-%dT%H:%M:%S', 'now'), last_touched_at = strftime('%Y-%m-%dT%H:%M:%S', 'now') WHERE id = ?",
+                &[&task.id as &dyn rusqlite::ToSql],
+            )?;
 
-        self.get_task(task.id)
+            repo.get_task(task.id)
+        })
     }
 
     /// Block a task.
     pub fn block_task(&mut self, id: i64) -> Result<Task> {
-        let task = self.get_task(id)?;
-
-        match task.status {
-            TaskStatus::Pending | TaskStatus::InProgress => {
-                self.conn.execute(
-                    "UPDATE tasks SET status = 'blocked', last_touched_at = strftime('%Y-%m-%dT%H:%M:%S', 'now') WHERE id = ?",
-                    &[&id as &dyn rusqlite::ToSql],
-                )?;
-            }
-            _ => {
-                return Err(Error::InvalidTransition(
-                    task.status.as_str().to_string(),
-                    "blocked".to_string(),
-                ))
+        self.track_task(format!("block #{id}"), id, |repo| {
+            let task = repo.get_task(id)?;
+
+            match task.status {
+                TaskStatus::Pending | TaskStatus::InProgress => {
+                    repo.conn.execute(
+                        "UPDATE tasks SET status = 'blocked', last_touched_at = strftime('%Y-%m-%dT%H:%M:%S', 'now') WHERE id = ?",
+                        &[&id as &dyn rusqlite::ToSql],
+                    )?;
+                }
+                _ => {
+                    return Err(Error::InvalidTransition(
+                        task.status.as_str().to_string(),
+                        "blocked".to_string(),
+                    ))
+                }
             }
-        }
 
-        self.get_task(id)
+            repo.get_task(id)
+        })
     }
 
     /// Unblock a task.
     pub fn unblock_task(&mut self, id: i64) -> Result<Task> {
-        let task = self.get_task(id)?;
+        self.track_task(format!("unblock #{id}"), id, |repo| {
+            let task = repo.get_task(id)?;
 
-        if task.status != TaskStatus::Blocked {
-            return Err(Error::TaskNotBlocked(id));
-        }
+            if task.status != TaskStatus::Blocked {
+                return Err(Error::TaskNotBlocked(id));
+            }
 
-        self.conn.execute(
-            "UPDATE tasks SET status = 'pending', last_touched_at = strftime('%Y-%m-%dT%H:%M:%S', 'now') WHERE id = ?",
-            &[&id as &dyn rusqlite::ToSql],
-        )?;
+            repo.conn.execute(
+                "UPDATE tasks SET status = 'pending', last_touched_at = strftime('%Y-%m-%dT%H:%M:%S', 'now') WHERE id = ?",
+                &[&id as &dyn rusqlite::ToSql],
+            )?;
 
-        self.get_task(id)
+            repo.get_task(id)
+        })
     }
 
     /// Set the target.
     pub fn set_target(&mut self, id: i64) -> Result<()> {
-        set_target(&mut self.conn, id)
+        self.atomically(|repo| {
+            let before = get_target(&mut repo.conn)?;
+            set_target(&mut repo.conn, id)?;
+
+            if before != Some(id) {
+                repo.record(
+                    format!("target #{id}"),
+                    vec![Change::Target {
+                        before,
+                        after: Some(id),
+                    }],
+                )?;
+            }
+            Ok(())
+        })
     }
 
     /// Get the target.
@@ -393,20 +532,163 @@ impl TaskRepository {
         Ok(result)
     }
 
+    /// Forecast the remaining effort for a target.
+    ///
+    /// Throughput is measured over the last `window_days` days of completions.
+    pub fn forecast(&mut self, target_id: i64, window_days: i64) -> Result<Forecast> {
+        let target = self.get_task(target_id)?;
+
+        // Recursive CTE over prerequisites, keeping completed tasks for the burndown scope
+        let sql = "
+            WITH RECURSIVE subgraph(id) AS (
+                SELECT ?
+                UNION
+                SELECT d.depends_on FROM dependencies d
+                INNER JOIN subgraph s ON d.task_id = s.id
+            )
+            SELECT t.* FROM tasks t INNER JOIN subgraph s ON t.id = s.id
+        ";
+        let subgraph = self
+            .conn
+            .query(
+                sql,
+                &[&target.id as &dyn rusqlite::ToSql],
+                TaskRow::from_row,
+            )?
+            .into_iter()
+            .map(Task::from_row)
+            .collect::<Result<Vec<_>>>()?;
+
+        let history = self
+            .conn
+            .query(
+                "SELECT * FROM tasks WHERE status = 'completed' AND completed_at IS NOT NULL",
+                &[],
+                TaskRow::from_row,
+            )?
+            .into_iter()
+            .map(Task::from_row)
+            .collect::<Result<Vec<_>>>()?;
+
+        let today = chrono::Utc::now().date_naive();
+        Ok(Forecast::compute(
+            target.id,
+            &subgraph,
+            &history,
+            today,
+            window_days,
+        ))
+    }
+
     /// Reindex all manual_order values.
     pub fn reindex(&mut self) -> Result<()> {
-        let tasks = self.get_all_tasks()?;
-        for (i, task) in tasks.iter().enumerate() {
-            let new_order = (i as i64 + 1) * 10;
-            self.conn.execute(
-                "UPDATE tasks SET manual_order = ? WHERE id = ?",
+        self.atomically(|repo| {
+            let before = undo::all_task_images(&mut repo.conn)?;
+
+            let tasks = repo.get_all_tasks()?;
+            for (i, task) in tasks.iter().enumerate() {
+                let new_order = (i as i64 + 1) * 10;
+                repo.conn.execute(
+                    "UPDATE tasks SET manual_order = ? WHERE id = ?",
+                    &[
+                        &new_order as &dyn rusqlite::ToSql,
+                        &task.id as &dyn rusqlite::ToSql,
+                    ],
+                )?;
+            }
+
+            let after = undo::all_task_images(&mut repo.conn)?;
+            let changes = before
+                .into_iter()
+                .zip(after)
+                .filter(|(before, after)| before != after)
+                .map(|(before, after)| Change::Task {
+                    id: before.id,
+                    before: Some(Box::new(before)),
+                    after: Some(Box::new(after)),
+                })
+                .collect();
+            repo.record("reindex".to_string(), changes)
+        })
+    }
+
+    /// Set a task's manual_order.
+    pub fn reorder_task(&mut self, id: i64, manual_order: f64) -> Result<()> {
+        self.track_task(format!("reorder #{id}"), id, |repo| {
+            repo.conn.execute(
+                "UPDATE tasks SET manual_order = ?, last_touched_at = strftime('%Y-%m-%dT%H:%M:%S', 'now') WHERE id = ?",
                 &[
-                    &new_order as &dyn rusqlite::ToSql,
-                    &task.id as &dyn rusqlite::ToSql,
+                    &manual_order as &dyn rusqlite::ToSql,
+                    &id as &dyn rusqlite::ToSql,
                 ],
             )?;
-        }
-        Ok(())
+            Ok(())
+        })
+    }
+
+    /// Add a dependency: `task_id` depends on `depends_on`.
+    pub fn add_dependency(&mut self, task_id: i64, depends_on: i64) -> Result<()> {
+        self.atomically(|repo| {
+            dependency::add_dependency(&mut repo.conn, task_id, depends_on)?;
+            repo.record(
+                format!("depend #{task_id} on #{depends_on}"),
+                vec![Change::Dependency {
+                    task_id,
+                    depends_on,
+                    added: true,
+                }],
+            )
+        })
+    }
+
+    /// Remove a dependency.
+    pub fn remove_dependency(&mut self, task_id: i64, depends_on: i64) -> Result<()> {
+        self.atomically(|repo| {
+            let existed =
+                dependency::get_dependencies(&mut repo.conn, task_id)?.contains(&depends_on);
+            dependency::remove_dependency(&mut repo.conn, task_id, depends_on)?;
+
+            if existed {
+                repo.record(
+                    format!("undepend #{task_id} from #{depends_on}"),
+                    vec![Change::Dependency {
+                        task_id,
+                        depends_on,
+                        added: false,
+                    }],
+                )?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Log an artifact for a task.
+    pub fn log_artifact(&mut self, task_id: i64, name: String, path: String) -> Result<Artifact> {
+        self.atomically(|repo| {
+            let artifact = artifact::add_artifact(&mut repo.conn, task_id, name, path)?;
+            let row = ArtifactRow {
+                id: artifact.id,
+                task_id: artifact.task_id,
+                name: artifact.name.clone(),
+                file_path: artifact.file_path.clone(),
+                created_at: artifact.created_at.clone(),
+            };
+            repo.record(
+                format!("log '{}' on #{task_id}", artifact.name),
+                vec![Change::Artifact { artifact: row }],
+            )?;
+            Ok(artifact)
+        })
+    }
+
+    /// Undo the last `n` commands, returning them newest first.
+    pub fn undo(&mut self, n: usize) -> Result<Vec<String>> {
+        self.atomically(|repo| undo::undo(&mut repo.conn, n))
+    }
+
+    /// Redo the most recently undone command.
+    pub fn redo(&mut self) -> Result<String> {
+        self.atomically(|repo| undo::redo(&mut repo.conn))
     }
 }
 
@@ -453,7 +735,7 @@ mod tests {
     fn setup_repo() -> TaskRepository {
         let mut conn = Connection::open_in_memory().unwrap();
         Schema::init(&mut conn).unwrap();
-        TaskRepository { conn }
+        TaskRepository::with_conn(conn)
     }
 
     #[test]
@@ -549,7 +831,7 @@ mod tests {
         let task2 = repo
             .create_task("Task 2".to_string(), None, None, 20.0)
             .unwrap();
-        dependency::add_dependency(&mut repo.conn, 2, 1).unwrap();
+        dependency::add_dependency(&mut repo.conn, task2.id, task1.id).unwrap();
 
         repo.set_target(2).unwrap();
 
@@ -561,4 +843,144 @@ mod tests {
         let next = repo.get_next_task().unwrap();
         assert_eq!(next.id, 2);
     }
+
+    #[test]
+    fn test_update_task_estimate() {
+        let mut repo = setup_repo();
+
+        let task = repo
+            .create_task("Test Task".to_string(), None, None, 10.0)
+            .unwrap();
+        let updated = repo
+            .update_task(task.id, None, None, None, Some(3.5))
+            .unwrap();
+        assert_eq!(updated.estimate, Some(3.5));
+
+        let result = repo.update_task(task.id, None, None, None, Some(-1.0));
+        assert!(matches!(result, Err(Error::InvalidEstimate(_))));
+    }
+
+    #[test]
+    fn test_forecast_walks_dependencies() {
+        let mut repo = setup_repo();
+
+        // 3 depends on 2 depends on 1; 4 is unrelated
+        for (title, estimate) in [
+            ("Task 1", 2.0),
+            ("Task 2", 3.0),
+            ("Task 3", 5.0),
+            ("Task 4", 8.0),
+        ] {
+            let task = repo
+                .create_task(title.to_string(), None, Some("Done".to_string()), 10.0)
+                .unwrap();
+            repo.update_task(task.id, None, None, None, Some(estimate))
+                .unwrap();
+        }
+        dependency::add_dependency(&mut repo.conn, 2, 1).unwrap();
+        dependency::add_dependency(&mut repo.conn, 3, 2).unwrap();
+
+        repo.start_task(1).unwrap();
+        repo.complete_task().unwrap();
+
+        let forecast = repo.forecast(3, 14).unwrap();
+        assert_eq!(forecast.scope, 10.0);
+        assert_eq!(forecast.remaining, 8.0);
+        assert_eq!(forecast.remaining_tasks, 2);
+        assert_eq!(forecast.throughput, Some(2.0 / 14.0));
+        assert!(forecast.projected_date.is_some());
+    }
+
+    fn add_task(repo: &mut TaskRepository, title: &str) -> Task {
+        repo.create_task(title.to_string(), None, Some("Done".to_string()), 10.0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_undo_redo_dependency() {
+        let mut repo = setup_repo();
+        add_task(&mut repo, "Task 1");
+        add_task(&mut repo, "Task 2");
+
+        repo.add_dependency(2, 1).unwrap();
+        assert_eq!(repo.undo(1).unwrap(), vec!["depend #2 on #1"]);
+        assert!(dependency::get_dependencies(&mut repo.conn, 2)
+            .unwrap()
+            .is_empty());
+
+        assert_eq!(repo.redo().unwrap(), "depend #2 on #1");
+        assert_eq!(
+            dependency::get_dependencies(&mut repo.conn, 2).unwrap(),
+            vec![1]
+        );
+        assert!(matches!(repo.redo(), Err(Error::NothingToRedo)));
+    }
+
+    #[test]
+    fn test_undo_edit_and_add() {
+        let mut repo = setup_repo();
+        let task = repo
+            .grouped(|repo| {
+                let task = add_task(repo, "Task 1");
+                repo.update_task(task.id, None, None, None, Some(3.0))
+            })
+            .unwrap();
+        repo.update_task(task.id, Some("Renamed".to_string()), None, None, None)
+            .unwrap();
+
+        assert_eq!(repo.undo(1).unwrap(), vec![format!("edit #{}", task.id)]);
+        assert_eq!(repo.get_task(task.id).unwrap().title, "Task 1");
+
+        // Creating the task and setting its estimate were one command
+        assert_eq!(repo.undo(1).unwrap(), vec![format!("add #{}", task.id)]);
+        assert!(repo.get_task(task.id).is_err());
+
+        assert!(matches!(repo.undo(1), Err(Error::NothingToUndo)));
+
+        repo.redo().unwrap();
+        let task = repo.get_task(task.id).unwrap();
+        assert_eq!(task.title, "Task 1");
+        assert_eq!(task.estimate, Some(3.0));
+    }
+
+    #[test]
+    fn test_undo_several_and_new_command_clears_redo() {
+        let mut repo = setup_repo();
+        let task = add_task(&mut repo, "Task 1");
+        repo.set_target(task.id).unwrap();
+        repo.start_task(task.id).unwrap();
+        repo.complete_task().unwrap();
+
+        let undone = repo.undo(2).unwrap();
+        assert_eq!(
+            undone,
+            vec![format!("done #{}", task.id), format!("start #{}", task.id)]
+        );
+        assert_eq!(repo.get_task(task.id).unwrap().status, TaskStatus::Pending);
+        assert_eq!(repo.get_target().unwrap(), Some(task.id));
+
+        repo.block_task(task.id).unwrap();
+        assert!(matches!(repo.redo(), Err(Error::NothingToRedo)));
+    }
+
+    #[test]
+    fn test_undo_refuses_to_break_dependents() {
+        let mut repo = setup_repo();
+        add_task(&mut repo, "Task 1");
+        add_task(&mut repo, "Task 2");
+        repo.add_dependency(2, 1).unwrap();
+        repo.start_task(1).unwrap();
+        repo.complete_task().unwrap();
+
+        // Start #2 without going through the log
+        repo.conn
+            .execute("UPDATE tasks SET status = 'in_progress' WHERE id = 2", &[])
+            .unwrap();
+
+        let err = repo.undo(1).unwrap_err();
+        assert!(matches!(err, Error::UndoConflict(..)));
+        assert!(err.to_string().contains("stop #2 first"));
+        assert_eq!(repo.get_task(1).unwrap().status, TaskStatus::Completed);
+        assert_eq!(repo.get_task(2).unwrap().status, TaskStatus::InProgress);
+    }
 }
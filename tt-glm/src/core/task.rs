@@ -50,6 +50,8 @@ pub struct Task {
     pub dod: Option<String>,
     pub status: TaskStatus,
     pub manual_order: f64,
+    /// Effort estimate (points or hours), used by `tt forecast`.
+    pub estimate: Option<f64>,
     pub created_at: String,
     pub started_at: Option<String>,
     pub completed_at: Option<String>,
@@ -66,6 +68,7 @@ impl Task {
             dod: row.dod,
             status: TaskStatus::parse(&row.status)?,
             manual_order: row.manual_order,
+            estimate: row.estimate,
             created_at: row.created_at,
             started_at: row.started_at,
             completed_at: row.completed_at,
@@ -126,6 +129,7 @@ mod tests {
             dod: None,
             status: TaskStatus::Pending,
             manual_order: 10.0,
+            estimate: None,
             created_at: String::new(),
             started_at: None,
             completed_at: None,
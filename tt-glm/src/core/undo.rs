@@ -0,0 +1,292 @@
+//! Undo log for mutating commands.
+//!
+//! Every command records the rows it changed as before/after images. Undo
+//! writes the before images back in reverse order, redo writes the after
+//! images again. Entries that have been undone stay in the log until a new
+//! command is recorded, which discards them.
+
+use crate::core::config::{clear_target, set_target};
+use crate::db::{
+    schema::{ArtifactRow, TaskRow},
+    Connection,
+};
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+/// One row changed by a command.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Change {
+    /// A task row; `None` means the row did not exist.
+    Task {
+        id: i64,
+        before: Option<Box<TaskRow>>,
+        after: Option<Box<TaskRow>>,
+    },
+    /// A dependency edge that was added (or removed when `added` is false).
+    Dependency {
+        task_id: i64,
+        depends_on: i64,
+        added: bool,
+    },
+    /// An artifact that was logged.
+    Artifact { artifact: ArtifactRow },
+    /// The target task id.
+    Target {
+        before: Option<i64>,
+        after: Option<i64>,
+    },
+}
+
+/// A recorded command and its changes.
+struct Entry {
+    id: i64,
+    command: String,
+    changes: Vec<Change>,
+}
+
+/// Read the current image of a task row.
+pub fn task_image(conn: &mut Connection, id: i64) -> Result<Option<TaskRow>> {
+    let rows = conn.query(
+        "SELECT * FROM tasks WHERE id = ?",
+        &[&id as &dyn rusqlite::ToSql],
+        TaskRow::from_row,
+    )?;
+    Ok(rows.into_iter().next())
+}
+
+/// Read the images of all task rows, by id.
+pub fn all_task_images(conn: &mut Connection) -> Result<Vec<TaskRow>> {
+    conn.query("SELECT * FROM tasks ORDER BY id", &[], TaskRow::from_row)
+}
+
+/// Append a command to the log, discarding anything that could be redone.
+pub fn record(conn: &mut Connection, command: &str, changes: &[Change]) -> Result<()> {
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    conn.execute("DELETE FROM undo_log WHERE undone = 1", &[])?;
+    conn.execute(
+        "INSERT INTO undo_log (command, changes) VALUES (?, ?)",
+        &[
+            &command as &dyn rusqlite::ToSql,
+            &serde_json::to_string(changes)? as &dyn rusqlite::ToSql,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Undo up to `n` of the most recent commands, newest first.
+///
+/// Returns the commands that were undone. The caller is expected to run this
+/// inside a transaction so a refused entry rolls back the whole batch.
+pub fn undo(conn: &mut Connection, n: usize) -> Result<Vec<String>> {
+    let mut undone = Vec::new();
+
+    for _ in 0..n {
+        let Some(entry) = next_entry(conn, false)? else {
+            break;
+        };
+
+        for change in entry.changes.iter().rev() {
+            apply(conn, change, true)?;
+        }
+        check_invariants(conn).map_err(|reason| {
+            Error::UndoConflict("undo".to_string(), entry.command.clone(), reason)
+        })?;
+
+        mark(conn, entry.id, true)?;
+        undone.push(entry.command);
+    }
+
+    if undone.is_empty() {
+        return Err(Error::NothingToUndo);
+    }
+    Ok(undone)
+}
+
+/// Redo the most recently undone command.
+pub fn redo(conn: &mut Connection) -> Result<String> {
+    let entry = next_entry(conn, true)?.ok_or(Error::NothingToRedo)?;
+
+    for change in &entry.changes {
+        apply(conn, change, false)?;
+    }
+    check_invariants(conn)
+        .map_err(|reason| Error::UndoConflict("redo".to_string(), entry.command.clone(), reason))?;
+
+    mark(conn, entry.id, false)?;
+    Ok(entry.command)
+}
+
+/// The newest live entry, or the oldest undone one when `undone` is set.
+fn next_entry(conn: &mut Connection, undone: bool) -> Result<Option<Entry>> {
+    let sql = if undone {
+        "SELECT id, command, changes FROM undo_log WHERE undone = 1 ORDER BY id ASC LIMIT 1"
+    } else {
+        "SELECT id, command, changes FROM undo_log WHERE undone = 0 ORDER BY id DESC LIMIT 1"
+    };
+    let rows = conn.query(sql, &[], |row| {
+        Ok((row.get(0)?, row.get(1)?, row.get::<_, String>(2)?))
+    })?;
+
+    match rows.into_iter().next() {
+        Some((id, command, changes)) => Ok(Some(Entry {
+            id,
+            command,
+            changes: serde_json::from_str(&changes)?,
+        })),
+        None => Ok(None),
+    }
+}
+
+fn mark(conn: &mut Connection, id: i64, undone: bool) -> Result<()> {
+    conn.execute(
+        "UPDATE undo_log SET undone = ? WHERE id = ?",
+        &[&undone as &dyn rusqlite::ToSql, &id as &dyn rusqlite::ToSql],
+    )?;
+    Ok(())
+}
+
+/// Write one side of a change: the before image when undoing, the after image when redoing.
+fn apply(conn: &mut Connection, change: &Change, undo: bool) -> Result<()> {
+    match change {
+        Change::Task { id, before, after } => {
+            let image = if undo { before } else { after };
+            restore_task(conn, *id, image.as_deref())
+        }
+        Change::Dependency {
+            task_id,
+            depends_on,
+            added,
+        } => {
+            let params = [
+                task_id as &dyn rusqlite::ToSql,
+                depends_on as &dyn rusqlite::ToSql,
+            ];
+            if *added != undo {
+                conn.execute(
+                    "INSERT OR IGNORE INTO dependencies (task_id, depends_on) VALUES (?, ?)",
+                    &params,
+                )?;
+            } else {
+                conn.execute(
+                    "DELETE FROM dependencies WHERE task_id = ? AND depends_on = ?",
+                    &params,
+                )?;
+            }
+            Ok(())
+        }
+        Change::Artifact { artifact } => {
+            if undo {
+                conn.execute(
+                    "DELETE FROM artifacts WHERE id = ?",
+                    &[&artifact.id as &dyn rusqlite::ToSql],
+                )?;
+            } else {
+                conn.execute(
+                    "INSERT INTO artifacts (id, task_id, name, file_path, created_at) VALUES (?, ?, ?, ?, ?)",
+                    &[
+                        &artifact.id as &dyn rusqlite::ToSql,
+                        &artifact.task_id as &dyn rusqlite::ToSql,
+                        &artifact.name as &dyn rusqlite::ToSql,
+                        &artifact.file_path as &dyn rusqlite::ToSql,
+                        &artifact.created_at as &dyn rusqlite::ToSql,
+                    ],
+                )?;
+            }
+            Ok(())
+        }
+        Change::Target { before, after } => match if undo { before } else { after } {
+            Some(id) => set_target(conn, *id),
+            None => clear_target(conn),
+        },
+    }
+}
+
+/// Make the task row match `image`, inserting or deleting it as needed.
+fn restore_task(conn: &mut Connection, id: i64, image: Option<&TaskRow>) -> Result<()> {
+    let Some(row) = image else {
+        conn.execute(
+            "DELETE FROM tasks WHERE id = ?",
+            &[&id as &dyn rusqlite::ToSql],
+        )?;
+        return Ok(());
+    };
+
+    let params = [
+        &row.title as &dyn rusqlite::ToSql,
+        &row.description as &dyn rusqlite::ToSql,
+        &row.dod as &dyn rusqlite::ToSql,
+        &row.status as &dyn rusqlite::ToSql,
+        &row.manual_order as &dyn rusqlite::ToSql,
+        &row.estimate as &dyn rusqlite::ToSql,
+        &row.created_at as &dyn rusqlite::ToSql,
+        &row.started_at as &dyn rusqlite::ToSql,
+        &row.completed_at as &dyn rusqlite::ToSql,
+        &row.last_touched_at as &dyn rusqlite::ToSql,
+        &row.id as &dyn rusqlite::ToSql,
+    ];
+
+    let updated = conn.execute(
+        "UPDATE tasks SET title = ?, description = ?, dod = ?, status = ?, manual_order = ?,
+            estimate = ?, created_at = ?, started_at = ?, completed_at = ?, last_touched_at = ?
+         WHERE id = ?",
+        &params,
+    )?;
+    if updated == 0 {
+        conn.execute(
+            "INSERT INTO tasks (title, description, dod, status, manual_order, estimate,
+                created_at, started_at, completed_at, last_touched_at, id)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            &params,
+        )?;
+    }
+    Ok(())
+}
+
+/// Check the workflow invariants, explaining the first violation.
+///
+/// Started or completed tasks must have all their dependencies completed, and
+/// at most one task may be in progress.
+fn check_invariants(conn: &mut Connection) -> std::result::Result<(), String> {
+    let violations: Vec<(i64, String, i64)> = conn
+        .query(
+            "SELECT t.id, t.status, d.depends_on FROM tasks t
+             JOIN dependencies d ON d.task_id = t.id
+             JOIN tasks dep ON dep.id = d.depends_on
+             WHERE t.status IN ('in_progress', 'completed') AND dep.status != 'completed'
+             ORDER BY t.id, d.depends_on
+             LIMIT 1",
+            &[],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .map_err(|e| e.to_string())?;
+    if let Some((id, status, dep)) = violations.into_iter().next() {
+        let hint = if status == "in_progress" {
+            format!("stop #{id} first")
+        } else {
+            format!("undo the completion of #{id} first")
+        };
+        return Err(format!(
+            "#{id} is {status} but depends on #{dep}, which would no longer be completed; {hint}"
+        ));
+    }
+
+    let active: Vec<i64> = conn
+        .query(
+            "SELECT id FROM tasks WHERE status = 'in_progress' ORDER BY id",
+            &[],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if active.len() > 1 {
+        return Err(format!(
+            "{} would all be in progress",
+            crate::error::format_task_ids(&active)
+        ));
+    }
+
+    Ok(())
+}
@@ -69,7 +69,7 @@ impl Connection {
     }
 
     /// Begin a new transaction.
-    pub fn transaction(&mut self) -> Result<Transaction> {
+    pub fn transaction(&mut self) -> Result<Transaction<'_>> {
         self.conn.transaction().map_err(Error::from)
     }
 
@@ -89,7 +89,7 @@ impl Connection {
     }
 
     /// Prepare a statement for execution.
-    pub fn prepare(&mut self, sql: &str) -> Result<rusqlite::Statement> {
+    pub fn prepare(&mut self, sql: &str) -> Result<rusqlite::Statement<'_>> {
         self.conn.prepare(sql).map_err(Error::from)
     }
 
@@ -3,13 +3,17 @@
 use crate::db::Connection as DbConnection;
 use crate::error::Result;
 use rusqlite::Row;
+use serde::{Deserialize, Serialize};
 
 /// Schema version and management.
 pub struct Schema;
 
 impl Schema {
     /// Current schema version.
-    pub const VERSION: i32 = 1;
+    ///
+    /// Stored in `PRAGMA user_version`. Version 2 added `tasks.estimate`,
+    /// version 3 the `undo_log` table.
+    pub const VERSION: i32 = 3;
 
     /// Initialize the database schema.
     ///
@@ -40,6 +44,7 @@ impl Schema {
                 status TEXT NOT NULL DEFAULT 'pending'
                     CHECK(status IN ('pending', 'in_progress', 'completed', 'blocked')),
                 manual_order REAL NOT NULL DEFAULT 0.0,
+                estimate REAL CHECK(estimate IS NULL OR estimate >= 0),
                 created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%S', 'now')),
                 started_at TEXT,
                 completed_at TEXT,
@@ -80,6 +85,8 @@ impl Schema {
             &[],
         )?;
 
+        Self::create_undo_log(conn)?;
+
         conn.execute("CREATE INDEX idx_tasks_status ON tasks(status)", &[])?;
         conn.execute(
             "CREATE INDEX idx_tasks_manual_order ON tasks(manual_order)",
@@ -98,9 +105,62 @@ impl Schema {
             &[],
         )?;
 
+        conn.execute_pragma(&format!("PRAGMA user_version = {}", Self::VERSION), &[])?;
+
         Ok(())
     }
 
+    /// Bring an existing database up to the current schema version.
+    ///
+    /// Databases created before versioning report `user_version = 0`.
+    pub fn migrate(conn: &mut DbConnection) -> Result<()> {
+        let version: i32 = conn.query_row("PRAGMA user_version", &[], |row| row.get(0))?;
+        if version >= Self::VERSION {
+            return Ok(());
+        }
+
+        if version < 2 && !Self::column_exists(conn, "tasks", "estimate")? {
+            conn.execute(
+                "ALTER TABLE tasks ADD COLUMN estimate REAL CHECK(estimate IS NULL OR estimate >= 0)",
+                &[],
+            )?;
+        }
+
+        if version < 3 {
+            Self::create_undo_log(conn)?;
+        }
+
+        conn.execute_pragma(&format!("PRAGMA user_version = {}", Self::VERSION), &[])?;
+        Ok(())
+    }
+
+    /// Create the journal of undoable commands.
+    ///
+    /// `changes` holds the JSON-encoded before/after row images of a command;
+    /// `undone` marks entries that `tt redo` can replay.
+    fn create_undo_log(conn: &mut DbConnection) -> Result<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS undo_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                command TEXT NOT NULL,
+                changes TEXT NOT NULL,
+                undone INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%S', 'now'))
+            )",
+            &[],
+        )?;
+        Ok(())
+    }
+
+    /// Check whether a table has a column with the given name.
+    fn column_exists(conn: &mut DbConnection, table: &str, column: &str) -> Result<bool> {
+        let columns: Vec<String> =
+            conn.query(&format!("PRAGMA table_info({table})"), &[], |row| {
+                row.get(1)
+            })?;
+        Ok(columns.iter().any(|c| c == column))
+    }
+
     /// Check if the database schema is valid (already initialized).
     pub fn is_initialized(conn: &mut DbConnection) -> bool {
         conn.prepare("SELECT name FROM sqlite_master WHERE type='table' AND name='tasks'")
@@ -110,7 +170,7 @@ impl Schema {
 }
 
 /// Row representation of a task from the database.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TaskRow {
     pub id: i64,
     pub title: String,
@@ -118,6 +178,7 @@ pub struct TaskRow {
     pub dod: Option<String>,
     pub status: String,
     pub manual_order: f64,
+    pub estimate: Option<f64>,
     pub created_at: String,
     pub started_at: Option<String>,
     pub completed_at: Option<String>,
@@ -134,6 +195,7 @@ impl TaskRow {
             dod: row.get("dod")?,
             status: row.get("status")?,
             manual_order: row.get("manual_order")?,
+            estimate: row.get("estimate")?,
             created_at: row.get("created_at")?,
             started_at: row.get("started_at")?,
             completed_at: row.get("completed_at")?,
@@ -160,7 +222,7 @@ impl DependencyRow {
 }
 
 /// Row representation of an artifact from the database.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ArtifactRow {
     pub id: i64,
     pub task_id: i64,
@@ -321,6 +383,38 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_schema_init_sets_version() {
+        let mut conn = create_temp_db();
+        Schema::init(&mut conn).unwrap();
+
+        let version: i32 = conn
+            .query_row("PRAGMA user_version", &[], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, Schema::VERSION);
+    }
+
+    #[test]
+    fn test_migrate_adds_estimate_column() {
+        let mut conn = create_temp_db();
+        // A version 1 database without the estimate column
+        conn.execute(
+            "CREATE TABLE tasks (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                title TEXT NOT NULL
+            )",
+            &[],
+        )
+        .unwrap();
+
+        Schema::migrate(&mut conn).unwrap();
+        assert!(Schema::column_exists(&mut conn, "tasks", "estimate").unwrap());
+        assert!(conn.table_exists("undo_log").unwrap());
+
+        // Running again is a no-op
+        Schema::migrate(&mut conn).unwrap();
+    }
+
     #[test]
     fn test_is_initialized() {
         let mut conn = create_temp_db();
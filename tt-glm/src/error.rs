@@ -104,9 +104,25 @@ pub enum Error {
     #[error("Cannot specify both --after and --before")]
     BothAfterAndBefore,
 
+    /// Estimate is negative or not a number.
+    #[error("Invalid estimate: {0}. Must be a non-negative number")]
+    InvalidEstimate(String),
+
     /// Reorder needs at least one of after or before.
     #[error("Must specify at least one of --after or --before")]
     NeedAfterOrBefore,
+
+    /// The undo log has no command to undo.
+    #[error("Nothing to undo")]
+    NothingToUndo,
+
+    /// No undone command is left to redo.
+    #[error("Nothing to redo")]
+    NothingToRedo,
+
+    /// Undoing or redoing a command would break a workflow invariant.
+    #[error("Cannot {0} '{1}': {2}")]
+    UndoConflict(String, String, String),
 }
 
 /// Format a list of task IDs as a comma-separated string with # prefix.
@@ -104,6 +104,7 @@ mod tests {
             dod: None,
             status: TaskStatus::Pending,
             manual_order: order,
+            estimate: None,
             created_at: String::new(),
             started_at: None,
             completed_at: None,
@@ -8,6 +8,7 @@ pub mod core;
 pub mod db;
 pub mod error;
 pub mod graph;
+pub mod mcp;
 
 // Re-export commonly used types
 pub use core::{Artifact, Task, TaskStatus};
@@ -0,0 +1,128 @@
+//! MCP server for tt.
+//!
+//! Serves tools over stdio so an agent can act on the tracker directly.
+
+use crate::core::TaskRepository;
+use crate::error::{Error, Result};
+use rmcp::{
+    handler::server::router::tool::ToolRouter, model::*, tool, tool_router, ErrorData as McpError,
+    ServiceExt,
+};
+use std::sync::{Arc, Mutex};
+
+/// Serve the MCP tools over stdio until the client disconnects.
+pub fn run(repo: TaskRepository) -> Result<()> {
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime.block_on(async {
+        let service = TtMcp::new(repo)
+            .serve((tokio::io::stdin(), tokio::io::stdout()))
+            .await
+            .map_err(|e| Error::Io(std::io::Error::other(e)))?;
+        service
+            .waiting()
+            .await
+            .map_err(|e| Error::Io(std::io::Error::other(e)))?;
+        Ok(())
+    })
+}
+
+/// rmcp handler exposing a [`TaskRepository`] as MCP tools.
+#[derive(Clone)]
+pub struct TtMcp {
+    repo: Arc<Mutex<TaskRepository>>,
+    tool_router: ToolRouter<Self>,
+}
+
+#[tool_router]
+impl TtMcp {
+    pub fn new(repo: TaskRepository) -> Self {
+        Self {
+            repo: Arc::new(Mutex::new(repo)),
+            tool_router: Self::tool_router(),
+        }
+    }
+
+    /// Undo the most recent command, the same as `tt undo`.
+    #[tool(
+        description = "Undo the last tt command, e.g. a dependency or reorder made by mistake. Refused if it would break a task that is in progress."
+    )]
+    pub fn undo_last(&self) -> std::result::Result<CallToolResult, McpError> {
+        let mut repo = self.repo.lock().unwrap();
+        Ok(match repo.undo(1) {
+            Ok(undone) => CallToolResult::success(vec![Content::text(format!(
+                "Undid: {}",
+                undone.join(", ")
+            ))]),
+            Err(e) => CallToolResult::error(vec![Content::text(e.to_string())]),
+        })
+    }
+}
+
+/// Kept apart from `crate::error::Result`, which `#[tool_handler]`'s
+/// generated code would otherwise pick up
+mod handler {
+    use super::TtMcp;
+    use rmcp::{model::*, tool_handler, ServerHandler};
+
+    #[tool_handler]
+    impl ServerHandler for TtMcp {
+        fn get_info(&self) -> ServerInfo {
+            ServerInfo {
+                instructions: Some(
+                    "DAG-based task tracker. Call undo_last to revert the last command."
+                        .to_string(),
+                ),
+                capabilities: ServerCapabilities::builder().enable_tools().build(),
+                ..Default::default()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::dependency;
+    use crate::db::schema::Schema;
+    use crate::db::Connection;
+
+    fn setup_mcp() -> TtMcp {
+        let mut conn = Connection::open_in_memory().unwrap();
+        Schema::init(&mut conn).unwrap();
+        TtMcp::new(TaskRepository::with_conn(conn))
+    }
+
+    fn text(result: &CallToolResult) -> String {
+        result.content[0].as_text().unwrap().text.clone()
+    }
+
+    #[test]
+    fn test_undo_last() {
+        let mcp = setup_mcp();
+        {
+            let mut repo = mcp.repo.lock().unwrap();
+            repo.create_task("Task 1".to_string(), None, None, 1.0)
+                .unwrap();
+            repo.create_task("Task 2".to_string(), None, None, 2.0)
+                .unwrap();
+            repo.add_dependency(2, 1).unwrap();
+        }
+
+        let result = mcp.undo_last().unwrap();
+        assert_eq!(result.is_error, Some(false));
+        assert_eq!(text(&result), "Undid: depend #2 on #1");
+        let mut repo = mcp.repo.lock().unwrap();
+        assert!(dependency::get_dependencies(repo.conn(), 2)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_undo_last_with_empty_log() {
+        let mcp = setup_mcp();
+
+        let result = mcp.undo_last().unwrap();
+        assert_eq!(result.is_error, Some(true));
+        assert_eq!(text(&result), Error::NothingToUndo.to_string());
+    }
+}
@@ -5,6 +5,7 @@ use std::path::PathBuf;
 
 const DB_FILE: &str = "tt.db";
 const ARTIFACTS_DIR: &str = ".tt/artifacts";
+const SNAPSHOTS_DIR: &str = ".tt/snapshots";
 
 #[derive(Parser)]
 #[command(name = "tt")]
@@ -118,6 +119,26 @@ pub enum Commands {
         on_id: i64,
     },
 
+    /// Split a task into subtasks
+    Split {
+        /// Task ID
+        id: i64,
+        /// Subtask titles
+        #[arg(long, num_args = 1.., required = true)]
+        into: Vec<String>,
+        /// Run subtasks in sequence instead of in parallel
+        #[arg(long)]
+        chain: bool,
+    },
+
+    /// Merge a task into another
+    Merge {
+        /// Task ID to keep
+        id: i64,
+        /// Task ID to merge in and remove
+        other_id: i64,
+    },
+
     /// Log an artifact for the active task
     Log {
         /// Artifact name
@@ -149,10 +170,31 @@ pub enum Commands {
     /// Reindex all task orders
     Reindex,
 
+    /// Copy the database into .tt/snapshots
+    #[command(args_conflicts_with_subcommands = true)]
+    Snapshot {
+        #[command(subcommand)]
+        action: Option<SnapshotAction>,
+        /// Snapshot name (defaults to the current time; "auto-" is reserved)
+        name: Option<String>,
+    },
+
+    /// Replace the database with a snapshot
+    Restore {
+        /// Snapshot name
+        name: String,
+    },
+
     /// Start MCP server
     Mcp,
 }
 
+#[derive(Subcommand)]
+pub enum SnapshotAction {
+    /// List snapshots
+    List,
+}
+
 pub fn run() -> TTResult<()> {
     let cli = Cli::parse();
 
@@ -183,10 +225,17 @@ pub fn run() -> TTResult<()> {
         Commands::Current => cmd_current(),
         Commands::Depend { id, on_id } => cmd_depend(id, on_id),
         Commands::Undepend { id, on_id } => cmd_undepend(id, on_id),
+        Commands::Split { id, into, chain } => cmd_split(id, into, chain),
+        Commands::Merge { id, other_id } => cmd_merge(id, other_id),
         Commands::Log { name, file } => cmd_log(name, file),
         Commands::Artifacts { task } => cmd_artifacts(task),
         Commands::Reorder { id, after, before } => cmd_reorder(id, after, before),
         Commands::Reindex => cmd_reindex(),
+        Commands::Snapshot { action, name } => match action {
+            Some(SnapshotAction::List) => cmd_snapshot_list(),
+            None => cmd_snapshot(name),
+        },
+        Commands::Restore { name } => cmd_restore(name),
         Commands::Mcp => crate::mcp::run_mcp_server(),
     }
 }
@@ -196,7 +245,7 @@ fn get_core() -> TTResult<AppCore> {
     if !db_path.exists() {
         return Err(TTError::NotInitialized);
     }
-    AppCore::open(db_path)
+    Ok(AppCore::open(db_path)?.with_snapshots(SNAPSHOTS_DIR))
 }
 
 fn cmd_init() -> TTResult<()> {
@@ -457,6 +506,27 @@ fn cmd_undepend(id: i64, on_id: i64) -> TTResult<()> {
     Ok(())
 }
 
+fn cmd_split(id: i64, into: Vec<String>, chain: bool) -> TTResult<()> {
+    let core = get_core()?;
+    let result = core.split_task(id, &into, chain)?;
+    println!(
+        "Split task #{} into {} subtasks:",
+        result.original.id,
+        result.subtasks.len()
+    );
+    for subtask in &result.subtasks {
+        println!("  [#{:>3}] {}", subtask.id, subtask.title);
+    }
+    Ok(())
+}
+
+fn cmd_merge(id: i64, other_id: i64) -> TTResult<()> {
+    let core = get_core()?;
+    let task = core.merge_tasks(id, other_id)?;
+    println!("Merged task #{other_id} into #{}: {}", task.id, task.title);
+    Ok(())
+}
+
 fn cmd_log(name: String, file: String) -> TTResult<()> {
     let core = get_core()?;
     let artifact = core.log_artifact(&name, &file)?;
@@ -499,3 +569,38 @@ fn cmd_reindex() -> TTResult<()> {
     println!("Reindexed all tasks");
     Ok(())
 }
+
+fn cmd_snapshot(name: Option<String>) -> TTResult<()> {
+    let core = get_core()?;
+    let snapshot = core.snapshot(name.as_deref())?;
+    println!("Created snapshot '{}': {}", snapshot.name, snapshot.path);
+    Ok(())
+}
+
+fn cmd_snapshot_list() -> TTResult<()> {
+    let core = get_core()?;
+    let snapshots = core.list_snapshots()?;
+
+    if snapshots.is_empty() {
+        println!("No snapshots found");
+        return Ok(());
+    }
+
+    for snapshot in snapshots {
+        println!(
+            "  {:<40} {}  {:>8} bytes",
+            snapshot.name,
+            snapshot.created_at.format("%Y-%m-%d %H:%M:%S"),
+            snapshot.size
+        );
+    }
+
+    Ok(())
+}
+
+fn cmd_restore(name: String) -> TTResult<()> {
+    let mut core = get_core()?;
+    let snapshot = core.restore_snapshot(&name)?;
+    println!("Restored snapshot '{}'", snapshot.name);
+    Ok(())
+}
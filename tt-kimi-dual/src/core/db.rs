@@ -1,7 +1,8 @@
 use crate::core::error::{TTError, TTResult};
 use crate::core::models::{Artifact, Dependency, Task, TaskStatus};
 use chrono::{DateTime, Utc};
-use rusqlite::{params, Connection, OptionalExtension, Row};
+use rusqlite::backup::Progress;
+use rusqlite::{params, Connection, DatabaseName, OptionalExtension, Row};
 
 use std::path::Path;
 
@@ -75,6 +76,30 @@ impl Database {
         Ok(())
     }
 
+    /// Run `f` inside a transaction, rolling back if it returns an error
+    pub fn transaction<T>(&self, f: impl FnOnce() -> TTResult<T>) -> TTResult<T> {
+        let tx = self.conn.unchecked_transaction()?;
+        let value = f()?;
+        tx.commit()?;
+        Ok(value)
+    }
+
+    // Snapshots
+
+    /// Copy the database to `path` with SQLite's online backup API, which
+    /// gives a consistent copy even while other connections write through WAL
+    pub fn backup_to(&self, path: &Path) -> TTResult<()> {
+        self.conn.backup(DatabaseName::Main, path, None)?;
+        Ok(())
+    }
+
+    /// Replace the contents of the database with the copy at `path`
+    pub fn restore_from(&mut self, path: &Path) -> TTResult<()> {
+        self.conn
+            .restore(DatabaseName::Main, path, None::<fn(Progress)>)?;
+        Ok(())
+    }
+
     // Task operations
 
     pub fn create_task(
@@ -213,6 +238,11 @@ impl Database {
         Ok(())
     }
 
+    pub fn delete_task(&self, id: i64) -> TTResult<()> {
+        self.conn.execute("DELETE FROM tasks WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
     pub fn update_manual_order(&self, id: i64, order: f64) -> TTResult<()> {
         let now = Utc::now().to_rfc3339();
         self.conn.execute(
@@ -363,6 +393,14 @@ impl Database {
         Ok(artifacts?)
     }
 
+    pub fn move_artifacts(&self, from_task: i64, to_task: i64) -> TTResult<()> {
+        self.conn.execute(
+            "UPDATE artifacts SET task_id = ?1 WHERE task_id = ?2",
+            params![to_task, from_task],
+        )?;
+        Ok(())
+    }
+
     // Config operations
 
     pub fn set_config(&self, key: &str, value: &str) -> TTResult<()> {
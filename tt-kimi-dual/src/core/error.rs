@@ -1,3 +1,5 @@
+use serde::Serialize;
+use serde_json::{json, Value};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -43,6 +45,12 @@ pub enum TTError {
     #[error("All remaining tasks are blocked: {0:?}")]
     AllBlocked(Vec<i64>),
 
+    #[error("Artifact #{0} not found")]
+    ArtifactNotFound(i64),
+
+    #[error("Artifact file {0} is outside the artifacts directory")]
+    ArtifactOutsideDir(String),
+
     #[error("Cannot calculate midpoint: float precision exhausted. Run `tt reindex` to fix.")]
     FloatPrecisionExhausted,
 
@@ -52,6 +60,18 @@ pub enum TTError {
     #[error("Already initialized")]
     AlreadyInitialized,
 
+    #[error("Unknown tool: {0}")]
+    UnknownTool(String),
+
+    #[error("Snapshot '{0}' not found")]
+    SnapshotNotFound(String),
+
+    #[error("Snapshot '{0}' already exists")]
+    SnapshotExists(String),
+
+    #[error("Invalid snapshot name '{0}': use letters, digits, '.', '-' and '_', and don't start with 'auto-'")]
+    InvalidSnapshotName(String),
+
     #[error("Database error: {0}")]
     Db(#[from] rusqlite::Error),
 
@@ -63,3 +83,354 @@ pub enum TTError {
 }
 
 pub type TTResult<T> = Result<T, TTError>;
+
+/// Machine-readable error codes reported to MCP clients as `error_code`.
+///
+/// Each code is the name of the `TTError` variant it comes from. The set is
+/// part of the MCP contract: codes are never renamed or reused, only added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[cfg_attr(test, derive(strum::EnumIter))]
+pub enum ErrorCode {
+    /// The task does not exist. Details: `task_id`.
+    TaskNotFound,
+    /// The task is not pending. Details: `task_id`.
+    TaskNotPending,
+    /// Another task is in progress; stop or complete it first. Details: `active_task_id`.
+    AnotherTaskActive,
+    /// No task is in progress.
+    NoActiveTask,
+    /// The task has incomplete dependencies. Details: `task_id`, `unmet_dependencies`.
+    UnmetDependencies,
+    /// The dependency would create a cycle. Details: `task_id`, `depends_on`, `cycle`.
+    CycleDetected,
+    /// No target is set.
+    NoTarget,
+    /// Every task for the target is completed. Details: `target_id`.
+    TargetReached,
+    /// The task has no definition of done. Details: `task_id`.
+    NoDod,
+    /// A task is ordered before one of its dependencies.
+    /// Details: `task_id`, `order`, `dependency_id`, `dependency_order`.
+    OrderConflict,
+    /// The status string is not a known status. Details: `status`.
+    InvalidStatus,
+    /// Every remaining task is blocked. Details: `blocked_task_ids`.
+    AllBlocked,
+    /// The artifact does not exist. Details: `artifact_id`.
+    ArtifactNotFound,
+    /// The artifact file lies outside the artifacts directory. Details: `path`.
+    ArtifactOutsideDir,
+    /// Manual orders need reindexing with `tt reindex`.
+    FloatPrecisionExhausted,
+    /// The project has no database; run `tt init`.
+    NotInitialized,
+    /// The project already has a database.
+    AlreadyInitialized,
+    /// The MCP tool does not exist. Details: `tool`.
+    UnknownTool,
+    /// No snapshot has that name. Details: `name`.
+    SnapshotNotFound,
+    /// A snapshot with that name already exists. Details: `name`.
+    SnapshotExists,
+    /// The snapshot name is not a plain file name. Details: `name`.
+    InvalidSnapshotName,
+    /// The database returned an error.
+    Db,
+    /// A filesystem operation failed.
+    Io,
+    /// JSON (de)serialization failed.
+    Serde,
+}
+
+impl ErrorCode {
+    /// Every code, in declaration order
+    pub const ALL: &[ErrorCode] = &[
+        Self::TaskNotFound,
+        Self::TaskNotPending,
+        Self::AnotherTaskActive,
+        Self::NoActiveTask,
+        Self::UnmetDependencies,
+        Self::CycleDetected,
+        Self::NoTarget,
+        Self::TargetReached,
+        Self::NoDod,
+        Self::OrderConflict,
+        Self::InvalidStatus,
+        Self::AllBlocked,
+        Self::ArtifactNotFound,
+        Self::ArtifactOutsideDir,
+        Self::FloatPrecisionExhausted,
+        Self::NotInitialized,
+        Self::AlreadyInitialized,
+        Self::UnknownTool,
+        Self::SnapshotNotFound,
+        Self::SnapshotExists,
+        Self::InvalidSnapshotName,
+        Self::Db,
+        Self::Io,
+        Self::Serde,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::TaskNotFound => "TaskNotFound",
+            Self::TaskNotPending => "TaskNotPending",
+            Self::AnotherTaskActive => "AnotherTaskActive",
+            Self::NoActiveTask => "NoActiveTask",
+            Self::UnmetDependencies => "UnmetDependencies",
+            Self::CycleDetected => "CycleDetected",
+            Self::NoTarget => "NoTarget",
+            Self::TargetReached => "TargetReached",
+            Self::NoDod => "NoDod",
+            Self::OrderConflict => "OrderConflict",
+            Self::InvalidStatus => "InvalidStatus",
+            Self::AllBlocked => "AllBlocked",
+            Self::ArtifactNotFound => "ArtifactNotFound",
+            Self::ArtifactOutsideDir => "ArtifactOutsideDir",
+            Self::FloatPrecisionExhausted => "FloatPrecisionExhausted",
+            Self::NotInitialized => "NotInitialized",
+            Self::AlreadyInitialized => "AlreadyInitialized",
+            Self::UnknownTool => "UnknownTool",
+            Self::SnapshotNotFound => "SnapshotNotFound",
+            Self::SnapshotExists => "SnapshotExists",
+            Self::InvalidSnapshotName => "InvalidSnapshotName",
+            Self::Db => "Db",
+            Self::Io => "Io",
+            Self::Serde => "Serde",
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TTError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::TaskNotFound(_) => ErrorCode::TaskNotFound,
+            Self::TaskNotPending(_) => ErrorCode::TaskNotPending,
+            Self::AnotherTaskActive(_) => ErrorCode::AnotherTaskActive,
+            Self::NoActiveTask => ErrorCode::NoActiveTask,
+            Self::UnmetDependencies(..) => ErrorCode::UnmetDependencies,
+            Self::CycleDetected(..) => ErrorCode::CycleDetected,
+            Self::NoTarget => ErrorCode::NoTarget,
+            Self::TargetReached(_) => ErrorCode::TargetReached,
+            Self::NoDod(_) => ErrorCode::NoDod,
+            Self::OrderConflict { .. } => ErrorCode::OrderConflict,
+            Self::InvalidStatus(_) => ErrorCode::InvalidStatus,
+            Self::AllBlocked(_) => ErrorCode::AllBlocked,
+            Self::ArtifactNotFound(_) => ErrorCode::ArtifactNotFound,
+            Self::ArtifactOutsideDir(_) => ErrorCode::ArtifactOutsideDir,
+            Self::FloatPrecisionExhausted => ErrorCode::FloatPrecisionExhausted,
+            Self::NotInitialized => ErrorCode::NotInitialized,
+            Self::AlreadyInitialized => ErrorCode::AlreadyInitialized,
+            Self::UnknownTool(_) => ErrorCode::UnknownTool,
+            Self::SnapshotNotFound(_) => ErrorCode::SnapshotNotFound,
+            Self::SnapshotExists(_) => ErrorCode::SnapshotExists,
+            Self::InvalidSnapshotName(_) => ErrorCode::InvalidSnapshotName,
+            Self::Db(_) => ErrorCode::Db,
+            Self::Io(_) => ErrorCode::Io,
+            Self::Serde(_) => ErrorCode::Serde,
+        }
+    }
+
+    /// Structured context for the error, as documented on [`ErrorCode`]
+    pub fn details(&self) -> Option<Value> {
+        let details = match self {
+            Self::TaskNotFound(id) | Self::TaskNotPending(id) | Self::NoDod(id) => {
+                json!({ "task_id": id })
+            }
+            Self::AnotherTaskActive(id) => json!({ "active_task_id": id }),
+            Self::UnmetDependencies(id, deps) => {
+                json!({ "task_id": id, "unmet_dependencies": deps })
+            }
+            Self::CycleDetected(id, depends_on, cycle) => {
+                json!({ "task_id": id, "depends_on": depends_on, "cycle": cycle })
+            }
+            Self::TargetReached(id) => json!({ "target_id": id }),
+            Self::OrderConflict {
+                id,
+                order,
+                dep_id,
+                dep_order,
+            } => json!({
+                "task_id": id,
+                "order": order,
+                "dependency_id": dep_id,
+                "dependency_order": dep_order,
+            }),
+            Self::InvalidStatus(status) => json!({ "status": status }),
+            Self::AllBlocked(ids) => json!({ "blocked_task_ids": ids }),
+            Self::ArtifactNotFound(id) => json!({ "artifact_id": id }),
+            Self::ArtifactOutsideDir(path) => json!({ "path": path }),
+            Self::UnknownTool(name) => json!({ "tool": name }),
+            Self::SnapshotNotFound(name)
+            | Self::SnapshotExists(name)
+            | Self::InvalidSnapshotName(name) => json!({ "name": name }),
+            Self::NoActiveTask
+            | Self::NoTarget
+            | Self::FloatPrecisionExhausted
+            | Self::NotInitialized
+            | Self::AlreadyInitialized
+            | Self::Db(_)
+            | Self::Io(_)
+            | Self::Serde(_) => return None,
+        };
+        Some(details)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use strum::IntoEnumIterator;
+
+    /// One instance of every variant with its expected code and details
+    fn conformance_cases() -> Vec<(TTError, &'static str, Option<Value>)> {
+        vec![
+            (
+                TTError::TaskNotFound(7),
+                "TaskNotFound",
+                Some(json!({ "task_id": 7 })),
+            ),
+            (
+                TTError::TaskNotPending(7),
+                "TaskNotPending",
+                Some(json!({ "task_id": 7 })),
+            ),
+            (
+                TTError::AnotherTaskActive(3),
+                "AnotherTaskActive",
+                Some(json!({ "active_task_id": 3 })),
+            ),
+            (TTError::NoActiveTask, "NoActiveTask", None),
+            (
+                TTError::UnmetDependencies(5, vec![1, 2]),
+                "UnmetDependencies",
+                Some(json!({ "task_id": 5, "unmet_dependencies": [1, 2] })),
+            ),
+            (
+                TTError::CycleDetected(1, 3, vec![3, 2, 1]),
+                "CycleDetected",
+                Some(json!({ "task_id": 1, "depends_on": 3, "cycle": [3, 2, 1] })),
+            ),
+            (TTError::NoTarget, "NoTarget", None),
+            (
+                TTError::TargetReached(9),
+                "TargetReached",
+                Some(json!({ "target_id": 9 })),
+            ),
+            (TTError::NoDod(4), "NoDod", Some(json!({ "task_id": 4 }))),
+            (
+                TTError::OrderConflict {
+                    id: 2,
+                    order: 10.0,
+                    dep_id: 3,
+                    dep_order: 20.0,
+                },
+                "OrderConflict",
+                Some(json!({
+                    "task_id": 2,
+                    "order": 10.0,
+                    "dependency_id": 3,
+                    "dependency_order": 20.0,
+                })),
+            ),
+            (
+                TTError::InvalidStatus("done".to_string()),
+                "InvalidStatus",
+                Some(json!({ "status": "done" })),
+            ),
+            (
+                TTError::AllBlocked(vec![4, 5]),
+                "AllBlocked",
+                Some(json!({ "blocked_task_ids": [4, 5] })),
+            ),
+            (
+                TTError::ArtifactNotFound(6),
+                "ArtifactNotFound",
+                Some(json!({ "artifact_id": 6 })),
+            ),
+            (
+                TTError::ArtifactOutsideDir("/etc/passwd".to_string()),
+                "ArtifactOutsideDir",
+                Some(json!({ "path": "/etc/passwd" })),
+            ),
+            (
+                TTError::FloatPrecisionExhausted,
+                "FloatPrecisionExhausted",
+                None,
+            ),
+            (TTError::NotInitialized, "NotInitialized", None),
+            (TTError::AlreadyInitialized, "AlreadyInitialized", None),
+            (
+                TTError::UnknownTool("nope".to_string()),
+                "UnknownTool",
+                Some(json!({ "tool": "nope" })),
+            ),
+            (
+                TTError::SnapshotNotFound("nightly".to_string()),
+                "SnapshotNotFound",
+                Some(json!({ "name": "nightly" })),
+            ),
+            (
+                TTError::SnapshotExists("nightly".to_string()),
+                "SnapshotExists",
+                Some(json!({ "name": "nightly" })),
+            ),
+            (
+                TTError::InvalidSnapshotName("../x".to_string()),
+                "InvalidSnapshotName",
+                Some(json!({ "name": "../x" })),
+            ),
+            (
+                TTError::Db(rusqlite::Error::QueryReturnedNoRows),
+                "Db",
+                None,
+            ),
+            (TTError::Io(std::io::Error::other("disk")), "Io", None),
+            (
+                TTError::Serde(serde_json::from_str::<Value>("{").unwrap_err()),
+                "Serde",
+                None,
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_error_code_conformance() {
+        for (error, code, details) in conformance_cases() {
+            assert_eq!(error.code().as_str(), code, "{error}");
+            assert_eq!(error.details(), details, "{code}");
+        }
+    }
+
+    #[test]
+    fn test_every_code_is_covered() {
+        let covered: HashSet<ErrorCode> = conformance_cases()
+            .iter()
+            .map(|(error, _, _)| error.code())
+            .collect();
+        let all: HashSet<ErrorCode> = ErrorCode::ALL.iter().copied().collect();
+        assert_eq!(covered, all);
+        assert_eq!(all.len(), ErrorCode::ALL.len());
+    }
+
+    #[test]
+    fn test_all_lists_every_code() {
+        let declared: Vec<ErrorCode> = ErrorCode::iter().collect();
+        assert_eq!(ErrorCode::ALL, declared.as_slice());
+    }
+
+    #[test]
+    fn test_error_code_serializes_as_name() {
+        for code in ErrorCode::ALL {
+            assert_eq!(serde_json::to_value(code).unwrap(), json!(code.as_str()));
+            assert_eq!(code.to_string(), code.as_str());
+        }
+    }
+}
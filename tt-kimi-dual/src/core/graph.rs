@@ -228,6 +228,23 @@ pub fn default_order(max_order: f64) -> f64 {
     }
 }
 
+/// Calculate `count` evenly spaced order values strictly between `low` and `high`
+/// Returns Err if float precision is exhausted
+pub fn orders_between(low: f64, high: f64, count: usize) -> TTResult<Vec<f64>> {
+    let step = (high - low) / (count + 1) as f64;
+    let mut orders = Vec::with_capacity(count);
+    let mut previous = low;
+    for i in 1..=count {
+        let order = low + step * i as f64;
+        if order <= previous || order >= high {
+            return Err(TTError::FloatPrecisionExhausted);
+        }
+        orders.push(order);
+        previous = order;
+    }
+    Ok(orders)
+}
+
 /// Build reindexed orders (10.0, 20.0, 30.0, ...) preserving current sorted order
 pub fn reindex_orders(tasks: &[Task]) -> Vec<(i64, f64)> {
     tasks
@@ -339,6 +356,16 @@ mod tests {
         assert_eq!(calculate_midpoint(10.0, 20.0).unwrap(), 15.0);
     }
 
+    #[test]
+    fn test_orders_between() {
+        assert_eq!(orders_between(10.0, 20.0, 1).unwrap(), vec![15.0]);
+        assert_eq!(
+            orders_between(0.0, 40.0, 3).unwrap(),
+            vec![10.0, 20.0, 30.0]
+        );
+        assert!(orders_between(1.0, 1.0, 2).is_err());
+    }
+
     #[test]
     fn test_order_conflict_detection() {
         // Task 1 depends on Task 2, but Task 1 has lower manual_order
@@ -5,23 +5,44 @@ pub mod models;
 
 use crate::core::db::Database;
 use crate::core::error::{TTError, TTResult};
-use crate::core::graph::{calculate_midpoint, default_order, order_after, order_before};
+use crate::core::graph::{
+    calculate_midpoint, default_order, detect_cycle, order_after, order_before, orders_between,
+};
 use crate::core::models::{
-    Artifact, OrderConflict, Task, TaskDetail, TaskStatus, TaskWithDependencies,
+    Artifact, OrderConflict, Snapshot, SplitResult, Task, TaskDetail, TaskStatus,
+    TaskWithDependencies,
 };
+use chrono::{DateTime, Utc};
 use std::collections::HashSet;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// Automatic snapshots kept before the oldest are pruned
+const AUTO_SNAPSHOT_LIMIT: usize = 10;
+
+/// Prefix of snapshots taken before risky operations
+const AUTO_SNAPSHOT_PREFIX: &str = "auto-";
 
 /// The main application core that coordinates all operations
 pub struct AppCore {
     pub db: Database,
+    snapshots_dir: Option<PathBuf>,
 }
 
 impl AppCore {
     /// Open the database at the given path
     pub fn open<P: AsRef<Path>>(path: P) -> TTResult<Self> {
         let db = Database::new(path)?;
-        Ok(Self { db })
+        Ok(Self {
+            db,
+            snapshots_dir: None,
+        })
+    }
+
+    /// Keep snapshots in `dir`, and take one automatically before reindex,
+    /// split and merge
+    pub fn with_snapshots(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.snapshots_dir = Some(dir.into());
+        self
     }
 
     /// Initialize a new project (create database and artifacts directory)
@@ -35,7 +56,10 @@ impl AppCore {
 
         std::fs::create_dir_all(&artifacts_dir)?;
 
-        Ok(Self { db })
+        Ok(Self {
+            db,
+            snapshots_dir: None,
+        })
     }
 
     // Task Management
@@ -239,7 +263,7 @@ impl AppCore {
             .map(|d| (d.task_id, d.depends_on))
             .collect();
 
-        if let Some(cycle) = crate::core::graph::detect_cycle(task_id, depends_on, &existing) {
+        if let Some(cycle) = detect_cycle(task_id, depends_on, &existing) {
             return Err(TTError::CycleDetected(task_id, depends_on, cycle));
         }
 
@@ -251,6 +275,150 @@ impl AppCore {
         self.db.remove_dependency(task_id, depends_on)
     }
 
+    // Restructuring
+
+    /// Split a task into subtasks placed just before it.
+    ///
+    /// The original's prerequisites move onto the first subtask. With `chain`,
+    /// each subtask depends on the previous one and the original depends on the
+    /// last; otherwise the original depends on all of them.
+    pub fn split_task(&self, id: i64, titles: &[String], chain: bool) -> TTResult<SplitResult> {
+        let original = self.get_task(id)?;
+
+        if titles.is_empty() {
+            return Err(TTError::InvalidStatus(
+                "Must specify at least one subtask title".to_string(),
+            ));
+        }
+        if original.is_completed() {
+            return Err(TTError::InvalidStatus(format!(
+                "Task #{id} is completed and cannot be split"
+            )));
+        }
+
+        let previous_order = self
+            .db
+            .get_all_tasks()?
+            .iter()
+            .map(|t| t.manual_order)
+            .filter(|order| *order < original.manual_order)
+            .fold(0.0, f64::max);
+        let orders = orders_between(previous_order, original.manual_order, titles.len())?;
+        let prerequisites = self.db.get_dependency_ids(id)?;
+
+        self.auto_snapshot("split")?;
+        self.db.transaction(|| {
+            let mut subtasks = Vec::with_capacity(titles.len());
+            for (title, order) in titles.iter().zip(orders) {
+                subtasks.push(self.db.create_task(title, None, None, order)?);
+            }
+
+            for prereq in &prerequisites {
+                self.db.remove_dependency(id, *prereq)?;
+            }
+
+            let first = subtasks[0].id;
+            for (i, subtask) in subtasks.iter().enumerate() {
+                if chain && i > 0 {
+                    self.add_dependency(subtask.id, subtasks[i - 1].id)?;
+                }
+                if subtask.id == first {
+                    for prereq in &prerequisites {
+                        self.add_dependency(subtask.id, *prereq)?;
+                    }
+                }
+                if !chain || i == subtasks.len() - 1 {
+                    self.add_dependency(id, subtask.id)?;
+                }
+            }
+
+            // The original now waits on unfinished subtasks
+            if original.is_in_progress() {
+                self.db.update_task_status(id, TaskStatus::Pending)?;
+            }
+
+            Ok(SplitResult {
+                original: self.get_task(id)?,
+                subtasks,
+            })
+        })
+    }
+
+    /// Merge task `other` into task `id`.
+    ///
+    /// `id` takes over the prerequisites, dependents and artifacts of `other`,
+    /// and its description and DoD are appended. `other` is deleted. Fails
+    /// without changes if the combined edges would form a cycle.
+    pub fn merge_tasks(&self, id: i64, other: i64) -> TTResult<Task> {
+        if id == other {
+            return Err(TTError::InvalidStatus(
+                "Cannot merge a task into itself".to_string(),
+            ));
+        }
+
+        let task = self.get_task(id)?;
+        let absorbed = self.get_task(other)?;
+
+        if absorbed.is_in_progress() {
+            return Err(TTError::InvalidStatus(format!(
+                "Task #{other} is in progress. Stop it before merging."
+            )));
+        }
+
+        // Re-point every edge of `other` at `id`, checking each against the
+        // graph that remains once `other` is gone
+        let mut edges: Vec<(i64, i64)> = self
+            .db
+            .get_all_dependencies()?
+            .into_iter()
+            .map(|d| (d.task_id, d.depends_on))
+            .filter(|(from, to)| *from != other && *to != other)
+            .collect();
+        let mut new_edges = Vec::new();
+        let prerequisites = self.db.get_dependency_ids(other)?;
+        let dependents = self.db.get_dependent_ids(other)?;
+        let candidates = prerequisites
+            .iter()
+            .map(|p| (id, *p))
+            .chain(dependents.iter().map(|d| (*d, id)));
+
+        for (from, to) in candidates {
+            if from == to || edges.contains(&(from, to)) {
+                continue;
+            }
+            if let Some(cycle) = detect_cycle(from, to, &edges) {
+                return Err(TTError::CycleDetected(from, to, cycle));
+            }
+            edges.push((from, to));
+            new_edges.push((from, to));
+        }
+
+        self.auto_snapshot("merge")?;
+        self.db.transaction(|| {
+            self.db.move_artifacts(other, id)?;
+            self.db.delete_task(other)?;
+            for (from, to) in &new_edges {
+                self.db.add_dependency(*from, *to)?;
+            }
+
+            let description = merge_text(&task.description, &absorbed.description);
+            let dod = merge_text(&task.dod, &absorbed.dod);
+            self.db
+                .update_task_fields(id, None, description.as_deref(), dod.as_deref())?;
+
+            // Unfinished work from `other` reopens a completed task
+            if task.is_completed() && !absorbed.is_completed() {
+                self.db.update_task_status(id, absorbed.status)?;
+            }
+
+            if self.db.get_target()? == Some(other) {
+                self.db.set_target(id)?;
+            }
+
+            self.get_task(id)
+        })
+    }
+
     // Ordering
 
     /// Reorder a task
@@ -304,6 +472,8 @@ impl AppCore {
         let tasks = self.db.get_all_tasks()?;
         let new_orders = crate::core::graph::reindex_orders(&tasks);
 
+        self.auto_snapshot("reindex")?;
+
         for (id, order) in new_orders {
             self.db.update_manual_order(id, order)?;
         }
@@ -330,6 +500,164 @@ impl AppCore {
         self.db.get_artifacts_for_task(id)
     }
 
+    /// Read the file behind an artifact.
+    ///
+    /// Relative paths are resolved against the working directory first and then
+    /// `artifacts_dir`. The file must live inside `artifacts_dir`.
+    pub fn read_artifact(&self, id: i64, artifacts_dir: &Path) -> TTResult<(Artifact, String)> {
+        let artifact = self
+            .db
+            .get_artifact(id)?
+            .ok_or(TTError::ArtifactNotFound(id))?;
+
+        let file_path = Path::new(&artifact.file_path);
+        let path = if file_path.is_relative() && !file_path.exists() {
+            artifacts_dir.join(file_path)
+        } else {
+            file_path.to_path_buf()
+        };
+
+        let path = path.canonicalize()?;
+        if !path.starts_with(artifacts_dir.canonicalize()?) {
+            return Err(TTError::ArtifactOutsideDir(artifact.file_path));
+        }
+
+        let content = std::fs::read_to_string(&path)?;
+        Ok((artifact, content))
+    }
+
+    // Snapshots
+
+    /// Copy the database into the snapshots directory.
+    ///
+    /// Without a name the snapshot is named after the current time.
+    pub fn snapshot(&self, name: Option<&str>) -> TTResult<Snapshot> {
+        match name {
+            // Automatic snapshots are pruned by prefix, so keep users out of it
+            Some(name) if name.starts_with(AUTO_SNAPSHOT_PREFIX) => {
+                Err(TTError::InvalidSnapshotName(name.to_string()))
+            }
+            Some(name) => self.write_snapshot(name),
+            None => self.write_timestamped_snapshot("", ""),
+        }
+    }
+
+    /// List snapshots, oldest first
+    pub fn list_snapshots(&self) -> TTResult<Vec<Snapshot>> {
+        let Some(dir) = self.snapshots_dir.as_deref().filter(|dir| dir.exists()) else {
+            return Ok(vec![]);
+        };
+
+        let mut snapshots = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "db") {
+                snapshots.push(read_snapshot(&path)?);
+            }
+        }
+        snapshots.sort_by(|a, b| (a.created_at, &a.name).cmp(&(b.created_at, &b.name)));
+        Ok(snapshots)
+    }
+
+    /// Replace the database with a snapshot.
+    ///
+    /// The current state is snapshotted first, so a restore can be undone by
+    /// restoring that snapshot.
+    pub fn restore_snapshot(&mut self, name: &str) -> TTResult<Snapshot> {
+        let path = self.snapshot_path(name)?;
+        if !path.exists() {
+            return Err(TTError::SnapshotNotFound(name.to_string()));
+        }
+
+        self.auto_snapshot("restore")?;
+        self.db.restore_from(&path)?;
+        read_snapshot(&path)
+    }
+
+    /// Snapshot the database before `operation`, pruning old automatic
+    /// snapshots. Does nothing without a snapshots directory.
+    fn auto_snapshot(&self, operation: &str) -> TTResult<()> {
+        if self.snapshots_dir.is_none() {
+            return Ok(());
+        }
+
+        self.write_timestamped_snapshot(AUTO_SNAPSHOT_PREFIX, &format!("-{operation}"))?;
+
+        let auto: Vec<Snapshot> = self
+            .list_snapshots()?
+            .into_iter()
+            .filter(|s| s.name.starts_with(AUTO_SNAPSHOT_PREFIX))
+            .collect();
+        for old in auto
+            .iter()
+            .take(auto.len().saturating_sub(AUTO_SNAPSHOT_LIMIT))
+        {
+            std::fs::remove_file(&old.path)?;
+        }
+        Ok(())
+    }
+
+    /// Write a new snapshot. An existing snapshot is never overwritten.
+    fn write_snapshot(&self, name: &str) -> TTResult<Snapshot> {
+        let path = self.snapshot_path(name)?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        // Claim the name before writing, so concurrent snapshots can't share it
+        match std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+        {
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                return Err(TTError::SnapshotExists(name.to_string()));
+            }
+            Err(e) => return Err(e.into()),
+        }
+
+        // Don't leave the empty placeholder behind, it would restore as an empty database
+        if let Err(e) = self.db.backup_to(&path) {
+            let _ = std::fs::remove_file(&path);
+            return Err(e);
+        }
+        read_snapshot(&path)
+    }
+
+    /// Write a snapshot named `{prefix}{time}{suffix}`, numbering it if
+    /// another snapshot was taken in the same millisecond.
+    fn write_timestamped_snapshot(&self, prefix: &str, suffix: &str) -> TTResult<Snapshot> {
+        let time = Utc::now().format("%Y%m%d-%H%M%S%.3f");
+        for n in 1.. {
+            let counter = if n == 1 {
+                String::new()
+            } else {
+                format!("-{n}")
+            };
+            match self.write_snapshot(&format!("{prefix}{time}{counter}{suffix}")) {
+                Err(TTError::SnapshotExists(_)) => continue,
+                result => return result,
+            }
+        }
+        unreachable!("snapshot counter overflowed")
+    }
+
+    fn snapshot_path(&self, name: &str) -> TTResult<PathBuf> {
+        let valid = !name.is_empty()
+            && !name.starts_with('.')
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'));
+        if !valid {
+            return Err(TTError::InvalidSnapshotName(name.to_string()));
+        }
+
+        let dir = self.snapshots_dir.as_deref().ok_or_else(|| {
+            TTError::Io(std::io::Error::other("no snapshots directory configured"))
+        })?;
+        Ok(dir.join(format!("{name}.db")))
+    }
+
     // Target Management
 
     /// Set the target task
@@ -422,6 +750,28 @@ impl AppCore {
     }
 }
 
+fn read_snapshot(path: &Path) -> TTResult<Snapshot> {
+    let metadata = std::fs::metadata(path)?;
+    Ok(Snapshot {
+        name: path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        path: path.display().to_string(),
+        created_at: DateTime::<Utc>::from(metadata.modified()?),
+        size: metadata.len(),
+    })
+}
+
+/// Combine two optional text fields, keeping both when both are set
+fn merge_text(a: &Option<String>, b: &Option<String>) -> Option<String> {
+    match (a, b) {
+        (Some(a), Some(b)) if a != b => Some(format!("{a}\n\n{b}")),
+        (Some(a), _) => Some(a.clone()),
+        (None, b) => b.clone(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -590,6 +940,135 @@ mod tests {
         assert!(unblocked.is_pending());
     }
 
+    #[test]
+    fn test_split_chain() {
+        let (core, _temp) = setup_test_core();
+
+        let pre = core.add_task("Prereq", None, None, None, None).unwrap();
+        let big = core.add_task("Big", None, None, None, None).unwrap();
+        let after = core.add_task("After", None, None, None, None).unwrap();
+        core.add_dependency(big.id, pre.id).unwrap();
+        core.add_dependency(after.id, big.id).unwrap();
+
+        let titles = vec!["A".to_string(), "B".to_string(), "C".to_string()];
+        let result = core.split_task(big.id, &titles, true).unwrap();
+        let ids: Vec<i64> = result.subtasks.iter().map(|t| t.id).collect();
+        assert_eq!(ids.len(), 3);
+
+        // Prerequisites move onto the first subtask, the chain follows
+        assert_eq!(core.db.get_dependency_ids(ids[0]).unwrap(), vec![pre.id]);
+        assert_eq!(core.db.get_dependency_ids(ids[1]).unwrap(), vec![ids[0]]);
+        assert_eq!(core.db.get_dependency_ids(ids[2]).unwrap(), vec![ids[1]]);
+        assert_eq!(core.db.get_dependency_ids(big.id).unwrap(), vec![ids[2]]);
+        assert_eq!(core.db.get_dependent_ids(big.id).unwrap(), vec![after.id]);
+
+        // Subtasks are ordered just before the original
+        assert!(result.subtasks[0].manual_order > pre.manual_order);
+        assert!(result.subtasks[2].manual_order < big.manual_order);
+    }
+
+    #[test]
+    fn test_split_parallel() {
+        let (core, _temp) = setup_test_core();
+
+        let pre = core.add_task("Prereq", None, None, None, None).unwrap();
+        let big = core.add_task("Big", None, None, None, None).unwrap();
+        core.add_dependency(big.id, pre.id).unwrap();
+
+        let titles = vec!["A".to_string(), "B".to_string()];
+        let result = core.split_task(big.id, &titles, false).unwrap();
+
+        let mut deps = core.db.get_dependency_ids(big.id).unwrap();
+        deps.sort();
+        assert_eq!(deps, vec![result.subtasks[0].id, result.subtasks[1].id]);
+        // Only the first subtask takes over the prerequisites
+        assert_eq!(
+            core.db.get_dependency_ids(result.subtasks[0].id).unwrap(),
+            vec![pre.id]
+        );
+        assert!(core
+            .db
+            .get_dependency_ids(result.subtasks[1].id)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_split_rejects_completed_and_empty() {
+        let (core, _temp) = setup_test_core();
+
+        let task = core
+            .add_task("Task", None, Some("DoD"), None, None)
+            .unwrap();
+        assert!(matches!(
+            core.split_task(task.id, &[], false),
+            Err(TTError::InvalidStatus(_))
+        ));
+
+        core.start_task(task.id).unwrap();
+        core.complete_task().unwrap();
+        let result = core.split_task(task.id, &["A".to_string()], false);
+        assert!(matches!(result, Err(TTError::InvalidStatus(_))));
+        assert_eq!(core.db.get_all_tasks().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_merge_combines_edges_and_artifacts() {
+        let (core, _temp) = setup_test_core();
+
+        let pre = core.add_task("Prereq", None, None, None, None).unwrap();
+        let a = core.add_task("A", None, Some("DoD A"), None, None).unwrap();
+        let b = core.add_task("B", None, Some("DoD B"), None, None).unwrap();
+        let after = core.add_task("After", None, None, None, None).unwrap();
+        core.add_dependency(b.id, pre.id).unwrap();
+        core.add_dependency(after.id, b.id).unwrap();
+        core.db.create_artifact(b.id, "notes", "notes.md").unwrap();
+        core.set_target(b.id).unwrap();
+
+        let merged = core.merge_tasks(a.id, b.id).unwrap();
+        assert_eq!(merged.dod.as_deref(), Some("DoD A\n\nDoD B"));
+        assert!(matches!(core.get_task(b.id), Err(TTError::TaskNotFound(_))));
+        assert_eq!(core.db.get_dependency_ids(a.id).unwrap(), vec![pre.id]);
+        assert_eq!(core.db.get_dependent_ids(a.id).unwrap(), vec![after.id]);
+        assert_eq!(core.get_artifacts(Some(a.id)).unwrap().len(), 1);
+        assert_eq!(core.get_target().unwrap(), Some(a.id));
+    }
+
+    #[test]
+    fn test_merge_rejects_cycle() {
+        let (core, _temp) = setup_test_core();
+
+        let a = core.add_task("A", None, None, None, None).unwrap();
+        let x = core.add_task("X", None, None, None, None).unwrap();
+        let b = core.add_task("B", None, None, None, None).unwrap();
+        // a -> x -> b: merging b into a would make x depend on a
+        core.add_dependency(a.id, x.id).unwrap();
+        core.add_dependency(x.id, b.id).unwrap();
+
+        let result = core.merge_tasks(a.id, b.id);
+        assert!(matches!(result, Err(TTError::CycleDetected(_, _, _))));
+
+        // Nothing changed
+        assert!(core.get_task(b.id).is_ok());
+        assert_eq!(core.db.get_dependency_ids(x.id).unwrap(), vec![b.id]);
+    }
+
+    #[test]
+    fn test_merge_drops_direct_edge() {
+        let (core, _temp) = setup_test_core();
+
+        let a = core.add_task("A", None, None, None, None).unwrap();
+        let b = core.add_task("B", None, None, None, None).unwrap();
+        core.add_dependency(a.id, b.id).unwrap();
+
+        core.merge_tasks(a.id, b.id).unwrap();
+        assert!(core.db.get_dependency_ids(a.id).unwrap().is_empty());
+        assert!(matches!(
+            core.merge_tasks(a.id, a.id),
+            Err(TTError::InvalidStatus(_))
+        ));
+    }
+
     #[test]
     fn test_reorder() {
         let (core, _temp) = setup_test_core();
@@ -605,4 +1084,143 @@ mod tests {
         assert!(t3_updated.manual_order > t1.manual_order);
         assert!(t3_updated.manual_order < t2.manual_order);
     }
+
+    #[test]
+    fn test_read_artifact() {
+        let (core, temp) = setup_test_core();
+        let artifacts_dir = temp.path().join(".tt").join("artifacts");
+
+        let task = core.add_task("Task", None, None, None, None).unwrap();
+        core.start_task(task.id).unwrap();
+
+        std::fs::write(artifacts_dir.join("plan.md"), "# Plan").unwrap();
+        let inside = core.log_artifact("plan", "plan.md").unwrap();
+        let (artifact, content) = core.read_artifact(inside.id, &artifacts_dir).unwrap();
+        assert_eq!(artifact.name, "plan");
+        assert_eq!(content, "# Plan");
+
+        let secret = temp.path().join("secret.txt");
+        std::fs::write(&secret, "nope").unwrap();
+        let outside = core
+            .log_artifact("secret", secret.to_str().unwrap())
+            .unwrap();
+        assert!(matches!(
+            core.read_artifact(outside.id, &artifacts_dir),
+            Err(TTError::ArtifactOutsideDir(_))
+        ));
+        assert!(matches!(
+            core.read_artifact(999, &artifacts_dir),
+            Err(TTError::ArtifactNotFound(999))
+        ));
+    }
+
+    #[test]
+    fn test_snapshot_and_restore() {
+        let (core, temp) = setup_test_core();
+        let mut core = core.with_snapshots(temp.path().join(".tt").join("snapshots"));
+
+        core.add_task("Keep", None, None, None, None).unwrap();
+        let snapshot = core.snapshot(Some("good")).unwrap();
+        assert_eq!(snapshot.name, "good");
+        assert!(matches!(
+            core.snapshot(Some("good")),
+            Err(TTError::SnapshotExists(_))
+        ));
+        assert!(matches!(
+            core.snapshot(Some("../escape")),
+            Err(TTError::InvalidSnapshotName(_))
+        ));
+        assert!(matches!(
+            core.snapshot(Some("auto-mine")),
+            Err(TTError::InvalidSnapshotName(_))
+        ));
+
+        core.add_task("Mistake", None, None, None, None).unwrap();
+        core.restore_snapshot("good").unwrap();
+
+        let titles: Vec<String> = core
+            .db
+            .get_all_tasks()
+            .unwrap()
+            .into_iter()
+            .map(|t| t.title)
+            .collect();
+        assert_eq!(titles, vec!["Keep"]);
+
+        // The state before the restore was kept
+        let names: Vec<String> = core
+            .list_snapshots()
+            .unwrap()
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        assert!(names.contains(&"good".to_string()));
+        assert!(names.iter().any(|n| n.ends_with("-restore")));
+
+        assert!(matches!(
+            core.restore_snapshot("missing"),
+            Err(TTError::SnapshotNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_risky_operations_take_snapshots() {
+        let (core, temp) = setup_test_core();
+        let core = core.with_snapshots(temp.path().join(".tt").join("snapshots"));
+
+        let a = core.add_task("A", None, None, None, None).unwrap();
+        let b = core.add_task("B", None, None, None, None).unwrap();
+        core.reindex().unwrap();
+        let split = core.split_task(a.id, &["A1".to_string()], false).unwrap();
+        core.merge_tasks(b.id, split.subtasks[0].id).unwrap();
+
+        let names: Vec<String> = core
+            .list_snapshots()
+            .unwrap()
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        for operation in ["reindex", "split", "merge"] {
+            assert!(
+                names.iter().any(|n| n.ends_with(&format!("-{operation}"))),
+                "{names:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_snapshots_in_quick_succession_are_all_kept() {
+        let (core, temp) = setup_test_core();
+        let core = core.with_snapshots(temp.path().join(".tt").join("snapshots"));
+        core.add_task("A", None, None, None, None).unwrap();
+
+        let first = core.snapshot(None).unwrap();
+        let second = core.snapshot(None).unwrap();
+        assert_ne!(first.name, second.name);
+        for _ in 0..3 {
+            core.reindex().unwrap();
+        }
+
+        let names: Vec<String> = core
+            .list_snapshots()
+            .unwrap()
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        assert_eq!(names.len(), 5, "{names:?}");
+        assert!(names.contains(&first.name));
+        assert_eq!(
+            names.iter().filter(|n| n.ends_with("-reindex")).count(),
+            3,
+            "{names:?}"
+        );
+    }
+
+    #[test]
+    fn test_no_snapshots_without_directory() {
+        let (core, _temp) = setup_test_core();
+        core.add_task("A", None, None, None, None).unwrap();
+        core.reindex().unwrap();
+        assert!(core.list_snapshots().unwrap().is_empty());
+    }
 }
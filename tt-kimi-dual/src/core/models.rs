@@ -130,3 +130,18 @@ pub struct NextTaskResult {
     pub dependencies_met: bool,
     pub unmet_dependencies: Vec<i64>,
 }
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SplitResult {
+    pub original: Task,
+    pub subtasks: Vec<Task>,
+}
+
+/// A copy of the database kept in the snapshots directory
+#[derive(Debug, Clone, Serialize)]
+pub struct Snapshot {
+    pub name: String,
+    pub path: String,
+    pub created_at: DateTime<Utc>,
+    pub size: u64,
+}
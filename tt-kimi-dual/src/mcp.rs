@@ -3,14 +3,20 @@
 //! This implements the Model Context Protocol manually since the rmcp crate
 //! API has changed significantly between versions.
 
-use crate::core::error::TTError;
+use crate::core::error::{ErrorCode, TTError};
+use crate::core::models::TaskDetail;
 use crate::core::AppCore;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::io::{self, BufRead, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 const DB_FILE: &str = "tt.db";
+const ARTIFACTS_DIR: &str = ".tt/artifacts";
+const SNAPSHOTS_DIR: &str = ".tt/snapshots";
+
+/// JSON-RPC error code for a resource that does not exist
+const RESOURCE_NOT_FOUND: i32 = -32002;
 
 /// JSON-RPC request
 #[derive(Debug, Deserialize)]
@@ -64,6 +70,15 @@ impl JsonRpcResponse {
             }),
         }
     }
+
+    /// A JSON-RPC error carrying the tt error code and details as `data`
+    fn tt_error(id: Option<Value>, code: i32, e: &TTError) -> Self {
+        let mut response = Self::error(id, code, e.to_string());
+        if let Some(error) = response.error.as_mut() {
+            error.data = Some(json!({ "error_code": e.code(), "details": e.details() }));
+        }
+        response
+    }
 }
 
 /// JSON response wrapper for tool results
@@ -73,9 +88,11 @@ struct McpResponse<T: Serialize> {
     #[serde(skip_serializing_if = "Option::is_none")]
     data: Option<T>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    error_code: Option<String>,
+    error_code: Option<ErrorCode>,
     #[serde(skip_serializing_if = "Option::is_none")]
     message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details: Option<Value>,
 }
 
 impl<T: Serialize> McpResponse<T> {
@@ -85,27 +102,23 @@ impl<T: Serialize> McpResponse<T> {
             data: Some(data),
             error_code: None,
             message: None,
+            details: None,
         }
     }
 
-    fn error(error_code: String, message: String) -> Self {
+    fn error(e: &TTError) -> Self {
         Self {
             status: "error",
             data: None,
-            error_code: Some(error_code),
-            message: Some(message),
+            error_code: Some(e.code()),
+            message: Some(e.to_string()),
+            details: e.details(),
         }
     }
 }
 
 fn error_to_response(e: TTError) -> Value {
-    let error_code = format!("{:?}", std::mem::discriminant(&e))
-        .split("::")
-        .last()
-        .unwrap_or("Unknown")
-        .to_string();
-
-    json!(McpResponse::<()>::error(error_code, e.to_string()))
+    json!(McpResponse::<()>::error(&e))
 }
 
 fn tt_result_to_json<T: Serialize>(result: Result<T, TTError>) -> Value {
@@ -115,9 +128,49 @@ fn tt_result_to_json<T: Serialize>(result: Result<T, TTError>) -> Value {
     }
 }
 
+/// A parsed `tt://` resource URI
+#[derive(Debug, PartialEq)]
+enum ResourceUri {
+    Task(i64),
+    Target,
+    Current,
+    Artifact(i64),
+}
+
+impl ResourceUri {
+    fn parse(uri: &str) -> Option<Self> {
+        let path = uri.strip_prefix("tt://")?;
+        match path.split_once('/') {
+            Some(("task", id)) => id.parse().ok().map(Self::Task),
+            Some(("artifact", id)) => id.parse().ok().map(Self::Artifact),
+            None if path == "target" => Some(Self::Target),
+            None if path == "current" => Some(Self::Current),
+            _ => None,
+        }
+    }
+}
+
+/// MIME type reported for an artifact, guessed from its extension
+fn artifact_mime_type(path: &str) -> &'static str {
+    match Path::new(path).extension().and_then(|e| e.to_str()) {
+        Some("md") => "text/markdown",
+        Some("json") => "application/json",
+        _ => "text/plain",
+    }
+}
+
+fn task_resource(uri: &str, detail: &TaskDetail) -> Result<Value, TTError> {
+    Ok(json!({
+        "uri": uri,
+        "mimeType": "application/json",
+        "text": serde_json::to_string_pretty(detail)?
+    }))
+}
+
 /// MCP Server
 pub struct McpServer {
     core: AppCore,
+    artifacts_dir: PathBuf,
 }
 
 impl McpServer {
@@ -126,15 +179,32 @@ impl McpServer {
         if !db_path.exists() {
             return Err(TTError::NotInitialized);
         }
-        let core = AppCore::open(db_path)?;
-        Ok(Self { core })
+        let core = AppCore::open(db_path)?.with_snapshots(SNAPSHOTS_DIR);
+        Ok(Self {
+            core,
+            artifacts_dir: PathBuf::from(ARTIFACTS_DIR),
+        })
     }
 
     fn handle_request(&mut self, request: JsonRpcRequest) -> JsonRpcResponse {
+        if request.jsonrpc != "2.0" {
+            return JsonRpcResponse::error(
+                request.id,
+                -32600,
+                format!(
+                    "Invalid Request: unsupported jsonrpc version '{}'",
+                    request.jsonrpc
+                ),
+            );
+        }
+
         match request.method.as_str() {
             "initialize" => self.handle_initialize(request.id),
             "tools/list" => self.handle_list_tools(request.id),
             "tools/call" => self.handle_call_tool(request.id, request.params),
+            "resources/list" => self.handle_list_resources(request.id),
+            "resources/templates/list" => self.handle_list_resource_templates(request.id),
+            "resources/read" => self.handle_read_resource(request.id, request.params),
             _ => JsonRpcResponse::error(
                 request.id,
                 -32601,
@@ -147,7 +217,8 @@ impl McpServer {
         let result = json!({
             "protocolVersion": "2024-11-05",
             "capabilities": {
-                "tools": {}
+                "tools": {},
+                "resources": {}
             },
             "serverInfo": {
                 "name": "tt",
@@ -224,6 +295,16 @@ impl McpServer {
                 "description": "Unblock a previously blocked task, moving it back to pending status.",
                 "inputSchema": {"type": "object", "properties": {"id": {"type": "integer", "description": "Task ID to unblock"}}, "required": ["id"]}
             },
+            {
+                "name": "split_task",
+                "description": "Split a task that is too big into subtasks. The original's prerequisites move onto the first subtask and the original then depends on the subtasks. Set chain=true to run the subtasks in sequence; otherwise they run in parallel and the original depends on all of them.",
+                "inputSchema": {"type": "object", "properties": {"id": {"type": "integer", "description": "Task ID to split"}, "titles": {"type": "array", "items": {"type": "string"}, "description": "Subtask titles, in order"}, "chain": {"type": "boolean", "description": "Make each subtask depend on the previous one"}}, "required": ["id", "titles"]}
+            },
+            {
+                "name": "merge_tasks",
+                "description": "Merge task 'other_id' into task 'id'. The kept task takes over the other's dependencies, dependents and artifacts, and the other task is deleted. Fails if the merge would create a cycle.",
+                "inputSchema": {"type": "object", "properties": {"id": {"type": "integer", "description": "Task ID to keep"}, "other_id": {"type": "integer", "description": "Task ID to merge in and remove"}}, "required": ["id", "other_id"]}
+            },
             {
                 "name": "log_artifact",
                 "description": "Record a file you have created as an artifact of the current task. Create the file first, then call this. Use descriptive names like 'research', 'plan', 'implementation-notes', 'test-report'.",
@@ -346,6 +427,26 @@ impl McpServer {
                 let id = args.get("id").and_then(|v| v.as_i64()).unwrap_or(0);
                 tt_result_to_json(self.core.unblock_task(id))
             }
+            "split_task" => {
+                let id = args.get("id").and_then(|v| v.as_i64()).unwrap_or(0);
+                let titles: Vec<String> = args
+                    .get("titles")
+                    .and_then(|v| v.as_array())
+                    .map(|titles| {
+                        titles
+                            .iter()
+                            .filter_map(|t| t.as_str().map(|s| s.to_string()))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let chain = args.get("chain").and_then(|v| v.as_bool()).unwrap_or(false);
+                tt_result_to_json(self.core.split_task(id, &titles, chain))
+            }
+            "merge_tasks" => {
+                let id = args.get("id").and_then(|v| v.as_i64()).unwrap_or(0);
+                let other_id = args.get("other_id").and_then(|v| v.as_i64()).unwrap_or(0);
+                tt_result_to_json(self.core.merge_tasks(id, other_id))
+            }
             "log_artifact" => {
                 let name = args
                     .get("name")
@@ -373,10 +474,119 @@ impl McpServer {
                 let before_id = args.get("before_id").and_then(|v| v.as_i64());
                 tt_result_to_json(self.core.reorder_task(id, after_id, before_id))
             }
-            _ => json!(McpResponse::<()>::error(
-                "UnknownTool".to_string(),
-                format!("Unknown tool: {name}")
-            )),
+            _ => error_to_response(TTError::UnknownTool(name.to_string())),
+        }
+    }
+
+    fn handle_list_resources(&self, id: Option<Value>) -> JsonRpcResponse {
+        let mut resources = vec![
+            json!({
+                "uri": "tt://target",
+                "name": "Target task",
+                "description": "The current target with its dependencies, dependents and artifacts",
+                "mimeType": "application/json"
+            }),
+            json!({
+                "uri": "tt://current",
+                "name": "Active task",
+                "description": "The task currently in progress with its dependencies, dependents and artifacts",
+                "mimeType": "application/json"
+            }),
+        ];
+
+        // Concrete resources for the target subgraph; everything else is reachable via templates
+        let tasks = self
+            .core
+            .list_tasks(false)
+            .map(|(tasks, _)| tasks)
+            .unwrap_or_default();
+        for task in &tasks {
+            resources.push(json!({
+                "uri": format!("tt://task/{}", task.id),
+                "name": format!("#{} {}", task.id, task.title),
+                "mimeType": "application/json"
+            }));
+        }
+        for task in &tasks {
+            for artifact in self.core.get_artifacts(Some(task.id)).unwrap_or_default() {
+                resources.push(json!({
+                    "uri": format!("tt://artifact/{}", artifact.id),
+                    "name": format!("#{} {}", task.id, artifact.name),
+                    "description": artifact.file_path,
+                    "mimeType": artifact_mime_type(&artifact.file_path)
+                }));
+            }
+        }
+
+        JsonRpcResponse::success(id, json!({ "resources": resources }))
+    }
+
+    fn handle_list_resource_templates(&self, id: Option<Value>) -> JsonRpcResponse {
+        let templates = json!([
+            {
+                "uriTemplate": "tt://task/{id}",
+                "name": "Task",
+                "description": "A task with its dependencies, dependents and artifacts",
+                "mimeType": "application/json"
+            },
+            {
+                "uriTemplate": "tt://artifact/{id}",
+                "name": "Artifact",
+                "description": "The content of an artifact file from .tt/artifacts"
+            }
+        ]);
+        JsonRpcResponse::success(id, json!({ "resourceTemplates": templates }))
+    }
+
+    fn handle_read_resource(&self, id: Option<Value>, params: Option<Value>) -> JsonRpcResponse {
+        let uri = match params
+            .as_ref()
+            .and_then(|p| p.get("uri"))
+            .and_then(|v| v.as_str())
+        {
+            Some(uri) => uri,
+            None => return JsonRpcResponse::error(id, -32602, "Missing resource uri".to_string()),
+        };
+
+        let resource = match ResourceUri::parse(uri) {
+            Some(resource) => resource,
+            None => {
+                return JsonRpcResponse::error(id, -32602, format!("Invalid resource uri: {uri}"))
+            }
+        };
+
+        match self.read_resource(uri, resource) {
+            Ok(contents) => JsonRpcResponse::success(id, json!({ "contents": [contents] })),
+            Err(
+                e @ (TTError::TaskNotFound(_)
+                | TTError::ArtifactNotFound(_)
+                | TTError::NoTarget
+                | TTError::NoActiveTask),
+            ) => JsonRpcResponse::tt_error(id, RESOURCE_NOT_FOUND, &e),
+            Err(e) => JsonRpcResponse::tt_error(id, -32603, &e),
+        }
+    }
+
+    fn read_resource(&self, uri: &str, resource: ResourceUri) -> Result<Value, TTError> {
+        match resource {
+            ResourceUri::Task(task_id) => task_resource(uri, &self.core.get_task_detail(task_id)?),
+            ResourceUri::Target => {
+                let target_id = self.core.get_target()?.ok_or(TTError::NoTarget)?;
+                task_resource(uri, &self.core.get_task_detail(target_id)?)
+            }
+            ResourceUri::Current => {
+                let active = self.core.get_active_task()?.ok_or(TTError::NoActiveTask)?;
+                task_resource(uri, &self.core.get_task_detail(active.id)?)
+            }
+            ResourceUri::Artifact(artifact_id) => {
+                let (artifact, content) =
+                    self.core.read_artifact(artifact_id, &self.artifacts_dir)?;
+                Ok(json!({
+                    "uri": uri,
+                    "mimeType": artifact_mime_type(&artifact.file_path),
+                    "text": content
+                }))
+            }
         }
     }
 
@@ -416,3 +626,46 @@ pub fn run_mcp_server() -> Result<(), TTError> {
     let mut server = McpServer::new()?;
     server.run().map_err(TTError::Io)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_response_format() {
+        let response = error_to_response(TTError::AnotherTaskActive(3));
+        assert_eq!(
+            response,
+            json!({
+                "status": "error",
+                "error_code": "AnotherTaskActive",
+                "message": "Task #3 is already in progress. Finish or stop it first.",
+                "details": { "active_task_id": 3 },
+            })
+        );
+
+        let response = error_to_response(TTError::NoTarget);
+        assert_eq!(response["error_code"], "NoTarget");
+        assert!(response.get("details").is_none());
+    }
+
+    #[test]
+    fn test_parse_resource_uri() {
+        assert_eq!(
+            ResourceUri::parse("tt://task/12"),
+            Some(ResourceUri::Task(12))
+        );
+        assert_eq!(ResourceUri::parse("tt://target"), Some(ResourceUri::Target));
+        assert_eq!(
+            ResourceUri::parse("tt://current"),
+            Some(ResourceUri::Current)
+        );
+        assert_eq!(
+            ResourceUri::parse("tt://artifact/3"),
+            Some(ResourceUri::Artifact(3))
+        );
+        assert_eq!(ResourceUri::parse("tt://task/abc"), None);
+        assert_eq!(ResourceUri::parse("tt://task"), None);
+        assert_eq!(ResourceUri::parse("file:///etc/passwd"), None);
+    }
+}
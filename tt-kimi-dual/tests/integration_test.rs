@@ -1,17 +1,17 @@
-use assert_cmd::Command;
+use assert_cmd::cargo::cargo_bin_cmd;
 use predicates::prelude::*;
 use tempfile::TempDir;
 
 #[test]
 fn test_full_workflow() {
     let temp_dir = TempDir::new().unwrap();
-    let mut cmd = Command::cargo_bin("tt").unwrap();
+    let mut cmd = cargo_bin_cmd!("tt");
     cmd.current_dir(&temp_dir);
     cmd.arg("init");
     cmd.assert().success();
 
     // Add Task A
-    let mut cmd = Command::cargo_bin("tt").unwrap();
+    let mut cmd = cargo_bin_cmd!("tt");
     cmd.current_dir(&temp_dir);
     cmd.args(["add", "Task A"]);
     cmd.assert()
@@ -19,7 +19,7 @@ fn test_full_workflow() {
         .stdout(predicate::str::contains("Created task #1"));
 
     // Add Task B
-    let mut cmd = Command::cargo_bin("tt").unwrap();
+    let mut cmd = cargo_bin_cmd!("tt");
     cmd.current_dir(&temp_dir);
     cmd.args(["add", "Task B"]);
     cmd.assert()
@@ -27,19 +27,19 @@ fn test_full_workflow() {
         .stdout(predicate::str::contains("Created task #2"));
 
     // Add dependency
-    let mut cmd = Command::cargo_bin("tt").unwrap();
+    let mut cmd = cargo_bin_cmd!("tt");
     cmd.current_dir(&temp_dir);
     cmd.args(["depend", "2", "1"]);
     cmd.assert().success();
 
     // Set target
-    let mut cmd = Command::cargo_bin("tt").unwrap();
+    let mut cmd = cargo_bin_cmd!("tt");
     cmd.current_dir(&temp_dir);
     cmd.args(["target", "2"]);
     cmd.assert().success();
 
     // Next should return Task 1
-    let mut cmd = Command::cargo_bin("tt").unwrap();
+    let mut cmd = cargo_bin_cmd!("tt");
     cmd.current_dir(&temp_dir);
     cmd.arg("next");
     cmd.assert()
@@ -47,31 +47,31 @@ fn test_full_workflow() {
         .stdout(predicate::str::contains("Task A"));
 
     // Start Task 1
-    let mut cmd = Command::cargo_bin("tt").unwrap();
+    let mut cmd = cargo_bin_cmd!("tt");
     cmd.current_dir(&temp_dir);
     cmd.args(["start", "1"]);
     cmd.assert().success();
 
     // Done should fail without DoD
-    let mut cmd = Command::cargo_bin("tt").unwrap();
+    let mut cmd = cargo_bin_cmd!("tt");
     cmd.current_dir(&temp_dir);
     cmd.arg("done");
     cmd.assert().failure();
 
     // Set DoD
-    let mut cmd = Command::cargo_bin("tt").unwrap();
+    let mut cmd = cargo_bin_cmd!("tt");
     cmd.current_dir(&temp_dir);
     cmd.args(["edit", "1", "--dod", "Schema exists"]);
     cmd.assert().success();
 
     // Now done should succeed
-    let mut cmd = Command::cargo_bin("tt").unwrap();
+    let mut cmd = cargo_bin_cmd!("tt");
     cmd.current_dir(&temp_dir);
     cmd.arg("done");
     cmd.assert().success();
 
     // Next should return Task 2
-    let mut cmd = Command::cargo_bin("tt").unwrap();
+    let mut cmd = cargo_bin_cmd!("tt");
     cmd.current_dir(&temp_dir);
     cmd.arg("next");
     cmd.assert()
@@ -79,27 +79,290 @@ fn test_full_workflow() {
         .stdout(predicate::str::contains("Task B"));
 
     // Set DoD for Task 2
-    let mut cmd = Command::cargo_bin("tt").unwrap();
+    let mut cmd = cargo_bin_cmd!("tt");
     cmd.current_dir(&temp_dir);
     cmd.args(["edit", "2", "--dod", "Feature works"]);
     cmd.assert().success();
 
     // Start and complete Task 2
-    let mut cmd = Command::cargo_bin("tt").unwrap();
+    let mut cmd = cargo_bin_cmd!("tt");
     cmd.current_dir(&temp_dir);
     cmd.args(["start", "2"]);
     cmd.assert().success();
 
-    let mut cmd = Command::cargo_bin("tt").unwrap();
+    let mut cmd = cargo_bin_cmd!("tt");
     cmd.current_dir(&temp_dir);
     cmd.arg("done");
     cmd.assert().success();
 
     // Next should show Target Reached
-    let mut cmd = Command::cargo_bin("tt").unwrap();
+    let mut cmd = cargo_bin_cmd!("tt");
     cmd.current_dir(&temp_dir);
     cmd.arg("next");
     cmd.assert()
         .failure()
         .stderr(predicate::str::contains("Target reached"));
 }
+
+#[test]
+fn test_split_and_merge() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut cmd = cargo_bin_cmd!("tt");
+    cmd.current_dir(&temp_dir);
+    cmd.arg("init");
+    cmd.assert().success();
+
+    let mut cmd = cargo_bin_cmd!("tt");
+    cmd.current_dir(&temp_dir);
+    cmd.args(["add", "Big task"]);
+    cmd.assert().success();
+
+    // Split into a chain of two subtasks (#2, #3)
+    let mut cmd = cargo_bin_cmd!("tt");
+    cmd.current_dir(&temp_dir);
+    cmd.args(["split", "1", "--into", "Part A", "Part B", "--chain"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Split task #1 into 2 subtasks"))
+        .stdout(predicate::str::contains("Part B"));
+
+    let mut cmd = cargo_bin_cmd!("tt");
+    cmd.current_dir(&temp_dir);
+    cmd.args(["show", "1"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Dependencies: #3"));
+
+    // Merge Part B into Part A
+    let mut cmd = cargo_bin_cmd!("tt");
+    cmd.current_dir(&temp_dir);
+    cmd.args(["merge", "2", "3"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Merged task #3 into #2"));
+
+    let mut cmd = cargo_bin_cmd!("tt");
+    cmd.current_dir(&temp_dir);
+    cmd.args(["show", "1"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Dependencies: #2"));
+}
+
+#[test]
+fn test_mcp_resources() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut cmd = cargo_bin_cmd!("tt");
+    cmd.current_dir(&temp_dir);
+    cmd.arg("init");
+    cmd.assert().success();
+
+    for args in [
+        vec!["add", "Research", "--dod", "Notes written"],
+        vec!["target", "1"],
+        vec!["start", "1"],
+    ] {
+        let mut cmd = cargo_bin_cmd!("tt");
+        cmd.current_dir(&temp_dir);
+        cmd.args(args);
+        cmd.assert().success();
+    }
+
+    std::fs::write(
+        temp_dir.path().join(".tt/artifacts/research.md"),
+        "# Findings",
+    )
+    .unwrap();
+    let mut cmd = cargo_bin_cmd!("tt");
+    cmd.current_dir(&temp_dir);
+    cmd.args(["log", "research", "--file", ".tt/artifacts/research.md"]);
+    cmd.assert().success();
+
+    let requests = [
+        r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#,
+        r#"{"jsonrpc":"2.0","id":2,"method":"resources/list"}"#,
+        r#"{"jsonrpc":"2.0","id":3,"method":"resources/templates/list"}"#,
+        r#"{"jsonrpc":"2.0","id":4,"method":"resources/read","params":{"uri":"tt://current"}}"#,
+        r#"{"jsonrpc":"2.0","id":5,"method":"resources/read","params":{"uri":"tt://artifact/1"}}"#,
+        r#"{"jsonrpc":"2.0","id":6,"method":"resources/read","params":{"uri":"tt://task/99"}}"#,
+        r#"{"jsonrpc":"2.0","id":7,"method":"resources/read","params":{"uri":"tt://nope"}}"#,
+    ];
+
+    let mut cmd = cargo_bin_cmd!("tt");
+    cmd.current_dir(&temp_dir);
+    cmd.arg("mcp");
+    cmd.write_stdin(requests.join("\n"));
+    let output = cmd.assert().success().get_output().stdout.clone();
+
+    let responses: Vec<serde_json::Value> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(responses.len(), requests.len());
+
+    assert!(responses[0]["result"]["capabilities"]["resources"].is_object());
+
+    let uris: Vec<&str> = responses[1]["result"]["resources"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["uri"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        uris,
+        [
+            "tt://target",
+            "tt://current",
+            "tt://task/1",
+            "tt://artifact/1"
+        ]
+    );
+
+    let templates = &responses[2]["result"]["resourceTemplates"];
+    assert_eq!(templates[0]["uriTemplate"], "tt://task/{id}");
+
+    let current = &responses[3]["result"]["contents"][0];
+    assert_eq!(current["uri"], "tt://current");
+    let detail: serde_json::Value =
+        serde_json::from_str(current["text"].as_str().unwrap()).unwrap();
+    assert_eq!(detail["title"], "Research");
+
+    let artifact = &responses[4]["result"]["contents"][0];
+    assert_eq!(artifact["mimeType"], "text/markdown");
+    assert_eq!(artifact["text"], "# Findings");
+
+    assert_eq!(responses[5]["error"]["code"], -32002);
+    assert_eq!(responses[5]["error"]["data"]["error_code"], "TaskNotFound");
+    assert_eq!(responses[5]["error"]["data"]["details"]["task_id"], 99);
+    assert_eq!(responses[6]["error"]["code"], -32602);
+}
+
+#[test]
+fn test_mcp_error_codes() {
+    let temp_dir = TempDir::new().unwrap();
+    for args in [
+        vec!["init"],
+        vec!["add", "First", "--dod", "Done"],
+        vec!["add", "Second", "--dod", "Done"],
+        vec!["depend", "2", "1"],
+        vec!["start", "1"],
+    ] {
+        let mut cmd = cargo_bin_cmd!("tt");
+        cmd.current_dir(&temp_dir);
+        cmd.args(args);
+        cmd.assert().success();
+    }
+
+    let requests = [
+        r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"start_task","arguments":{"id":2}}}"#,
+        r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"add_dependency","arguments":{"task_id":1,"depends_on":2}}}"#,
+        r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"nope"}}"#,
+    ];
+
+    let mut cmd = cargo_bin_cmd!("tt");
+    cmd.current_dir(&temp_dir);
+    cmd.arg("mcp");
+    cmd.write_stdin(requests.join("\n"));
+    let output = cmd.assert().success().get_output().stdout.clone();
+
+    let results: Vec<serde_json::Value> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["result"].clone())
+        .collect();
+
+    assert_eq!(results[0]["status"], "error");
+    assert_eq!(results[0]["error_code"], "AnotherTaskActive");
+    assert_eq!(results[0]["details"]["active_task_id"], 1);
+
+    assert_eq!(results[1]["error_code"], "CycleDetected");
+    assert_eq!(results[1]["details"]["task_id"], 1);
+    assert_eq!(results[1]["details"]["depends_on"], 2);
+    assert!(results[1]["details"]["cycle"].is_array());
+
+    assert_eq!(results[2]["error_code"], "UnknownTool");
+    assert_eq!(results[2]["details"]["tool"], "nope");
+}
+
+#[test]
+fn test_mcp_rejects_other_jsonrpc_versions() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut cmd = cargo_bin_cmd!("tt");
+    cmd.current_dir(&temp_dir);
+    cmd.arg("init");
+    cmd.assert().success();
+
+    let requests = [
+        r#"{"jsonrpc":"1.0","id":1,"method":"tools/list"}"#,
+        r#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#,
+    ];
+
+    let mut cmd = cargo_bin_cmd!("tt");
+    cmd.current_dir(&temp_dir);
+    cmd.arg("mcp");
+    cmd.write_stdin(requests.join("\n"));
+    let output = cmd.assert().success().get_output().stdout.clone();
+
+    let responses: Vec<serde_json::Value> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(responses.len(), requests.len());
+
+    assert_eq!(responses[0]["id"], 1);
+    assert_eq!(responses[0]["error"]["code"], -32600);
+    assert!(responses[0]["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("'1.0'"));
+    assert!(responses[1]["error"].is_null());
+}
+
+#[test]
+fn test_snapshot_and_restore() {
+    let temp_dir = TempDir::new().unwrap();
+    for args in [
+        vec!["init"],
+        vec!["add", "Keep"],
+        vec!["snapshot", "good"],
+        vec!["add", "Mistake"],
+        vec!["reindex"],
+    ] {
+        let mut cmd = cargo_bin_cmd!("tt");
+        cmd.current_dir(&temp_dir);
+        cmd.args(args);
+        cmd.assert().success();
+    }
+
+    let mut cmd = cargo_bin_cmd!("tt");
+    cmd.current_dir(&temp_dir);
+    cmd.args(["snapshot", "list"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("good"))
+        .stdout(predicate::str::contains("-reindex"));
+
+    let mut cmd = cargo_bin_cmd!("tt");
+    cmd.current_dir(&temp_dir);
+    cmd.args(["restore", "good"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Restored snapshot 'good'"));
+
+    let mut cmd = cargo_bin_cmd!("tt");
+    cmd.current_dir(&temp_dir);
+    cmd.args(["list", "--all"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Keep"))
+        .stdout(predicate::str::contains("Mistake").not());
+
+    let mut cmd = cargo_bin_cmd!("tt");
+    cmd.current_dir(&temp_dir);
+    cmd.args(["restore", "missing"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Snapshot 'missing' not found"));
+}
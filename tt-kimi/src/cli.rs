@@ -1,4 +1,6 @@
+use chrono::{DateTime, Utc};
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(name = "tt")]
@@ -30,6 +32,15 @@ pub enum Commands {
         /// Insert before this task ID
         #[arg(long)]
         before: Option<i64>,
+        /// Instantiate this template from .tt/templates, using the title as {{name}}
+        #[arg(long, conflicts_with_all = ["desc", "dod", "after", "before"])]
+        template: Option<String>,
+        /// Task that should depend on the template's final tasks
+        #[arg(long, requires = "template")]
+        parent: Option<i64>,
+        /// Extra placeholder value for the template (key=value)
+        #[arg(long = "var", requires = "template", value_parser = parse_var)]
+        vars: Vec<(String, String)>,
     },
 
     /// Edit an existing task
@@ -51,6 +62,10 @@ pub enum Commands {
         /// Clear DoD
         #[arg(long)]
         no_dod: bool,
+        /// Reject the edit unless the task was last touched at this time, as
+        /// printed by `tt show` (RFC 3339)
+        #[arg(long, value_name = "TIMESTAMP")]
+        expect_touched: Option<DateTime<Utc>>,
     },
 
     /// Show task details
@@ -149,6 +164,40 @@ pub enum Commands {
     /// Reindex all task orders
     Reindex,
 
+    /// Work across every project registered in ~/.local/share/tt/projects
+    Global {
+        #[command(subcommand)]
+        command: GlobalCommands,
+    },
+
     /// Start MCP server
-    Mcp,
+    Mcp {
+        /// Serve Streamable HTTP on this address (e.g. 127.0.0.1:8765) instead of stdio
+        #[arg(long, value_name = "ADDR")]
+        http: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum GlobalCommands {
+    /// Register a project root (defaults to the current directory)
+    Add { path: Option<PathBuf> },
+
+    /// Unregister a project root (defaults to the current directory)
+    Remove { path: Option<PathBuf> },
+
+    /// List registered project roots
+    List,
+
+    /// Show each project's target, active task and remaining tasks
+    Status,
+
+    /// Suggest the next task across all projects
+    Next,
+}
+
+fn parse_var(s: &str) -> Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(k, v)| (k.trim().to_string(), v.to_string()))
+        .ok_or_else(|| format!("expected key=value, got '{s}'"))
 }
@@ -1,6 +1,10 @@
 use crate::core::TaskTracker;
 use crate::error::TaskError;
+use crate::global::{self, Registry};
 use crate::models::{NextTaskResult, Status};
+use crate::templates::{self, Template};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
@@ -17,9 +21,17 @@ pub fn handle_init() -> Result<(), TaskError> {
     // Create .tt/artifacts directory
     fs::create_dir_all(".tt/artifacts")?;
 
+    // Seed the templates directory with the built-in RPI template
+    fs::create_dir_all(templates::TEMPLATES_DIR)?;
+    fs::write(
+        Path::new(templates::TEMPLATES_DIR).join("rpi.toml"),
+        templates::RPI_TEMPLATE,
+    )?;
+
     println!("Initialized task tracker in current directory");
     println!("  - Created: tt.db");
     println!("  - Created: .tt/artifacts/");
+    println!("  - Created: .tt/templates/rpi.toml");
 
     Ok(())
 }
@@ -43,6 +55,37 @@ pub fn handle_add(
     Ok(())
 }
 
+/// Handle the add command with --template
+pub fn handle_add_template(
+    name: &str,
+    title: &str,
+    parent: Option<i64>,
+    vars: Vec<(String, String)>,
+) -> Result<(), TaskError> {
+    let tracker = TaskTracker::open()?;
+    check_initialized(&tracker)?;
+
+    let template = Template::load(Path::new(templates::TEMPLATES_DIR), name)?;
+    let mut vars: HashMap<String, String> = vars.into_iter().collect();
+    vars.insert("name".to_string(), title.to_string());
+
+    let instance = tracker.instantiate_template(name, &template, &vars, parent)?;
+
+    println!(
+        "Created {} tasks from template '{}':",
+        instance.tasks.len(),
+        instance.template
+    );
+    for task in &instance.tasks {
+        println!("  [#{}] {}", task.id, task.title);
+    }
+    if let Some(parent_id) = instance.parent_id {
+        println!("  Wired as dependencies of #{parent_id}");
+    }
+
+    Ok(())
+}
+
 /// Handle the edit command
 pub fn handle_edit(
     id: i64,
@@ -51,6 +94,7 @@ pub fn handle_edit(
     no_desc: bool,
     dod: Option<&str>,
     no_dod: bool,
+    expect_touched: Option<DateTime<Utc>>,
 ) -> Result<(), TaskError> {
     let tracker = TaskTracker::open()?;
     check_initialized(&tracker)?;
@@ -59,7 +103,7 @@ pub fn handle_edit(
 
     let dod_value = if no_dod { Some(None) } else { dod.map(Some) };
 
-    let detail = tracker.update_task(id, title, description, dod_value)?;
+    let detail = tracker.update_task(id, title, description, dod_value, expect_touched)?;
 
     println!("Updated task #{}: {}", detail.task.id, detail.task.title);
 
@@ -78,6 +122,8 @@ pub fn handle_show(id: i64) -> Result<(), TaskError> {
     println!("Status:       {}", task.status);
     println!("Order:        {}", task.manual_order);
     println!("Created:      {}", task.created_at.format("%Y-%m-%d %H:%M"));
+    // Full precision, so it can be passed back to `tt edit --expect-touched`
+    println!("Touched:      {}", task.last_touched_at.to_rfc3339());
 
     if let Some(ref dod) = task.dod {
         println!("DoD:          {dod}");
@@ -449,6 +495,83 @@ pub fn handle_reindex() -> Result<(), TaskError> {
     Ok(())
 }
 
+/// Handle the global add command
+pub fn handle_global_add(path: Option<&Path>) -> Result<(), TaskError> {
+    let mut registry = Registry::open_default()?;
+    let root = registry.add(path.unwrap_or(Path::new(".")))?;
+    println!("Registered {}", root.display());
+    Ok(())
+}
+
+/// Handle the global remove command
+pub fn handle_global_remove(path: Option<&Path>) -> Result<(), TaskError> {
+    let mut registry = Registry::open_default()?;
+    let root = Registry::resolve(path.unwrap_or(Path::new(".")));
+    if registry.remove(&root)? {
+        println!("Unregistered {}", root.display());
+    } else {
+        println!("{} was not registered", root.display());
+    }
+    Ok(())
+}
+
+/// Handle the global list command
+pub fn handle_global_list() -> Result<(), TaskError> {
+    let registry = Registry::open_default()?;
+    if registry.roots().is_empty() {
+        println!("No projects registered. Run `tt global add` in a project.");
+    }
+    for root in registry.roots() {
+        println!("{}", root.display());
+    }
+    Ok(())
+}
+
+/// Handle the global status command
+pub fn handle_global_status() -> Result<(), TaskError> {
+    let registry = Registry::open_default()?;
+    if registry.roots().is_empty() {
+        println!("No projects registered. Run `tt global add` in a project.");
+        return Ok(());
+    }
+
+    for (root, status) in global::status(registry.roots()) {
+        println!("{}", root.display());
+        match status {
+            Ok(status) => {
+                match status.target {
+                    Some(t) => println!("  Target:    [#{}] {}", t.id, t.title),
+                    None => println!("  Target:    none"),
+                }
+                match status.active {
+                    Some(t) => println!("  Active:    [#{}] {}", t.id, t.title),
+                    None => println!("  Active:    none"),
+                }
+                println!("  Remaining: {}", status.remaining);
+            }
+            Err(e) => println!("  Unavailable: {e}"),
+        }
+    }
+    Ok(())
+}
+
+/// Handle the global next command
+pub fn handle_global_next() -> Result<(), TaskError> {
+    let registry = Registry::open_default()?;
+    match global::next(registry.roots()) {
+        Some(next) => {
+            let label = if next.resume { "Resume" } else { "Next" };
+            println!("{label}: [#{}] {}", next.task.task.id, next.task.task.title);
+            println!("  Project: {}", next.root.display());
+            if let Some(ref dod) = next.task.task.dod {
+                println!("  DoD: {dod}");
+            }
+        }
+        None => println!("No ready tasks in any registered project"),
+    }
+    Ok(())
+}
+
 // Helper function
 fn check_initialized(tracker: &TaskTracker) -> Result<(), TaskError> {
     if !tracker.is_initialized()? {
@@ -2,28 +2,49 @@ use crate::db::Database;
 use crate::error::{Result, TaskError};
 use crate::graph;
 use crate::models::{
-    Artifact, BlockedTaskInfo, DependencyInfo, NextTaskResult, OrderConflict, Status, Task,
-    TaskDetail, WaitingOnInfo,
+    Artifact, BlockedTaskInfo, Decomposition, DependencyInfo, NextTaskResult, OrderConflict,
+    Status, Task, TaskDetail, TemplateInstance, WaitingOnInfo,
 };
-use std::collections::HashMap;
+use crate::store::{MemoryStore, Store};
+use crate::templates::Template;
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 
 /// Core business logic
 pub struct TaskTracker {
-    db: Database,
+    db: Box<dyn Store>,
 }
 
 impl TaskTracker {
     /// Open the database in the current directory
     pub fn open() -> Result<Self> {
         let db = Database::open_current_dir()?;
-        Ok(TaskTracker { db })
+        Ok(Self::with_store(db))
     }
 
     /// Open database at specific path
     pub fn open_at<P: AsRef<Path>>(path: P) -> Result<Self> {
         let db = Database::open(path)?;
-        Ok(TaskTracker { db })
+        Ok(Self::with_store(db))
+    }
+
+    /// Open an existing database at a specific path for reading only
+    pub fn open_read_only<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let db = Database::open_read_only(path)?;
+        Ok(Self::with_store(db))
+    }
+
+    /// Tracker backed by a fresh [`MemoryStore`]; nothing touches the filesystem
+    pub fn in_memory() -> Self {
+        Self::with_store(MemoryStore::new())
+    }
+
+    /// Tracker backed by any [`Store`] implementation
+    pub fn with_store(store: impl Store + 'static) -> Self {
+        TaskTracker {
+            db: Box::new(store),
+        }
     }
 
     /// Check if initialized
@@ -36,6 +57,11 @@ impl TaskTracker {
         self.db.init()
     }
 
+    /// Counter that changes when another connection modifies the database
+    pub fn data_version(&self) -> Result<i64> {
+        self.db.data_version()
+    }
+
     // ==================== Task Operations ====================
 
     /// Create a new task
@@ -47,7 +73,14 @@ impl TaskTracker {
         after_id: Option<i64>,
         before_id: Option<i64>,
     ) -> Result<Task> {
-        // Calculate manual_order based on positioning hints
+        self.with_transaction(|| {
+            let manual_order = self.position(after_id, before_id)?;
+            self.db.create_task(title, description, dod, manual_order)
+        })
+    }
+
+    /// Manual order for a new task placed by positioning hints
+    fn position(&self, after_id: Option<i64>, before_id: Option<i64>) -> Result<f64> {
         let manual_order = match (after_id, before_id) {
             (Some(after), Some(before)) => {
                 let after_task = self
@@ -80,7 +113,7 @@ impl TaskTracker {
             }
         };
 
-        self.db.create_task(title, description, dod, manual_order)
+        Ok(manual_order)
     }
 
     /// Get a task by ID
@@ -95,14 +128,30 @@ impl TaskTracker {
     }
 
     /// Update a task
+    ///
+    /// If `expected_last_touched` is given, the edit is rejected with
+    /// [`TaskError::StaleWrite`] when the task was touched since then.
     pub fn update_task(
         &self,
         id: i64,
         title: Option<&str>,
         description: Option<Option<&str>>,
         dod: Option<Option<&str>>,
+        expected_last_touched: Option<DateTime<Utc>>,
     ) -> Result<TaskDetail> {
-        let task = self.db.update_task(id, title, description, dod)?;
+        let task = self.with_transaction(|| {
+            let task = self.db.get_task(id)?.ok_or(TaskError::TaskNotFound(id))?;
+            if let Some(expected) = expected_last_touched
+                && task.last_touched_at != expected
+            {
+                return Err(TaskError::StaleWrite {
+                    id,
+                    expected,
+                    actual: task.last_touched_at,
+                });
+            }
+            self.db.update_task(id, title, description, dod)
+        })?;
         self.load_task_details(task)
     }
 
@@ -115,11 +164,13 @@ impl TaskTracker {
 
     /// Set the target task
     pub fn set_target(&self, id: i64) -> Result<()> {
-        // Verify task exists
-        if self.db.get_task(id)?.is_none() {
-            return Err(TaskError::TaskNotFound(id));
-        }
-        self.db.set_config("target_id", &id.to_string())
+        self.with_transaction(|| {
+            // Verify task exists
+            if self.db.get_task(id)?.is_none() {
+                return Err(TaskError::TaskNotFound(id));
+            }
+            self.db.set_config("target_id", &id.to_string())
+        })
     }
 
     /// Get the current target
@@ -274,76 +325,90 @@ impl TaskTracker {
     }
 
     /// Start a task
+    ///
+    /// The checks and the status change run in one write transaction, so two
+    /// writers can't both see "no active task" and start different tasks.
     pub fn start_task(&self, id: i64) -> Result<TaskDetail> {
-        let task = self.db.get_task(id)?.ok_or(TaskError::TaskNotFound(id))?;
+        let task = self.with_transaction(|| {
+            let task = self.db.get_task(id)?.ok_or(TaskError::TaskNotFound(id))?;
 
-        // Idempotent: if already in progress, just return it
-        if task.status == Status::InProgress {
-            return self.load_task_details(task);
-        }
+            // Idempotent: if already in progress, just return it
+            if task.status == Status::InProgress {
+                return Ok(task);
+            }
 
-        // Check if task is blocked
-        if task.status == Status::Blocked {
-            return Err(TaskError::TaskIsBlocked(id));
-        }
+            // Check if task is blocked
+            if task.status == Status::Blocked {
+                return Err(TaskError::TaskIsBlocked(id));
+            }
 
-        // Check if already completed
-        if task.status == Status::Completed {
-            return Err(TaskError::TaskAlreadyCompleted(id));
-        }
+            // Check if already completed
+            if task.status == Status::Completed {
+                return Err(TaskError::TaskAlreadyCompleted(id));
+            }
 
-        // Check for active task
-        if let Some(active) = self.db.get_active_task()? {
-            if active.id != id {
+            // Check for active task
+            if let Some(active) = self.db.get_active_task()?
+                && active.id != id
+            {
                 return Err(TaskError::AnotherTaskActive(active.id));
             }
-        }
 
-        // Check dependencies
-        let deps = self.db.get_dependency_statuses(id)?;
-        let unmet: Vec<i64> = deps
-            .iter()
-            .filter(|(_, _, status)| *status != Status::Completed)
-            .map(|(id, _, _)| *id)
-            .collect();
+            // Check dependencies
+            let deps = self.db.get_dependency_statuses(id)?;
+            let unmet: Vec<i64> = deps
+                .iter()
+                .filter(|(_, _, status)| *status != Status::Completed)
+                .map(|(id, _, _)| *id)
+                .collect();
 
-        if !unmet.is_empty() {
-            return Err(TaskError::UnmetDependencies { id, deps: unmet });
-        }
+            if !unmet.is_empty() {
+                return Err(TaskError::UnmetDependencies { id, deps: unmet });
+            }
 
-        let task = self.db.set_task_status(id, Status::InProgress)?;
+            self.db.set_task_status(id, Status::InProgress)
+        })?;
         self.load_task_details(task)
     }
 
     /// Stop the active task
     pub fn stop_task(&self) -> Result<TaskDetail> {
-        let active = self.db.get_active_task()?.ok_or(TaskError::NoActiveTask)?;
-        let task = self.db.set_task_status(active.id, Status::Pending)?;
+        let task = self.with_transaction(|| {
+            let active = self.db.get_active_task()?.ok_or(TaskError::NoActiveTask)?;
+            self.db.set_task_status(active.id, Status::Pending)
+        })?;
         self.load_task_details(task)
     }
 
     /// Complete the active task
     pub fn complete_task(&self) -> Result<TaskDetail> {
-        let active = self.db.get_active_task()?.ok_or(TaskError::NoActiveTask)?;
+        let task = self.with_transaction(|| {
+            let active = self.db.get_active_task()?.ok_or(TaskError::NoActiveTask)?;
 
-        // Check for DoD
-        let task = self
-            .db
-            .get_task(active.id)?
-            .ok_or(TaskError::TaskNotFound(active.id))?;
-        if task
-            .dod
-            .as_ref()
-            .map(|s| s.trim().is_empty())
-            .unwrap_or(true)
-        {
-            return Err(TaskError::NoDod(active.id));
-        }
+            // Check for DoD
+            let task = self
+                .db
+                .get_task(active.id)?
+                .ok_or(TaskError::TaskNotFound(active.id))?;
+            if task
+                .dod
+                .as_ref()
+                .map(|s| s.trim().is_empty())
+                .unwrap_or(true)
+            {
+                return Err(TaskError::NoDod(active.id));
+            }
 
-        let task = self.db.set_task_status(active.id, Status::Completed)?;
+            self.db.set_task_status(active.id, Status::Completed)
+        })?;
         self.load_task_details(task)
     }
 
+    /// Whether any task is in progress
+    pub fn has_active_task(&self) -> Result<bool> {
+        Ok(self.db.get_active_task()?.is_some())
+    }
+
     /// Get the currently active task
     pub fn get_current_task(&self) -> Result<TaskDetail> {
         let active = self.db.get_active_task()?.ok_or(TaskError::NoActiveTask)?;
@@ -352,29 +417,33 @@ impl TaskTracker {
 
     /// Block a task
     pub fn block_task(&self, id: i64) -> Result<TaskDetail> {
-        let task = self.db.get_task(id)?.ok_or(TaskError::TaskNotFound(id))?;
+        let task = self.with_transaction(|| {
+            let task = self.db.get_task(id)?.ok_or(TaskError::TaskNotFound(id))?;
 
-        if task.status == Status::Blocked {
-            return Err(TaskError::TaskNotBlocked(id)); // Already blocked
-        }
+            if task.status == Status::Blocked {
+                return Err(TaskError::TaskNotBlocked(id)); // Already blocked
+            }
 
-        if task.status == Status::Completed {
-            return Err(TaskError::TaskAlreadyCompleted(id));
-        }
+            if task.status == Status::Completed {
+                return Err(TaskError::TaskAlreadyCompleted(id));
+            }
 
-        let task = self.db.set_task_status(id, Status::Blocked)?;
+            self.db.set_task_status(id, Status::Blocked)
+        })?;
         self.load_task_details(task)
     }
 
     /// Unblock a task
     pub fn unblock_task(&self, id: i64) -> Result<TaskDetail> {
-        let task = self.db.get_task(id)?.ok_or(TaskError::TaskNotFound(id))?;
+        let task = self.with_transaction(|| {
+            let task = self.db.get_task(id)?.ok_or(TaskError::TaskNotFound(id))?;
 
-        if task.status != Status::Blocked {
-            return Err(TaskError::TaskNotBlocked(id));
-        }
+            if task.status != Status::Blocked {
+                return Err(TaskError::TaskNotBlocked(id));
+            }
 
-        let task = self.db.set_task_status(id, Status::Pending)?;
+            self.db.set_task_status(id, Status::Pending)
+        })?;
         self.load_task_details(task)
     }
 
@@ -386,31 +455,33 @@ impl TaskTracker {
             return Err(TaskError::SelfDependency);
         }
 
-        // Verify both tasks exist
-        if self.db.get_task(task_id)?.is_none() {
-            return Err(TaskError::TaskNotFound(task_id));
-        }
-        if self.db.get_task(depends_on)?.is_none() {
-            return Err(TaskError::TaskNotFound(depends_on));
-        }
+        self.with_transaction(|| {
+            // Verify both tasks exist
+            if self.db.get_task(task_id)?.is_none() {
+                return Err(TaskError::TaskNotFound(task_id));
+            }
+            if self.db.get_task(depends_on)?.is_none() {
+                return Err(TaskError::TaskNotFound(depends_on));
+            }
 
-        // Get all existing dependencies
-        let all_deps = self.db.get_all_dependencies()?;
-        let dep_pairs: Vec<(i64, i64)> = all_deps
-            .into_iter()
-            .map(|d| (d.task_id, d.depends_on))
-            .collect();
+            // Get all existing dependencies
+            let all_deps = self.db.get_all_dependencies()?;
+            let dep_pairs: Vec<(i64, i64)> = all_deps
+                .into_iter()
+                .map(|d| (d.task_id, d.depends_on))
+                .collect();
 
-        // Check for cycle
-        if let Some(cycle) = graph::would_create_cycle(&dep_pairs, task_id, depends_on) {
-            return Err(TaskError::CycleDetected {
-                from: task_id,
-                to: depends_on,
-                path: cycle,
-            });
-        }
+            // Check for cycle
+            if let Some(cycle) = graph::would_create_cycle(&dep_pairs, task_id, depends_on) {
+                return Err(TaskError::CycleDetected {
+                    from: task_id,
+                    to: depends_on,
+                    path: cycle,
+                });
+            }
 
-        self.db.add_dependency(task_id, depends_on)
+            self.db.add_dependency(task_id, depends_on)
+        })
     }
 
     /// Remove a dependency
@@ -418,12 +489,97 @@ impl TaskTracker {
         self.db.remove_dependency(task_id, depends_on)
     }
 
+    // ==================== Template Operations ====================
+
+    /// Create the tasks and dependencies of a template in one transaction.
+    ///
+    /// `vars` fills `{{placeholders}}` in titles, descriptions and DoDs. If
+    /// `parent_id` is given, the parent depends on every leaf task of the template.
+    pub fn instantiate_template(
+        &self,
+        name: &str,
+        template: &Template,
+        vars: &HashMap<String, String>,
+        parent_id: Option<i64>,
+    ) -> Result<TemplateInstance> {
+        let template = template.render(vars)?;
+        let (tasks, ids) = self.create_task_group(&template, parent_id)?;
+
+        Ok(TemplateInstance {
+            template: name.to_string(),
+            tasks,
+            ids,
+            parent_id,
+        })
+    }
+
+    /// Split a task into the subtasks of `plan` in one transaction.
+    ///
+    /// The task depends on every leaf subtask, so it becomes available again
+    /// once the whole plan is done.
+    pub fn decompose_task(&self, id: i64, plan: &Template) -> Result<Decomposition> {
+        let (tasks, ids) = self.create_task_group(plan, Some(id))?;
+
+        Ok(Decomposition {
+            task_id: id,
+            tasks,
+            ids,
+        })
+    }
+
+    /// Create a template's tasks and internal dependencies, wiring its leaves
+    /// into `parent_id`
+    fn create_task_group(
+        &self,
+        template: &Template,
+        parent_id: Option<i64>,
+    ) -> Result<(Vec<Task>, BTreeMap<String, i64>)> {
+        self.with_transaction(|| {
+            if let Some(parent) = parent_id
+                && self.db.get_task(parent)?.is_none()
+            {
+                return Err(TaskError::TaskNotFound(parent));
+            }
+
+            let mut tasks = Vec::new();
+            let mut ids = BTreeMap::new();
+
+            for entry in &template.tasks {
+                let task = self.create_task(
+                    &entry.title,
+                    entry.description.as_deref(),
+                    entry.dod.as_deref(),
+                    None,
+                    None,
+                )?;
+                ids.insert(entry.key.clone(), task.id);
+                tasks.push(task);
+            }
+
+            for entry in &template.tasks {
+                for dep in &entry.depends_on {
+                    self.add_dependency(ids[&entry.key], ids[dep])?;
+                }
+            }
+
+            if let Some(parent) = parent_id {
+                for leaf in template.leaves() {
+                    self.add_dependency(parent, ids[&leaf.key])?;
+                }
+            }
+
+            Ok((tasks, ids))
+        })
+    }
+
     // ==================== Artifact Operations ====================
 
     /// Log an artifact for the active task
     pub fn log_artifact(&self, name: &str, file_path: &str) -> Result<Artifact> {
-        let active = self.db.get_active_task()?.ok_or(TaskError::NoActiveTask)?;
-        self.db.create_artifact(active.id, name, file_path)
+        self.with_transaction(|| {
+            let active = self.db.get_active_task()?.ok_or(TaskError::NoActiveTask)?;
+            self.db.create_artifact(active.id, name, file_path)
+        })
     }
 
     /// Log an artifact for a specific task
@@ -465,48 +621,24 @@ impl TaskTracker {
             return Err(TaskError::MissingPositionHint);
         }
 
-        let new_order = match (after_id, before_id) {
-            (Some(after), Some(before)) => {
-                let after_task = self
-                    .db
-                    .get_task(after)?
-                    .ok_or(TaskError::TaskNotFound(after))?;
-                let before_task = self
-                    .db
-                    .get_task(before)?
-                    .ok_or(TaskError::TaskNotFound(before))?;
-                graph::calculate_midpoint(after_task.manual_order, before_task.manual_order)?
-            }
-            (Some(after), None) => {
-                let after_task = self
-                    .db
-                    .get_task(after)?
-                    .ok_or(TaskError::TaskNotFound(after))?;
-                graph::calculate_order_after(after_task.manual_order)
-            }
-            (None, Some(before)) => {
-                let before_task = self
-                    .db
-                    .get_task(before)?
-                    .ok_or(TaskError::TaskNotFound(before))?;
-                graph::calculate_order_before(before_task.manual_order)
-            }
-            (None, None) => unreachable!(),
-        };
-
-        self.db.update_manual_order(id, new_order)
+        self.with_transaction(|| {
+            let new_order = self.position(after_id, before_id)?;
+            self.db.update_manual_order(id, new_order)
+        })
     }
 
     /// Reindex all orders
     pub fn reindex(&self) -> Result<Vec<Task>> {
-        let tasks = self.db.get_all_tasks_with_order()?;
-        let reindexed = graph::reindex_orders(&tasks);
+        self.with_transaction(|| {
+            let tasks = self.db.get_all_tasks_with_order()?;
+            let reindexed = graph::reindex_orders(&tasks);
 
-        for (id, order) in reindexed {
-            self.db.update_manual_order(id, order)?;
-        }
+            for (id, order) in reindexed {
+                self.db.update_manual_order(id, order)?;
+            }
 
-        self.db.get_all_tasks_with_order()
+            self.db.get_all_tasks_with_order()
+        })
     }
 
     // ==================== List Operations ====================
@@ -542,11 +674,22 @@ impl TaskTracker {
 
     /// Get tasks for the target subgraph
     pub fn get_target_tasks(&self, target_id: i64) -> Result<Vec<Task>> {
-        self.db.get_full_target_subgraph(target_id)
+        self.db.get_target_subgraph(target_id)
     }
 
     // ==================== Helper Methods ====================
 
+    /// Run `f` in a store transaction, returning its value on commit
+    fn with_transaction<T>(&self, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        let mut f = Some(f);
+        let mut value = None;
+        self.db.with_transaction(&mut || {
+            value = Some((f.take().expect("transaction body runs once"))()?);
+            Ok(())
+        })?;
+        Ok(value.expect("committed transaction produced a value"))
+    }
+
     fn load_task_details(&self, task: Task) -> Result<TaskDetail> {
         let deps = self.db.get_dependency_statuses(task.id)?;
         let dependencies: Vec<DependencyInfo> = deps
@@ -644,7 +787,15 @@ mod tests {
     #[test]
     fn test_target_workflow() {
         let (tracker, _temp) = setup();
+        check_target_workflow(&tracker);
+    }
 
+    #[test]
+    fn test_target_workflow_in_memory() {
+        check_target_workflow(&TaskTracker::in_memory());
+    }
+
+    fn check_target_workflow(tracker: &TaskTracker) {
         // Create tasks: A -> B -> C
         let a = tracker
             .create_task("A", None, Some("DoD A"), None, None)
@@ -761,4 +912,89 @@ mod tests {
             matches!(result, Err(TaskError::UnmetDependencies { id, deps }) if id == b.id && deps == vec![a.id])
         );
     }
+
+    #[test]
+    fn test_instantiate_template() {
+        let (tracker, _temp) = setup();
+
+        let parent = tracker
+            .create_task("Ship auth", None, Some("DoD"), None, None)
+            .unwrap();
+        let template = Template::parse(crate::templates::RPI_TEMPLATE).unwrap();
+        let vars = HashMap::from([("name".to_string(), "Auth".to_string())]);
+
+        let instance = tracker
+            .instantiate_template("rpi", &template, &vars, Some(parent.id))
+            .unwrap();
+        assert_eq!(instance.tasks.len(), 3);
+        assert_eq!(instance.tasks[0].title, "Research Auth");
+
+        let research = instance.ids["research"];
+        let plan = instance.ids["plan"];
+        let implement = instance.ids["implement"];
+
+        let plan_detail = tracker.get_task(plan).unwrap();
+        assert_eq!(plan_detail.dependencies[0].id, research);
+
+        // The parent waits on the last task of the template
+        let parent_detail = tracker.get_task(parent.id).unwrap();
+        let parent_deps: Vec<i64> = parent_detail.dependencies.iter().map(|d| d.id).collect();
+        assert_eq!(parent_deps, vec![implement]);
+    }
+
+    #[test]
+    fn test_update_task_rejects_stale_write() {
+        let (tracker, _temp) = setup();
+        let task = tracker.create_task("Task", None, None, None, None).unwrap();
+        let read_at = task.last_touched_at;
+
+        let updated = tracker
+            .update_task(task.id, Some("First"), None, None, Some(read_at))
+            .unwrap();
+        assert_eq!(updated.task.title, "First");
+
+        let result = tracker.update_task(task.id, Some("Second"), None, None, Some(read_at));
+        match result {
+            Err(TaskError::StaleWrite {
+                id,
+                expected,
+                actual,
+            }) => {
+                assert_eq!(id, task.id);
+                assert_eq!(expected, read_at);
+                assert_eq!(actual, updated.task.last_touched_at);
+            }
+            other => panic!("expected StaleWrite, got {other:?}"),
+        }
+        assert_eq!(tracker.get_task(task.id).unwrap().task.title, "First");
+    }
+
+    #[test]
+    fn test_instantiate_template_is_atomic() {
+        let (tracker, _temp) = setup();
+        check_instantiate_template_is_atomic(&tracker);
+    }
+
+    #[test]
+    fn test_instantiate_template_is_atomic_in_memory() {
+        check_instantiate_template_is_atomic(&TaskTracker::in_memory());
+    }
+
+    fn check_instantiate_template_is_atomic(tracker: &TaskTracker) {
+        let source = r#"
+            [[task]]
+            key = "a"
+            title = "A {{name}}"
+
+            [[task]]
+            key = "b"
+            title = "B {{unknown}}"
+        "#;
+        let template = Template::parse(source).unwrap();
+        let vars = HashMap::from([("name".to_string(), "x".to_string())]);
+
+        let result = tracker.instantiate_template("broken", &template, &vars, None);
+        assert!(matches!(result, Err(TaskError::InvalidTemplate(_))));
+        assert!(tracker.get_all_tasks().unwrap().is_empty());
+    }
 }
@@ -1,8 +1,16 @@
 use crate::error::{Result, TaskError};
 use crate::models::{Artifact, Dependency, Status, Task};
+use crate::store::Store;
 use chrono::{DateTime, NaiveDateTime, Utc};
-use rusqlite::{Connection, OptionalExtension, Row};
+use rusqlite::{Connection, OpenFlags, OptionalExtension, Row, Transaction, TransactionBehavior};
 use std::path::Path;
+use std::time::Duration;
+
+/// Database file at the root of a project
+pub const DB_FILE: &str = "tt.db";
+
+/// How long a write waits for another connection's lock before failing
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// Database handle
 pub struct Database {
@@ -14,6 +22,9 @@ impl Database {
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
         let conn = Connection::open(path)?;
 
+        // Wait for other writers (the CLI and the MCP server) instead of failing
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+
         // Enable foreign keys
         conn.pragma_update(None, "foreign_keys", "ON")?;
 
@@ -23,16 +34,21 @@ impl Database {
         Ok(Database { conn })
     }
 
-    /// Open database in current directory (tt.db)
-    pub fn open_current_dir() -> Result<Self> {
-        Self::open("tt.db")
+    /// Open an existing database without taking any locks that would
+    /// block the project's own writers
+    pub fn open_read_only<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let conn = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+
+        Ok(Database { conn })
     }
 
-    /// Initialize the database schema
-    pub fn init(&self) -> Result<()> {
-        self.create_tables()?;
-        self.create_indexes()?;
-        Ok(())
+    /// Open database in current directory (tt.db)
+    pub fn open_current_dir() -> Result<Self> {
+        Self::open(DB_FILE)
     }
 
     fn create_tables(&self) -> Result<()> {
@@ -112,8 +128,58 @@ impl Database {
         Ok(())
     }
 
+    pub fn clear_active_task(&self) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        self.conn.execute(
+            "UPDATE tasks SET status = 'blocked', last_touched_at = ?1 WHERE status = 'in_progress'",
+            [&now],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_artifact(&self, id: i64) -> Result<Option<Artifact>> {
+        self.conn
+            .query_row(
+                "SELECT id, task_id, name, file_path, created_at FROM artifacts WHERE id = ?1",
+                [id],
+                artifact_from_row,
+            )
+            .optional()
+            .map_err(|e| e.into())
+    }
+}
+
+impl Store for Database {
+    /// Takes the write lock up front (`BEGIN IMMEDIATE`) so a read-then-write
+    /// can't be interleaved with another process's write. Nested calls use a
+    /// savepoint inside the outer transaction.
+    fn with_transaction(&self, f: &mut dyn FnMut() -> Result<()>) -> Result<()> {
+        if !self.conn.is_autocommit() {
+            self.conn.execute_batch("SAVEPOINT nested")?;
+            return match f() {
+                Ok(()) => Ok(self.conn.execute_batch("RELEASE nested")?),
+                Err(e) => {
+                    self.conn
+                        .execute_batch("ROLLBACK TO nested; RELEASE nested")?;
+                    Err(e)
+                }
+            };
+        }
+
+        let tx = Transaction::new_unchecked(&self.conn, TransactionBehavior::Immediate)?;
+        f()?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn init(&self) -> Result<()> {
+        self.create_tables()?;
+        self.create_indexes()?;
+        Ok(())
+    }
+
     /// Check if database is initialized
-    pub fn is_initialized(&self) -> Result<bool> {
+    fn is_initialized(&self) -> Result<bool> {
         let count: i64 = self.conn.query_row(
             "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='tasks'",
             [],
@@ -122,9 +188,16 @@ impl Database {
         Ok(count > 0)
     }
 
+    /// SQLite `data_version`; changes whenever another connection commits
+    fn data_version(&self) -> Result<i64> {
+        Ok(self
+            .conn
+            .pragma_query_value(None, "data_version", |row| row.get(0))?)
+    }
+
     // ==================== Task Operations ====================
 
-    pub fn create_task(
+    fn create_task(
         &self,
         title: &str,
         description: Option<&str>,
@@ -144,7 +217,7 @@ impl Database {
         self.get_task(id).map(|t| t.unwrap())
     }
 
-    pub fn get_task(&self, id: i64) -> Result<Option<Task>> {
+    fn get_task(&self, id: i64) -> Result<Option<Task>> {
         self.conn
             .query_row(
                 "SELECT id, title, description, dod, status, manual_order, 
@@ -157,7 +230,7 @@ impl Database {
             .map_err(|e| e.into())
     }
 
-    pub fn get_all_tasks(&self) -> Result<Vec<Task>> {
+    fn get_all_tasks(&self) -> Result<Vec<Task>> {
         let mut stmt = self.conn.prepare(
             "SELECT id, title, description, dod, status, manual_order,
                     created_at, started_at, completed_at, last_touched_at
@@ -171,7 +244,7 @@ impl Database {
             .map_err(|e| e.into())
     }
 
-    pub fn update_task(
+    fn update_task(
         &self,
         id: i64,
         title: Option<&str>,
@@ -204,7 +277,7 @@ impl Database {
         self.get_task(id)?.ok_or(TaskError::TaskNotFound(id))
     }
 
-    pub fn set_task_status(&self, id: i64, status: Status) -> Result<Task> {
+    fn set_task_status(&self, id: i64, status: Status) -> Result<Task> {
         let now = Utc::now().to_rfc3339();
 
         match status {
@@ -231,7 +304,7 @@ impl Database {
         self.get_task(id)?.ok_or(TaskError::TaskNotFound(id))
     }
 
-    pub fn get_active_task(&self) -> Result<Option<Task>> {
+    fn get_active_task(&self) -> Result<Option<Task>> {
         self.conn
             .query_row(
                 "SELECT id, title, description, dod, status, manual_order,
@@ -245,16 +318,7 @@ impl Database {
             .map_err(|e| e.into())
     }
 
-    pub fn clear_active_task(&self) -> Result<()> {
-        let now = Utc::now().to_rfc3339();
-        self.conn.execute(
-            "UPDATE tasks SET status = 'blocked', last_touched_at = ?1 WHERE status = 'in_progress'",
-            [&now],
-        )?;
-        Ok(())
-    }
-
-    pub fn update_manual_order(&self, id: i64, order: f64) -> Result<Task> {
+    fn update_manual_order(&self, id: i64, order: f64) -> Result<Task> {
         let now = Utc::now().to_rfc3339();
         self.conn.execute(
             "UPDATE tasks SET manual_order = ?1, last_touched_at = ?2 WHERE id = ?3",
@@ -263,7 +327,7 @@ impl Database {
         self.get_task(id)?.ok_or(TaskError::TaskNotFound(id))
     }
 
-    pub fn get_max_manual_order(&self) -> Result<f64> {
+    fn get_max_manual_order(&self) -> Result<f64> {
         let result: Option<f64> = self
             .conn
             .query_row(
@@ -275,7 +339,7 @@ impl Database {
         Ok(result.unwrap_or(0.0))
     }
 
-    pub fn get_all_tasks_with_order(&self) -> Result<Vec<Task>> {
+    fn get_all_tasks_with_order(&self) -> Result<Vec<Task>> {
         let mut stmt = self.conn.prepare(
             "SELECT id, title, description, dod, status, manual_order,
                     created_at, started_at, completed_at, last_touched_at
@@ -291,7 +355,7 @@ impl Database {
 
     // ==================== Dependency Operations ====================
 
-    pub fn add_dependency(&self, task_id: i64, depends_on: i64) -> Result<()> {
+    fn add_dependency(&self, task_id: i64, depends_on: i64) -> Result<()> {
         if task_id == depends_on {
             return Err(TaskError::SelfDependency);
         }
@@ -302,7 +366,7 @@ impl Database {
         Ok(())
     }
 
-    pub fn remove_dependency(&self, task_id: i64, depends_on: i64) -> Result<()> {
+    fn remove_dependency(&self, task_id: i64, depends_on: i64) -> Result<()> {
         let rows = self.conn.execute(
             "DELETE FROM dependencies WHERE task_id = ?1 AND depends_on = ?2",
             (task_id, depends_on),
@@ -313,7 +377,7 @@ impl Database {
         Ok(())
     }
 
-    pub fn get_dependencies(&self, task_id: i64) -> Result<Vec<Dependency>> {
+    fn get_dependencies(&self, task_id: i64) -> Result<Vec<Dependency>> {
         let mut stmt = self
             .conn
             .prepare("SELECT task_id, depends_on FROM dependencies WHERE task_id = ?1")?;
@@ -329,7 +393,7 @@ impl Database {
             .map_err(|e| e.into())
     }
 
-    pub fn get_dependency_statuses(&self, task_id: i64) -> Result<Vec<(i64, String, Status)>> {
+    fn get_dependency_statuses(&self, task_id: i64) -> Result<Vec<(i64, String, Status)>> {
         let mut stmt = self.conn.prepare(
             "SELECT t.id, t.title, t.status 
              FROM tasks t
@@ -354,7 +418,7 @@ impl Database {
             .map_err(|e| e.into())
     }
 
-    pub fn get_dependents(&self, task_id: i64) -> Result<Vec<i64>> {
+    fn get_dependents(&self, task_id: i64) -> Result<Vec<i64>> {
         let mut stmt = self
             .conn
             .prepare("SELECT task_id FROM dependencies WHERE depends_on = ?1")?;
@@ -365,7 +429,7 @@ impl Database {
             .map_err(|e| e.into())
     }
 
-    pub fn get_all_dependencies(&self) -> Result<Vec<Dependency>> {
+    fn get_all_dependencies(&self) -> Result<Vec<Dependency>> {
         let mut stmt = self
             .conn
             .prepare("SELECT task_id, depends_on FROM dependencies")?;
@@ -383,7 +447,7 @@ impl Database {
 
     // ==================== Artifact Operations ====================
 
-    pub fn create_artifact(&self, task_id: i64, name: &str, file_path: &str) -> Result<Artifact> {
+    fn create_artifact(&self, task_id: i64, name: &str, file_path: &str) -> Result<Artifact> {
         self.conn.execute(
             "INSERT INTO artifacts (task_id, name, file_path) VALUES (?1, ?2, ?3)",
             (task_id, name, file_path),
@@ -393,18 +457,7 @@ impl Database {
         self.get_artifact(id).map(|a| a.unwrap())
     }
 
-    pub fn get_artifact(&self, id: i64) -> Result<Option<Artifact>> {
-        self.conn
-            .query_row(
-                "SELECT id, task_id, name, file_path, created_at FROM artifacts WHERE id = ?1",
-                [id],
-                artifact_from_row,
-            )
-            .optional()
-            .map_err(|e| e.into())
-    }
-
-    pub fn get_artifacts_for_task(&self, task_id: i64) -> Result<Vec<Artifact>> {
+    fn get_artifacts_for_task(&self, task_id: i64) -> Result<Vec<Artifact>> {
         let mut stmt = self.conn.prepare(
             "SELECT id, task_id, name, file_path, created_at 
              FROM artifacts 
@@ -420,7 +473,7 @@ impl Database {
 
     // ==================== Config Operations ====================
 
-    pub fn get_config(&self, key: &str) -> Result<Option<String>> {
+    fn get_config(&self, key: &str) -> Result<Option<String>> {
         self.conn
             .query_row("SELECT value FROM config WHERE key = ?1", [key], |row| {
                 row.get(0)
@@ -429,7 +482,7 @@ impl Database {
             .map_err(|e| e.into())
     }
 
-    pub fn set_config(&self, key: &str, value: &str) -> Result<()> {
+    fn set_config(&self, key: &str, value: &str) -> Result<()> {
         self.conn.execute(
             "INSERT INTO config (key, value) VALUES (?1, ?2)
              ON CONFLICT(key) DO UPDATE SET value = excluded.value",
@@ -438,7 +491,7 @@ impl Database {
         Ok(())
     }
 
-    pub fn delete_config(&self, key: &str) -> Result<()> {
+    fn delete_config(&self, key: &str) -> Result<()> {
         self.conn
             .execute("DELETE FROM config WHERE key = ?1", [key])?;
         Ok(())
@@ -449,42 +502,7 @@ impl Database {
     /// Get the transitive dependencies of a target (the active subgraph)
     /// Note: This includes ALL tasks in the subgraph (including completed)
     /// The caller should filter as needed
-    pub fn get_target_subgraph(&self, target_id: i64) -> Result<Vec<Task>> {
-        let mut stmt = self.conn.prepare(
-            "WITH RECURSIVE subgraph(id) AS (
-                SELECT ?1
-                UNION
-                SELECT d.depends_on
-                FROM dependencies d
-                JOIN subgraph s ON d.task_id = s.id
-            )
-            SELECT t.id, t.title, t.description, t.dod, t.status, t.manual_order,
-                   t.created_at, t.started_at, t.completed_at, t.last_touched_at
-            FROM tasks t
-            JOIN subgraph s ON t.id = s.id",
-        )?;
-
-        let tasks = stmt.query_map([target_id], task_from_row)?;
-        tasks
-            .collect::<std::result::Result<Vec<_>, _>>()
-            .map_err(|e| e.into())
-    }
-
-    pub fn get_target_subgraph_with_deps(&self, target_id: i64) -> Result<Vec<(Task, Vec<i64>)>> {
-        let tasks = self.get_target_subgraph(target_id)?;
-        let mut result = Vec::new();
-
-        for task in tasks {
-            let deps = self.get_dependencies(task.id)?;
-            let dep_ids: Vec<i64> = deps.into_iter().map(|d| d.depends_on).collect();
-            result.push((task, dep_ids));
-        }
-
-        Ok(result)
-    }
-
-    /// Get all tasks in the target subgraph including completed ones
-    pub fn get_full_target_subgraph(&self, target_id: i64) -> Result<Vec<Task>> {
+    fn get_target_subgraph(&self, target_id: i64) -> Result<Vec<Task>> {
         let mut stmt = self.conn.prepare(
             "WITH RECURSIVE subgraph(id) AS (
                 SELECT ?1
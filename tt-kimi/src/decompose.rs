@@ -0,0 +1,176 @@
+//! Task decomposition proposed by the client's model through MCP sampling.
+//!
+//! The server sends the task's context in a `sampling/createMessage` request
+//! and asks for a JSON plan of subtasks, which is validated like a template.
+
+use crate::error::{Result, TaskError};
+use crate::models::TaskDetail;
+use crate::templates::{Template, TemplateTask};
+use rmcp::model::{CreateMessageRequestParams, CreateMessageResult, SamplingMessage};
+use serde::Deserialize;
+use std::fmt::Write;
+
+/// Upper bound on the length of the model's answer
+const MAX_TOKENS: u32 = 2000;
+
+const SYSTEM_PROMPT: &str = r#"You break software development tasks into smaller subtasks.
+Answer with a single JSON object and nothing else, in this format:
+{"subtasks": [{"key": "short-id", "title": "...", "description": "...", "dod": "...", "depends_on": ["other-key"]}]}
+Each subtask needs a unique key, a title and a concrete, verifiable definition of done ("dod").
+"depends_on" lists the keys of subtasks that must be finished first. Propose 2 to 7 subtasks."#;
+
+/// The JSON answer expected from the model
+#[derive(Debug, Deserialize)]
+struct Plan {
+    subtasks: Vec<TemplateTask>,
+}
+
+/// The sampling request asking the client's model to decompose `detail`
+pub fn sampling_request(detail: &TaskDetail) -> CreateMessageRequestParams {
+    CreateMessageRequestParams {
+        meta: None,
+        task: None,
+        messages: vec![SamplingMessage::user_text(task_context(detail))],
+        model_preferences: None,
+        system_prompt: Some(SYSTEM_PROMPT.to_string()),
+        include_context: None,
+        temperature: None,
+        max_tokens: MAX_TOKENS,
+        stop_sequences: None,
+        metadata: None,
+        tools: None,
+        tool_choice: None,
+    }
+}
+
+/// Describe the task, its dependencies and artifacts for the model
+fn task_context(detail: &TaskDetail) -> String {
+    let task = &detail.task;
+    let mut text = format!("Decompose task #{}: {}\n", task.id, task.title);
+    if let Some(description) = &task.description {
+        let _ = writeln!(text, "\nDescription:\n{description}");
+    }
+    if let Some(dod) = &task.dod {
+        let _ = writeln!(text, "\nDefinition of done:\n{dod}");
+    }
+    if !detail.dependencies.is_empty() {
+        text.push_str("\nAlready depends on:\n");
+        for dep in &detail.dependencies {
+            let _ = writeln!(text, "- #{} {} ({})", dep.id, dep.title, dep.status);
+        }
+    }
+    if !detail.artifacts.is_empty() {
+        text.push_str("\nArtifacts:\n");
+        for artifact in &detail.artifacts {
+            let _ = writeln!(text, "- {}: {}", artifact.name, artifact.file_path);
+        }
+    }
+    text
+}
+
+/// Parse the model's answer into a validated plan
+pub fn parse_response(result: &CreateMessageResult) -> Result<Template> {
+    let contents = result.message.content.clone().into_vec();
+    let text = contents
+        .iter()
+        .filter_map(|content| content.as_text())
+        .map(|t| t.text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+    parse_plan(&text)
+}
+
+/// Parse a JSON plan, tolerating surrounding prose or a Markdown code fence
+pub fn parse_plan(text: &str) -> Result<Template> {
+    let json = match (text.find('{'), text.rfind('}')) {
+        (Some(start), Some(end)) if start < end => &text[start..=end],
+        _ => {
+            return Err(TaskError::InvalidPlan(
+                "the answer contains no JSON object".to_string(),
+            ));
+        }
+    };
+    let plan: Plan =
+        serde_json::from_str(json).map_err(|e| TaskError::InvalidPlan(e.to_string()))?;
+    plan_from_subtasks(plan.subtasks)
+}
+
+/// Validate subtasks given directly, e.g. a previously proposed plan
+pub fn plan_from_subtasks(subtasks: Vec<TemplateTask>) -> Result<Template> {
+    Template::new(subtasks).map_err(|e| match e {
+        TaskError::InvalidTemplate(message) => TaskError::InvalidPlan(message),
+        e => e,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{DependencyInfo, Status, Task};
+
+    #[test]
+    fn test_parse_plan_in_code_fence() {
+        let answer = r#"Here is a plan:
+```json
+{"subtasks": [
+  {"key": "schema", "title": "Design schema", "dod": "Schema reviewed"},
+  {"key": "api", "title": "Build API", "dod": "Endpoints tested", "depends_on": ["schema"]}
+]}
+```"#;
+        let plan = parse_plan(answer).unwrap();
+        assert_eq!(plan.tasks.len(), 2);
+        assert_eq!(plan.tasks[1].depends_on, vec!["schema"]);
+        assert_eq!(plan.leaves()[0].key, "api");
+    }
+
+    #[test]
+    fn test_parse_plan_rejects_invalid_answers() {
+        for answer in [
+            "I cannot help with that",
+            r#"{"steps": []}"#,
+            r#"{"subtasks": []}"#,
+            r#"{"subtasks": [{"key": "a", "title": "A", "depends_on": ["a"]}]}"#,
+        ] {
+            assert!(
+                matches!(parse_plan(answer), Err(TaskError::InvalidPlan(_))),
+                "{answer}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_sampling_request_describes_task() {
+        let detail = TaskDetail {
+            task: Task {
+                id: 7,
+                title: "Improve onboarding".to_string(),
+                description: Some("New users get lost".to_string()),
+                dod: None,
+                status: Status::Pending,
+                manual_order: 10.0,
+                created_at: chrono::Utc::now(),
+                started_at: None,
+                completed_at: None,
+                last_touched_at: chrono::Utc::now(),
+            },
+            dependencies: vec![DependencyInfo {
+                id: 3,
+                title: "User research".to_string(),
+                status: Status::Completed,
+            }],
+            dependents: vec![],
+            artifacts: vec![],
+        };
+
+        let request = sampling_request(&detail);
+        assert!(request.system_prompt.unwrap().contains("\"subtasks\""));
+        let text = request.messages[0].content.clone().into_vec()[0]
+            .as_text()
+            .unwrap()
+            .text
+            .clone();
+        assert!(text.contains("#7: Improve onboarding"));
+        assert!(text.contains("New users get lost"));
+        assert!(text.contains("#3 User research"));
+    }
+}
@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use thiserror::Error;
 
 /// All possible errors in the task tracker
@@ -82,11 +83,39 @@ pub enum TaskError {
     #[error("Cannot depend on self")]
     SelfDependency,
 
+    #[error("Task #{id} was modified at {actual} (expected {expected}). Reload it and retry.")]
+    StaleWrite {
+        id: i64,
+        expected: DateTime<Utc>,
+        actual: DateTime<Utc>,
+    },
+
+    #[error("Template '{0}' not found in .tt/templates")]
+    TemplateNotFound(String),
+
+    #[error("Invalid template name '{0}': use a file name without path separators or '..'")]
+    InvalidTemplateName(String),
+
+    #[error("Invalid template: {0}")]
+    InvalidTemplate(String),
+
+    #[error("Invalid decomposition plan: {0}")]
+    InvalidPlan(String),
+
+    #[error("Invalid resource URI: {0}")]
+    InvalidResource(String),
+
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
 
     #[error("MCP error: {0}")]
     Mcp(String),
+
+    #[error("Cannot locate the global registry: neither XDG_DATA_HOME nor HOME is set")]
+    NoDataDir,
+
+    #[error("{0} is not a tt project (no tt.db found)")]
+    NotAProject(String),
 }
 
 /// Summary of a blocked task for error messages
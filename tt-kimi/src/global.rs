@@ -0,0 +1,304 @@
+use crate::core::TaskTracker;
+use crate::db::DB_FILE;
+use crate::error::{Result, TaskError};
+use crate::models::{NextTaskResult, Status, Task, TaskDetail};
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// Registry location under the user's data directory
+pub const REGISTRY_FILE: &str = "tt/projects";
+
+/// The project roots `tt global` knows about, one absolute path per line
+#[derive(Debug, Clone)]
+pub struct Registry {
+    path: PathBuf,
+    roots: Vec<PathBuf>,
+}
+
+impl Registry {
+    /// `$XDG_DATA_HOME/tt/projects`, falling back to `~/.local/share/tt/projects`
+    pub fn default_path() -> Result<PathBuf> {
+        let data_dir = match env::var_os("XDG_DATA_HOME").filter(|dir| !dir.is_empty()) {
+            Some(dir) => PathBuf::from(dir),
+            None => {
+                let home = env::var_os("HOME").ok_or(TaskError::NoDataDir)?;
+                PathBuf::from(home).join(".local/share")
+            }
+        };
+        Ok(data_dir.join(REGISTRY_FILE))
+    }
+
+    /// Load the registry at the default location
+    pub fn open_default() -> Result<Self> {
+        Self::load(Self::default_path()?)
+    }
+
+    /// Load a registry file; a missing file is an empty registry
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let roots = match fs::read_to_string(&path) {
+            Ok(contents) => contents
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(PathBuf::from)
+                .collect(),
+            Err(e) if e.kind() == ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Registry { path, roots })
+    }
+
+    /// Registered project roots, in the order they were added
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    /// Register a project root, returning its canonical path
+    pub fn add(&mut self, root: &Path) -> Result<PathBuf> {
+        let root = root
+            .canonicalize()
+            .map_err(|_| TaskError::NotAProject(root.display().to_string()))?;
+        if !root.join(DB_FILE).is_file() {
+            return Err(TaskError::NotAProject(root.display().to_string()));
+        }
+
+        if !self.roots.contains(&root) {
+            self.roots.push(root.clone());
+            self.save()?;
+        }
+        Ok(root)
+    }
+
+    /// The path a root is registered under: canonical, or as given if the
+    /// project is already gone
+    pub fn resolve(root: &Path) -> PathBuf {
+        root.canonicalize().unwrap_or_else(|_| root.to_path_buf())
+    }
+
+    /// Forget a project root. Returns false if it wasn't registered.
+    pub fn remove(&mut self, root: &Path) -> Result<bool> {
+        let root = Self::resolve(root);
+        let before = self.roots.len();
+        self.roots.retain(|r| *r != root);
+        if self.roots.len() == before {
+            return Ok(false);
+        }
+        self.save()?;
+        Ok(true)
+    }
+
+    fn save(&self) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let contents: String = self
+            .roots
+            .iter()
+            .map(|root| format!("{}\n", root.display()))
+            .collect();
+        fs::write(&self.path, contents)?;
+        Ok(())
+    }
+}
+
+/// Where one project stands
+#[derive(Debug, Clone)]
+pub struct ProjectStatus {
+    pub root: PathBuf,
+    pub target: Option<Task>,
+    pub active: Option<Task>,
+    /// Incomplete tasks toward the target, or in the whole project if none is set
+    pub remaining: usize,
+}
+
+/// The task `tt global next` suggests
+#[derive(Debug, Clone)]
+pub struct GlobalNext {
+    pub root: PathBuf,
+    pub task: TaskDetail,
+    /// The task is already in progress
+    pub resume: bool,
+}
+
+/// Open a project's database read-only
+fn open_project(root: &Path) -> Result<TaskTracker> {
+    let path = root.join(DB_FILE);
+    if !path.is_file() {
+        return Err(TaskError::NotAProject(root.display().to_string()));
+    }
+    let tracker = TaskTracker::open_read_only(path)?;
+    if !tracker.is_initialized()? {
+        return Err(TaskError::NotInitialized);
+    }
+    Ok(tracker)
+}
+
+/// Summarize one project
+pub fn project_status(root: &Path) -> Result<ProjectStatus> {
+    let tracker = open_project(root)?;
+
+    let target = match tracker.get_target()? {
+        Some(id) => Some(tracker.get_task(id)?.task),
+        None => None,
+    };
+    let active = match tracker.get_current_task() {
+        Ok(detail) => Some(detail.task),
+        Err(TaskError::NoActiveTask) => None,
+        Err(e) => return Err(e),
+    };
+    let tasks = match &target {
+        Some(target) => tracker.get_target_tasks(target.id)?,
+        None => tracker.get_all_tasks()?,
+    };
+    let remaining = tasks
+        .iter()
+        .filter(|t| t.status != Status::Completed)
+        .count();
+
+    Ok(ProjectStatus {
+        root: root.to_path_buf(),
+        target,
+        active,
+        remaining,
+    })
+}
+
+/// Summarize every registered project, keeping per-project failures
+pub fn status(roots: &[PathBuf]) -> Vec<(PathBuf, Result<ProjectStatus>)> {
+    roots
+        .iter()
+        .map(|root| (root.clone(), project_status(root)))
+        .collect()
+}
+
+/// Pick one task across projects.
+///
+/// A task already in progress wins, so work gets finished before more is
+/// started. Otherwise each project offers its next ready task (toward its
+/// target, or overall if it has none) and the one untouched the longest is
+/// chosen, so no project starves. Projects that can't be read are skipped.
+pub fn next(roots: &[PathBuf]) -> Option<GlobalNext> {
+    let mut best: Option<GlobalNext> = None;
+
+    for root in roots {
+        let Ok(tracker) = open_project(root) else {
+            continue;
+        };
+
+        if let Ok(task) = tracker.get_current_task() {
+            return Some(GlobalNext {
+                root: root.clone(),
+                task,
+                resume: true,
+            });
+        }
+
+        let all = !matches!(tracker.get_target(), Ok(Some(_)));
+        let Ok(NextTaskResult::Task { task }) = tracker.get_next_task(all) else {
+            continue;
+        };
+        if best
+            .as_ref()
+            .is_none_or(|b| task.task.last_touched_at < b.task.task.last_touched_at)
+        {
+            best = Some(GlobalNext {
+                root: root.clone(),
+                task,
+                resume: false,
+            });
+        }
+    }
+
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn project(dir: &TempDir, name: &str) -> (PathBuf, TaskTracker) {
+        let root = dir.path().join(name);
+        fs::create_dir_all(&root).unwrap();
+        let tracker = TaskTracker::open_at(root.join(DB_FILE)).unwrap();
+        tracker.init().unwrap();
+        (root.canonicalize().unwrap(), tracker)
+    }
+
+    #[test]
+    fn test_registry_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let (root, _tracker) = project(&dir, "a");
+        let path = dir.path().join("data").join(REGISTRY_FILE);
+
+        let mut registry = Registry::load(&path).unwrap();
+        assert!(registry.roots().is_empty());
+        assert_eq!(registry.add(&root).unwrap(), root);
+        registry.add(&root).unwrap();
+        assert!(matches!(
+            registry.add(dir.path()),
+            Err(TaskError::NotAProject(_))
+        ));
+
+        let mut registry = Registry::load(&path).unwrap();
+        assert_eq!(registry.roots(), std::slice::from_ref(&root));
+        assert_eq!(Registry::resolve(&root.join("../a")), root);
+        assert!(registry.remove(&root.join("../a")).unwrap());
+        assert!(!registry.remove(&root).unwrap());
+        assert!(Registry::load(&path).unwrap().roots().is_empty());
+    }
+
+    #[test]
+    fn test_status_and_next_across_projects() {
+        let dir = TempDir::new().unwrap();
+        let (a, tracker_a) = project(&dir, "a");
+        let (b, tracker_b) = project(&dir, "b");
+
+        let older = tracker_b
+            .create_task("Older", None, None, None, None)
+            .unwrap();
+        let first = tracker_a
+            .create_task("First", None, None, None, None)
+            .unwrap();
+        let second = tracker_a
+            .create_task("Second", None, None, None, None)
+            .unwrap();
+        tracker_a.add_dependency(second.id, first.id).unwrap();
+        tracker_a.set_target(second.id).unwrap();
+
+        let roots = vec![a.clone(), b.clone(), dir.path().join("missing")];
+        let statuses = status(&roots);
+        let a_status = statuses[0].1.as_ref().unwrap();
+        assert_eq!(a_status.target.as_ref().unwrap().id, second.id);
+        assert!(a_status.active.is_none());
+        assert_eq!(a_status.remaining, 2);
+        assert_eq!(statuses[1].1.as_ref().unwrap().remaining, 1);
+        assert!(matches!(statuses[2].1, Err(TaskError::NotAProject(_))));
+
+        // The task waiting longest wins
+        let pick = next(&roots).unwrap();
+        assert_eq!((pick.root, pick.task.task.id), (b.clone(), older.id));
+        assert!(!pick.resume);
+
+        // Work in progress anywhere comes first
+        tracker_a.start_task(first.id).unwrap();
+        let pick = next(&roots).unwrap();
+        assert_eq!((pick.root, pick.task.task.id), (a, first.id));
+        assert!(pick.resume);
+    }
+
+    #[test]
+    fn test_open_project_is_read_only() {
+        let dir = TempDir::new().unwrap();
+        let (root, _tracker) = project(&dir, "a");
+
+        let tracker = open_project(&root).unwrap();
+        assert!(matches!(
+            tracker.create_task("Nope", None, None, None, None),
+            Err(TaskError::Db(_))
+        ));
+    }
+}
@@ -77,13 +77,13 @@ pub fn topological_sort(tasks_with_deps: Vec<(Task, Vec<i64>)>) -> Result<Topolo
     // Seed min-heap with in-degree 0 tasks
     let mut heap: BinaryHeap<HeapTask> = BinaryHeap::new();
     for (task_id, degree) in &in_degree {
-        if *degree == 0 {
-            if let Some(task) = task_map.get(task_id) {
-                heap.push(HeapTask {
-                    manual_order: task.manual_order,
-                    task_id: *task_id,
-                });
-            }
+        if *degree == 0
+            && let Some(task) = task_map.get(task_id)
+        {
+            heap.push(HeapTask {
+                manual_order: task.manual_order,
+                task_id: *task_id,
+            });
         }
     }
 
@@ -98,13 +98,13 @@ pub fn topological_sort(tasks_with_deps: Vec<(Task, Vec<i64>)>) -> Result<Topolo
             for &dependent_id in dependents {
                 if let Some(degree) = in_degree.get_mut(&dependent_id) {
                     *degree -= 1;
-                    if *degree == 0 {
-                        if let Some(dep_task) = task_map.get(&dependent_id) {
-                            heap.push(HeapTask {
-                                manual_order: dep_task.manual_order,
-                                task_id: dependent_id,
-                            });
-                        }
+                    if *degree == 0
+                        && let Some(dep_task) = task_map.get(&dependent_id)
+                    {
+                        heap.push(HeapTask {
+                            manual_order: dep_task.manual_order,
+                            task_id: dependent_id,
+                        });
                     }
                 }
             }
@@ -138,15 +138,15 @@ fn detect_order_conflicts(
     for task in sorted_tasks {
         if let Some(deps) = deps_map.get(&task.id) {
             for &dep_id in deps {
-                if let Some(&dep_order) = order_map.get(&dep_id) {
-                    if task.manual_order < dep_order {
-                        conflicts.push(OrderConflict {
-                            task_id: task.id,
-                            task_order: task.manual_order,
-                            dep_id,
-                            dep_order,
-                        });
-                    }
+                if let Some(&dep_order) = order_map.get(&dep_id)
+                    && task.manual_order < dep_order
+                {
+                    conflicts.push(OrderConflict {
+                        task_id: task.id,
+                        task_order: task.manual_order,
+                        dep_id,
+                        dep_order,
+                    });
                 }
             }
         }
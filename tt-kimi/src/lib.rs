@@ -2,10 +2,16 @@ pub mod cli;
 pub mod cli_handlers;
 pub mod core;
 pub mod db;
+pub mod decompose;
 pub mod error;
+pub mod global;
 pub mod graph;
 pub mod mcp;
+pub mod mcp_http;
+pub mod mcp_resources;
 pub mod models;
+pub mod store;
+pub mod templates;
 
 pub use error::{Result, TaskError};
 pub use models::*;
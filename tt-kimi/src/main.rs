@@ -1,8 +1,9 @@
 use clap::Parser;
 use std::process;
-use tt::cli::{Cli, Commands};
+use tt::cli::{Cli, Commands, GlobalCommands};
 use tt::cli_handlers;
 use tt::mcp::run_mcp_server;
+use tt::mcp_http::run_http_server;
 
 #[tokio::main]
 async fn main() {
@@ -21,7 +22,15 @@ async fn main() {
             dod,
             after,
             before,
-        } => cli_handlers::handle_add(&title, desc.as_deref(), dod.as_deref(), after, before),
+            template,
+            parent,
+            vars,
+        } => match template {
+            Some(template) => cli_handlers::handle_add_template(&template, &title, parent, vars),
+            None => {
+                cli_handlers::handle_add(&title, desc.as_deref(), dod.as_deref(), after, before)
+            }
+        },
         Commands::Edit {
             id,
             title,
@@ -29,6 +38,7 @@ async fn main() {
             no_desc,
             dod,
             no_dod,
+            expect_touched,
         } => cli_handlers::handle_edit(
             id,
             title.as_deref(),
@@ -36,6 +46,7 @@ async fn main() {
             no_desc,
             dod.as_deref(),
             no_dod,
+            expect_touched,
         ),
         Commands::Show { id } => cli_handlers::handle_show(id),
         Commands::List { all } => cli_handlers::handle_list(all),
@@ -53,8 +64,19 @@ async fn main() {
         Commands::Artifacts { task } => cli_handlers::handle_artifacts(task),
         Commands::Reorder { id, after, before } => cli_handlers::handle_reorder(id, after, before),
         Commands::Reindex => cli_handlers::handle_reindex(),
-        Commands::Mcp => {
-            if let Err(e) = run_mcp_server().await {
+        Commands::Global { command } => match command {
+            GlobalCommands::Add { path } => cli_handlers::handle_global_add(path.as_deref()),
+            GlobalCommands::Remove { path } => cli_handlers::handle_global_remove(path.as_deref()),
+            GlobalCommands::List => cli_handlers::handle_global_list(),
+            GlobalCommands::Status => cli_handlers::handle_global_status(),
+            GlobalCommands::Next => cli_handlers::handle_global_next(),
+        },
+        Commands::Mcp { http } => {
+            let result = match http {
+                Some(addr) => run_http_server(&addr).await,
+                None => run_mcp_server().await,
+            };
+            if let Err(e) = result {
                 eprintln!("MCP server error: {e}");
                 process::exit(1);
             }
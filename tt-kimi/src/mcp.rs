@@ -1,19 +1,41 @@
 use crate::core::TaskTracker;
+use crate::decompose;
 use crate::error::TaskError;
-use crate::models::NextTaskResult;
+use crate::mcp_resources::{self, ACTIVE_TASK_TOOLS, POLL_INTERVAL, ResourceUri, WatchState};
+use crate::models::{
+    Artifact, Decomposition, NextTaskResult, OrderConflict, Task, TaskDetail, TemplateInstance,
+};
+use crate::templates::{self, Template, TemplateTask};
+use chrono::{DateTime, Utc};
 use rmcp::{
-    ErrorData as McpError, ServerHandler, ServiceExt, handler::server::tool::ToolRouter,
-    handler::server::wrapper::Parameters, model::*, schemars, tool, tool_handler, tool_router,
+    ErrorData as McpError, Peer, RoleServer, ServerHandler, ServiceExt,
+    handler::server::common::schema_for_output,
+    handler::server::tool::{ToolCallContext, ToolRouter},
+    handler::server::wrapper::Parameters,
+    model::*,
+    schemars,
+    service::{NotificationContext, RequestContext},
+    tool, tool_router,
     transport::stdio,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use tokio::sync::Mutex;
 
 /// Task tracker MCP server
+///
+/// Clones share the same client session. Use [`TaskTrackerMcp::session`] to
+/// serve another client from the same tracker.
 #[derive(Clone)]
 pub struct TaskTrackerMcp {
     tracker: Arc<Mutex<TaskTracker>>,
+    /// Tool calls made through any session, so that every session's watcher
+    /// sees writes that do not bump `data_version`
+    writes: Arc<AtomicU64>,
+    watch: Arc<Mutex<WatchState>>,
     tool_router: ToolRouter<Self>,
 }
 
@@ -45,6 +67,10 @@ pub struct EditTaskInput {
     pub description: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub dod: Option<String>,
+    /// The task's `last_touched_at` as last read; the edit fails with
+    /// StaleWrite if the task has changed since
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected_last_touched_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
@@ -85,9 +111,72 @@ pub struct GetArtifactsInput {
     pub task_id: Option<i64>,
 }
 
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct CreateFromTemplateInput {
+    /// Template name, e.g. "rpi" for .tt/templates/rpi.toml
+    pub template: String,
+    /// Value for the {{name}} placeholder
+    pub name: String,
+    /// Task that should depend on the template's final tasks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<i64>,
+    /// Extra placeholder values
+    #[serde(default)]
+    pub vars: HashMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct DecomposeTaskInput {
+    pub id: i64,
+    /// Create the subtasks. By default the plan is only proposed.
+    #[serde(default)]
+    pub apply: bool,
+    /// A previously proposed plan to use instead of asking the model again
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subtasks: Option<Vec<TemplateTask>>,
+}
+
+// Output types for tools that do not return a model directly
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct MessageOutput {
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct TemplateListOutput {
+    pub templates: Vec<String>,
+}
+
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct TaskListOutput {
+    pub tasks: Vec<TaskDetail>,
+    pub order_conflicts: Vec<OrderConflict>,
+}
+
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct TargetOutput {
+    pub target_id: Option<i64>,
+}
+
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct ReindexOutput {
+    pub reindexed_count: usize,
+}
+
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct DecomposeOutput {
+    pub task_id: i64,
+    /// True if the plan was only proposed and nothing was created
+    pub dry_run: bool,
+    pub subtasks: Vec<TemplateTask>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<Decomposition>,
+}
+
 // Response type
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, schemars::JsonSchema)]
 pub struct McpResponse<T: Serialize> {
+    #[schemars(extend("enum" = ["ok", "error"]))]
     pub status: &'static str,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub data: Option<T>,
@@ -117,10 +206,30 @@ impl<T: Serialize> McpResponse<T> {
     }
 }
 
+/// `get_next_task` data: the task itself, flat as SPEC's "Next task object",
+/// or a `TargetReached` / `AllBlocked` result tagged with its `type`
+#[derive(Serialize, schemars::JsonSchema)]
+#[serde(untagged)]
+enum NextTaskReply {
+    Task(TaskDetail),
+    Other(NextTaskResult),
+}
+
+/// `outputSchema` of a tool whose response `data` is a `T`
+fn output_schema<T: Serialize + schemars::JsonSchema + 'static>() -> Arc<JsonObject> {
+    schema_for_output::<McpResponse<T>>().expect("tool output schema must be an object")
+}
+
+/// The response as `structuredContent`, with its JSON text as the content fallback
 fn to_json<T: Serialize>(response: McpResponse<T>) -> Result<CallToolResult, McpError> {
-    let json = serde_json::to_string(&response)
+    let is_error = response.status == "error";
+    let value = serde_json::to_value(&response)
         .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-    Ok(CallToolResult::success(vec![Content::text(json)]))
+    Ok(if is_error {
+        CallToolResult::structured_error(value)
+    } else {
+        CallToolResult::structured(value)
+    })
 }
 
 fn error_to_response(e: TaskError) -> McpResponse<serde_json::Value> {
@@ -129,60 +238,132 @@ fn error_to_response(e: TaskError) -> McpResponse<serde_json::Value> {
     McpResponse::error(&error_code, &message)
 }
 
+fn resource_error(e: TaskError) -> McpError {
+    match e {
+        TaskError::TaskNotFound(_) => McpError::resource_not_found(e.to_string(), None),
+        TaskError::InvalidResource(_) => McpError::invalid_params(e.to_string(), None),
+        _ => McpError::internal_error(e.to_string(), None),
+    }
+}
+
 #[tool_router]
 impl TaskTrackerMcp {
     pub fn new() -> Result<Self, TaskError> {
-        let tracker = TaskTracker::open()?;
-        Ok(Self {
+        Ok(Self::with_tracker(TaskTracker::open()?))
+    }
+
+    pub fn with_tracker(tracker: TaskTracker) -> Self {
+        Self {
             tracker: Arc::new(Mutex::new(tracker)),
+            writes: Arc::default(),
+            watch: Arc::new(Mutex::new(WatchState::default())),
             tool_router: Self::tool_router(),
-        })
+        }
+    }
+
+    /// A handler for a new client session sharing this server's tracker
+    pub fn session(&self) -> Self {
+        Self {
+            tracker: self.tracker.clone(),
+            writes: self.writes.clone(),
+            watch: Arc::new(Mutex::new(WatchState::default())),
+            tool_router: self.tool_router.clone(),
+        }
+    }
+
+    /// Poll for changes and notify the client until the connection closes
+    async fn watch_changes(self, peer: Peer<RoleServer>) {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        let mut seen_writes = 0;
+        while !peer.is_transport_closed() {
+            interval.tick().await;
+
+            let changes = {
+                let mut watch = self.watch.lock().await;
+                let writes = self.writes.load(Ordering::Relaxed);
+                if writes != seen_writes {
+                    seen_writes = writes;
+                    watch.mark_dirty();
+                }
+                let tracker = self.tracker.lock().await;
+                watch.poll(&tracker)
+            };
+            let changes = match changes {
+                Ok(changes) => changes,
+                Err(e) => {
+                    tracing::warn!("resource watcher: {e}");
+                    continue;
+                }
+            };
+
+            for uri in changes.resources {
+                let param = ResourceUpdatedNotificationParam { uri };
+                if peer.notify_resource_updated(param).await.is_err() {
+                    return;
+                }
+            }
+            if changes.tools && peer.notify_tool_list_changed().await.is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Ask the client's model to propose subtasks for task `id`
+    async fn propose_decomposition(
+        &self,
+        peer: &Peer<RoleServer>,
+        id: i64,
+    ) -> Result<Template, TaskError> {
+        let detail = self.tracker.lock().await.get_task(id)?;
+
+        let supports_sampling = peer
+            .peer_info()
+            .is_some_and(|info| info.capabilities.sampling.is_some());
+        if !supports_sampling {
+            return Err(TaskError::Mcp(
+                "the client does not support sampling; pass subtasks instead".to_string(),
+            ));
+        }
+
+        let result = peer
+            .create_message(decompose::sampling_request(&detail))
+            .await
+            .map_err(|e| TaskError::Mcp(format!("sampling failed: {e}")))?;
+        decompose::parse_response(&result)
     }
 
     #[tool(
-        description = "Returns the next task to work on toward the current target. Call this after completing a task. If the response is TargetReached, stop working and report to the user."
+        description = "Returns the next task to work on toward the current target. Call this after completing a task. If the response is TargetReached, stop working and report to the user.",
+        output_schema = output_schema::<NextTaskReply>()
     )]
     async fn get_next_task(&self) -> Result<CallToolResult, McpError> {
         let tracker = self.tracker.lock().await;
 
-        let result = tracker.get_next_task(false);
-
-        let response = match result {
+        match tracker.get_next_task(false) {
             Ok(NextTaskResult::Task { task }) => {
-                to_json(McpResponse::success(serde_json::to_value(task).unwrap()))?
-            }
-            Ok(NextTaskResult::TargetReached { target_id }) => {
-                to_json(McpResponse::success(serde_json::json!({
-                    "type": "TargetReached",
-                    "target_id": target_id
-                })))?
+                to_json(McpResponse::success(NextTaskReply::Task(task)))
             }
-            Ok(NextTaskResult::AllBlocked { tasks }) => {
-                to_json(McpResponse::success(serde_json::json!({
-                    "type": "AllBlocked",
-                    "tasks": tasks
-                })))?
-            }
-            Err(e) => to_json(error_to_response(e))?,
-        };
-
-        Ok(response)
+            Ok(other) => to_json(McpResponse::success(NextTaskReply::Other(other))),
+            Err(e) => to_json(error_to_response(e)),
+        }
     }
 
     #[tool(
-        description = "Returns the currently active task being worked on, including its artifacts."
+        description = "Returns the currently active task being worked on, including its artifacts.",
+        output_schema = output_schema::<TaskDetail>()
     )]
     async fn get_current_task(&self) -> Result<CallToolResult, McpError> {
         let tracker = self.tracker.lock().await;
 
         match tracker.get_current_task() {
-            Ok(detail) => to_json(McpResponse::success(serde_json::to_value(detail).unwrap())),
+            Ok(detail) => to_json(McpResponse::success(detail)),
             Err(e) => to_json(error_to_response(e)),
         }
     }
 
     #[tool(
-        description = "Start working on a specific task. This will fail if another task is already active or if dependencies are not yet completed."
+        description = "Start working on a specific task. This will fail if another task is already active or if dependencies are not yet completed.",
+        output_schema = output_schema::<TaskDetail>()
     )]
     async fn start_task(
         &self,
@@ -192,37 +373,40 @@ impl TaskTrackerMcp {
         let id = params.0.id;
 
         match tracker.start_task(id) {
-            Ok(detail) => to_json(McpResponse::success(serde_json::to_value(detail).unwrap())),
+            Ok(detail) => to_json(McpResponse::success(detail)),
             Err(e) => to_json(error_to_response(e)),
         }
     }
 
     #[tool(
-        description = "Complete the currently active task. The task must have a Definition of Done set. Call this when you've finished the work."
+        description = "Complete the currently active task. The task must have a Definition of Done set. Call this when you've finished the work.",
+        output_schema = output_schema::<TaskDetail>()
     )]
     async fn complete_task(&self) -> Result<CallToolResult, McpError> {
         let tracker = self.tracker.lock().await;
 
         match tracker.complete_task() {
-            Ok(detail) => to_json(McpResponse::success(serde_json::to_value(detail).unwrap())),
+            Ok(detail) => to_json(McpResponse::success(detail)),
             Err(e) => to_json(error_to_response(e)),
         }
     }
 
     #[tool(
-        description = "Stop the currently active task and return it to pending status. Use this if you need to pause work on the current task."
+        description = "Stop the currently active task and return it to pending status. Use this if you need to pause work on the current task.",
+        output_schema = output_schema::<TaskDetail>()
     )]
     async fn stop_task(&self) -> Result<CallToolResult, McpError> {
         let tracker = self.tracker.lock().await;
 
         match tracker.stop_task() {
-            Ok(detail) => to_json(McpResponse::success(serde_json::to_value(detail).unwrap())),
+            Ok(detail) => to_json(McpResponse::success(detail)),
             Err(e) => to_json(error_to_response(e)),
         }
     }
 
     #[tool(
-        description = "Create a new task. If you discover during implementation that a task needs to be broken into smaller pieces, create subtasks and add dependencies."
+        description = "Create a new task. If you discover during implementation that a task needs to be broken into smaller pieces, create subtasks and add dependencies.",
+        output_schema = output_schema::<Task>()
     )]
     async fn create_task(
         &self,
@@ -238,13 +422,85 @@ impl TaskTrackerMcp {
             p.after_id,
             p.before_id,
         ) {
-            Ok(task) => to_json(McpResponse::success(serde_json::to_value(task).unwrap())),
+            Ok(task) => to_json(McpResponse::success(task)),
+            Err(e) => to_json(error_to_response(e)),
+        }
+    }
+
+    #[tool(
+        description = "Create a group of tasks with their internal dependencies from a template in .tt/templates (e.g. 'rpi' creates research → plan → implement). Optionally wires the result as dependencies of parent_id. Either all tasks are created or none.",
+        output_schema = output_schema::<TemplateInstance>()
+    )]
+    async fn create_from_template(
+        &self,
+        params: Parameters<CreateFromTemplateInput>,
+    ) -> Result<CallToolResult, McpError> {
+        let tracker = self.tracker.lock().await;
+        let p = params.0;
+
+        let mut vars = p.vars;
+        vars.insert("name".to_string(), p.name);
+
+        let result = Template::load(Path::new(templates::TEMPLATES_DIR), &p.template)
+            .and_then(|t| tracker.instantiate_template(&p.template, &t, &vars, p.parent_id));
+
+        match result {
+            Ok(instance) => to_json(McpResponse::success(instance)),
+            Err(e) => to_json(error_to_response(e)),
+        }
+    }
+
+    #[tool(
+        description = "Break a vague task into subtasks proposed by your model through sampling. By default this is a dry run that only returns the plan: show it to the user, then call again with apply=true and the returned subtasks to create them. The task will depend on the plan's final subtasks.",
+        output_schema = output_schema::<DecomposeOutput>()
+    )]
+    async fn decompose_task(
+        &self,
+        peer: Peer<RoleServer>,
+        params: Parameters<DecomposeTaskInput>,
+    ) -> Result<CallToolResult, McpError> {
+        let p = params.0;
+
+        let plan = match p.subtasks {
+            Some(subtasks) => decompose::plan_from_subtasks(subtasks),
+            None => self.propose_decomposition(&peer, p.id).await,
+        };
+        let plan = match plan {
+            Ok(plan) => plan,
+            Err(e) => return to_json(error_to_response(e)),
+        };
+
+        let created = if p.apply {
+            match self.tracker.lock().await.decompose_task(p.id, &plan) {
+                Ok(decomposition) => Some(decomposition),
+                Err(e) => return to_json(error_to_response(e)),
+            }
+        } else {
+            None
+        };
+
+        to_json(McpResponse::success(DecomposeOutput {
+            task_id: p.id,
+            dry_run: !p.apply,
+            subtasks: plan.tasks,
+            created,
+        }))
+    }
+
+    #[tool(
+        description = "List the task templates available to create_from_template.",
+        output_schema = output_schema::<TemplateListOutput>()
+    )]
+    async fn list_templates(&self) -> Result<CallToolResult, McpError> {
+        match templates::list(Path::new(templates::TEMPLATES_DIR)) {
+            Ok(templates) => to_json(McpResponse::success(TemplateListOutput { templates })),
             Err(e) => to_json(error_to_response(e)),
         }
     }
 
     #[tool(
-        description = "Edit an existing task's title, description, or Definition of Done. Only the fields you provide will be changed."
+        description = "Edit an existing task's title, description, or Definition of Done. Only the fields you provide will be changed.",
+        output_schema = output_schema::<TaskDetail>()
     )]
     async fn edit_task(
         &self,
@@ -256,27 +512,35 @@ impl TaskTrackerMcp {
         let desc = p.description.as_ref().map(|d| Some(d.as_str()));
         let dod = p.dod.as_ref().map(|d| Some(d.as_str()));
 
-        match tracker.update_task(p.id, p.title.as_deref(), desc, dod) {
-            Ok(detail) => to_json(McpResponse::success(serde_json::to_value(detail).unwrap())),
+        match tracker.update_task(
+            p.id,
+            p.title.as_deref(),
+            desc,
+            dod,
+            p.expected_last_touched_at,
+        ) {
+            Ok(detail) => to_json(McpResponse::success(detail)),
             Err(e) => to_json(error_to_response(e)),
         }
     }
 
     #[tool(
-        description = "Show full details of a specific task including its dependencies, dependents, and artifacts."
+        description = "Show full details of a specific task including its dependencies, dependents, and artifacts.",
+        output_schema = output_schema::<TaskDetail>()
     )]
     async fn show_task(&self, params: Parameters<TaskIdInput>) -> Result<CallToolResult, McpError> {
         let tracker = self.tracker.lock().await;
         let id = params.0.id;
 
         match tracker.get_task(id) {
-            Ok(detail) => to_json(McpResponse::success(serde_json::to_value(detail).unwrap())),
+            Ok(detail) => to_json(McpResponse::success(detail)),
             Err(e) => to_json(error_to_response(e)),
         }
     }
 
     #[tool(
-        description = "List all tasks. By default shows only tasks in the target subgraph. Use all=true to see every task in the system."
+        description = "List all tasks. By default shows only tasks in the target subgraph. Use all=true to see every task in the system.",
+        output_schema = output_schema::<TaskListOutput>()
     )]
     async fn list_tasks(
         &self,
@@ -286,16 +550,17 @@ impl TaskTrackerMcp {
         let all = params.0.all;
 
         match tracker.list_tasks(all) {
-            Ok((tasks, conflicts)) => to_json(McpResponse::success(serde_json::json!({
-                "tasks": tasks,
-                "order_conflicts": conflicts
-            }))),
+            Ok((tasks, order_conflicts)) => to_json(McpResponse::success(TaskListOutput {
+                tasks,
+                order_conflicts,
+            })),
             Err(e) => to_json(error_to_response(e)),
         }
     }
 
     #[tool(
-        description = "Add a dependency: task_id will depend on depends_on being completed first. This will fail if it would create a cycle in the dependency graph."
+        description = "Add a dependency: task_id will depend on depends_on being completed first. This will fail if it would create a cycle in the dependency graph.",
+        output_schema = output_schema::<MessageOutput>()
     )]
     async fn add_dependency(
         &self,
@@ -305,14 +570,17 @@ impl TaskTrackerMcp {
         let p = params.0;
 
         match tracker.add_dependency(p.task_id, p.depends_on) {
-            Ok(()) => to_json(McpResponse::success(serde_json::json!({
-                "message": format!("Task #{} now depends on task #{}", p.task_id, p.depends_on)
-            }))),
+            Ok(()) => to_json(McpResponse::success(MessageOutput {
+                message: format!("Task #{} now depends on task #{}", p.task_id, p.depends_on),
+            })),
             Err(e) => to_json(error_to_response(e)),
         }
     }
 
-    #[tool(description = "Remove a dependency relationship between two tasks.")]
+    #[tool(
+        description = "Remove a dependency relationship between two tasks.",
+        output_schema = output_schema::<MessageOutput>()
+    )]
     async fn remove_dependency(
         &self,
         params: Parameters<DependencyInput>,
@@ -321,15 +589,19 @@ impl TaskTrackerMcp {
         let p = params.0;
 
         match tracker.remove_dependency(p.task_id, p.depends_on) {
-            Ok(()) => to_json(McpResponse::success(serde_json::json!({
-                "message": format!("Removed dependency: #{} no longer depends on #{}", p.task_id, p.depends_on)
-            }))),
+            Ok(()) => to_json(McpResponse::success(MessageOutput {
+                message: format!(
+                    "Removed dependency: #{} no longer depends on #{}",
+                    p.task_id, p.depends_on
+                ),
+            })),
             Err(e) => to_json(error_to_response(e)),
         }
     }
 
     #[tool(
-        description = "Block a task, preventing it from being started. Use this when a task is waiting on external factors or has issues that need resolution."
+        description = "Block a task, preventing it from being started. Use this when a task is waiting on external factors or has issues that need resolution.",
+        output_schema = output_schema::<TaskDetail>()
     )]
     async fn block_task(
         &self,
@@ -339,12 +611,15 @@ impl TaskTrackerMcp {
         let id = params.0.id;
 
         match tracker.block_task(id) {
-            Ok(detail) => to_json(McpResponse::success(serde_json::to_value(detail).unwrap())),
+            Ok(detail) => to_json(McpResponse::success(detail)),
             Err(e) => to_json(error_to_response(e)),
         }
     }
 
-    #[tool(description = "Unblock a task, returning it to pending status so it can be worked on.")]
+    #[tool(
+        description = "Unblock a task, returning it to pending status so it can be worked on.",
+        output_schema = output_schema::<TaskDetail>()
+    )]
     async fn unblock_task(
         &self,
         params: Parameters<TaskIdInput>,
@@ -353,13 +628,14 @@ impl TaskTrackerMcp {
         let id = params.0.id;
 
         match tracker.unblock_task(id) {
-            Ok(detail) => to_json(McpResponse::success(serde_json::to_value(detail).unwrap())),
+            Ok(detail) => to_json(McpResponse::success(detail)),
             Err(e) => to_json(error_to_response(e)),
         }
     }
 
     #[tool(
-        description = "Records a file you have created as an artifact of the current task. Create the file first, then call this. Use descriptive names like 'research', 'plan', 'implementation-notes', 'test-report'."
+        description = "Records a file you have created as an artifact of the current task. Create the file first, then call this. Use descriptive names like 'research', 'plan', 'implementation-notes', 'test-report'.",
+        output_schema = output_schema::<Artifact>()
     )]
     async fn log_artifact(
         &self,
@@ -369,15 +645,14 @@ impl TaskTrackerMcp {
         let p = params.0;
 
         match tracker.log_artifact(&p.name, &p.file_path) {
-            Ok(artifact) => to_json(McpResponse::success(
-                serde_json::to_value(artifact).unwrap(),
-            )),
+            Ok(artifact) => to_json(McpResponse::success(artifact)),
             Err(e) => to_json(error_to_response(e)),
         }
     }
 
     #[tool(
-        description = "Get all artifacts for a specific task, or for the currently active task if no task_id is provided."
+        description = "Get all artifacts for a specific task, or for the currently active task if no task_id is provided.",
+        output_schema = output_schema::<Vec<Artifact>>()
     )]
     async fn get_artifacts(
         &self,
@@ -386,15 +661,14 @@ impl TaskTrackerMcp {
         let tracker = self.tracker.lock().await;
 
         match tracker.get_artifacts(params.0.task_id) {
-            Ok(artifacts) => to_json(McpResponse::success(
-                serde_json::to_value(artifacts).unwrap(),
-            )),
+            Ok(artifacts) => to_json(McpResponse::success(artifacts)),
             Err(e) => to_json(error_to_response(e)),
         }
     }
 
     #[tool(
-        description = "Set the target task. When a target is set, get_next_task and list_tasks will only consider the subgraph of tasks that are transitive dependencies of the target."
+        description = "Set the target task. When a target is set, get_next_task and list_tasks will only consider the subgraph of tasks that are transitive dependencies of the target.",
+        output_schema = output_schema::<MessageOutput>()
     )]
     async fn set_target(
         &self,
@@ -404,30 +678,29 @@ impl TaskTrackerMcp {
         let id = params.0.id;
 
         match tracker.set_target(id) {
-            Ok(()) => to_json(McpResponse::success(serde_json::json!({
-                "message": format!("Set target to task #{}", id)
-            }))),
+            Ok(()) => to_json(McpResponse::success(MessageOutput {
+                message: format!("Set target to task #{}", id),
+            })),
             Err(e) => to_json(error_to_response(e)),
         }
     }
 
-    #[tool(description = "Get the current target task ID, if one is set.")]
+    #[tool(
+        description = "Get the current target task ID, if one is set.",
+        output_schema = output_schema::<TargetOutput>()
+    )]
     async fn get_target(&self) -> Result<CallToolResult, McpError> {
         let tracker = self.tracker.lock().await;
 
         match tracker.get_target() {
-            Ok(Some(id)) => to_json(McpResponse::success(serde_json::json!({
-                "target_id": id
-            }))),
-            Ok(None) => to_json(McpResponse::success(serde_json::json!({
-                "target_id": None::<i64>
-            }))),
+            Ok(target_id) => to_json(McpResponse::success(TargetOutput { target_id })),
             Err(e) => to_json(error_to_response(e)),
         }
     }
 
     #[tool(
-        description = "Reorder a task by specifying its new position. Provide either after_id, before_id, or both."
+        description = "Reorder a task by specifying its new position. Provide either after_id, before_id, or both.",
+        output_schema = output_schema::<Task>()
     )]
     async fn reorder_task(
         &self,
@@ -437,27 +710,27 @@ impl TaskTrackerMcp {
         let p = params.0;
 
         match tracker.reorder_task(p.id, p.after_id, p.before_id) {
-            Ok(task) => to_json(McpResponse::success(serde_json::to_value(task).unwrap())),
+            Ok(task) => to_json(McpResponse::success(task)),
             Err(e) => to_json(error_to_response(e)),
         }
     }
 
     #[tool(
-        description = "Reindex all task orders to clean integers (10.0, 20.0, 30.0, etc.) preserving current sorted order."
+        description = "Reindex all task orders to clean integers (10.0, 20.0, 30.0, etc.) preserving current sorted order.",
+        output_schema = output_schema::<ReindexOutput>()
     )]
     async fn reindex(&self) -> Result<CallToolResult, McpError> {
         let tracker = self.tracker.lock().await;
 
         match tracker.reindex() {
-            Ok(tasks) => to_json(McpResponse::success(serde_json::json!({
-                "reindexed_count": tasks.len()
-            }))),
+            Ok(tasks) => to_json(McpResponse::success(ReindexOutput {
+                reindexed_count: tasks.len(),
+            })),
             Err(e) => to_json(error_to_response(e)),
         }
     }
 }
 
-#[tool_handler]
 impl ServerHandler for TaskTrackerMcp {
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
@@ -466,10 +739,127 @@ impl ServerHandler for TaskTrackerMcp {
                  Key workflow: 1) Set a target with set_target, 2) Call get_next_task to find what to work on, 3) Call start_task to begin work, \
                  4) Create artifacts and log them with log_artifact, 5) Call complete_task when done, 6) Repeat from step 2.".to_string()
             ),
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            capabilities: ServerCapabilities::builder()
+                .enable_tools()
+                .enable_tool_list_changed()
+                .enable_resources()
+                .enable_resources_subscribe()
+                .build(),
             ..Default::default()
         }
     }
+
+    async fn on_initialized(&self, context: NotificationContext<RoleServer>) {
+        tokio::spawn(self.clone().watch_changes(context.peer));
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let tcc = ToolCallContext::new(self, request, context);
+        let result = self.tool_router.call(tcc).await;
+        self.writes.fetch_add(1, Ordering::Relaxed);
+        result
+    }
+
+    /// Tools that need an active task are only listed while one is in progress
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, McpError> {
+        let active = self
+            .tracker
+            .lock()
+            .await
+            .has_active_task()
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        self.watch.lock().await.tools_listed(active);
+
+        let tools = self
+            .tool_router
+            .list_all()
+            .into_iter()
+            .filter(|tool| active || !ACTIVE_TASK_TOOLS.contains(&tool.name.as_ref()))
+            .collect();
+        Ok(ListToolsResult {
+            tools,
+            meta: None,
+            next_cursor: None,
+        })
+    }
+
+    fn get_tool(&self, name: &str) -> Option<Tool> {
+        self.tool_router.get(name).cloned()
+    }
+
+    async fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, McpError> {
+        let tracker = self.tracker.lock().await;
+        let resources = mcp_resources::list(&tracker).map_err(resource_error)?;
+        Ok(ListResourcesResult {
+            resources,
+            meta: None,
+            next_cursor: None,
+        })
+    }
+
+    async fn list_resource_templates(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourceTemplatesResult, McpError> {
+        Ok(ListResourceTemplatesResult {
+            resource_templates: mcp_resources::templates(),
+            meta: None,
+            next_cursor: None,
+        })
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, McpError> {
+        let tracker = self.tracker.lock().await;
+        let text = ResourceUri::parse(&request.uri)
+            .and_then(|uri| uri.read(&tracker))
+            .map_err(resource_error)?;
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents::TextResourceContents {
+                uri: request.uri,
+                mime_type: Some("application/json".to_string()),
+                text,
+                meta: None,
+            }],
+        })
+    }
+
+    async fn subscribe(
+        &self,
+        request: SubscribeRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<(), McpError> {
+        let mut watch = self.watch.lock().await;
+        let tracker = self.tracker.lock().await;
+        watch
+            .subscribe(&tracker, &request.uri)
+            .map_err(resource_error)
+    }
+
+    async fn unsubscribe(
+        &self,
+        request: UnsubscribeRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<(), McpError> {
+        self.watch.lock().await.unsubscribe(&request.uri);
+        Ok(())
+    }
 }
 
 pub async fn run_mcp_server() -> Result<(), Box<dyn std::error::Error>> {
@@ -485,3 +875,274 @@ pub async fn run_mcp_server() -> Result<(), Box<dyn std::error::Error>> {
     service.waiting().await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::{ClientHandler, service::RoleClient};
+    use tempfile::TempDir;
+
+    fn setup() -> (TaskTrackerMcp, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let tracker = TaskTracker::open_at(temp_dir.path().join("test.db")).unwrap();
+        tracker.init().unwrap();
+        (TaskTrackerMcp::with_tracker(tracker), temp_dir)
+    }
+
+    fn structured(result: &CallToolResult) -> &serde_json::Value {
+        result.structured_content.as_ref().unwrap()
+    }
+
+    #[test]
+    fn test_every_tool_declares_output_schema() {
+        for tool in TaskTrackerMcp::tool_router().list_all() {
+            let schema = tool
+                .output_schema
+                .unwrap_or_else(|| panic!("{} has no output schema", tool.name));
+            assert_eq!(schema["type"], "object", "{}", tool.name);
+            assert_eq!(schema["required"], serde_json::json!(["status"]));
+            assert_eq!(
+                schema["properties"]["status"]["enum"],
+                serde_json::json!(["ok", "error"])
+            );
+            assert!(schema["properties"]["data"].is_object(), "{}", tool.name);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_results_are_structured() {
+        let (mcp, _temp) = setup();
+
+        let result = mcp
+            .create_task(Parameters(CreateTaskInput {
+                title: "Write docs".to_string(),
+                description: None,
+                dod: Some("Docs merged".to_string()),
+                after_id: None,
+                before_id: None,
+            }))
+            .await
+            .unwrap();
+        assert_eq!(result.is_error, Some(false));
+        assert_eq!(structured(&result)["status"], "ok");
+        assert_eq!(structured(&result)["data"]["title"], "Write docs");
+
+        // The text content carries the same JSON for clients without structured support
+        let text = result.content[0].as_text().unwrap();
+        let fallback: serde_json::Value = serde_json::from_str(&text.text).unwrap();
+        assert_eq!(&fallback, structured(&result));
+
+        mcp.set_target(Parameters(SetTargetInput { id: 1 }))
+            .await
+            .unwrap();
+        let result = mcp.get_next_task().await.unwrap();
+        assert!(structured(&result)["data"].get("type").is_none());
+        assert_eq!(structured(&result)["data"]["id"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_errors_set_is_error() {
+        let (mcp, _temp) = setup();
+
+        let result = mcp
+            .start_task(Parameters(TaskIdInput { id: 99 }))
+            .await
+            .unwrap();
+        assert_eq!(result.is_error, Some(true));
+        assert_eq!(structured(&result)["status"], "error");
+        assert!(structured(&result)["message"].is_string());
+        assert!(structured(&result).get("data").is_none());
+    }
+
+    /// Client whose model answers every sampling request with `answer`
+    #[derive(Clone)]
+    struct MockModel {
+        answer: String,
+        prompts: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl MockModel {
+        fn new(answer: &str) -> Self {
+            Self {
+                answer: answer.to_string(),
+                prompts: Arc::default(),
+            }
+        }
+    }
+
+    impl ClientHandler for MockModel {
+        async fn create_message(
+            &self,
+            params: CreateMessageRequestParams,
+            _context: RequestContext<RoleClient>,
+        ) -> Result<CreateMessageResult, McpError> {
+            for message in params.messages {
+                for content in message.content.into_vec() {
+                    if let Some(text) = content.as_text() {
+                        self.prompts.lock().unwrap().push(text.text.clone());
+                    }
+                }
+            }
+            Ok(CreateMessageResult {
+                model: "mock".to_string(),
+                stop_reason: Some(CreateMessageResult::STOP_REASON_END_TURN.to_string()),
+                message: SamplingMessage::assistant_text(self.answer.clone()),
+            })
+        }
+
+        fn get_info(&self) -> ClientInfo {
+            ClientInfo {
+                capabilities: ClientCapabilities::builder().enable_sampling().build(),
+                ..Default::default()
+            }
+        }
+    }
+
+    async fn decompose(
+        client: &rmcp::service::RunningService<RoleClient, impl ClientHandler>,
+        arguments: serde_json::Value,
+    ) -> serde_json::Value {
+        let result = client
+            .call_tool(CallToolRequestParams {
+                meta: None,
+                name: "decompose_task".into(),
+                arguments: arguments.as_object().cloned(),
+                task: None,
+            })
+            .await
+            .unwrap();
+        result.structured_content.unwrap()
+    }
+
+    /// Serve a new session over an in-memory pipe, returning the client end
+    fn serve_in_memory(mcp: &TaskTrackerMcp) -> tokio::io::DuplexStream {
+        let (server_io, client_io) = tokio::io::duplex(64 * 1024);
+        let session = mcp.session();
+        tokio::spawn(async move {
+            if let Ok(service) = session.serve(server_io).await {
+                let _ = service.waiting().await;
+            }
+        });
+        client_io
+    }
+
+    const PLAN: &str = r#"{"subtasks": [
+        {"key": "survey", "title": "Survey new users", "dod": "Ten interviews logged"},
+        {"key": "fix", "title": "Fix top issue", "dod": "Issue closed", "depends_on": ["survey"]}
+    ]}"#;
+
+    #[tokio::test]
+    async fn test_decompose_task_with_sampling() {
+        let (mcp, _temp) = setup();
+        mcp.create_task(Parameters(CreateTaskInput {
+            title: "Improve onboarding".to_string(),
+            description: Some("New users get lost".to_string()),
+            dod: None,
+            after_id: None,
+            before_id: None,
+        }))
+        .await
+        .unwrap();
+
+        let client_io = serve_in_memory(&mcp);
+        let model = MockModel::new(PLAN);
+        let client = model.clone().serve(client_io).await.unwrap();
+
+        // Dry run: the plan is proposed but nothing is created
+        let proposed = decompose(&client, serde_json::json!({"id": 1})).await;
+        assert_eq!(proposed["status"], "ok");
+        assert_eq!(proposed["data"]["dry_run"], true);
+        assert_eq!(proposed["data"]["subtasks"][1]["title"], "Fix top issue");
+        assert!(proposed["data"].get("created").is_none());
+        assert_eq!(mcp.tracker.lock().await.get_all_tasks().unwrap().len(), 1);
+        assert!(model.prompts.lock().unwrap()[0].contains("Improve onboarding"));
+
+        // Apply the confirmed plan without sampling again
+        let applied = decompose(
+            &client,
+            serde_json::json!({
+                "id": 1,
+                "apply": true,
+                "subtasks": proposed["data"]["subtasks"],
+            }),
+        )
+        .await;
+        assert_eq!(applied["data"]["dry_run"], false);
+        assert_eq!(model.prompts.lock().unwrap().len(), 1);
+
+        let ids = &applied["data"]["created"]["ids"];
+        let (survey, fix) = (
+            ids["survey"].as_i64().unwrap(),
+            ids["fix"].as_i64().unwrap(),
+        );
+        let tracker = mcp.tracker.lock().await;
+        let fix_deps: Vec<i64> = tracker
+            .get_task(fix)
+            .unwrap()
+            .dependencies
+            .iter()
+            .map(|d| d.id)
+            .collect();
+        assert_eq!(fix_deps, vec![survey]);
+        let parent_deps: Vec<i64> = tracker
+            .get_task(1)
+            .unwrap()
+            .dependencies
+            .iter()
+            .map(|d| d.id)
+            .collect();
+        assert_eq!(parent_deps, vec![fix]);
+        drop(tracker);
+
+        client.cancel().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_decompose_task_rejects_bad_plans() {
+        let (mcp, _temp) = setup();
+        mcp.create_task(Parameters(CreateTaskInput {
+            title: "Vague".to_string(),
+            description: None,
+            dod: None,
+            after_id: None,
+            before_id: None,
+        }))
+        .await
+        .unwrap();
+
+        let client_io = serve_in_memory(&mcp);
+        let client = MockModel::new("Sorry, I can't do that.")
+            .serve(client_io)
+            .await
+            .unwrap();
+
+        let result = decompose(&client, serde_json::json!({"id": 1, "apply": true})).await;
+        assert_eq!(result["status"], "error");
+        assert!(
+            result["message"]
+                .as_str()
+                .unwrap()
+                .contains("Invalid decomposition plan")
+        );
+        assert_eq!(mcp.tracker.lock().await.get_all_tasks().unwrap().len(), 1);
+        client.cancel().await.unwrap();
+
+        // A client without sampling can still apply an explicit plan
+        let client_io = serve_in_memory(&mcp);
+        let client = ().serve(client_io).await.unwrap();
+
+        let result = decompose(&client, serde_json::json!({"id": 1})).await;
+        assert_eq!(result["status"], "error");
+        assert!(result["message"].as_str().unwrap().contains("sampling"));
+
+        let plan: serde_json::Value = serde_json::from_str(PLAN).unwrap();
+        let result = decompose(
+            &client,
+            serde_json::json!({"id": 1, "apply": true, "subtasks": plan["subtasks"]}),
+        )
+        .await;
+        assert_eq!(result["status"], "ok");
+        assert_eq!(mcp.tracker.lock().await.get_all_tasks().unwrap().len(), 3);
+        client.cancel().await.unwrap();
+    }
+}
@@ -0,0 +1,234 @@
+//! Streamable HTTP transport for the MCP server.
+//!
+//! Lets several clients share one long-lived server. Each client gets its own
+//! session (resource subscriptions, tool list state) on top of one tracker.
+
+use crate::mcp::TaskTrackerMcp;
+use axum::{
+    Router,
+    extract::Request,
+    http::{StatusCode, header},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+};
+use rmcp::transport::{
+    StreamableHttpServerConfig, StreamableHttpService,
+    streamable_http_server::session::local::LocalSessionManager,
+};
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+
+/// Path the MCP endpoint is served on
+pub const MCP_PATH: &str = "/mcp";
+
+/// Serve MCP over HTTP on `addr` until interrupted
+pub async fn run_http_server(addr: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mcp = TaskTrackerMcp::new().map_err(|e| {
+        eprintln!("Failed to initialize MCP server: {e}");
+        e
+    })?;
+
+    let listener = TcpListener::bind(addr).await?;
+    let local_addr = listener.local_addr()?;
+    if !local_addr.ip().is_loopback() {
+        eprintln!(
+            "Warning: {local_addr} is not a loopback address and the server has no authentication"
+        );
+    }
+    eprintln!("MCP server listening on http://{local_addr}{MCP_PATH}");
+
+    let shutdown = CancellationToken::new();
+    tokio::spawn({
+        let shutdown = shutdown.clone();
+        async move {
+            let _ = tokio::signal::ctrl_c().await;
+            shutdown.cancel();
+        }
+    });
+
+    serve(listener, mcp, shutdown).await?;
+    Ok(())
+}
+
+/// Serve MCP on an already bound listener until `shutdown` is cancelled
+pub async fn serve(
+    listener: TcpListener,
+    mcp: TaskTrackerMcp,
+    shutdown: CancellationToken,
+) -> std::io::Result<()> {
+    let config = StreamableHttpServerConfig {
+        cancellation_token: shutdown.child_token(),
+        ..Default::default()
+    };
+    let service = StreamableHttpService::new(
+        move || Ok(mcp.session()),
+        Arc::new(LocalSessionManager::default()),
+        config,
+    );
+    let router = Router::new()
+        .nest_service(MCP_PATH, service)
+        .layer(middleware::from_fn(check_origin));
+
+    axum::serve(listener, router)
+        .with_graceful_shutdown(shutdown.cancelled_owned())
+        .await
+}
+
+/// Reject browser requests from non-local origins (DNS rebinding protection)
+async fn check_origin(request: Request, next: Next) -> Response {
+    let origin = request.headers().get(header::ORIGIN);
+    if origin.is_some_and(|origin| !origin.to_str().is_ok_and(is_local_origin)) {
+        return (StatusCode::FORBIDDEN, "Origin not allowed").into_response();
+    }
+    next.run(request).await
+}
+
+fn is_local_origin(origin: &str) -> bool {
+    let Some((_, authority)) = origin.split_once("://") else {
+        return false;
+    };
+    let host = match authority.strip_prefix('[') {
+        Some(rest) => rest.split(']').next().unwrap_or_default(),
+        None => authority.split(':').next().unwrap_or_default(),
+    };
+    matches!(host, "localhost" | "127.0.0.1" | "::1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::TaskTracker;
+    use rmcp::{
+        ClientHandler, ServiceExt,
+        model::{CallToolRequestParams, ResourceUpdatedNotificationParam, SubscribeRequestParams},
+        service::{NotificationContext, RoleClient},
+        transport::StreamableHttpClientTransport,
+    };
+    use std::time::Duration;
+    use tempfile::TempDir;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::sync::mpsc;
+
+    /// Client that forwards resource update notifications to a channel
+    #[derive(Clone)]
+    struct UpdateListener(mpsc::UnboundedSender<String>);
+
+    impl ClientHandler for UpdateListener {
+        async fn on_resource_updated(
+            &self,
+            params: ResourceUpdatedNotificationParam,
+            _context: NotificationContext<RoleClient>,
+        ) {
+            let _ = self.0.send(params.uri);
+        }
+    }
+
+    async fn start_server() -> (String, CancellationToken, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let tracker = TaskTracker::open_at(temp_dir.path().join("test.db")).unwrap();
+        tracker.init().unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}{MCP_PATH}", listener.local_addr().unwrap());
+        let shutdown = CancellationToken::new();
+        tokio::spawn(serve(
+            listener,
+            TaskTrackerMcp::with_tracker(tracker),
+            shutdown.clone(),
+        ));
+        (url, shutdown, temp_dir)
+    }
+
+    fn tool_call(name: &str, arguments: serde_json::Value) -> CallToolRequestParams {
+        CallToolRequestParams {
+            meta: None,
+            name: name.to_string().into(),
+            arguments: arguments.as_object().cloned(),
+            task: None,
+        }
+    }
+
+    #[test]
+    fn test_local_origin() {
+        for origin in [
+            "http://localhost:3000",
+            "http://127.0.0.1",
+            "https://[::1]:8080",
+        ] {
+            assert!(is_local_origin(origin), "{origin}");
+        }
+        for origin in ["null", "http://example.com", "http://localhost.evil.com"] {
+            assert!(!is_local_origin(origin), "{origin}");
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_clients_share_server() {
+        let (url, shutdown, _temp) = start_server().await;
+
+        let writer = ().serve(StreamableHttpClientTransport::from_uri(url.as_str())).await.unwrap();
+        let (tx, mut updates) = mpsc::unbounded_channel();
+        let reader = UpdateListener(tx)
+            .serve(StreamableHttpClientTransport::from_uri(url.as_str()))
+            .await
+            .unwrap();
+
+        writer
+            .call_tool(tool_call(
+                "create_task",
+                serde_json::json!({"title": "Shared", "dod": "Done"}),
+            ))
+            .await
+            .unwrap();
+
+        // The other session sees the task and is notified when it changes
+        let resources = reader.list_resources(None).await.unwrap().resources;
+        assert!(resources.iter().any(|r| r.uri == "tt://task/1"));
+        reader
+            .subscribe(SubscribeRequestParams {
+                meta: None,
+                uri: "tt://task/1".to_string(),
+            })
+            .await
+            .unwrap();
+
+        writer
+            .call_tool(tool_call("start_task", serde_json::json!({"id": 1})))
+            .await
+            .unwrap();
+        let uri = tokio::time::timeout(Duration::from_secs(5), updates.recv())
+            .await
+            .expect("no resource update received")
+            .unwrap();
+        assert_eq!(uri, "tt://task/1");
+
+        writer.cancel().await.unwrap();
+        reader.cancel().await.unwrap();
+        shutdown.cancel();
+    }
+
+    #[tokio::test]
+    async fn test_rejects_foreign_origin() {
+        let (url, shutdown, _temp) = start_server().await;
+        let addr = url
+            .trim_start_matches("http://")
+            .trim_end_matches(MCP_PATH)
+            .to_string();
+
+        let body = r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#;
+        let request = format!(
+            "POST {MCP_PATH} HTTP/1.1\r\nHost: {addr}\r\nOrigin: http://example.com\r\n\
+             Content-Type: application/json\r\nAccept: application/json, text/event-stream\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        let mut stream = tokio::net::TcpStream::connect(&addr).await.unwrap();
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 403"), "{response}");
+
+        shutdown.cancel();
+    }
+}
@@ -0,0 +1,262 @@
+//! MCP resources for tasks, and change tracking for subscribed clients.
+
+use crate::core::TaskTracker;
+use crate::error::{Result, TaskError};
+use rmcp::model::{AnnotateAble, RawResource, RawResourceTemplate, Resource, ResourceTemplate};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// How often the watcher checks the database for changes
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Tools that are only listed while a task is in progress
+pub const ACTIVE_TASK_TOOLS: &[&str] = &["complete_task", "stop_task", "log_artifact"];
+
+const JSON_MIME_TYPE: &str = "application/json";
+
+/// A `tt://` resource URI
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ResourceUri {
+    /// `tt://task/{id}`
+    Task(i64),
+    /// `tt://current`
+    Current,
+}
+
+impl ResourceUri {
+    pub fn parse(uri: &str) -> Result<Self> {
+        let invalid = || TaskError::InvalidResource(uri.to_string());
+        match uri.strip_prefix("tt://").ok_or_else(invalid)? {
+            "current" => Ok(Self::Current),
+            path => path
+                .strip_prefix("task/")
+                .and_then(|id| id.parse().ok())
+                .map(Self::Task)
+                .ok_or_else(invalid),
+        }
+    }
+
+    /// Read the resource as pretty-printed JSON.
+    ///
+    /// `tt://current` reads as `null` when no task is in progress.
+    pub fn read(self, tracker: &TaskTracker) -> Result<String> {
+        let detail = match self {
+            Self::Task(id) => tracker.get_task(id)?,
+            Self::Current => match tracker.get_current_task() {
+                Ok(detail) => detail,
+                Err(TaskError::NoActiveTask) => return Ok("null".to_string()),
+                Err(e) => return Err(e),
+            },
+        };
+        Ok(serde_json::to_string_pretty(&detail)?)
+    }
+
+    /// Content used to detect changes; a deleted task snapshots as empty
+    fn snapshot(self, tracker: &TaskTracker) -> Result<String> {
+        match self.read(tracker) {
+            Err(TaskError::TaskNotFound(_)) => Ok(String::new()),
+            other => other,
+        }
+    }
+}
+
+/// `tt://current` plus one resource per task in the target subgraph, or every
+/// task when no target is set
+pub fn list(tracker: &TaskTracker) -> Result<Vec<Resource>> {
+    let mut current = RawResource::new("tt://current", "Current task");
+    current.description = Some("The task currently in progress, or null".to_string());
+    current.mime_type = Some(JSON_MIME_TYPE.to_string());
+    let mut resources = vec![current.no_annotation()];
+
+    let all = tracker.get_target()?.is_none();
+    let (tasks, _) = tracker.list_tasks(all)?;
+    for detail in tasks {
+        let task = detail.task;
+        let mut resource = RawResource::new(
+            format!("tt://task/{}", task.id),
+            format!("#{} {}", task.id, task.title),
+        );
+        resource.mime_type = Some(JSON_MIME_TYPE.to_string());
+        resources.push(resource.no_annotation());
+    }
+
+    Ok(resources)
+}
+
+pub fn templates() -> Vec<ResourceTemplate> {
+    vec![
+        RawResourceTemplate {
+            uri_template: "tt://task/{id}".to_string(),
+            name: "Task".to_string(),
+            title: None,
+            description: Some("A task with its dependencies, dependents and artifacts".to_string()),
+            mime_type: Some(JSON_MIME_TYPE.to_string()),
+            icons: None,
+        }
+        .no_annotation(),
+    ]
+}
+
+/// What changed since the last poll
+#[derive(Debug, Default, PartialEq)]
+pub struct Changes {
+    /// Subscribed resource URIs whose content changed
+    pub resources: Vec<String>,
+    /// Whether the set of available tools changed
+    pub tools: bool,
+}
+
+/// Tracks what a client has seen so that changes can be reported to it.
+///
+/// Writes from other connections (e.g. a concurrent `tt` CLI process) are
+/// detected through `data_version`. Writes through this server's own
+/// connection do not bump it, so tool calls mark the state dirty instead.
+#[derive(Debug, Default)]
+pub struct WatchState {
+    data_version: Option<i64>,
+    dirty: bool,
+    /// Subscribed URIs and their last seen content
+    subscriptions: BTreeMap<String, String>,
+    /// Whether a task was active when the client last listed tools
+    listed_active_task: Option<bool>,
+}
+
+impl WatchState {
+    pub fn subscribe(&mut self, tracker: &TaskTracker, uri: &str) -> Result<()> {
+        let snapshot = ResourceUri::parse(uri)?.snapshot(tracker)?;
+        self.subscriptions.insert(uri.to_string(), snapshot);
+        Ok(())
+    }
+
+    pub fn unsubscribe(&mut self, uri: &str) {
+        self.subscriptions.remove(uri);
+    }
+
+    /// Force the next poll to re-check subscriptions
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Record the tool set the client was just given
+    pub fn tools_listed(&mut self, active_task: bool) {
+        self.listed_active_task = Some(active_task);
+    }
+
+    pub fn poll(&mut self, tracker: &TaskTracker) -> Result<Changes> {
+        let version = tracker.data_version()?;
+        if !self.dirty && self.data_version == Some(version) {
+            return Ok(Changes::default());
+        }
+        self.dirty = false;
+        self.data_version = Some(version);
+
+        let mut changes = Changes::default();
+        for (uri, last) in self.subscriptions.iter_mut() {
+            let snapshot = ResourceUri::parse(uri)?.snapshot(tracker)?;
+            if *last != snapshot {
+                *last = snapshot;
+                changes.resources.push(uri.clone());
+            }
+        }
+
+        if let Some(listed) = self.listed_active_task {
+            let active = tracker.has_active_task()?;
+            if active != listed {
+                self.listed_active_task = Some(active);
+                changes.tools = true;
+            }
+        }
+
+        Ok(changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn setup() -> (TaskTracker, TaskTracker, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let server = TaskTracker::open_at(&db_path).unwrap();
+        server.init().unwrap();
+        // A second connection stands in for a concurrent CLI process
+        let cli = TaskTracker::open_at(&db_path).unwrap();
+        (server, cli, temp_dir)
+    }
+
+    #[test]
+    fn test_parse_uri() {
+        assert_eq!(
+            ResourceUri::parse("tt://task/7").unwrap(),
+            ResourceUri::Task(7)
+        );
+        assert_eq!(
+            ResourceUri::parse("tt://current").unwrap(),
+            ResourceUri::Current
+        );
+        for uri in ["tt://task/x", "tt://target", "file:///tmp/x"] {
+            assert!(matches!(
+                ResourceUri::parse(uri),
+                Err(TaskError::InvalidResource(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_read_current_without_active_task() {
+        let (server, _cli, _temp) = setup();
+        assert_eq!(ResourceUri::Current.read(&server).unwrap(), "null");
+    }
+
+    #[test]
+    fn test_detects_changes_from_other_connection() {
+        let (server, cli, _temp) = setup();
+        let task = server
+            .create_task("Task", None, Some("DoD"), None, None)
+            .unwrap();
+        let other = server
+            .create_task("Other", None, Some("DoD"), None, None)
+            .unwrap();
+
+        let mut watch = WatchState::default();
+        let task_uri = format!("tt://task/{}", task.id);
+        watch.subscribe(&server, &task_uri).unwrap();
+        watch.subscribe(&server, "tt://current").unwrap();
+        watch.tools_listed(false);
+        assert_eq!(watch.poll(&server).unwrap(), Changes::default());
+
+        cli.start_task(task.id).unwrap();
+        let changes = watch.poll(&server).unwrap();
+        assert_eq!(
+            changes.resources,
+            vec!["tt://current".to_string(), task_uri]
+        );
+        assert!(changes.tools);
+
+        // Unsubscribed tasks do not produce notifications
+        cli.update_task(other.id, Some("Renamed"), None, None, None)
+            .unwrap();
+        assert_eq!(watch.poll(&server).unwrap(), Changes::default());
+    }
+
+    #[test]
+    fn test_own_writes_need_mark_dirty() {
+        let (server, _cli, _temp) = setup();
+        let task = server
+            .create_task("Task", None, Some("DoD"), None, None)
+            .unwrap();
+
+        let mut watch = WatchState::default();
+        let task_uri = format!("tt://task/{}", task.id);
+        watch.subscribe(&server, &task_uri).unwrap();
+        watch.poll(&server).unwrap();
+
+        server.block_task(task.id).unwrap();
+        assert_eq!(watch.poll(&server).unwrap(), Changes::default());
+
+        watch.mark_dirty();
+        assert_eq!(watch.poll(&server).unwrap().resources, vec![task_uri]);
+    }
+}
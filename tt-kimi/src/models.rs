@@ -1,9 +1,11 @@
 use chrono::{DateTime, Utc};
+use rmcp::schemars::{self, JsonSchema};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt;
 
 /// Task status in the state machine
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum Status {
     Pending,
@@ -53,7 +55,7 @@ impl TryFrom<&str> for Status {
 }
 
 /// A task in the system
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Task {
     pub id: i64,
     pub title: String,
@@ -68,7 +70,7 @@ pub struct Task {
 }
 
 /// An artifact linked to a task
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Artifact {
     pub id: i64,
     pub task_id: i64,
@@ -85,7 +87,7 @@ pub struct Dependency {
 }
 
 /// Full task details including dependencies and artifacts
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TaskDetail {
     #[serde(flatten)]
     pub task: Task,
@@ -95,7 +97,7 @@ pub struct TaskDetail {
 }
 
 /// Dependency info with task status
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct DependencyInfo {
     pub id: i64,
     pub title: String,
@@ -134,7 +136,7 @@ pub struct TaskUpdate {
 }
 
 /// Next task result
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(tag = "type")]
 pub enum NextTaskResult {
     Task { task: TaskDetail },
@@ -143,7 +145,7 @@ pub enum NextTaskResult {
 }
 
 /// Blocked task info for error reporting
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct BlockedTaskInfo {
     pub id: i64,
     pub title: String,
@@ -151,7 +153,7 @@ pub struct BlockedTaskInfo {
 }
 
 /// What a blocked task is waiting on
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct WaitingOnInfo {
     pub id: i64,
     pub title: String,
@@ -159,10 +161,31 @@ pub struct WaitingOnInfo {
 }
 
 /// Order conflict warning
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct OrderConflict {
     pub task_id: i64,
     pub task_order: f64,
     pub dep_id: i64,
     pub dep_order: f64,
 }
+
+/// Tasks created by instantiating a template
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct TemplateInstance {
+    pub template: String,
+    /// Created tasks, in template order
+    pub tasks: Vec<Task>,
+    /// Template task key to created task ID
+    pub ids: BTreeMap<String, i64>,
+    pub parent_id: Option<i64>,
+}
+
+/// Subtasks created by decomposing a task
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Decomposition {
+    pub task_id: i64,
+    /// Created subtasks, in plan order
+    pub tasks: Vec<Task>,
+    /// Plan key to created task ID
+    pub ids: BTreeMap<String, i64>,
+}
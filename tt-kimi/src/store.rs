@@ -0,0 +1,491 @@
+use crate::error::{Result, TaskError};
+use crate::models::{Artifact, Dependency, Status, Task};
+use chrono::Utc;
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+
+/// Storage backend for the task tracker
+///
+/// Implemented by the SQLite [`Database`](crate::db::Database) and by
+/// [`MemoryStore`]. Ordering guarantees match the SQLite backend: task lists are
+/// sorted by `manual_order`, artifacts by creation.
+pub trait Store: Send {
+    /// Check if the schema exists
+    fn is_initialized(&self) -> Result<bool>;
+
+    /// Create the schema
+    fn init(&self) -> Result<()>;
+
+    /// Counter that changes when another connection modifies the data
+    fn data_version(&self) -> Result<i64>;
+
+    /// Run `f` atomically, discarding its writes if it returns an error
+    fn with_transaction(&self, f: &mut dyn FnMut() -> Result<()>) -> Result<()>;
+
+    // ==================== Task Operations ====================
+
+    fn create_task(
+        &self,
+        title: &str,
+        description: Option<&str>,
+        dod: Option<&str>,
+        manual_order: f64,
+    ) -> Result<Task>;
+
+    fn get_task(&self, id: i64) -> Result<Option<Task>>;
+
+    fn get_all_tasks(&self) -> Result<Vec<Task>>;
+
+    fn update_task(
+        &self,
+        id: i64,
+        title: Option<&str>,
+        description: Option<Option<&str>>,
+        dod: Option<Option<&str>>,
+    ) -> Result<Task>;
+
+    /// Set a task's status, stamping `started_at`/`completed_at` as needed
+    fn set_task_status(&self, id: i64, status: Status) -> Result<Task>;
+
+    fn get_active_task(&self) -> Result<Option<Task>>;
+
+    fn update_manual_order(&self, id: i64, order: f64) -> Result<Task>;
+
+    fn get_max_manual_order(&self) -> Result<f64>;
+
+    /// All tasks ordered by `manual_order`, then id
+    fn get_all_tasks_with_order(&self) -> Result<Vec<Task>>;
+
+    // ==================== Dependency Operations ====================
+
+    fn add_dependency(&self, task_id: i64, depends_on: i64) -> Result<()>;
+
+    fn remove_dependency(&self, task_id: i64, depends_on: i64) -> Result<()>;
+
+    fn get_dependencies(&self, task_id: i64) -> Result<Vec<Dependency>>;
+
+    /// Id, title and status of each task `task_id` depends on
+    fn get_dependency_statuses(&self, task_id: i64) -> Result<Vec<(i64, String, Status)>>;
+
+    fn get_dependents(&self, task_id: i64) -> Result<Vec<i64>>;
+
+    fn get_all_dependencies(&self) -> Result<Vec<Dependency>>;
+
+    // ==================== Artifact Operations ====================
+
+    fn create_artifact(&self, task_id: i64, name: &str, file_path: &str) -> Result<Artifact>;
+
+    fn get_artifacts_for_task(&self, task_id: i64) -> Result<Vec<Artifact>>;
+
+    // ==================== Config Operations ====================
+
+    fn get_config(&self, key: &str) -> Result<Option<String>>;
+
+    fn set_config(&self, key: &str, value: &str) -> Result<()>;
+
+    fn delete_config(&self, key: &str) -> Result<()>;
+
+    // ==================== Target Walk ====================
+
+    /// The target and its transitive dependencies, including completed tasks
+    fn get_target_subgraph(&self, target_id: i64) -> Result<Vec<Task>>;
+
+    fn get_target_subgraph_with_deps(&self, target_id: i64) -> Result<Vec<(Task, Vec<i64>)>> {
+        let tasks = self.get_target_subgraph(target_id)?;
+        let mut result = Vec::new();
+
+        for task in tasks {
+            let deps = self.get_dependencies(task.id)?;
+            let dep_ids: Vec<i64> = deps.into_iter().map(|d| d.depends_on).collect();
+            result.push((task, dep_ids));
+        }
+
+        Ok(result)
+    }
+}
+
+/// Store that keeps everything in memory and is dropped with the tracker
+#[derive(Default)]
+pub struct MemoryStore {
+    state: RefCell<State>,
+}
+
+#[derive(Clone, Default)]
+struct State {
+    tasks: HashMap<i64, Task>,
+    dependencies: BTreeSet<(i64, i64)>,
+    artifacts: HashMap<i64, Artifact>,
+    config: HashMap<String, String>,
+    last_task_id: i64,
+    last_artifact_id: i64,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn touch(&self, id: i64, f: impl FnOnce(&mut Task)) -> Result<Task> {
+        let mut state = self.state.borrow_mut();
+        let task = state
+            .tasks
+            .get_mut(&id)
+            .ok_or(TaskError::TaskNotFound(id))?;
+        f(task);
+        task.last_touched_at = Utc::now();
+        Ok(task.clone())
+    }
+
+    fn sorted_tasks(&self) -> Vec<Task> {
+        let mut tasks: Vec<Task> = self.state.borrow().tasks.values().cloned().collect();
+        tasks.sort_by(|a, b| {
+            a.manual_order
+                .total_cmp(&b.manual_order)
+                .then(a.id.cmp(&b.id))
+        });
+        tasks
+    }
+}
+
+impl Store for MemoryStore {
+    fn is_initialized(&self) -> Result<bool> {
+        Ok(true)
+    }
+
+    fn init(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn data_version(&self) -> Result<i64> {
+        // Nothing outside this store can write to it
+        Ok(0)
+    }
+
+    fn with_transaction(&self, f: &mut dyn FnMut() -> Result<()>) -> Result<()> {
+        let snapshot = self.state.borrow().clone();
+        let result = f();
+        if result.is_err() {
+            *self.state.borrow_mut() = snapshot;
+        }
+        result
+    }
+
+    // ==================== Task Operations ====================
+
+    fn create_task(
+        &self,
+        title: &str,
+        description: Option<&str>,
+        dod: Option<&str>,
+        manual_order: f64,
+    ) -> Result<Task> {
+        let mut state = self.state.borrow_mut();
+        state.last_task_id += 1;
+
+        let now = Utc::now();
+        let task = Task {
+            id: state.last_task_id,
+            title: title.to_string(),
+            description: description.map(str::to_string),
+            dod: dod.map(str::to_string),
+            status: Status::Pending,
+            manual_order,
+            created_at: now,
+            started_at: None,
+            completed_at: None,
+            last_touched_at: now,
+        };
+        state.tasks.insert(task.id, task.clone());
+        Ok(task)
+    }
+
+    fn get_task(&self, id: i64) -> Result<Option<Task>> {
+        Ok(self.state.borrow().tasks.get(&id).cloned())
+    }
+
+    fn get_all_tasks(&self) -> Result<Vec<Task>> {
+        Ok(self.sorted_tasks())
+    }
+
+    fn update_task(
+        &self,
+        id: i64,
+        title: Option<&str>,
+        description: Option<Option<&str>>,
+        dod: Option<Option<&str>>,
+    ) -> Result<Task> {
+        self.touch(id, |task| {
+            if let Some(t) = title {
+                task.title = t.to_string();
+            }
+            if let Some(d) = description {
+                task.description = d.map(str::to_string);
+            }
+            if let Some(d) = dod {
+                task.dod = d.map(str::to_string);
+            }
+        })
+    }
+
+    fn set_task_status(&self, id: i64, status: Status) -> Result<Task> {
+        let now = Utc::now();
+        self.touch(id, |task| {
+            task.status = status;
+            match status {
+                Status::InProgress => task.started_at = Some(now),
+                Status::Completed => task.completed_at = Some(now),
+                _ => {}
+            }
+        })
+    }
+
+    fn get_active_task(&self) -> Result<Option<Task>> {
+        Ok(self
+            .state
+            .borrow()
+            .tasks
+            .values()
+            .find(|t| t.status == Status::InProgress)
+            .cloned())
+    }
+
+    fn update_manual_order(&self, id: i64, order: f64) -> Result<Task> {
+        self.touch(id, |task| task.manual_order = order)
+    }
+
+    fn get_max_manual_order(&self) -> Result<f64> {
+        Ok(self
+            .state
+            .borrow()
+            .tasks
+            .values()
+            .map(|t| t.manual_order)
+            .reduce(f64::max)
+            .unwrap_or(0.0))
+    }
+
+    fn get_all_tasks_with_order(&self) -> Result<Vec<Task>> {
+        Ok(self.sorted_tasks())
+    }
+
+    // ==================== Dependency Operations ====================
+
+    fn add_dependency(&self, task_id: i64, depends_on: i64) -> Result<()> {
+        if task_id == depends_on {
+            return Err(TaskError::SelfDependency);
+        }
+
+        let mut state = self.state.borrow_mut();
+        for id in [task_id, depends_on] {
+            if !state.tasks.contains_key(&id) {
+                return Err(TaskError::TaskNotFound(id));
+            }
+        }
+        if !state.dependencies.insert((task_id, depends_on)) {
+            return Err(TaskError::DependencyAlreadyExists);
+        }
+        Ok(())
+    }
+
+    fn remove_dependency(&self, task_id: i64, depends_on: i64) -> Result<()> {
+        if !self
+            .state
+            .borrow_mut()
+            .dependencies
+            .remove(&(task_id, depends_on))
+        {
+            return Err(TaskError::DependencyNotFound);
+        }
+        Ok(())
+    }
+
+    fn get_dependencies(&self, task_id: i64) -> Result<Vec<Dependency>> {
+        Ok(self
+            .state
+            .borrow()
+            .dependencies
+            .range((task_id, i64::MIN)..=(task_id, i64::MAX))
+            .map(|&(task_id, depends_on)| Dependency {
+                task_id,
+                depends_on,
+            })
+            .collect())
+    }
+
+    fn get_dependency_statuses(&self, task_id: i64) -> Result<Vec<(i64, String, Status)>> {
+        let state = self.state.borrow();
+        Ok(state
+            .dependencies
+            .range((task_id, i64::MIN)..=(task_id, i64::MAX))
+            .filter_map(|(_, depends_on)| state.tasks.get(depends_on))
+            .map(|t| (t.id, t.title.clone(), t.status))
+            .collect())
+    }
+
+    fn get_dependents(&self, task_id: i64) -> Result<Vec<i64>> {
+        Ok(self
+            .state
+            .borrow()
+            .dependencies
+            .iter()
+            .filter(|(_, depends_on)| *depends_on == task_id)
+            .map(|(id, _)| *id)
+            .collect())
+    }
+
+    fn get_all_dependencies(&self) -> Result<Vec<Dependency>> {
+        Ok(self
+            .state
+            .borrow()
+            .dependencies
+            .iter()
+            .map(|&(task_id, depends_on)| Dependency {
+                task_id,
+                depends_on,
+            })
+            .collect())
+    }
+
+    // ==================== Artifact Operations ====================
+
+    fn create_artifact(&self, task_id: i64, name: &str, file_path: &str) -> Result<Artifact> {
+        let mut state = self.state.borrow_mut();
+        if !state.tasks.contains_key(&task_id) {
+            return Err(TaskError::TaskNotFound(task_id));
+        }
+        state.last_artifact_id += 1;
+
+        let artifact = Artifact {
+            id: state.last_artifact_id,
+            task_id,
+            name: name.to_string(),
+            file_path: file_path.to_string(),
+            created_at: Utc::now(),
+        };
+        state.artifacts.insert(artifact.id, artifact.clone());
+        Ok(artifact)
+    }
+
+    fn get_artifacts_for_task(&self, task_id: i64) -> Result<Vec<Artifact>> {
+        let mut artifacts: Vec<Artifact> = self
+            .state
+            .borrow()
+            .artifacts
+            .values()
+            .filter(|a| a.task_id == task_id)
+            .cloned()
+            .collect();
+        artifacts.sort_by_key(|a| a.id);
+        Ok(artifacts)
+    }
+
+    // ==================== Config Operations ====================
+
+    fn get_config(&self, key: &str) -> Result<Option<String>> {
+        Ok(self.state.borrow().config.get(key).cloned())
+    }
+
+    fn set_config(&self, key: &str, value: &str) -> Result<()> {
+        self.state
+            .borrow_mut()
+            .config
+            .insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn delete_config(&self, key: &str) -> Result<()> {
+        self.state.borrow_mut().config.remove(key);
+        Ok(())
+    }
+
+    // ==================== Target Walk ====================
+
+    fn get_target_subgraph(&self, target_id: i64) -> Result<Vec<Task>> {
+        let state = self.state.borrow();
+        let mut seen = BTreeSet::from([target_id]);
+        let mut stack = vec![target_id];
+
+        while let Some(id) = stack.pop() {
+            for &(_, depends_on) in state.dependencies.range((id, i64::MIN)..=(id, i64::MAX)) {
+                if seen.insert(depends_on) {
+                    stack.push(depends_on);
+                }
+            }
+        }
+
+        Ok(seen
+            .into_iter()
+            .filter_map(|id| state.tasks.get(&id).cloned())
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transaction_rolls_back_on_error() {
+        let store = MemoryStore::new();
+        let a = store.create_task("A", None, None, 10.0).unwrap();
+
+        let result = store.with_transaction(&mut || {
+            store.create_task("B", None, None, 20.0)?;
+            store.set_config("target_id", "2")?;
+            store.add_dependency(a.id, 99)
+        });
+
+        assert!(matches!(result, Err(TaskError::TaskNotFound(99))));
+        assert_eq!(store.get_all_tasks().unwrap().len(), 1);
+        assert_eq!(store.get_config("target_id").unwrap(), None);
+
+        // Ids handed out inside the failed transaction are reused
+        let c = store.create_task("C", None, None, 20.0).unwrap();
+        assert_eq!(c.id, 2);
+    }
+
+    #[test]
+    fn test_dependency_constraints() {
+        let store = MemoryStore::new();
+        let a = store.create_task("A", None, None, 10.0).unwrap();
+        let b = store.create_task("B", None, None, 20.0).unwrap();
+
+        store.add_dependency(b.id, a.id).unwrap();
+        assert!(matches!(
+            store.add_dependency(b.id, a.id),
+            Err(TaskError::DependencyAlreadyExists)
+        ));
+        assert!(matches!(
+            store.add_dependency(a.id, a.id),
+            Err(TaskError::SelfDependency)
+        ));
+        assert_eq!(store.get_dependents(a.id).unwrap(), vec![b.id]);
+
+        store.remove_dependency(b.id, a.id).unwrap();
+        assert!(matches!(
+            store.remove_dependency(b.id, a.id),
+            Err(TaskError::DependencyNotFound)
+        ));
+    }
+
+    #[test]
+    fn test_target_subgraph() {
+        let store = MemoryStore::new();
+        let a = store.create_task("A", None, None, 10.0).unwrap();
+        let b = store.create_task("B", None, None, 20.0).unwrap();
+        let c = store.create_task("C", None, None, 30.0).unwrap();
+        let unrelated = store.create_task("D", None, None, 40.0).unwrap();
+
+        store.add_dependency(c.id, b.id).unwrap();
+        store.add_dependency(b.id, a.id).unwrap();
+        store.add_dependency(unrelated.id, a.id).unwrap();
+
+        let ids: Vec<i64> = store
+            .get_target_subgraph(c.id)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.id)
+            .collect();
+        assert_eq!(ids, vec![a.id, b.id, c.id]);
+    }
+}
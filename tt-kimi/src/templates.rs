@@ -0,0 +1,361 @@
+use crate::error::{Result, TaskError};
+use rmcp::schemars::{self, JsonSchema};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directory holding project templates, relative to the project root
+pub const TEMPLATES_DIR: &str = ".tt/templates";
+
+/// Built-in research → plan → implement template, used when no `rpi.toml` exists
+pub const RPI_TEMPLATE: &str = r#"description = "Research, plan and implement a change"
+
+[[task]]
+key = "research"
+title = "Research {{name}}"
+dod = "Findings for {{name}} are logged as a 'research' artifact"
+
+[[task]]
+key = "plan"
+title = "Plan {{name}}"
+dod = "An implementation plan for {{name}} is logged as a 'plan' artifact"
+depends_on = ["research"]
+
+[[task]]
+key = "implement"
+title = "Implement {{name}}"
+dod = "{{name}} is implemented according to the plan and all tests pass"
+depends_on = ["plan"]
+"#;
+
+/// A task template: several tasks with internal dependencies
+#[derive(Debug, Clone, Deserialize)]
+pub struct Template {
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(rename = "task", default)]
+    pub tasks: Vec<TemplateTask>,
+}
+
+/// A single task inside a template
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TemplateTask {
+    /// Identifier used by `depends_on` within the template
+    pub key: String,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dod: Option<String>,
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+}
+
+impl Template {
+    /// Validate a template built from `tasks`
+    pub fn new(tasks: Vec<TemplateTask>) -> Result<Self> {
+        let template = Template {
+            description: None,
+            tasks,
+        };
+        template.validate()?;
+        Ok(template)
+    }
+
+    /// Parse and validate a template from TOML source
+    pub fn parse(source: &str) -> Result<Self> {
+        let template: Template =
+            toml::from_str(source).map_err(|e| TaskError::InvalidTemplate(e.to_string()))?;
+        template.validate()?;
+        Ok(template)
+    }
+
+    /// Load a template by name from `dir`, falling back to the built-in templates
+    pub fn load(dir: &Path, name: &str) -> Result<Self> {
+        let path = template_path(dir, name)?;
+        match fs::read_to_string(&path) {
+            Ok(source) => Self::parse(&source),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => match name {
+                "rpi" => Self::parse(RPI_TEMPLATE),
+                _ => Err(TaskError::TemplateNotFound(name.to_string())),
+            },
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Fill `{{placeholders}}` in every title, description and DoD
+    pub fn render(&self, vars: &HashMap<String, String>) -> Result<Self> {
+        let render_opt =
+            |text: &Option<String>| text.as_deref().map(|t| render(t, vars)).transpose();
+        let tasks = self
+            .tasks
+            .iter()
+            .map(|task| {
+                Ok(TemplateTask {
+                    key: task.key.clone(),
+                    title: render(&task.title, vars)?,
+                    description: render_opt(&task.description)?,
+                    dod: render_opt(&task.dod)?,
+                    depends_on: task.depends_on.clone(),
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Template {
+            description: self.description.clone(),
+            tasks,
+        })
+    }
+
+    /// Tasks that no other task in the template depends on
+    pub fn leaves(&self) -> Vec<&TemplateTask> {
+        let depended_on: HashSet<&str> = self
+            .tasks
+            .iter()
+            .flat_map(|t| t.depends_on.iter().map(String::as_str))
+            .collect();
+        self.tasks
+            .iter()
+            .filter(|t| !depended_on.contains(t.key.as_str()))
+            .collect()
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.tasks.is_empty() {
+            return Err(TaskError::InvalidTemplate(
+                "template defines no tasks".to_string(),
+            ));
+        }
+
+        let mut keys = HashSet::new();
+        for task in &self.tasks {
+            if !keys.insert(task.key.as_str()) {
+                return Err(TaskError::InvalidTemplate(format!(
+                    "duplicate task key '{}'",
+                    task.key
+                )));
+            }
+        }
+
+        for task in &self.tasks {
+            for dep in &task.depends_on {
+                if !keys.contains(dep.as_str()) {
+                    return Err(TaskError::InvalidTemplate(format!(
+                        "task '{}' depends on unknown key '{dep}'",
+                        task.key
+                    )));
+                }
+            }
+        }
+
+        // Remove tasks whose dependencies are all placed; anything left is in a cycle
+        let mut placed: HashSet<&str> = HashSet::new();
+        while placed.len() < self.tasks.len() {
+            let ready: Vec<&str> = self
+                .tasks
+                .iter()
+                .filter(|t| !placed.contains(t.key.as_str()))
+                .filter(|t| t.depends_on.iter().all(|d| placed.contains(d.as_str())))
+                .map(|t| t.key.as_str())
+                .collect();
+            if ready.is_empty() {
+                let stuck = self
+                    .tasks
+                    .iter()
+                    .find(|t| !placed.contains(t.key.as_str()))
+                    .map(|t| t.key.as_str())
+                    .unwrap_or_default();
+                return Err(TaskError::InvalidTemplate(format!(
+                    "task '{stuck}' is part of a dependency cycle"
+                )));
+            }
+            placed.extend(ready);
+        }
+
+        Ok(())
+    }
+}
+
+/// Replace `{{var}}` placeholders with values from `vars`
+pub fn render(text: &str, vars: &HashMap<String, String>) -> Result<String> {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after.find("}}").ok_or_else(|| {
+            TaskError::InvalidTemplate(format!("unterminated placeholder in '{text}'"))
+        })?;
+        let var = after[..end].trim();
+        let value = vars.get(var).ok_or_else(|| {
+            TaskError::InvalidTemplate(format!("no value for placeholder '{{{{{var}}}}}'"))
+        })?;
+        out.push_str(value);
+        rest = &after[end + 2..];
+    }
+
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Names of the templates available in `dir`, including built-ins
+pub fn list(dir: &Path) -> Result<Vec<String>> {
+    let mut names = vec!["rpi".to_string()];
+    if dir.is_dir() {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "toml")
+                && let Some(stem) = path.file_stem().and_then(|s| s.to_str())
+            {
+                names.push(stem.to_string());
+            }
+        }
+    }
+    names.sort();
+    names.dedup();
+    Ok(names)
+}
+
+/// Path of a named template inside `dir`. Names come from MCP clients, so
+/// anything that could leave `dir` is rejected.
+fn template_path(dir: &Path, name: &str) -> Result<PathBuf> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.contains("..") {
+        return Err(TaskError::InvalidTemplateName(name.to_string()));
+    }
+    Ok(dir.join(format!("{name}.toml")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn vars(name: &str) -> HashMap<String, String> {
+        HashMap::from([("name".to_string(), name.to_string())])
+    }
+
+    #[test]
+    fn test_parse_builtin_rpi() {
+        let template = Template::parse(RPI_TEMPLATE).unwrap();
+        assert_eq!(template.tasks.len(), 3);
+        assert_eq!(template.tasks[1].depends_on, vec!["research"]);
+
+        let leaves: Vec<&str> = template.leaves().iter().map(|t| t.key.as_str()).collect();
+        assert_eq!(leaves, vec!["implement"]);
+    }
+
+    #[test]
+    fn test_render_placeholders() {
+        assert_eq!(
+            render("Research {{name}} ({{ name }})", &vars("Auth")).unwrap(),
+            "Research Auth (Auth)"
+        );
+        assert_eq!(
+            render("No placeholders", &vars("Auth")).unwrap(),
+            "No placeholders"
+        );
+        assert!(matches!(
+            render("{{missing}}", &vars("Auth")),
+            Err(TaskError::InvalidTemplate(_))
+        ));
+        assert!(matches!(
+            render("{{name", &vars("Auth")),
+            Err(TaskError::InvalidTemplate(_))
+        ));
+    }
+
+    #[test]
+    fn test_rejects_unknown_dependency() {
+        let source = r#"
+            [[task]]
+            key = "a"
+            title = "A"
+            depends_on = ["b"]
+        "#;
+        assert!(matches!(
+            Template::parse(source),
+            Err(TaskError::InvalidTemplate(_))
+        ));
+    }
+
+    #[test]
+    fn test_rejects_duplicate_keys() {
+        let source = r#"
+            [[task]]
+            key = "a"
+            title = "A"
+
+            [[task]]
+            key = "a"
+            title = "A again"
+        "#;
+        assert!(matches!(
+            Template::parse(source),
+            Err(TaskError::InvalidTemplate(_))
+        ));
+    }
+
+    #[test]
+    fn test_rejects_cycles() {
+        let source = r#"
+            [[task]]
+            key = "a"
+            title = "A"
+            depends_on = ["b"]
+
+            [[task]]
+            key = "b"
+            title = "B"
+            depends_on = ["a"]
+        "#;
+        assert!(matches!(
+            Template::parse(source),
+            Err(TaskError::InvalidTemplate(message)) if message.contains("cycle")
+        ));
+    }
+
+    #[test]
+    fn test_load_from_directory() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("review.toml"),
+            "[[task]]\nkey = \"review\"\ntitle = \"Review {{name}}\"\n",
+        )
+        .unwrap();
+
+        let template = Template::load(dir.path(), "review").unwrap();
+        assert_eq!(template.tasks[0].title, "Review {{name}}");
+
+        // Built-in fallback
+        assert!(Template::load(dir.path(), "rpi").is_ok());
+        assert!(matches!(
+            Template::load(dir.path(), "missing"),
+            Err(TaskError::TemplateNotFound(_))
+        ));
+
+        assert_eq!(list(dir.path()).unwrap(), vec!["review", "rpi"]);
+    }
+
+    #[test]
+    fn test_rejects_names_outside_directory() {
+        let root = TempDir::new().unwrap();
+        let dir = root.path().join("templates");
+        fs::create_dir(&dir).unwrap();
+        fs::write(
+            root.path().join("secret.toml"),
+            "[[task]]\nkey = \"a\"\ntitle = \"A\"\n",
+        )
+        .unwrap();
+
+        for name in ["", "../secret", "..", "sub/name", "sub\\name"] {
+            assert!(
+                matches!(
+                    Template::load(&dir, name),
+                    Err(TaskError::InvalidTemplateName(_))
+                ),
+                "{name:?} was accepted"
+            );
+        }
+    }
+}
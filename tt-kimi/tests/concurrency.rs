@@ -0,0 +1,157 @@
+//! Several `tt` processes writing to the same database at once.
+
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+use tempfile::TempDir;
+use tt::Status;
+use tt::core::TaskTracker;
+
+fn tt(dir: &Path) -> Command {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_tt"));
+    cmd.current_dir(dir);
+    cmd
+}
+
+fn setup() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    let output = tt(dir.path()).arg("init").output().unwrap();
+    assert!(output.status.success(), "{output:?}");
+    dir
+}
+
+/// Spawn every command before waiting on any, so they race for the database
+fn spawn_all(commands: Vec<Command>) -> Vec<Output> {
+    let children: Vec<_> = commands
+        .into_iter()
+        .map(|mut cmd| {
+            cmd.stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+                .unwrap()
+        })
+        .collect();
+    children
+        .into_iter()
+        .map(|child| child.wait_with_output().unwrap())
+        .collect()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+fn tracker(dir: &TempDir) -> TaskTracker {
+    TaskTracker::open_at(dir.path().join("tt.db")).unwrap()
+}
+
+#[test]
+fn concurrent_adds_all_succeed() {
+    let dir = setup();
+
+    let outputs = spawn_all(
+        (0..16)
+            .map(|i| {
+                let mut cmd = tt(dir.path());
+                cmd.args(["add", &format!("Task {i}")]);
+                cmd
+            })
+            .collect(),
+    );
+
+    for output in &outputs {
+        assert!(output.status.success(), "{}", stderr(output));
+    }
+    assert_eq!(tracker(&dir).get_all_tasks().unwrap().len(), 16);
+}
+
+#[test]
+fn concurrent_starts_activate_one_task() {
+    let dir = setup();
+    let tracker = tracker(&dir);
+    let ids: Vec<i64> = (0..8)
+        .map(|i| {
+            tracker
+                .create_task(&format!("Task {i}"), None, None, None, None)
+                .unwrap()
+                .id
+        })
+        .collect();
+
+    let outputs = spawn_all(
+        ids.iter()
+            .map(|id| {
+                let mut cmd = tt(dir.path());
+                cmd.args(["start", &id.to_string()]);
+                cmd
+            })
+            .collect(),
+    );
+
+    let started = outputs.iter().filter(|o| o.status.success()).count();
+    assert_eq!(started, 1);
+    for output in outputs.iter().filter(|o| !o.status.success()) {
+        assert!(
+            stderr(output).contains("is already in progress"),
+            "{}",
+            stderr(output)
+        );
+    }
+
+    let in_progress = tracker
+        .get_all_tasks()
+        .unwrap()
+        .into_iter()
+        .filter(|t| t.status == Status::InProgress)
+        .count();
+    assert_eq!(in_progress, 1);
+}
+
+#[test]
+fn concurrent_edits_with_same_expectation_apply_once() {
+    let dir = setup();
+    let tracker = tracker(&dir);
+    let task = tracker
+        .create_task("Original", None, None, None, None)
+        .unwrap();
+    // Read the timestamp the way a CLI user would
+    let show = tt(dir.path())
+        .args(["show", &task.id.to_string()])
+        .output()
+        .unwrap();
+    let show = String::from_utf8_lossy(&show.stdout).into_owned();
+    let read_at = show
+        .lines()
+        .find_map(|line| line.strip_prefix("Touched:"))
+        .unwrap()
+        .trim()
+        .to_string();
+    assert_eq!(read_at, task.last_touched_at.to_rfc3339());
+
+    let outputs = spawn_all(
+        (0..6)
+            .map(|i| {
+                let mut cmd = tt(dir.path());
+                cmd.args([
+                    "edit",
+                    &task.id.to_string(),
+                    "--title",
+                    &format!("Edit {i}"),
+                    "--expect-touched",
+                    &read_at,
+                ]);
+                cmd
+            })
+            .collect(),
+    );
+
+    let applied = outputs.iter().filter(|o| o.status.success()).count();
+    assert_eq!(applied, 1);
+    for output in outputs.iter().filter(|o| !o.status.success()) {
+        assert!(
+            stderr(output).contains("was modified"),
+            "{}",
+            stderr(output)
+        );
+    }
+    assert_ne!(tracker.get_task(task.id).unwrap().task.title, "Original");
+}
@@ -4,7 +4,7 @@ use std::fs;
 use crate::core::CoreImpl;
 use crate::db::Database;
 use crate::error::{Error, Result};
-use crate::models::Status;
+use crate::models::{Blocker, NextTask, Status, TaskList};
 
 #[derive(Parser)]
 #[command(name = "tt")]
@@ -55,6 +55,14 @@ pub enum Commands {
     Done,
     Block {
         id: i64,
+        #[arg(long)]
+        reason: Option<String>,
+        /// Unblock automatically once this date (YYYY-MM-DD or RFC 3339) has passed
+        #[arg(long, conflicts_with = "on_file")]
+        until: Option<String>,
+        /// Unblock automatically once this file exists
+        #[arg(long)]
+        on_file: Option<String>,
     },
     Unblock {
         id: i64,
@@ -131,7 +139,19 @@ pub fn run() -> std::result::Result<(), Box<dyn std::error::Error>> {
         Commands::Start { id } => cmd_start(id)?,
         Commands::Stop => cmd_stop()?,
         Commands::Done => cmd_done()?,
-        Commands::Block { id } => cmd_block(id)?,
+        Commands::Block {
+            id,
+            reason,
+            until,
+            on_file,
+        } => cmd_block(
+            id,
+            Blocker {
+                reason,
+                until,
+                on_file,
+            },
+        )?,
         Commands::Unblock { id } => cmd_unblock(id)?,
         Commands::Current => cmd_current()?,
         Commands::Depend { id, on_id } => cmd_depend(id, on_id)?,
@@ -199,6 +219,14 @@ fn cmd_show(id: i64) -> Result<()> {
     if let Some(dod) = &task.dod {
         println!("DoD:          {dod}");
     }
+    if let Some(blocker) = &task.blocker {
+        if let Some(reason) = &blocker.reason {
+            println!("Blocked:      {reason}");
+        }
+        if let Some(condition) = blocker.condition() {
+            println!("Unblocks:     {condition}");
+        }
+    }
 
     print!("Dependencies: ");
     if deps.is_empty() {
@@ -239,17 +267,21 @@ fn cmd_show(id: i64) -> Result<()> {
 
 fn cmd_list(all: bool) -> Result<()> {
     let core = get_core()?;
-    let (tasks, target_id, warnings) = core.list_tasks(all)?;
+    let TaskList {
+        tasks,
+        target_id,
+        warnings,
+    } = core.list_tasks(all)?;
 
     if tasks.is_empty() {
         println!("(no tasks)");
         return Ok(());
     }
 
-    if let Some(tid) = target_id {
-        if let Ok(target_task) = get_core()?.show_task(tid) {
-            println!("Target: #{} ({})", tid, target_task.task.title);
-        }
+    if let Some(tid) = target_id
+        && let Ok(target_task) = get_core()?.show_task(tid)
+    {
+        println!("Target: #{} ({})", tid, target_task.task.title);
     }
 
     for task in &tasks {
@@ -298,7 +330,11 @@ fn cmd_next() -> Result<()> {
     let core = get_core()?;
 
     match core.next_task() {
-        Ok((Some(task), _)) => {
+        Ok(NextTask { task, unblocked }) => {
+            for id in unblocked {
+                println!("Unblocked: #{id} (blocker cleared)");
+            }
+
             let deps = core.db.get_dependencies(task.id).unwrap_or_default();
             let dep_status: Vec<(i64, String)> = deps
                 .iter()
@@ -324,15 +360,16 @@ fn cmd_next() -> Result<()> {
                 println!("  DoD: {dod}");
             }
         }
-        Ok((None, _)) => {
-            if let Some(target_id) = core.get_target()? {
-                println!("Target Reached: all tasks for #{target_id} are completed.");
-            }
-        }
-        Err(Error::AllBlocked(ids)) => {
+        Err(Error::AllBlocked(tasks)) => {
             println!("All remaining tasks are blocked:");
-            for id in ids.split(", ") {
-                println!("  #{} ✗ blocked", id.trim_start_matches('#'));
+            for task in tasks {
+                println!(
+                    "  [#{}] {} {} — {}",
+                    task.id,
+                    status_icon(&task.status),
+                    task.title,
+                    task.describe()
+                );
             }
         }
         Err(e) => {
@@ -365,10 +402,18 @@ fn cmd_done() -> Result<()> {
     Ok(())
 }
 
-fn cmd_block(id: i64) -> Result<()> {
+fn cmd_block(id: i64, blocker: Blocker) -> Result<()> {
     let core = get_core()?;
-    let task = core.block_task(id)?;
+    let task = core.block_task(id, blocker)?;
     println!("Blocked: [#{}] {}", task.id, task.title);
+    if let Some(blocker) = &task.blocker {
+        if let Some(reason) = &blocker.reason {
+            println!("  Reason: {reason}");
+        }
+        if let Some(condition) = blocker.condition() {
+            println!("  Unblocks {condition}");
+        }
+    }
     Ok(())
 }
 
@@ -1,9 +1,15 @@
 use std::cmp::Ordering;
 use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::path::Path;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
 
 use crate::db::Database;
 use crate::error::{Error, Result};
-use crate::models::{DependencyInfo, Status, Task, TaskWithDeps};
+use crate::models::{
+    AppliedChanges, BlockedTask, Blocker, Change, DependencyInfo, NextTask, Status, Task, TaskList,
+    TaskWithDeps, Warning,
+};
 
 #[derive(Clone)]
 struct F64Wrapper(f64);
@@ -37,7 +43,7 @@ impl CoreImpl {
         Self { db }
     }
 
-    pub fn topological_sort(&self, tasks: Vec<Task>) -> Result<(Vec<Task>, Vec<String>)> {
+    pub fn topological_sort(&self, tasks: Vec<Task>) -> Result<(Vec<Task>, Vec<Warning>)> {
         if tasks.is_empty() {
             return Ok((vec![], vec![]));
         }
@@ -97,19 +103,21 @@ impl CoreImpl {
         Ok((sorted, warnings))
     }
 
-    fn check_order_conflicts(&self, tasks: &[Task]) -> Vec<String> {
+    fn check_order_conflicts(&self, tasks: &[Task]) -> Vec<Warning> {
         let mut warnings = Vec::new();
 
         for task in tasks {
             if let Ok(deps) = self.db.get_dependencies(task.id) {
                 for dep_id in deps {
-                    if let Ok(dep_task) = self.db.get_task(dep_id) {
-                        if dep_task.manual_order > task.manual_order {
-                            warnings.push(format!(
-                                "Warning: #{} (order {}) depends on #{} (order {}) which has higher manual_order",
-                                task.id, task.manual_order, dep_task.id, dep_task.manual_order
-                            ));
-                        }
+                    if let Ok(dep_task) = self.db.get_task(dep_id)
+                        && dep_task.manual_order > task.manual_order
+                    {
+                        warnings.push(Warning::OrderConflict {
+                            task_id: task.id,
+                            task_order: task.manual_order,
+                            dep_id: dep_task.id,
+                            dep_order: dep_task.manual_order,
+                        });
                     }
                 }
             }
@@ -169,7 +177,7 @@ impl CoreImpl {
         self.get_task_with_deps(id)
     }
 
-    pub fn list_tasks(&self, all: bool) -> Result<(Vec<Task>, Option<i64>, Vec<String>)> {
+    pub fn list_tasks(&self, all: bool) -> Result<TaskList> {
         let target_id = self.db.get_target()?;
 
         let tasks = if all {
@@ -181,9 +189,13 @@ impl CoreImpl {
             }
         };
 
-        let (sorted, warnings) = self.topological_sort(tasks)?;
+        let (tasks, warnings) = self.topological_sort(tasks)?;
 
-        Ok((sorted, target_id, warnings))
+        Ok(TaskList {
+            tasks,
+            target_id,
+            warnings,
+        })
     }
 
     pub fn set_target(&self, id: i64) -> Result<()> {
@@ -250,25 +262,32 @@ impl CoreImpl {
             .update_task_status(task.id, Status::Completed, false, true)
     }
 
-    pub fn block_task(&self, id: i64) -> Result<Task> {
+    /// Block a task, recording why and what releases it.
+    ///
+    /// `blocker.until` accepts a date (`YYYY-MM-DD`), a UTC timestamp
+    /// (`YYYY-MM-DDTHH:MM:SS`) or RFC 3339, and is stored as a UTC timestamp.
+    /// `blocker.on_file` is stored as an absolute path, so the file is found
+    /// whatever directory `next` later runs in.
+    pub fn block_task(&self, id: i64, blocker: Blocker) -> Result<Task> {
         let task = self.db.get_task(id)?;
 
         if task.status != Status::Pending && task.status != Status::InProgress {
             return Err(Error::TaskNotPending(id));
         }
 
-        if task.status == Status::InProgress {
-            let active = self.db.get_active_task().ok();
-            if let Some(a) = active {
-                if a.id == id {
-                    self.db
-                        .update_task_status(id, Status::Blocked, false, false)?;
-                }
-            }
+        if blocker.until.is_some() && blocker.on_file.is_some() {
+            return Err(Error::InvalidBlocker(
+                "use either a date or a file, not both".to_string(),
+            ));
         }
 
-        self.db
-            .update_task_status(id, Status::Blocked, false, false)
+        let blocker = Blocker {
+            until: blocker.until.as_deref().map(parse_until).transpose()?,
+            on_file: blocker.on_file.as_deref().map(absolute_file).transpose()?,
+            ..blocker
+        };
+
+        self.db.set_blocked(id, Some(&blocker))
     }
 
     pub fn unblock_task(&self, id: i64) -> Result<Task> {
@@ -278,8 +297,31 @@ impl CoreImpl {
             return Err(Error::TaskNotBlocked(id));
         }
 
-        self.db
-            .update_task_status(id, Status::Pending, false, false)
+        self.db.set_blocked(id, None)
+    }
+
+    /// Unblock tasks whose date has passed or whose awaited file now exists.
+    pub fn release_blockers(&self, tasks: &[Task]) -> Result<Vec<i64>> {
+        let now = Database::now();
+        let mut released = vec![];
+
+        for task in tasks.iter().filter(|t| t.status == Status::Blocked) {
+            let Some(blocker) = &task.blocker else {
+                continue;
+            };
+            let date_passed = blocker.until.as_deref().is_some_and(|u| u <= now.as_str());
+            let file_exists = blocker
+                .on_file
+                .as_deref()
+                .is_some_and(|p| Path::new(p).exists());
+
+            if date_passed || file_exists {
+                self.unblock_task(task.id)?;
+                released.push(task.id);
+            }
+        }
+
+        Ok(released)
     }
 
     pub fn current_task(&self) -> Result<TaskWithDeps> {
@@ -287,7 +329,11 @@ impl CoreImpl {
         self.get_task_with_deps(task.id)
     }
 
-    pub fn next_task(&self) -> Result<(Option<Task>, Vec<i64>)> {
+    /// Pick the next available task for the target.
+    ///
+    /// Blocked tasks whose release condition is met are unblocked first; their
+    /// ids are returned alongside the next task.
+    pub fn next_task(&self) -> Result<NextTask> {
         let target_id = self.db.get_target()?;
 
         let target_id = match target_id {
@@ -295,12 +341,17 @@ impl CoreImpl {
             None => return Err(Error::NoTarget),
         };
 
-        let tasks = self.db.get_target_subgraph(target_id)?;
+        let mut tasks = self.db.get_target_subgraph(target_id)?;
 
         if tasks.is_empty() || tasks.iter().all(|t| t.status == Status::Completed) {
             return Err(Error::TargetReached(target_id));
         }
 
+        let released = self.release_blockers(&tasks)?;
+        if !released.is_empty() {
+            tasks = self.db.get_target_subgraph(target_id)?;
+        }
+
         let (sorted, _) = self.topological_sort(tasks)?;
 
         let pending: Vec<&Task> = sorted
@@ -324,17 +375,18 @@ impl CoreImpl {
 
         if !available.is_empty() {
             let task = (*available[0]).clone();
-            Ok((Some(task), vec![]))
+            Ok(NextTask {
+                task,
+                unblocked: released,
+            })
         } else {
-            let blocked: Vec<i64> = pending.iter().map(|t| t.id).collect();
-            if !blocked.is_empty() {
-                Err(Error::AllBlocked(
-                    blocked
-                        .iter()
-                        .map(|i| i.to_string())
-                        .collect::<Vec<_>>()
-                        .join(", #"),
-                ))
+            let stuck: Vec<BlockedTask> = sorted
+                .iter()
+                .filter(|t| matches!(t.status, Status::Blocked | Status::Pending))
+                .map(BlockedTask::from)
+                .collect();
+            if !stuck.is_empty() {
+                Err(Error::AllBlocked(stuck))
             } else {
                 Err(Error::TargetReached(target_id))
             }
@@ -361,6 +413,11 @@ impl CoreImpl {
         self.db.remove_dependency(task_id, depends_on)
     }
 
+    /// Apply a batch of changes atomically. See [`Database::apply_changes`].
+    pub fn apply_changes(&self, changes: &[Change]) -> Result<AppliedChanges> {
+        self.db.apply_changes(changes)
+    }
+
     pub fn log_artifact(&self, name: &str, file_path: &str) -> Result<crate::models::Artifact> {
         let task = self.db.get_active_task()?;
         self.db.create_artifact(task.id, name, file_path)
@@ -383,3 +440,37 @@ impl CoreImpl {
         self.db.reindex()
     }
 }
+
+/// Normalize a `--until` value to the `%Y-%m-%dT%H:%M:%S` UTC format used in the database.
+fn parse_until(value: &str) -> Result<String> {
+    const FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_time(NaiveTime::MIN).format(FORMAT).to_string());
+    }
+    if let Ok(datetime) = NaiveDateTime::parse_from_str(value, FORMAT) {
+        return Ok(datetime.format(FORMAT).to_string());
+    }
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Ok(datetime.with_timezone(&Utc).format(FORMAT).to_string());
+    }
+
+    Err(Error::InvalidBlocker(format!(
+        "invalid date '{value}', expected YYYY-MM-DD or RFC 3339"
+    )))
+}
+
+/// Resolve a `--on-file` path against the current directory.
+///
+/// The file itself usually doesn't exist yet, so only its directory is
+/// canonicalized, when that exists.
+fn absolute_file(value: &str) -> Result<String> {
+    let path = std::path::absolute(value)?;
+    let path = match (path.parent(), path.file_name()) {
+        (Some(dir), Some(name)) => dir
+            .canonicalize()
+            .map_or(path.clone(), |dir| dir.join(name)),
+        _ => path,
+    };
+    Ok(path.to_string_lossy().into_owned())
+}
@@ -1,9 +1,12 @@
 use rusqlite::{Connection, params};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::Path;
 use std::sync::Mutex;
 
 use crate::error::{Error, Result};
-use crate::models::{Artifact, Status, Task};
+use crate::models::{AppliedChanges, Artifact, Blocker, Change, Status, Task, TaskRef};
+
+const TASK_COLUMNS: &str = "id, title, description, dod, status, manual_order, created_at, started_at, completed_at, last_touched_at, blocked_reason, blocked_until, blocked_on_file";
 
 pub struct Database {
     conn: Mutex<Connection>,
@@ -42,7 +45,10 @@ impl Database {
                 created_at TEXT NOT NULL,
                 started_at TEXT,
                 completed_at TEXT,
-                last_touched_at TEXT NOT NULL
+                last_touched_at TEXT NOT NULL,
+                blocked_reason TEXT,
+                blocked_until TEXT,
+                blocked_on_file TEXT
             );
 
             CREATE TABLE IF NOT EXISTS dependencies (
@@ -75,13 +81,52 @@ impl Database {
             CREATE INDEX IF NOT EXISTS idx_artifacts_task_id ON artifacts(task_id);
         "#)?;
 
+        Self::migrate(&conn)?;
+
+        Ok(())
+    }
+
+    /// Add columns introduced after the initial schema to existing databases.
+    fn migrate(conn: &Connection) -> Result<()> {
+        let has_blocker: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('tasks') WHERE name = 'blocked_reason'",
+            [],
+            |row| row.get(0),
+        )?;
+
+        if !has_blocker {
+            conn.execute_batch(
+                r#"
+                ALTER TABLE tasks ADD COLUMN blocked_reason TEXT;
+                ALTER TABLE tasks ADD COLUMN blocked_until TEXT;
+                ALTER TABLE tasks ADD COLUMN blocked_on_file TEXT;
+            "#,
+            )?;
+        }
+
         Ok(())
     }
 
-    fn now() -> String {
+    pub(crate) fn now() -> String {
         chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S").to_string()
     }
 
+    fn row_to_task(row: &rusqlite::Row) -> rusqlite::Result<Task> {
+        Ok(Task {
+            id: row.get(0)?,
+            title: row.get(1)?,
+            description: row.get(2)?,
+            dod: row.get(3)?,
+            status: Status::from_db_str(&row.get::<_, String>(4)?).unwrap(),
+            manual_order: row.get(5)?,
+            created_at: row.get(6)?,
+            started_at: row.get(7)?,
+            completed_at: row.get(8)?,
+            last_touched_at: row.get(9)?,
+            blocker: Blocker::from_columns(row.get(10)?, row.get(11)?, row.get(12)?),
+        })
+    }
+
     pub fn create_task(
         &self,
         title: &str,
@@ -91,7 +136,17 @@ impl Database {
         before_id: Option<i64>,
     ) -> Result<Task> {
         let conn = self.conn.lock().unwrap();
+        Self::insert_task(&conn, title, description, dod, after_id, before_id)
+    }
 
+    fn insert_task(
+        conn: &Connection,
+        title: &str,
+        description: Option<&str>,
+        dod: Option<&str>,
+        after_id: Option<i64>,
+        before_id: Option<i64>,
+    ) -> Result<Task> {
         let manual_order = match (after_id, before_id) {
             (Some(after), None) => {
                 let order: f64 = conn.query_row(
@@ -146,22 +201,9 @@ impl Database {
         let id = conn.last_insert_rowid();
 
         let task = conn.query_row(
-            "SELECT id, title, description, dod, status, manual_order, created_at, started_at, completed_at, last_touched_at FROM tasks WHERE id = ?",
+            &format!("SELECT {TASK_COLUMNS} FROM tasks WHERE id = ?"),
             [id],
-            |row| {
-                Ok(Task {
-                    id: row.get(0)?,
-                    title: row.get(1)?,
-                    description: row.get(2)?,
-                    dod: row.get(3)?,
-                    status: Status::from_db_str(&row.get::<_, String>(4)?).unwrap(),
-                    manual_order: row.get(5)?,
-                    created_at: row.get(6)?,
-                    started_at: row.get(7)?,
-                    completed_at: row.get(8)?,
-                    last_touched_at: row.get(9)?,
-                })
-            }
+            Self::row_to_task,
         )?;
 
         Ok(task)
@@ -171,23 +213,11 @@ impl Database {
         let conn = self.conn.lock().unwrap();
 
         conn.query_row(
-            "SELECT id, title, description, dod, status, manual_order, created_at, started_at, completed_at, last_touched_at FROM tasks WHERE id = ?",
+            &format!("SELECT {TASK_COLUMNS} FROM tasks WHERE id = ?"),
             [id],
-            |row| {
-                Ok(Task {
-                    id: row.get(0)?,
-                    title: row.get(1)?,
-                    description: row.get(2)?,
-                    dod: row.get(3)?,
-                    status: Status::from_db_str(&row.get::<_, String>(4)?).unwrap(),
-                    manual_order: row.get(5)?,
-                    created_at: row.get(6)?,
-                    started_at: row.get(7)?,
-                    completed_at: row.get(8)?,
-                    last_touched_at: row.get(9)?,
-                })
-            }
-        ).map_err(|_| Error::TaskNotFound(id))
+            Self::row_to_task,
+        )
+        .map_err(|_| Error::TaskNotFound(id))
     }
 
     pub fn update_task(
@@ -198,6 +228,19 @@ impl Database {
         dod: Option<&str>,
     ) -> Result<Task> {
         let conn = self.conn.lock().unwrap();
+        Self::update_fields(&conn, id, title, description, dod)?;
+
+        drop(conn);
+        self.get_task(id)
+    }
+
+    fn update_fields(
+        conn: &Connection,
+        id: i64,
+        title: Option<&str>,
+        description: Option<&str>,
+        dod: Option<&str>,
+    ) -> Result<()> {
         let now = Self::now();
 
         let mut updates = vec!["last_touched_at = ?".to_string()];
@@ -224,8 +267,7 @@ impl Database {
 
         conn.execute(&sql, params.as_slice())?;
 
-        drop(conn);
-        self.get_task(id)
+        Ok(())
     }
 
     pub fn update_task_status(
@@ -270,27 +312,43 @@ impl Database {
         self.get_task(id)
     }
 
+    /// Block a task for `blocker`, or return it to pending with `None`.
+    ///
+    /// The status and the blocker are written by one statement, so a task is
+    /// never left blocked without its reason or pending with a stale one.
+    pub fn set_blocked(&self, id: i64, blocker: Option<&Blocker>) -> Result<Task> {
+        let conn = self.conn.lock().unwrap();
+        let status = if blocker.is_some() {
+            Status::Blocked
+        } else {
+            Status::Pending
+        };
+
+        conn.execute(
+            "UPDATE tasks SET status = ?, last_touched_at = ?, blocked_reason = ?, blocked_until = ?, blocked_on_file = ? WHERE id = ?",
+            params![
+                status.as_str(),
+                Self::now(),
+                blocker.and_then(|b| b.reason.as_deref()),
+                blocker.and_then(|b| b.until.as_deref()),
+                blocker.and_then(|b| b.on_file.as_deref()),
+                id
+            ],
+        )?;
+
+        drop(conn);
+        self.get_task(id)
+    }
+
     pub fn get_active_task(&self) -> Result<Task> {
         let conn = self.conn.lock().unwrap();
 
         conn.query_row(
-            "SELECT id, title, description, dod, status, manual_order, created_at, started_at, completed_at, last_touched_at FROM tasks WHERE status = 'in_progress'",
+            &format!("SELECT {TASK_COLUMNS} FROM tasks WHERE status = 'in_progress'"),
             [],
-            |row| {
-                Ok(Task {
-                    id: row.get(0)?,
-                    title: row.get(1)?,
-                    description: row.get(2)?,
-                    dod: row.get(3)?,
-                    status: Status::from_db_str(&row.get::<_, String>(4)?).unwrap(),
-                    manual_order: row.get(5)?,
-                    created_at: row.get(6)?,
-                    started_at: row.get(7)?,
-                    completed_at: row.get(8)?,
-                    last_touched_at: row.get(9)?,
-                })
-            }
-        ).map_err(|_| Error::NoActiveTask)
+            Self::row_to_task,
+        )
+        .map_err(|_| Error::NoActiveTask)
     }
 
     pub fn get_all_tasks(&self, filter: crate::models::TaskFilter) -> Result<Vec<Task>> {
@@ -311,10 +369,10 @@ impl Database {
         }
 
         let sql = if conditions.is_empty() {
-            "SELECT id, title, description, dod, status, manual_order, created_at, started_at, completed_at, last_touched_at FROM tasks ORDER BY manual_order".to_string()
+            format!("SELECT {TASK_COLUMNS} FROM tasks ORDER BY manual_order")
         } else {
             format!(
-                "SELECT id, title, description, dod, status, manual_order, created_at, started_at, completed_at, last_touched_at FROM tasks WHERE {} ORDER BY manual_order",
+                "SELECT {TASK_COLUMNS} FROM tasks WHERE {} ORDER BY manual_order",
                 conditions.join(" AND ")
             )
         };
@@ -322,20 +380,7 @@ impl Database {
         let mut stmt = conn.prepare(&sql)?;
 
         let tasks = stmt
-            .query_map([], |row| {
-                Ok(Task {
-                    id: row.get(0)?,
-                    title: row.get(1)?,
-                    description: row.get(2)?,
-                    dod: row.get(3)?,
-                    status: Status::from_db_str(&row.get::<_, String>(4)?).unwrap(),
-                    manual_order: row.get(5)?,
-                    created_at: row.get(6)?,
-                    started_at: row.get(7)?,
-                    completed_at: row.get(8)?,
-                    last_touched_at: row.get(9)?,
-                })
-            })?
+            .query_map([], Self::row_to_task)?
             .collect::<std::result::Result<Vec<_>, _>>()?;
 
         Ok(tasks)
@@ -369,7 +414,7 @@ impl Database {
 
         let placeholders: Vec<String> = task_ids.iter().map(|_| "?".to_string()).collect();
         let sql = format!(
-            "SELECT id, title, description, dod, status, manual_order, created_at, started_at, completed_at, last_touched_at FROM tasks WHERE id IN ({}) AND status != 'completed' ORDER BY manual_order",
+            "SELECT {TASK_COLUMNS} FROM tasks WHERE id IN ({}) AND status != 'completed' ORDER BY manual_order",
             placeholders.join(", ")
         );
 
@@ -380,20 +425,7 @@ impl Database {
             .collect();
 
         let tasks = stmt
-            .query_map(params.as_slice(), |row| {
-                Ok(Task {
-                    id: row.get(0)?,
-                    title: row.get(1)?,
-                    description: row.get(2)?,
-                    dod: row.get(3)?,
-                    status: Status::from_db_str(&row.get::<_, String>(4)?).unwrap(),
-                    manual_order: row.get(5)?,
-                    created_at: row.get(6)?,
-                    started_at: row.get(7)?,
-                    completed_at: row.get(8)?,
-                    last_touched_at: row.get(9)?,
-                })
-            })?
+            .query_map(params.as_slice(), Self::row_to_task)?
             .collect::<std::result::Result<Vec<_>, _>>()?;
 
         Ok(tasks)
@@ -524,6 +556,194 @@ impl Database {
         }
     }
 
+    /// Apply a batch of changes in a single transaction.
+    ///
+    /// Changes may refer to tasks created earlier in the batch by their
+    /// temporary id. The dependency graph is checked for cycles once the whole
+    /// batch is applied, so edges may be added in any order. If any change
+    /// fails nothing is written.
+    pub fn apply_changes(&self, changes: &[Change]) -> Result<AppliedChanges> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        let mut ids = BTreeMap::new();
+        let mut added = vec![];
+
+        for (index, change) in changes.iter().enumerate() {
+            let resolve = |task: &TaskRef| Self::resolve_ref(&tx, &ids, index, task);
+
+            match change {
+                Change::CreateTask {
+                    temp_id,
+                    title,
+                    description,
+                    dod,
+                    after_id,
+                    before_id,
+                } => {
+                    let after_id = after_id.as_ref().map(resolve).transpose()?;
+                    let before_id = before_id.as_ref().map(resolve).transpose()?;
+                    if let Some(temp_id) = temp_id {
+                        if !temp_id.starts_with('$') {
+                            return Err(Error::InvalidChange(
+                                index,
+                                format!("temporary id '{temp_id}' must start with '$'"),
+                            ));
+                        }
+                        if ids.contains_key(temp_id) {
+                            return Err(Error::InvalidChange(
+                                index,
+                                format!("temporary id '{temp_id}' is already used"),
+                            ));
+                        }
+                    }
+
+                    let task = Self::insert_task(
+                        &tx,
+                        title,
+                        description.as_deref(),
+                        dod.as_deref(),
+                        after_id,
+                        before_id,
+                    )?;
+                    if let Some(temp_id) = temp_id {
+                        ids.insert(temp_id.clone(), task.id);
+                    }
+                }
+                Change::EditTask {
+                    id,
+                    title,
+                    description,
+                    dod,
+                } => {
+                    let id = resolve(id)?;
+                    Self::update_fields(
+                        &tx,
+                        id,
+                        title.as_deref(),
+                        description.as_deref(),
+                        dod.as_deref(),
+                    )?;
+                }
+                Change::AddDependency {
+                    task_id,
+                    depends_on,
+                } => {
+                    let (task_id, depends_on) = (resolve(task_id)?, resolve(depends_on)?);
+                    if task_id == depends_on {
+                        return Err(Error::CycleDetected(
+                            task_id,
+                            depends_on,
+                            format_cycle(&[task_id, task_id]),
+                        ));
+                    }
+                    tx.execute(
+                        "INSERT OR IGNORE INTO dependencies (task_id, depends_on) VALUES (?, ?)",
+                        [task_id, depends_on],
+                    )?;
+                    added.push((task_id, depends_on));
+                }
+                Change::RemoveDependency {
+                    task_id,
+                    depends_on,
+                } => {
+                    let (task_id, depends_on) = (resolve(task_id)?, resolve(depends_on)?);
+                    tx.execute(
+                        "DELETE FROM dependencies WHERE task_id = ? AND depends_on = ?",
+                        [task_id, depends_on],
+                    )?;
+                }
+                Change::SetTarget { id } => {
+                    let id = resolve(id)?;
+                    tx.execute(
+                        "INSERT OR REPLACE INTO config (key, value) VALUES ('target_id', ?)",
+                        [id.to_string()],
+                    )?;
+                }
+            }
+        }
+
+        if let Some(cycle) = Self::find_cycle(&tx, &added)? {
+            return Err(Error::CycleDetected(
+                cycle[0],
+                cycle[1],
+                format_cycle(&cycle),
+            ));
+        }
+
+        tx.commit()?;
+
+        Ok(AppliedChanges {
+            ids,
+            applied: changes.len(),
+        })
+    }
+
+    fn resolve_ref(
+        conn: &Connection,
+        ids: &BTreeMap<String, i64>,
+        index: usize,
+        task: &TaskRef,
+    ) -> Result<i64> {
+        match task {
+            TaskRef::Id(id) => {
+                conn.query_row("SELECT id FROM tasks WHERE id = ?", [id], |_| Ok(()))
+                    .map_err(|_| Error::TaskNotFound(*id))?;
+                Ok(*id)
+            }
+            TaskRef::Temp(name) => ids.get(name).copied().ok_or_else(|| {
+                Error::InvalidChange(index, format!("unknown temporary id '{name}'"))
+            }),
+        }
+    }
+
+    /// Find a cycle through any of the `added` edges, as the path
+    /// `task_id → depends_on → … → task_id`.
+    fn find_cycle(conn: &Connection, added: &[(i64, i64)]) -> Result<Option<Vec<i64>>> {
+        if added.is_empty() {
+            return Ok(None);
+        }
+
+        let mut deps: HashMap<i64, Vec<i64>> = HashMap::new();
+        let mut stmt = conn.prepare("SELECT task_id, depends_on FROM dependencies")?;
+        for edge in stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))? {
+            let (task_id, depends_on): (i64, i64) = edge?;
+            deps.entry(task_id).or_default().push(depends_on);
+        }
+
+        for &(task_id, depends_on) in added {
+            if !deps.get(&task_id).is_some_and(|d| d.contains(&depends_on)) {
+                continue;
+            }
+
+            // Walk the prerequisites of `depends_on` looking for `task_id`.
+            let mut parent = HashMap::new();
+            let mut visited = HashSet::from([depends_on]);
+            let mut stack = vec![depends_on];
+            while let Some(current) = stack.pop() {
+                if current == task_id {
+                    let mut path = vec![task_id];
+                    let mut node = task_id;
+                    while node != depends_on {
+                        node = parent[&node];
+                        path.push(node);
+                    }
+                    path.push(task_id);
+                    path.reverse();
+                    return Ok(Some(path));
+                }
+                for &next in deps.get(&current).into_iter().flatten() {
+                    if visited.insert(next) {
+                        parent.insert(next, current);
+                        stack.push(next);
+                    }
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
     pub fn create_artifact(&self, task_id: i64, name: &str, file_path: &str) -> Result<Artifact> {
         let conn = self.conn.lock().unwrap();
         let now = Self::now();
@@ -658,3 +878,10 @@ impl Database {
         Ok(new_order)
     }
 }
+
+fn format_cycle(path: &[i64]) -> String {
+    path.iter()
+        .map(|id| format!("#{id}"))
+        .collect::<Vec<_>>()
+        .join(" → ")
+}
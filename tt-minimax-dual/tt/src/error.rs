@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use crate::models::{BlockedTask, format_blocked};
+
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("Task #{0} not found")]
@@ -32,8 +34,11 @@ pub enum Error {
     #[error("Invalid status: {0}")]
     InvalidStatus(String),
 
-    #[error("All remaining tasks are blocked: #{0}")]
-    AllBlocked(String),
+    #[error("All remaining tasks are blocked: {}", format_blocked(.0))]
+    AllBlocked(Vec<BlockedTask>),
+
+    #[error("Invalid blocker: {0}")]
+    InvalidBlocker(String),
 
     #[error("Database error: {0}")]
     Db(#[from] rusqlite::Error),
@@ -55,6 +60,9 @@ pub enum Error {
 
     #[error("Cannot reorder: {0}")]
     ReorderError(String),
+
+    #[error("Change {0}: {1}")]
+    InvalidChange(usize, String),
 }
 
 impl Error {
@@ -71,6 +79,7 @@ impl Error {
             Error::NoDod(_) => "NoDod",
             Error::InvalidStatus(_) => "InvalidStatus",
             Error::AllBlocked(_) => "AllBlocked",
+            Error::InvalidBlocker(_) => "InvalidBlocker",
             Error::Db(_) => "Db",
             Error::Io(_) => "Io",
             Error::Serde(_) => "Serde",
@@ -78,6 +87,7 @@ impl Error {
             Error::TaskNotBlocked(_) => "TaskNotBlocked",
             Error::TaskAlreadyCompleted(_) => "TaskAlreadyCompleted",
             Error::ReorderError(_) => "ReorderError",
+            Error::InvalidChange(_, _) => "InvalidChange",
         }
     }
 }
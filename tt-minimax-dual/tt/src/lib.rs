@@ -5,8 +5,10 @@ pub mod error;
 pub mod mcp;
 pub mod models;
 pub mod tests;
+pub mod tracker;
 
 pub use core::CoreImpl;
 pub use db::Database;
 pub use error::{Error, Result};
 pub use models::*;
+pub use tracker::{Subscriber, Tracker, TrackerBuilder};
@@ -5,7 +5,7 @@ use std::io::{self, Write};
 use crate::core::CoreImpl;
 use crate::db::Database;
 use crate::error::{Error, Result};
-use crate::models::McpResponse;
+use crate::models::{Blocker, Change, McpResponse, format_blocked};
 
 #[derive(Debug, Deserialize)]
 struct JsonRpcRequest {
@@ -43,17 +43,16 @@ fn handle_tool(name: &str, params: Value) -> McpResponse {
 
     match name {
         "get_next_task" => match core.next_task() {
-            Ok((Some(task), _)) => McpResponse::ok(serde_json::json!({
-                "task": task,
+            Ok(next) => McpResponse::ok(serde_json::json!({
+                "task": next.task,
+                "unblocked": next.unblocked,
                 "message": "Next task available"
             })),
-            Ok((None, _)) => McpResponse::ok(serde_json::json!({
-                "message": "Target reached",
-                "target_reached": true
-            })),
-            Err(Error::AllBlocked(ids)) => {
-                McpResponse::error("AllBlocked", format!("Blocked tasks: {ids}"))
-            }
+            Err(Error::AllBlocked(tasks)) => McpResponse::error(
+                "AllBlocked",
+                format!("Blocked tasks: {}", format_blocked(&tasks)),
+            )
+            .with_data(serde_json::json!({ "blocked": tasks })),
             Err(Error::NoTarget) => McpResponse::error("NoTarget", "No target set"),
             Err(e) => McpResponse::error("Error", e.to_string()),
         },
@@ -164,9 +163,42 @@ fn handle_tool(name: &str, params: Value) -> McpResponse {
             }
         }
 
+        "apply_changes" => {
+            let changes = match parse_changes(&params) {
+                Ok(changes) => changes,
+                Err(response) => return response,
+            };
+
+            match core.apply_changes(&changes) {
+                Ok(applied) => McpResponse::ok(applied),
+                Err(Error::TaskNotFound(id)) => {
+                    McpResponse::error("TaskNotFound", format!("Task #{id} not found"))
+                }
+                Err(Error::CycleDetected(_, _, cycle)) => {
+                    McpResponse::error("CycleDetected", cycle)
+                }
+                Err(e @ Error::InvalidChange(index, _)) => {
+                    McpResponse::error("InvalidChange", e.to_string())
+                        .with_data(serde_json::json!({ "index": index }))
+                }
+                Err(e) => McpResponse::error("Error", e.to_string()),
+            }
+        }
+
         "block_task" => {
             let id = params.get("id").and_then(|v| v.as_i64()).unwrap_or(0);
-            match core.block_task(id) {
+            let field = |name: &str| {
+                params
+                    .get(name)
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string())
+            };
+            let blocker = Blocker {
+                reason: field("reason"),
+                until: field("until"),
+                on_file: field("on_file"),
+            };
+            match core.block_task(id, blocker) {
                 Ok(task) => McpResponse::ok(task),
                 Err(Error::TaskNotFound(id)) => {
                     McpResponse::error("TaskNotFound", format!("Task #{id} not found"))
@@ -192,11 +224,7 @@ fn handle_tool(name: &str, params: Value) -> McpResponse {
         "list_tasks" => {
             let all = params.get("all").and_then(|v| v.as_bool()).unwrap_or(false);
             match core.list_tasks(all) {
-                Ok((tasks, target_id, warnings)) => McpResponse::ok(serde_json::json!({
-                    "tasks": tasks,
-                    "target_id": target_id,
-                    "warnings": warnings
-                })),
+                Ok(list) => McpResponse::ok(list),
                 Err(Error::NoTarget) => McpResponse::error("NoTarget", "No target set"),
                 Err(e) => McpResponse::error("Error", e.to_string()),
             }
@@ -263,6 +291,18 @@ fn handle_tool(name: &str, params: Value) -> McpResponse {
     }
 }
 
+/// Read the `changes` argument of `apply_changes`, which is required.
+pub(crate) fn parse_changes(params: &Value) -> std::result::Result<Vec<Change>, McpResponse> {
+    let Some(changes) = params.get("changes") else {
+        return Err(McpResponse::error(
+            "InvalidParams",
+            "Missing required argument: changes",
+        ));
+    };
+    serde_json::from_value(changes.clone())
+        .map_err(|e| McpResponse::error("InvalidChange", e.to_string()))
+}
+
 fn handle_initialize() -> Value {
     serde_json::json!({
         "protocolVersion": "2024-11-05",
@@ -378,13 +418,46 @@ fn handle_tools_list() -> Value {
                 "required": ["task_id", "depends_on"]
             }
         }),
+        serde_json::json!({
+            "name": "apply_changes",
+            "description": "Applies a list of changes atomically: either all succeed or none are written. Give new tasks a temporary id like \"$a\" and use it in later changes instead of a task ID. Dependencies are checked for cycles after the whole batch. Returns the real ID of each temporary id.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "changes": {
+                        "type": "array",
+                        "description": "Changes to apply in order",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "op": {"type": "string", "enum": ["create_task", "edit_task", "add_dependency", "remove_dependency", "set_target"]},
+                                "temp_id": {"type": "string", "description": "create_task: temporary id starting with $"},
+                                "id": {"type": ["integer", "string"], "description": "edit_task, set_target: task ID or temporary id"},
+                                "title": {"type": "string"},
+                                "description": {"type": "string"},
+                                "dod": {"type": "string", "description": "Definition of Done"},
+                                "after_id": {"type": ["integer", "string"], "description": "create_task: insert after this task"},
+                                "before_id": {"type": ["integer", "string"], "description": "create_task: insert before this task"},
+                                "task_id": {"type": ["integer", "string"], "description": "add/remove_dependency: the dependent task"},
+                                "depends_on": {"type": ["integer", "string"], "description": "add/remove_dependency: the prerequisite task"}
+                            },
+                            "required": ["op"]
+                        }
+                    }
+                },
+                "required": ["changes"]
+            }
+        }),
         serde_json::json!({
             "name": "block_task",
-            "description": "Blocks a task, moving it to blocked status.",
+            "description": "Blocks a task, moving it to blocked status. Optionally records why and when it should be unblocked automatically.",
             "inputSchema": {
                 "type": "object",
                 "properties": {
-                    "id": {"type": "integer", "description": "Task ID to block"}
+                    "id": {"type": "integer", "description": "Task ID to block"},
+                    "reason": {"type": "string", "description": "Why the task is blocked"},
+                    "until": {"type": "string", "description": "Unblock once this date (YYYY-MM-DD or RFC 3339) has passed"},
+                    "on_file": {"type": "string", "description": "Unblock once this file exists. Cannot be combined with until."}
                 },
                 "required": ["id"]
             }
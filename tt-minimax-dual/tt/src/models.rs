@@ -42,6 +42,90 @@ pub struct Task {
     pub started_at: Option<String>,
     pub completed_at: Option<String>,
     pub last_touched_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blocker: Option<Blocker>,
+}
+
+/// Why a task is blocked and, optionally, the condition that releases it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Blocker {
+    pub reason: Option<String>,
+    /// Timestamp (`%Y-%m-%dT%H:%M:%S`, UTC) after which the task is unblocked.
+    pub until: Option<String>,
+    /// Path whose existence unblocks the task.
+    pub on_file: Option<String>,
+}
+
+impl Blocker {
+    pub fn from_columns(
+        reason: Option<String>,
+        until: Option<String>,
+        on_file: Option<String>,
+    ) -> Option<Self> {
+        if reason.is_none() && until.is_none() && on_file.is_none() {
+            return None;
+        }
+        Some(Self {
+            reason,
+            until,
+            on_file,
+        })
+    }
+
+    /// Describe the release condition, e.g. "after 2026-10-20T00:00:00".
+    pub fn condition(&self) -> Option<String> {
+        match (&self.until, &self.on_file) {
+            (Some(until), _) => Some(format!("after {until}")),
+            (None, Some(path)) => Some(format!("when {path} exists")),
+            (None, None) => None,
+        }
+    }
+}
+
+/// A task reported by `AllBlocked`, with the reason it cannot proceed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockedTask {
+    pub id: i64,
+    pub title: String,
+    pub status: Status,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blocker: Option<Blocker>,
+}
+
+impl BlockedTask {
+    pub fn describe(&self) -> String {
+        let Some(blocker) = &self.blocker else {
+            return match self.status {
+                Status::Blocked => "blocked".to_string(),
+                _ => "waiting on dependencies".to_string(),
+            };
+        };
+        let reason = blocker.reason.as_deref().unwrap_or("blocked");
+        match blocker.condition() {
+            Some(condition) => format!("{reason} ({condition})"),
+            None => reason.to_string(),
+        }
+    }
+}
+
+/// Render blocked tasks as "#12 (waiting on vendor), #14 (blocked)".
+pub fn format_blocked(tasks: &[BlockedTask]) -> String {
+    tasks
+        .iter()
+        .map(|t| format!("#{} ({})", t.id, t.describe()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl From<&Task> for BlockedTask {
+    fn from(task: &Task) -> Self {
+        Self {
+            id: task.id,
+            title: task.title.clone(),
+            status: task.status.clone(),
+            blocker: task.blocker.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -67,6 +151,51 @@ pub struct Artifact {
     pub created_at: String,
 }
 
+/// Something worth flagging about the task graph that does not stop the operation.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Warning {
+    /// `task_id` depends on `dep_id`, but `dep_id` is ordered after it.
+    OrderConflict {
+        task_id: i64,
+        task_order: f64,
+        dep_id: i64,
+        dep_order: f64,
+    },
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Warning::OrderConflict {
+                task_id,
+                task_order,
+                dep_id,
+                dep_order,
+            } => write!(
+                f,
+                "Warning: #{task_id} (order {task_order}) depends on #{dep_id} (order {dep_order}) which has higher manual_order"
+            ),
+        }
+    }
+}
+
+/// Tasks in execution order, as returned by `list_tasks`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskList {
+    pub tasks: Vec<Task>,
+    pub target_id: Option<i64>,
+    pub warnings: Vec<Warning>,
+}
+
+/// The task to work on next, as returned by `next_task`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NextTask {
+    pub task: Task,
+    /// Blocked tasks released because their date passed or their file appeared.
+    pub unblocked: Vec<i64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ListOptions {
     pub all: bool,
@@ -122,6 +251,54 @@ pub struct ReorderOptions {
     pub before_id: Option<i64>,
 }
 
+/// A task referenced by its real id or by a `$name` temporary id from the same batch.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum TaskRef {
+    Id(i64),
+    Temp(String),
+}
+
+/// One operation of an `apply_changes` batch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Change {
+    CreateTask {
+        /// Temporary id (`$a`) later operations can use to refer to this task.
+        temp_id: Option<String>,
+        title: String,
+        description: Option<String>,
+        dod: Option<String>,
+        after_id: Option<TaskRef>,
+        before_id: Option<TaskRef>,
+    },
+    EditTask {
+        id: TaskRef,
+        title: Option<String>,
+        description: Option<String>,
+        dod: Option<String>,
+    },
+    AddDependency {
+        task_id: TaskRef,
+        depends_on: TaskRef,
+    },
+    RemoveDependency {
+        task_id: TaskRef,
+        depends_on: TaskRef,
+    },
+    SetTarget {
+        id: TaskRef,
+    },
+}
+
+/// Result of an `apply_changes` batch.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AppliedChanges {
+    /// Real ids of the tasks created with a temporary id.
+    pub ids: std::collections::BTreeMap<String, i64>,
+    pub applied: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct McpResponse {
     pub status: String,
@@ -148,4 +325,10 @@ impl McpResponse {
             message: Some(message.into()),
         }
     }
+
+    /// Attach structured details to an error response.
+    pub fn with_data<T: Serialize>(mut self, data: T) -> Self {
+        self.data = Some(serde_json::to_value(data).unwrap_or(serde_json::Value::Null));
+        self
+    }
 }
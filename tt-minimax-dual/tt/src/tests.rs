@@ -1,10 +1,19 @@
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
+    use std::sync::{Arc, Mutex};
+
     use tempfile::TempDir;
 
     use crate::core::CoreImpl;
     use crate::db::Database;
-    use crate::models::Status;
+    use crate::error::Error;
+    use crate::mcp::parse_changes;
+    use crate::models::{
+        Blocker, Change, CreateTaskOptions, ListOptions, ReorderOptions, Status, Task, TaskFilter,
+        Warning,
+    };
+    use crate::tracker::{Subscriber, Tracker};
 
     fn setup_test() -> (TempDir, CoreImpl) {
         let temp_dir = TempDir::new().unwrap();
@@ -161,9 +170,8 @@ mod tests {
         core.add_dependency(task2.id, task1.id).unwrap();
         core.set_target(task2.id).unwrap();
 
-        let (next, _) = core.next_task().unwrap();
-        assert!(next.is_some());
-        assert_eq!(next.unwrap().id, task1.id);
+        let next = core.next_task().unwrap();
+        assert_eq!(next.task.id, task1.id);
     }
 
     #[test]
@@ -171,13 +179,201 @@ mod tests {
         let (_temp, core) = setup_test();
         let task = core.add_task("Task", None, None, None, None).unwrap();
 
-        let blocked = core.block_task(task.id).unwrap();
+        let blocked = core.block_task(task.id, Blocker::default()).unwrap();
         assert_eq!(blocked.status, Status::Blocked);
 
         let unblocked = core.unblock_task(task.id).unwrap();
         assert_eq!(unblocked.status, Status::Pending);
     }
 
+    #[test]
+    fn test_block_stores_and_clears_blocker() {
+        let (_temp, core) = setup_test();
+        let task = core.add_task("Task", None, None, None, None).unwrap();
+
+        let blocked = core
+            .block_task(
+                task.id,
+                Blocker {
+                    reason: Some("waiting on vendor".to_string()),
+                    until: Some("2999-01-15".to_string()),
+                    on_file: None,
+                },
+            )
+            .unwrap();
+        let blocker = blocked.blocker.unwrap();
+        assert_eq!(blocker.reason.as_deref(), Some("waiting on vendor"));
+        assert_eq!(blocker.until.as_deref(), Some("2999-01-15T00:00:00"));
+
+        let unblocked = core.unblock_task(task.id).unwrap();
+        assert!(unblocked.blocker.is_none());
+    }
+
+    #[test]
+    fn test_block_stores_absolute_file_path() {
+        let (_temp, core) = setup_test();
+        let task = core.add_task("Task", None, None, None, None).unwrap();
+
+        let blocked = core
+            .block_task(
+                task.id,
+                Blocker {
+                    on_file: Some("src/../approved".to_string()),
+                    ..Blocker::default()
+                },
+            )
+            .unwrap();
+        let expected = std::env::current_dir()
+            .unwrap()
+            .canonicalize()
+            .unwrap()
+            .join("approved");
+        assert_eq!(
+            blocked.blocker.unwrap().on_file.as_deref(),
+            Some(expected.to_str().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_block_rejects_invalid_blocker() {
+        let (_temp, core) = setup_test();
+        let task = core.add_task("Task", None, None, None, None).unwrap();
+
+        let bad_date = Blocker {
+            until: Some("next tuesday".to_string()),
+            ..Blocker::default()
+        };
+        assert!(matches!(
+            core.block_task(task.id, bad_date),
+            Err(Error::InvalidBlocker(_))
+        ));
+
+        let both = Blocker {
+            until: Some("2999-01-15".to_string()),
+            on_file: Some("done.txt".to_string()),
+            ..Blocker::default()
+        };
+        assert!(matches!(
+            core.block_task(task.id, both),
+            Err(Error::InvalidBlocker(_))
+        ));
+        assert_eq!(core.db.get_task(task.id).unwrap().status, Status::Pending);
+    }
+
+    #[test]
+    fn test_next_releases_expired_and_file_blockers() {
+        let (temp, core) = setup_test();
+        let task1 = core
+            .add_task("Task 1", None, Some("DoD"), None, None)
+            .unwrap();
+        let task2 = core
+            .add_task("Task 2", None, Some("DoD"), None, None)
+            .unwrap();
+        let target = core
+            .add_task("Target", None, Some("DoD"), None, None)
+            .unwrap();
+        core.add_dependency(target.id, task1.id).unwrap();
+        core.add_dependency(target.id, task2.id).unwrap();
+        core.set_target(target.id).unwrap();
+
+        let marker = temp.path().join("approved");
+        core.block_task(
+            task1.id,
+            Blocker {
+                until: Some("2000-01-01T09:30:00Z".to_string()),
+                ..Blocker::default()
+            },
+        )
+        .unwrap();
+        core.block_task(
+            task2.id,
+            Blocker {
+                on_file: Some(marker.to_string_lossy().into_owned()),
+                ..Blocker::default()
+            },
+        )
+        .unwrap();
+
+        let next = core.next_task().unwrap();
+        assert_eq!(next.task.id, task1.id);
+        assert_eq!(next.unblocked, vec![task1.id]);
+        assert_eq!(core.db.get_task(task2.id).unwrap().status, Status::Blocked);
+
+        std::fs::write(&marker, "").unwrap();
+        let next = core.next_task().unwrap();
+        assert_eq!(next.unblocked, vec![task2.id]);
+        assert!(core.db.get_task(task2.id).unwrap().blocker.is_none());
+    }
+
+    #[test]
+    fn test_all_blocked_includes_reasons() {
+        let (_temp, core) = setup_test();
+        let task1 = core
+            .add_task("Task 1", None, Some("DoD"), None, None)
+            .unwrap();
+        let task2 = core
+            .add_task("Task 2", None, Some("DoD"), None, None)
+            .unwrap();
+        core.add_dependency(task2.id, task1.id).unwrap();
+        core.set_target(task2.id).unwrap();
+
+        core.block_task(
+            task1.id,
+            Blocker {
+                reason: Some("waiting on API keys".to_string()),
+                until: Some("2999-01-01".to_string()),
+                on_file: None,
+            },
+        )
+        .unwrap();
+
+        let Err(Error::AllBlocked(tasks)) = core.next_task() else {
+            panic!("expected AllBlocked");
+        };
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].id, task1.id);
+        assert_eq!(
+            tasks[0].describe(),
+            "waiting on API keys (after 2999-01-01T00:00:00)"
+        );
+        assert_eq!(tasks[1].describe(), "waiting on dependencies");
+
+        let message = Error::AllBlocked(tasks).to_string();
+        assert!(message.contains("waiting on API keys"));
+    }
+
+    #[test]
+    fn test_migrates_schema_without_blocker_columns() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("tt.db");
+        {
+            let conn = rusqlite::Connection::open(&db_path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE tasks (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    title TEXT NOT NULL,
+                    description TEXT,
+                    dod TEXT,
+                    status TEXT NOT NULL DEFAULT 'pending',
+                    manual_order REAL NOT NULL DEFAULT 10.0,
+                    created_at TEXT NOT NULL,
+                    started_at TEXT,
+                    completed_at TEXT,
+                    last_touched_at TEXT NOT NULL
+                );
+                INSERT INTO tasks (title, status, created_at, last_touched_at)
+                VALUES ('Old', 'blocked', '2024-01-01T00:00:00', '2024-01-01T00:00:00');",
+            )
+            .unwrap();
+        }
+
+        let core = CoreImpl::new(Database::new(&db_path).unwrap());
+        let task = core.db.get_task(1).unwrap();
+        assert_eq!(task.status, Status::Blocked);
+        assert!(task.blocker.is_none());
+        assert_eq!(core.unblock_task(1).unwrap().status, Status::Pending);
+    }
+
     #[test]
     fn test_artifacts() {
         let (_temp, core) = setup_test();
@@ -223,4 +419,296 @@ mod tests {
         assert_eq!(tasks[0].id, task_a.id);
         assert_eq!(tasks[1].id, task_b.id);
     }
+
+    fn changes(value: serde_json::Value) -> Vec<Change> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_apply_changes_resolves_temp_ids() {
+        let (_temp, core) = setup_test();
+        let existing = core
+            .add_task("Existing", None, Some("DoD"), None, None)
+            .unwrap();
+
+        let applied = core
+            .apply_changes(&changes(serde_json::json!([
+                {"op": "create_task", "temp_id": "$feature", "title": "Feature", "dod": "Works"},
+                {"op": "create_task", "temp_id": "$design", "title": "Design", "dod": "Written",
+                 "before_id": "$feature"},
+                {"op": "add_dependency", "task_id": "$feature", "depends_on": "$design"},
+                {"op": "add_dependency", "task_id": "$design", "depends_on": existing.id},
+                {"op": "edit_task", "id": existing.id, "title": "Existing, renamed"},
+                {"op": "set_target", "id": "$feature"},
+            ])))
+            .unwrap();
+
+        assert_eq!(applied.applied, 6);
+        let feature = applied.ids["$feature"];
+        let design = applied.ids["$design"];
+        assert_eq!(core.db.get_dependencies(feature).unwrap(), vec![design]);
+        assert_eq!(core.db.get_dependencies(design).unwrap(), vec![existing.id]);
+        assert_eq!(core.get_target().unwrap(), Some(feature));
+        assert_eq!(
+            core.db.get_task(existing.id).unwrap().title,
+            "Existing, renamed"
+        );
+        assert!(
+            core.db.get_task(design).unwrap().manual_order
+                < core.db.get_task(feature).unwrap().manual_order
+        );
+    }
+
+    #[test]
+    fn test_apply_changes_rejects_cycle_as_batch() {
+        let (_temp, core) = setup_test();
+
+        let result = core.apply_changes(&changes(serde_json::json!([
+            {"op": "create_task", "temp_id": "$a", "title": "A"},
+            {"op": "create_task", "temp_id": "$b", "title": "B"},
+            {"op": "create_task", "temp_id": "$c", "title": "C"},
+            {"op": "add_dependency", "task_id": "$a", "depends_on": "$b"},
+            {"op": "add_dependency", "task_id": "$b", "depends_on": "$c"},
+            {"op": "add_dependency", "task_id": "$c", "depends_on": "$a"},
+        ])));
+
+        match result {
+            Err(Error::CycleDetected(_, _, cycle)) => assert_eq!(cycle, "#1 → #2 → #3 → #1"),
+            other => panic!("expected a cycle, got {other:?}"),
+        }
+        assert!(core.db.get_all_tasks(TaskFilter::all()).unwrap().is_empty());
+
+        // An edge that is removed again within the batch does not count
+        let applied = core
+            .apply_changes(&changes(serde_json::json!([
+                {"op": "create_task", "temp_id": "$a", "title": "A"},
+                {"op": "create_task", "temp_id": "$b", "title": "B"},
+                {"op": "add_dependency", "task_id": "$a", "depends_on": "$b"},
+                {"op": "add_dependency", "task_id": "$b", "depends_on": "$a"},
+                {"op": "remove_dependency", "task_id": "$a", "depends_on": "$b"},
+            ])))
+            .unwrap();
+        let (a, b) = (applied.ids["$a"], applied.ids["$b"]);
+        assert_eq!(core.db.get_dependencies(b).unwrap(), vec![a]);
+        assert!(core.db.get_dependencies(a).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_apply_changes_detects_cycle_through_existing_tasks() {
+        let (_temp, core) = setup_test();
+        let task1 = core.add_task("Task 1", None, None, None, None).unwrap();
+        let task2 = core.add_task("Task 2", None, None, None, None).unwrap();
+        core.add_dependency(task2.id, task1.id).unwrap();
+
+        let result = core.apply_changes(&changes(serde_json::json!([
+            {"op": "create_task", "temp_id": "$new", "title": "New"},
+            {"op": "add_dependency", "task_id": task1.id, "depends_on": "$new"},
+            {"op": "add_dependency", "task_id": "$new", "depends_on": task2.id},
+        ])));
+
+        assert!(matches!(result, Err(Error::CycleDetected(..))));
+        assert_eq!(core.db.get_all_tasks(TaskFilter::all()).unwrap().len(), 2);
+        assert!(core.db.get_dependencies(task1.id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_apply_changes_rolls_back_on_invalid_change() {
+        let (_temp, core) = setup_test();
+
+        let result = core.apply_changes(&changes(serde_json::json!([
+            {"op": "create_task", "temp_id": "$a", "title": "A"},
+            {"op": "add_dependency", "task_id": "$a", "depends_on": "$missing"},
+        ])));
+        assert!(matches!(result, Err(Error::InvalidChange(1, _))));
+
+        let result = core.apply_changes(&changes(serde_json::json!([
+            {"op": "create_task", "temp_id": "$a", "title": "A"},
+            {"op": "create_task", "temp_id": "$a", "title": "Again"},
+        ])));
+        assert!(matches!(result, Err(Error::InvalidChange(1, _))));
+
+        let result = core.apply_changes(&changes(serde_json::json!([
+            {"op": "create_task", "temp_id": "a", "title": "A"},
+        ])));
+        assert!(matches!(result, Err(Error::InvalidChange(0, _))));
+
+        let result = core.apply_changes(&changes(serde_json::json!([
+            {"op": "create_task", "temp_id": "$a", "title": "A"},
+            {"op": "edit_task", "id": 99, "title": "Nope"},
+        ])));
+        assert!(matches!(result, Err(Error::TaskNotFound(99))));
+
+        assert!(core.db.get_all_tasks(TaskFilter::all()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_apply_changes_requires_changes_argument() {
+        let error_code = |params| parse_changes(&params).unwrap_err().error_code;
+
+        assert_eq!(
+            error_code(serde_json::json!({})).as_deref(),
+            Some("InvalidParams")
+        );
+        assert_eq!(
+            error_code(serde_json::json!({"changes": "nope"})).as_deref(),
+            Some("InvalidChange")
+        );
+        assert!(
+            parse_changes(&serde_json::json!({"changes": []}))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl Recorder {
+        fn take(&self) -> Vec<String> {
+            std::mem::take(&mut *self.0.lock().unwrap())
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn on_task_created(&self, task: &Task) {
+            self.0.lock().unwrap().push(format!("created #{}", task.id));
+        }
+
+        fn on_task_started(&self, task: &Task) {
+            self.0.lock().unwrap().push(format!("started #{}", task.id));
+        }
+
+        fn on_task_completed(&self, task: &Task) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("completed #{}", task.id));
+        }
+
+        fn on_task_unblocked(&self, task: &Task) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("unblocked #{}", task.id));
+        }
+
+        fn on_dependency_added(&self, task_id: i64, depends_on: i64) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("depend #{task_id} on #{depends_on}"));
+        }
+    }
+
+    fn new_task(title: &str) -> CreateTaskOptions {
+        CreateTaskOptions {
+            title: title.to_string(),
+            description: None,
+            dod: Some("DoD".to_string()),
+            after_id: None,
+            before_id: None,
+        }
+    }
+
+    #[test]
+    fn test_tracker_notifies_subscribers() {
+        let temp_dir = TempDir::new().unwrap();
+        let recorder = Recorder::default();
+        let tracker = Tracker::builder()
+            .path(temp_dir.path().join("tt.db"))
+            .subscriber(recorder.clone())
+            .open()
+            .unwrap();
+
+        let a = tracker.add_task(new_task("A")).unwrap();
+        let b = tracker.add_task(new_task("B")).unwrap();
+        tracker.add_dependency(b.id, a.id).unwrap();
+        tracker.start_task(a.id).unwrap();
+        tracker.start_task(a.id).unwrap();
+        tracker.complete_task().unwrap();
+
+        assert_eq!(
+            recorder.take(),
+            vec![
+                "created #1",
+                "created #2",
+                "depend #2 on #1",
+                "started #1",
+                "completed #1"
+            ]
+        );
+
+        // Failed operations emit nothing
+        assert!(tracker.complete_task().is_err());
+        assert!(recorder.take().is_empty());
+    }
+
+    #[test]
+    fn test_tracker_reports_released_blockers() {
+        let temp_dir = TempDir::new().unwrap();
+        let recorder = Recorder::default();
+        let mut tracker = Tracker::builder()
+            .path(temp_dir.path().join("tt.db"))
+            .open()
+            .unwrap();
+        tracker.subscribe(recorder.clone());
+
+        let task = tracker.add_task(new_task("A")).unwrap();
+        tracker.set_target(task.id).unwrap();
+        tracker
+            .block_task(
+                task.id,
+                Blocker {
+                    until: Some("2000-01-01".to_string()),
+                    ..Blocker::default()
+                },
+            )
+            .unwrap();
+        recorder.take();
+
+        let next = tracker.next_task().unwrap();
+        assert_eq!(next.task.id, task.id);
+        assert_eq!(next.unblocked, vec![task.id]);
+        assert_eq!(recorder.take(), vec!["unblocked #1"]);
+    }
+
+    #[test]
+    fn test_list_returns_order_conflicts_as_data() {
+        let temp_dir = TempDir::new().unwrap();
+        let tracker = Tracker::builder()
+            .path(temp_dir.path().join("tt.db"))
+            .open()
+            .unwrap();
+
+        let a = tracker.add_task(new_task("A")).unwrap();
+        let b = tracker.add_task(new_task("B")).unwrap();
+        tracker.add_dependency(a.id, b.id).unwrap();
+        tracker.set_target(a.id).unwrap();
+
+        let list = tracker.list_tasks(ListOptions { all: false }).unwrap();
+        let ids: Vec<i64> = list.tasks.iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![b.id, a.id]);
+        assert_eq!(
+            list.warnings,
+            vec![Warning::OrderConflict {
+                task_id: a.id,
+                task_order: a.manual_order,
+                dep_id: b.id,
+                dep_order: b.manual_order,
+            }]
+        );
+
+        let order = tracker
+            .reorder_task(
+                b.id,
+                ReorderOptions {
+                    after_id: None,
+                    before_id: Some(a.id),
+                },
+            )
+            .unwrap();
+        assert!(order < a.manual_order);
+        let list = tracker.list_tasks(ListOptions { all: false }).unwrap();
+        assert!(list.warnings.is_empty());
+    }
 }
@@ -0,0 +1,262 @@
+//! Library entry point for embedding tt in another program.
+//!
+//! ```no_run
+//! use tt::{CreateTaskOptions, Subscriber, Task, Tracker};
+//!
+//! struct Announce;
+//!
+//! impl Subscriber for Announce {
+//!     fn on_task_completed(&self, task: &Task) {
+//!         println!("done: #{} {}", task.id, task.title);
+//!     }
+//! }
+//!
+//! let tracker = Tracker::builder()
+//!     .path("tasks.db")
+//!     .subscriber(Announce)
+//!     .open()?;
+//!
+//! let task = tracker.add_task(CreateTaskOptions {
+//!     title: "Write docs".to_string(),
+//!     description: None,
+//!     dod: Some("Published".to_string()),
+//!     after_id: None,
+//!     before_id: None,
+//! })?;
+//! tracker.start_task(task.id)?;
+//! tracker.complete_task()?;
+//! # Ok::<(), tt::Error>(())
+//! ```
+
+use std::path::PathBuf;
+
+use crate::core::CoreImpl;
+use crate::db::Database;
+use crate::error::Result;
+use crate::models::{
+    AppliedChanges, Artifact, Blocker, Change, CreateTaskOptions, EditTaskOptions, ListOptions,
+    NextTask, ReorderOptions, Task, TaskList, TaskWithDeps,
+};
+
+/// Receives a callback after each change a [`Tracker`] makes.
+///
+/// Every method has an empty default, so implementors only override the events
+/// they care about. Callbacks run synchronously, after the change is committed.
+pub trait Subscriber: Send + Sync {
+    fn on_task_created(&self, _task: &Task) {}
+
+    fn on_task_updated(&self, _task: &Task) {}
+
+    fn on_task_started(&self, _task: &Task) {}
+
+    fn on_task_stopped(&self, _task: &Task) {}
+
+    fn on_task_completed(&self, _task: &Task) {}
+
+    fn on_task_blocked(&self, _task: &Task) {}
+
+    /// Also called for tasks released automatically by [`Tracker::next_task`].
+    fn on_task_unblocked(&self, _task: &Task) {}
+
+    fn on_dependency_added(&self, _task_id: i64, _depends_on: i64) {}
+
+    fn on_dependency_removed(&self, _task_id: i64, _depends_on: i64) {}
+
+    fn on_target_set(&self, _id: i64) {}
+
+    fn on_artifact_logged(&self, _artifact: &Artifact) {}
+
+    fn on_changes_applied(&self, _changes: &AppliedChanges) {}
+}
+
+/// Configures and opens a [`Tracker`].
+pub struct TrackerBuilder {
+    path: PathBuf,
+    subscribers: Vec<Box<dyn Subscriber>>,
+}
+
+impl TrackerBuilder {
+    /// Database file to open, created if missing. Defaults to `tt.db`.
+    pub fn path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    /// Register a subscriber; may be called more than once.
+    pub fn subscriber(mut self, subscriber: impl Subscriber + 'static) -> Self {
+        self.subscribers.push(Box::new(subscriber));
+        self
+    }
+
+    pub fn open(self) -> Result<Tracker> {
+        let db = Database::new(&self.path)?;
+        Ok(Tracker {
+            core: CoreImpl::new(db),
+            subscribers: self.subscribers,
+        })
+    }
+}
+
+/// A task database plus the subscribers notified of its changes.
+///
+/// Methods mirror the CLI commands and return typed results; graph warnings
+/// come back in [`TaskList::warnings`] rather than being printed.
+pub struct Tracker {
+    core: CoreImpl,
+    subscribers: Vec<Box<dyn Subscriber>>,
+}
+
+impl Tracker {
+    pub fn builder() -> TrackerBuilder {
+        TrackerBuilder {
+            path: PathBuf::from("tt.db"),
+            subscribers: vec![],
+        }
+    }
+
+    /// Register a subscriber after the tracker has been opened.
+    pub fn subscribe(&mut self, subscriber: impl Subscriber + 'static) {
+        self.subscribers.push(Box::new(subscriber));
+    }
+
+    fn emit(&self, event: impl Fn(&dyn Subscriber)) {
+        for subscriber in &self.subscribers {
+            event(subscriber.as_ref());
+        }
+    }
+
+    pub fn add_task(&self, options: CreateTaskOptions) -> Result<Task> {
+        let task = self.core.add_task(
+            &options.title,
+            options.description.as_deref(),
+            options.dod.as_deref(),
+            options.after_id,
+            options.before_id,
+        )?;
+        self.emit(|s| s.on_task_created(&task));
+        Ok(task)
+    }
+
+    pub fn edit_task(&self, id: i64, options: EditTaskOptions) -> Result<Task> {
+        let task = self.core.edit_task(
+            id,
+            options.title.as_deref(),
+            options.description.as_deref(),
+            options.dod.as_deref(),
+        )?;
+        self.emit(|s| s.on_task_updated(&task));
+        Ok(task)
+    }
+
+    pub fn show_task(&self, id: i64) -> Result<TaskWithDeps> {
+        self.core.show_task(id)
+    }
+
+    /// Tasks of the target subgraph, or every task with `all`, in execution order.
+    pub fn list_tasks(&self, options: ListOptions) -> Result<TaskList> {
+        self.core.list_tasks(options.all)
+    }
+
+    pub fn set_target(&self, id: i64) -> Result<()> {
+        self.core.set_target(id)?;
+        self.emit(|s| s.on_target_set(id));
+        Ok(())
+    }
+
+    pub fn get_target(&self) -> Result<Option<i64>> {
+        self.core.get_target()
+    }
+
+    /// Pick the next available task for the target. See [`CoreImpl::next_task`].
+    pub fn next_task(&self) -> Result<NextTask> {
+        let next = self.core.next_task()?;
+        for &id in &next.unblocked {
+            let task = self.core.db.get_task(id)?;
+            self.emit(|s| s.on_task_unblocked(&task));
+        }
+        Ok(next)
+    }
+
+    pub fn start_task(&self, id: i64) -> Result<Task> {
+        let before = self.core.db.get_task(id)?;
+        let task = self.core.start_task(id)?;
+        // Starting the active task again is a no-op, not a new start
+        if before.status != task.status {
+            self.emit(|s| s.on_task_started(&task));
+        }
+        Ok(task)
+    }
+
+    pub fn stop_task(&self) -> Result<Task> {
+        let task = self.core.stop_task()?;
+        self.emit(|s| s.on_task_stopped(&task));
+        Ok(task)
+    }
+
+    pub fn complete_task(&self) -> Result<Task> {
+        let task = self.core.complete_task()?;
+        self.emit(|s| s.on_task_completed(&task));
+        Ok(task)
+    }
+
+    pub fn block_task(&self, id: i64, blocker: Blocker) -> Result<Task> {
+        let task = self.core.block_task(id, blocker)?;
+        self.emit(|s| s.on_task_blocked(&task));
+        Ok(task)
+    }
+
+    pub fn unblock_task(&self, id: i64) -> Result<Task> {
+        let task = self.core.unblock_task(id)?;
+        self.emit(|s| s.on_task_unblocked(&task));
+        Ok(task)
+    }
+
+    pub fn current_task(&self) -> Result<TaskWithDeps> {
+        self.core.current_task()
+    }
+
+    pub fn add_dependency(&self, task_id: i64, depends_on: i64) -> Result<()> {
+        self.core.add_dependency(task_id, depends_on)?;
+        self.emit(|s| s.on_dependency_added(task_id, depends_on));
+        Ok(())
+    }
+
+    pub fn remove_dependency(&self, task_id: i64, depends_on: i64) -> Result<()> {
+        self.core.remove_dependency(task_id, depends_on)?;
+        self.emit(|s| s.on_dependency_removed(task_id, depends_on));
+        Ok(())
+    }
+
+    /// Apply a batch of changes atomically. Subscribers see one
+    /// `on_changes_applied` call for the whole batch.
+    pub fn apply_changes(&self, changes: &[Change]) -> Result<AppliedChanges> {
+        let applied = self.core.apply_changes(changes)?;
+        self.emit(|s| s.on_changes_applied(&applied));
+        Ok(applied)
+    }
+
+    /// Log an artifact for the active task.
+    pub fn log_artifact(&self, name: &str, file_path: &str) -> Result<Artifact> {
+        let artifact = self.core.log_artifact(name, file_path)?;
+        self.emit(|s| s.on_artifact_logged(&artifact));
+        Ok(artifact)
+    }
+
+    pub fn get_artifacts(&self, task_id: Option<i64>) -> Result<Vec<Artifact>> {
+        self.core.get_artifacts(task_id)
+    }
+
+    /// Move a task relative to others; returns its new `manual_order`.
+    pub fn reorder_task(&self, id: i64, options: ReorderOptions) -> Result<f64> {
+        let order = self
+            .core
+            .reorder_task(id, options.after_id, options.before_id)?;
+        let task = self.core.db.get_task(id)?;
+        self.emit(|s| s.on_task_updated(&task));
+        Ok(order)
+    }
+
+    pub fn reindex(&self) -> Result<()> {
+        self.core.reindex()
+    }
+}
@@ -1,5 +1,5 @@
 use crate::core::*;
-use crate::db::{Artifact, Db, Task, TaskDetail, TaskStatus};
+use crate::db::{Db, Task, TaskDetail};
 use crate::error::{Error, Result};
 use clap::{Parser, Subcommand};
 use std::fs;
@@ -148,6 +148,13 @@ enum Commands {
     /// Reindex all task orders
     Reindex,
 
+    /// Check the database for problems
+    Doctor {
+        /// Apply safe repairs in a single transaction
+        #[arg(long)]
+        fix: bool,
+    },
+
     /// Start the MCP server
     Mcp,
 }
@@ -166,10 +173,10 @@ pub fn run() -> Result<()> {
             }
 
             // Create .tt/artifacts directory
-            fs::create_dir_all(&artifacts_dir).map_err(|e| Error::Io(e))?;
+            fs::create_dir_all(&artifacts_dir).map_err(Error::Io)?;
 
             // Create database
-            let mut db = Db::open(&db_path)?;
+            Db::open(&db_path)?;
             println!(
                 "Initialized task tracker in {}",
                 std::env::current_dir().unwrap().display()
@@ -379,6 +386,70 @@ pub fn run() -> Result<()> {
             Ok(())
         }
 
+        Commands::Doctor { fix } => {
+            let mut db = open_db()?;
+            let base_dir = std::env::current_dir()?;
+            let findings = if fix {
+                repair(&mut db, &base_dir)?
+            } else {
+                diagnose(&db, &base_dir)?
+            };
+
+            if findings.is_empty() {
+                println!("No problems found");
+                return Ok(());
+            }
+
+            let mut unresolved = 0;
+            for finding in &findings {
+                match (&finding.fix, fix) {
+                    (Some(repair), true) => {
+                        println!(
+                            "  ✓ [{}] {}: {}",
+                            finding.check.as_str(),
+                            finding.message,
+                            repair
+                        )
+                    }
+                    (Some(repair), false) => {
+                        unresolved += 1;
+                        println!(
+                            "  ✗ [{}] {} (fix: {})",
+                            finding.check.as_str(),
+                            finding.message,
+                            repair
+                        )
+                    }
+                    (None, _) => {
+                        unresolved += 1;
+                        println!("  ✗ [{}] {}", finding.check.as_str(), finding.message)
+                    }
+                }
+            }
+
+            let fixable = findings.iter().filter(|f| f.fix.is_some()).count();
+            if fix {
+                println!(
+                    "\nFixed {} of {} problems",
+                    findings.len() - unresolved,
+                    findings.len()
+                );
+            } else if fixable > 0 {
+                println!(
+                    "\n{} problems found, {} can be fixed with `tt doctor --fix`",
+                    findings.len(),
+                    fixable
+                );
+            } else {
+                println!("\n{} problems found", findings.len());
+            }
+
+            if unresolved > 0 {
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+
         Commands::Mcp => {
             // MCP is handled in main.rs
             Ok(())
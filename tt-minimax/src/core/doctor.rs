@@ -0,0 +1,527 @@
+use crate::db::Db;
+use crate::error::Result;
+use crate::graph::detect_all_cycles;
+use chrono::{DateTime, NaiveDateTime};
+use serde::Serialize;
+use std::path::Path;
+
+/// The area of the database a finding concerns
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Check {
+    Integrity,
+    ForeignKeys,
+    Target,
+    ActiveTasks,
+    ManualOrder,
+    Timestamps,
+    Cycles,
+    Artifacts,
+}
+
+impl Check {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Check::Integrity => "integrity",
+            Check::ForeignKeys => "foreign_keys",
+            Check::Target => "target",
+            Check::ActiveTasks => "active_tasks",
+            Check::ManualOrder => "manual_order",
+            Check::Timestamps => "timestamps",
+            Check::Cycles => "cycles",
+            Check::Artifacts => "artifacts",
+        }
+    }
+}
+
+/// A safe repair for a finding
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Fix {
+    /// Delete a row whose parent task no longer exists
+    DeleteRow { table: String, rowid: i64 },
+    /// Clear a target that does not name an existing task
+    ClearTarget,
+    /// Move an extra in-progress task back to pending
+    StopTask { id: i64 },
+    /// Renumber every manual_order as 10, 20, 30, ...
+    Reindex,
+    /// Replace a malformed timestamp, clearing it if `value` is None
+    SetTimestamp {
+        table: String,
+        id: i64,
+        column: String,
+        value: Option<String>,
+    },
+}
+
+impl std::fmt::Display for Fix {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Fix::DeleteRow { table, rowid } => write!(f, "delete {} row {}", table, rowid),
+            Fix::ClearTarget => write!(f, "clear the target"),
+            Fix::StopTask { id } => write!(f, "move #{} back to pending", id),
+            Fix::Reindex => write!(f, "reindex all tasks"),
+            Fix::SetTimestamp {
+                column,
+                value: Some(value),
+                ..
+            } => write!(f, "set {} to {}", column, value),
+            Fix::SetTimestamp { column, .. } => write!(f, "clear {}", column),
+        }
+    }
+}
+
+/// A problem found by `diagnose`
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    pub check: Check,
+    pub message: String,
+    /// None when the problem needs a human to resolve
+    pub fix: Option<Fix>,
+}
+
+impl Finding {
+    fn new(check: Check, message: String, fix: Option<Fix>) -> Self {
+        Finding {
+            check,
+            message,
+            fix,
+        }
+    }
+}
+
+/// Run every check against the database.
+///
+/// Relative artifact paths are resolved against `base_dir`.
+pub fn diagnose(db: &Db, base_dir: &Path) -> Result<Vec<Finding>> {
+    let mut findings = check_integrity(db)?;
+    if !findings.is_empty() {
+        // The remaining checks would read a damaged file
+        return Ok(findings);
+    }
+
+    findings.extend(check_foreign_keys(db)?);
+    findings.extend(check_target(db)?);
+    findings.extend(check_active_tasks(db)?);
+    findings.extend(check_manual_order(db)?);
+    findings.extend(check_timestamps(db)?);
+    findings.extend(check_cycles(db)?);
+    findings.extend(check_artifacts(db, base_dir)?);
+    Ok(findings)
+}
+
+/// Run every check and apply the safe repairs in a single transaction.
+///
+/// Returns all findings; those with a fix have been repaired. Nothing is
+/// repaired when SQLite reports the file itself as damaged.
+pub fn repair(db: &mut Db, base_dir: &Path) -> Result<Vec<Finding>> {
+    let findings = diagnose(db, base_dir)?;
+    if findings.iter().any(|f| f.check == Check::Integrity) {
+        return Ok(findings);
+    }
+
+    let mut tx = db.transaction()?;
+    let mut reindexed = false;
+
+    for fix in findings.iter().filter_map(|f| f.fix.as_ref()) {
+        match fix {
+            Fix::DeleteRow { table, rowid } => {
+                tx.execute(&format!("DELETE FROM {} WHERE rowid = ?1", table), [rowid])?;
+            }
+            Fix::ClearTarget => {
+                tx.execute("DELETE FROM config WHERE key = 'target_id'", [])?;
+            }
+            Fix::StopTask { id } => {
+                tx.execute(
+                    "UPDATE tasks SET status = 'pending', last_touched_at = ?1 WHERE id = ?2",
+                    (Db::now(), id),
+                )?;
+            }
+            Fix::Reindex if !reindexed => {
+                reindexed = true;
+                let mut stmt = tx.prepare(
+                    "SELECT id FROM tasks
+                     ORDER BY (typeof(manual_order) IN ('integer', 'real')
+                               AND abs(manual_order) <= 1.7976931348623157e308) DESC,
+                              manual_order, id",
+                )?;
+                let ids = stmt
+                    .query_map([], |row| row.get::<_, i64>(0))?
+                    .collect::<std::result::Result<Vec<_>, _>>()?;
+                drop(stmt);
+
+                for (i, id) in ids.iter().enumerate() {
+                    tx.execute(
+                        "UPDATE tasks SET manual_order = ?1 WHERE id = ?2",
+                        ((i as i64 + 1) * 10, id),
+                    )?;
+                }
+            }
+            Fix::Reindex => {}
+            Fix::SetTimestamp {
+                table,
+                id,
+                column,
+                value,
+            } => {
+                tx.execute(
+                    &format!("UPDATE {} SET {} = ?1 WHERE id = ?2", table, column),
+                    (value, id),
+                )?;
+            }
+        }
+    }
+
+    tx.commit()?;
+    Ok(findings)
+}
+
+/// SQLite's own consistency check of the file
+fn check_integrity(db: &Db) -> Result<Vec<Finding>> {
+    let mut stmt = db.conn.prepare("PRAGMA integrity_check")?;
+    let messages = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    Ok(messages
+        .into_iter()
+        .filter(|m| m != "ok")
+        .map(|m| Finding::new(Check::Integrity, m, None))
+        .collect())
+}
+
+/// Dependencies and artifacts left behind by tasks deleted with foreign keys off
+fn check_foreign_keys(db: &Db) -> Result<Vec<Finding>> {
+    let mut stmt = db.conn.prepare("PRAGMA foreign_key_check")?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, Option<i64>>(1)?))
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(table, rowid)| {
+            let fix = rowid
+                .filter(|_| table == "dependencies" || table == "artifacts")
+                .map(|rowid| Fix::DeleteRow {
+                    table: table.clone(),
+                    rowid,
+                });
+            Finding::new(
+                Check::ForeignKeys,
+                format!("{} row refers to a task that does not exist", table),
+                fix,
+            )
+        })
+        .collect())
+}
+
+fn check_target(db: &Db) -> Result<Vec<Finding>> {
+    let mut stmt = db
+        .conn
+        .prepare("SELECT value FROM config WHERE key = 'target_id'")?;
+    let values = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let mut findings = Vec::new();
+    for value in values {
+        let message = match value.parse::<i64>() {
+            Ok(id) if db.task_exists(id)? => continue,
+            Ok(id) => format!("Target #{} does not exist", id),
+            Err(_) => format!("Target '{}' is not a task ID", value),
+        };
+        findings.push(Finding::new(Check::Target, message, Some(Fix::ClearTarget)));
+    }
+    Ok(findings)
+}
+
+/// At most one task may be in progress; the most recently started one is kept
+fn check_active_tasks(db: &Db) -> Result<Vec<Finding>> {
+    let mut stmt = db.conn.prepare(
+        "SELECT id FROM tasks WHERE status = 'in_progress'
+         ORDER BY started_at DESC, last_touched_at DESC, id DESC",
+    )?;
+    let ids = stmt
+        .query_map([], |row| row.get::<_, i64>(0))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let Some((&kept, extra)) = ids.split_first() else {
+        return Ok(vec![]);
+    };
+    Ok(extra
+        .iter()
+        .map(|&id| {
+            Finding::new(
+                Check::ActiveTasks,
+                format!("#{} is in progress alongside #{}", id, kept),
+                Some(Fix::StopTask { id }),
+            )
+        })
+        .collect())
+}
+
+fn check_manual_order(db: &Db) -> Result<Vec<Finding>> {
+    let mut findings = Vec::new();
+
+    let mut stmt = db.conn.prepare(
+        "SELECT id, quote(manual_order) FROM tasks
+         WHERE typeof(manual_order) NOT IN ('integer', 'real')
+            OR abs(manual_order) > 1.7976931348623157e308
+         ORDER BY id",
+    )?;
+    let invalid = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    for (id, order) in invalid {
+        findings.push(Finding::new(
+            Check::ManualOrder,
+            format!(
+                "#{} has manual_order {}, which is not a finite number",
+                id, order
+            ),
+            Some(Fix::Reindex),
+        ));
+    }
+
+    let mut stmt = db.conn.prepare(
+        "SELECT manual_order, group_concat(id, ', #') FROM tasks
+         WHERE typeof(manual_order) IN ('integer', 'real')
+         GROUP BY manual_order HAVING count(*) > 1
+         ORDER BY manual_order",
+    )?;
+    let duplicates = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, f64>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    for (order, ids) in duplicates {
+        findings.push(Finding::new(
+            Check::ManualOrder,
+            format!("#{} share manual_order {}", ids, order),
+            Some(Fix::Reindex),
+        ));
+    }
+
+    Ok(findings)
+}
+
+/// Timestamps must be RFC 3339, or the `YYYY-MM-DDTHH:MM:SS` the schema defaults write
+fn check_timestamps(db: &Db) -> Result<Vec<Finding>> {
+    const COLUMNS: &[(&str, &str, bool)] = &[
+        ("tasks", "created_at", true),
+        ("tasks", "last_touched_at", true),
+        ("tasks", "started_at", false),
+        ("tasks", "completed_at", false),
+        ("artifacts", "created_at", true),
+    ];
+
+    let mut findings = Vec::new();
+    for &(table, column, required) in COLUMNS {
+        let mut stmt = db.conn.prepare(&format!(
+            "SELECT id, CAST({column} AS TEXT) FROM {table} WHERE {column} IS NOT NULL ORDER BY id"
+        ))?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        for (id, value) in rows {
+            if is_valid_timestamp(&value) {
+                continue;
+            }
+            let subject = match table {
+                "tasks" => format!("#{}", id),
+                _ => format!("Artifact {}", id),
+            };
+            findings.push(Finding::new(
+                Check::Timestamps,
+                format!("{} has malformed {} '{}'", subject, column, value),
+                Some(Fix::SetTimestamp {
+                    table: table.to_string(),
+                    id,
+                    column: column.to_string(),
+                    value: required.then(Db::now),
+                }),
+            ));
+        }
+    }
+    Ok(findings)
+}
+
+fn is_valid_timestamp(value: &str) -> bool {
+    DateTime::parse_from_rfc3339(value).is_ok()
+        || NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S").is_ok()
+}
+
+/// Cycles can only come from edits that bypass `tt depend`; which edge to
+/// drop is a judgement call, so they are reported but not fixed
+fn check_cycles(db: &Db) -> Result<Vec<Finding>> {
+    Ok(detect_all_cycles(db)?
+        .into_iter()
+        .map(|cycle| {
+            let path: Vec<String> = cycle.iter().map(|id| format!("#{}", id)).collect();
+            Finding::new(
+                Check::Cycles,
+                format!("Dependency cycle: {}", path.join(" → ")),
+                None,
+            )
+        })
+        .collect())
+}
+
+fn check_artifacts(db: &Db, base_dir: &Path) -> Result<Vec<Finding>> {
+    let mut stmt = db
+        .conn
+        .prepare("SELECT id, task_id, name, file_path FROM artifacts ORDER BY id")?;
+    let artifacts = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    Ok(artifacts
+        .into_iter()
+        .filter(|(_, _, _, file_path)| !base_dir.join(file_path).exists())
+        .map(|(id, task_id, name, file_path)| {
+            Finding::new(
+                Check::Artifacts,
+                format!(
+                    "Artifact {} ({}) of #{} points at missing file {}",
+                    id, name, task_id, file_path
+                ),
+                None,
+            )
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add_tasks(db: &Db, count: i64) {
+        for i in 1..=count {
+            db.conn
+                .execute(
+                    "INSERT INTO tasks (title, status, manual_order) VALUES (?1, 'pending', ?2)",
+                    (format!("Task {}", i), i as f64 * 10.0),
+                )
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_healthy_database() {
+        let db = Db::in_memory().unwrap();
+        add_tasks(&db, 3);
+        db.conn
+            .execute("INSERT INTO dependencies VALUES (2, 1)", [])
+            .unwrap();
+
+        assert!(diagnose(&db, Path::new(".")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_repair() {
+        let mut db = Db::in_memory().unwrap();
+        add_tasks(&db, 3);
+        db.conn
+            .execute_batch(
+                "INSERT INTO config VALUES ('target_id', '99');
+                 UPDATE tasks SET status = 'in_progress', started_at = '2024-01-01T00:00:00Z'
+                     WHERE id IN (1, 2);
+                 UPDATE tasks SET started_at = '2024-02-01T00:00:00Z' WHERE id = 2;
+                 UPDATE tasks SET manual_order = 10.0 WHERE id = 3;
+                 UPDATE tasks SET created_at = 'yesterday', completed_at = 'soon' WHERE id = 3;",
+            )
+            .unwrap();
+
+        let findings = diagnose(&db, Path::new(".")).unwrap();
+        let checks: Vec<Check> = findings.iter().map(|f| f.check).collect();
+        assert_eq!(
+            checks,
+            vec![
+                Check::Target,
+                Check::ActiveTasks,
+                Check::ManualOrder,
+                Check::Timestamps,
+                Check::Timestamps,
+            ]
+        );
+        assert_eq!(findings[1].fix, Some(Fix::StopTask { id: 1 }));
+        assert!(findings.iter().all(|f| f.fix.is_some()));
+
+        repair(&mut db, Path::new(".")).unwrap();
+        assert!(diagnose(&db, Path::new(".")).unwrap().is_empty());
+
+        let (status, completed_at): (String, Option<String>) = db
+            .conn
+            .query_row(
+                "SELECT t1.status, t3.completed_at FROM tasks t1, tasks t3
+                 WHERE t1.id = 1 AND t3.id = 3",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(status, "pending");
+        assert_eq!(completed_at, None);
+    }
+
+    #[test]
+    fn test_repair_orphans_and_invalid_orders() {
+        let mut db = Db::in_memory().unwrap();
+        add_tasks(&db, 2);
+        db.conn
+            .execute_batch(
+                "PRAGMA foreign_keys = OFF;
+                 INSERT INTO dependencies VALUES (2, 7);
+                 UPDATE tasks SET manual_order = 'first' WHERE id = 2;
+                 PRAGMA foreign_keys = ON;",
+            )
+            .unwrap();
+
+        let findings = repair(&mut db, Path::new(".")).unwrap();
+        let checks: Vec<Check> = findings.iter().map(|f| f.check).collect();
+        assert_eq!(checks, vec![Check::ForeignKeys, Check::ManualOrder]);
+        assert!(diagnose(&db, Path::new(".")).unwrap().is_empty());
+
+        // The task with an unusable order moves to the end
+        let order: f64 = db
+            .conn
+            .query_row("SELECT manual_order FROM tasks WHERE id = 2", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(order, 20.0);
+    }
+
+    #[test]
+    fn test_unfixable_findings() {
+        let mut db = Db::in_memory().unwrap();
+        add_tasks(&db, 2);
+        db.conn
+            .execute_batch(
+                "INSERT INTO dependencies VALUES (1, 2), (2, 1);
+                 INSERT INTO artifacts (task_id, name, file_path) VALUES (1, 'plan', 'missing.md');",
+            )
+            .unwrap();
+
+        let findings = repair(&mut db, Path::new(".")).unwrap();
+        let checks: Vec<Check> = findings.iter().map(|f| f.check).collect();
+        assert_eq!(checks, vec![Check::Cycles, Check::Artifacts]);
+        assert_eq!(findings[0].message, "Dependency cycle: #1 → #2 → #1");
+        assert!(findings.iter().all(|f| f.fix.is_none()));
+        assert_eq!(diagnose(&db, Path::new(".")).unwrap().len(), 2);
+    }
+}
@@ -1,7 +1,9 @@
+pub mod doctor;
 pub mod target;
 pub mod task;
 pub mod workflow;
 
+pub use doctor::*;
 pub use target::*;
 pub use task::*;
 pub use workflow::*;
@@ -20,7 +20,7 @@ pub fn add_task(
     let mut tx = db.transaction()?;
     let now = Db::now();
 
-    let task_id = tx.execute(
+    tx.execute(
         "INSERT INTO tasks (title, description, dod, status, manual_order, created_at, last_touched_at)
          VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
         (
@@ -34,7 +34,7 @@ pub fn add_task(
         ),
     )?;
 
-    let task_id = task_id as i64;
+    let task_id = tx.last_insert_rowid();
 
     tx.commit()?;
 
@@ -51,7 +51,7 @@ impl Db {
     }
 
     /// Begin a transaction
-    pub fn transaction(&mut self) -> Result<Transaction> {
+    pub fn transaction(&mut self) -> Result<Transaction<'_>> {
         Ok(Transaction {
             tx: self.conn.transaction()?,
         })
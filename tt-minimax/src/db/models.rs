@@ -22,6 +22,7 @@ impl TaskStatus {
         }
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Result<Self> {
         match s {
             "pending" => Ok(TaskStatus::Pending),
@@ -79,10 +80,10 @@ pub struct Task {
 impl Task {
     /// Parse a datetime string to DateTime<Utc>
     pub fn parse_datetime(s: &str) -> Result<DateTime<Utc>> {
-        Ok(s.parse::<DateTime<Utc>>()
+        s.parse::<DateTime<Utc>>()
             .map_err(|_| crate::error::Error::InvalidStatus {
                 status: format!("Invalid datetime: {}", s),
-            })?)
+            })
     }
 
     /// Get the display time for a datetime string
@@ -153,4 +153,31 @@ impl Error {
     pub fn all_blocked(blocked_ids: Vec<i64>) -> Self {
         Error::AllBlocked { blocked_ids }
     }
+
+    /// The variant name, reported to MCP clients as `error_code`
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            Error::TaskNotFound { .. } => "TaskNotFound",
+            Error::InvalidTaskStatus { .. } => "InvalidTaskStatus",
+            Error::AnotherTaskActive { .. } => "AnotherTaskActive",
+            Error::NoActiveTask => "NoActiveTask",
+            Error::UnmetDependencies { .. } => "UnmetDependencies",
+            Error::CycleDetected { .. } => "CycleDetected",
+            Error::NoTarget => "NoTarget",
+            Error::TargetReached { .. } => "TargetReached",
+            Error::NoDod { .. } => "NoDod",
+            Error::OrderConflict { .. } => "OrderConflict",
+            Error::InvalidStatus { .. } => "InvalidStatus",
+            Error::AllBlocked { .. } => "AllBlocked",
+            Error::FloatPrecisionExhausted { .. } => "FloatPrecisionExhausted",
+            Error::DeletionNotSupported => "DeletionNotSupported",
+            Error::Db(_) => "Db",
+            Error::Io(_) => "Io",
+            Error::Json(_) => "Json",
+            Error::InvalidTransition { .. } => "InvalidTransition",
+            Error::ReorderConflict => "ReorderConflict",
+            Error::AlreadyInState { .. } => "AlreadyInState",
+            Error::ArtifactNotFound { .. } => "ArtifactNotFound",
+        }
+    }
 }
@@ -0,0 +1,391 @@
+use super::sort::{get_task, MinHeapItem};
+use crate::db::{Db, Task, TaskStatus};
+use crate::error::{Error, Result};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+/// Status and order of a task, as far as the graph is concerned
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    status: TaskStatus,
+    manual_order: f64,
+}
+
+/// Result of the last next-task computation
+#[derive(Debug, Clone)]
+enum Next {
+    Task(i64),
+    TargetReached(i64),
+    AllBlocked(Vec<i64>),
+}
+
+/// In-memory copy of the task DAG for a long-running process such as the MCP
+/// server.
+///
+/// The owner reports each mutation it performs so the cache stays current
+/// without re-reading the database. Writes by other connections are detected
+/// through SQLite's `data_version` and trigger a full rebuild.
+#[derive(Debug, Default)]
+pub struct GraphCache {
+    nodes: HashMap<i64, Node>,
+    dependencies: HashMap<i64, Vec<i64>>,
+    target: Option<i64>,
+    data_version: i64,
+    /// Cleared by every mutation
+    next: Option<Next>,
+}
+
+impl GraphCache {
+    /// Load tasks, dependencies and the target from the database
+    pub fn load(db: &Db) -> Result<Self> {
+        let mut cache = GraphCache {
+            data_version: data_version(db)?,
+            target: crate::core::get_current_target(db)?,
+            ..Default::default()
+        };
+
+        let mut stmt = db
+            .conn
+            .prepare("SELECT id, status, manual_order FROM tasks")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, f64>(2)?,
+            ))
+        })?;
+        for row in rows {
+            let (id, status, manual_order) = row?;
+            let status = TaskStatus::from_str(&status)?;
+            cache.nodes.insert(
+                id,
+                Node {
+                    status,
+                    manual_order,
+                },
+            );
+        }
+
+        let mut stmt = db
+            .conn
+            .prepare("SELECT task_id, depends_on FROM dependencies")?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)))?;
+        for row in rows {
+            let (task_id, depends_on) = row?;
+            cache
+                .dependencies
+                .entry(task_id)
+                .or_default()
+                .push(depends_on);
+        }
+
+        Ok(cache)
+    }
+
+    /// Rebuild the cache if another connection has written to the database
+    /// since it was loaded. Returns whether it was rebuilt.
+    pub fn refresh(&mut self, db: &Db) -> Result<bool> {
+        if data_version(db)? == self.data_version {
+            return Ok(false);
+        }
+        *self = Self::load(db)?;
+        Ok(true)
+    }
+
+    /// Get the next task to work on toward the target
+    pub fn next_task(&mut self, db: &Db) -> Result<Task> {
+        self.refresh(db)?;
+
+        let next = match &self.next {
+            Some(next) => next.clone(),
+            None => {
+                let target = self.target.ok_or(Error::NoTarget)?;
+                let next = self.compute_next(target)?;
+                self.next = Some(next.clone());
+                next
+            }
+        };
+
+        match next {
+            Next::Task(id) => get_task(db, id),
+            Next::TargetReached(id) => Err(Error::TargetReached {
+                id,
+                title: get_task(db, id)?.title,
+            }),
+            Next::AllBlocked(blocked_ids) => Err(Error::AllBlocked { blocked_ids }),
+        }
+    }
+
+    /// Record a created or changed task
+    pub fn update_task(&mut self, task: &Task) {
+        self.nodes.insert(
+            task.id,
+            Node {
+                status: task.status,
+                manual_order: task.manual_order,
+            },
+        );
+        self.next = None;
+    }
+
+    /// Record a new manual_order for a task
+    pub fn set_order(&mut self, id: i64, manual_order: f64) {
+        if let Some(node) = self.nodes.get_mut(&id) {
+            node.manual_order = manual_order;
+        }
+        self.next = None;
+    }
+
+    /// Record a new dependency
+    pub fn add_dependency(&mut self, task_id: i64, depends_on: i64) {
+        let deps = self.dependencies.entry(task_id).or_default();
+        if !deps.contains(&depends_on) {
+            deps.push(depends_on);
+        }
+        self.next = None;
+    }
+
+    /// Record a removed dependency
+    pub fn remove_dependency(&mut self, task_id: i64, depends_on: i64) {
+        if let Some(deps) = self.dependencies.get_mut(&task_id) {
+            deps.retain(|&d| d != depends_on);
+        }
+        self.next = None;
+    }
+
+    /// Record a new target
+    pub fn set_target(&mut self, id: i64) {
+        self.target = Some(id);
+        self.next = None;
+    }
+
+    fn status(&self, id: i64) -> Result<TaskStatus> {
+        self.nodes
+            .get(&id)
+            .map(|node| node.status)
+            .ok_or(Error::TaskNotFound { id })
+    }
+
+    fn deps(&self, id: i64) -> &[i64] {
+        self.dependencies.get(&id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Same rules as `graph::get_next_task`, without touching the database
+    fn compute_next(&self, target: i64) -> Result<Next> {
+        // Target subgraph, excluding completed tasks
+        let mut seen = HashSet::from([target]);
+        let mut stack = vec![target];
+        let mut active = Vec::new();
+        while let Some(id) = stack.pop() {
+            if self.status(id)? != TaskStatus::Completed {
+                active.push(id);
+            }
+            for &dep in self.deps(id) {
+                if seen.insert(dep) {
+                    stack.push(dep);
+                }
+            }
+        }
+
+        if active.is_empty() {
+            return Ok(Next::TargetReached(target));
+        }
+
+        // Kahn's algorithm over the active tasks
+        let active_set: HashSet<i64> = active.iter().copied().collect();
+        let mut in_degree: HashMap<i64, usize> = HashMap::new();
+        let mut dependents: HashMap<i64, Vec<i64>> = HashMap::new();
+        for &id in &active {
+            let active_deps: Vec<i64> = self
+                .deps(id)
+                .iter()
+                .copied()
+                .filter(|dep| active_set.contains(dep))
+                .collect();
+            in_degree.insert(id, active_deps.len());
+            for dep in active_deps {
+                dependents.entry(dep).or_default().push(id);
+            }
+        }
+
+        let item = |id: i64| MinHeapItem {
+            task_id: id,
+            manual_order: self.nodes[&id].manual_order,
+        };
+        let mut heap: BinaryHeap<MinHeapItem> = active
+            .iter()
+            .filter(|id| in_degree[id] == 0)
+            .map(|&id| item(id))
+            .collect();
+
+        let mut sorted_count = 0;
+        while let Some(MinHeapItem { task_id, .. }) = heap.pop() {
+            sorted_count += 1;
+
+            // Only the sorted order up to the first ready task matters
+            if self.nodes[&task_id].status == TaskStatus::Pending
+                && self
+                    .deps(task_id)
+                    .iter()
+                    .all(|dep| self.nodes.get(dep).map(|n| n.status) == Some(TaskStatus::Completed))
+            {
+                return Ok(Next::Task(task_id));
+            }
+
+            for &dependent in dependents.get(&task_id).map_or(&[][..], Vec::as_slice) {
+                let degree = in_degree.get_mut(&dependent).expect("active task");
+                *degree -= 1;
+                if *degree == 0 {
+                    heap.push(item(dependent));
+                }
+            }
+        }
+
+        if sorted_count != active.len() {
+            let unsorted = in_degree
+                .into_iter()
+                .filter(|&(_, degree)| degree > 0)
+                .map(|(id, _)| id)
+                .collect();
+            let cycle_path = self.find_cycle(&unsorted);
+            return Err(Error::CycleDetected {
+                from_id: cycle_path[cycle_path.len() - 2],
+                to_id: cycle_path[0],
+                cycle_path,
+            });
+        }
+
+        let blocked_ids = active
+            .into_iter()
+            .filter(|id| self.nodes[id].status == TaskStatus::Blocked)
+            .collect();
+        Ok(Next::AllBlocked(blocked_ids))
+    }
+
+    /// A cycle among tasks Kahn's algorithm could not sort, as a path along
+    /// dependency edges that ends where it starts.
+    ///
+    /// Every unsorted task has an unsorted dependency, so following those
+    /// must revisit a task.
+    fn find_cycle(&self, unsorted: &HashSet<i64>) -> Vec<i64> {
+        let mut path = Vec::new();
+        let mut position = HashMap::new();
+        let mut current = *unsorted.iter().min().expect("unsorted tasks");
+        while !position.contains_key(&current) {
+            position.insert(current, path.len());
+            path.push(current);
+            current = *self
+                .deps(current)
+                .iter()
+                .find(|dep| unsorted.contains(dep))
+                .expect("unsorted dependency");
+        }
+
+        let mut cycle = path.split_off(position[&current]);
+        cycle.push(current);
+        cycle
+    }
+}
+
+/// SQLite's counter of commits made by other connections
+fn data_version(db: &Db) -> Result<i64> {
+    Ok(db
+        .conn
+        .query_row("PRAGMA data_version", [], |row| row.get(0))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{add_task, complete_task, set_target, start_task};
+    use tempfile::TempDir;
+
+    fn add(db: &mut Db, title: &str) -> Task {
+        add_task(
+            db,
+            title.to_string(),
+            None,
+            Some("Done".to_string()),
+            None,
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_rebuilds_after_external_write() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("tt.db");
+        let mut db = Db::open(&path).unwrap();
+        let target = add(&mut db, "Target");
+        set_target(&mut db, target.id).unwrap();
+
+        let mut cache = GraphCache::load(&db).unwrap();
+        assert_eq!(cache.next_task(&db).unwrap().id, target.id);
+        assert!(!cache.refresh(&db).unwrap());
+
+        // Another process adds a prerequisite to the target
+        let mut other = Db::open(&path).unwrap();
+        let prerequisite = add(&mut other, "Prerequisite");
+        crate::core::add_dependency(&mut other, target.id, prerequisite.id).unwrap();
+
+        assert_eq!(cache.next_task(&db).unwrap().id, prerequisite.id);
+    }
+
+    #[test]
+    fn test_matches_uncached_next() {
+        let mut db = Db::in_memory().unwrap();
+        let ids: Vec<i64> = ["A", "B", "C", "D"]
+            .iter()
+            .map(|title| add(&mut db, title).id)
+            .collect();
+        let deps = [(ids[3], ids[1]), (ids[3], ids[2]), (ids[1], ids[0])];
+        for (task_id, depends_on) in deps {
+            crate::core::add_dependency(&mut db, task_id, depends_on).unwrap();
+        }
+        set_target(&mut db, ids[3]).unwrap();
+
+        let mut cache = GraphCache::load(&db).unwrap();
+        for _ in 0..ids.len() {
+            let expected = super::super::get_next_task(&db, ids[3]).unwrap();
+            let next = cache.next_task(&db).unwrap();
+            assert_eq!(next.id, expected.id);
+
+            start_task(&mut db, next.id).unwrap();
+            let done = complete_task(&mut db).unwrap();
+            cache.update_task(&done);
+        }
+
+        assert!(matches!(
+            cache.next_task(&db),
+            Err(Error::TargetReached { id, .. }) if id == ids[3]
+        ));
+    }
+
+    #[test]
+    fn test_reports_cycle_path() {
+        let mut db = Db::in_memory().unwrap();
+        let ids: Vec<i64> = ["A", "B", "C"]
+            .iter()
+            .map(|title| add(&mut db, title).id)
+            .collect();
+        set_target(&mut db, ids[2]).unwrap();
+
+        // C -> B -> A -> C, written behind the cycle check's back
+        let mut cache = GraphCache::load(&db).unwrap();
+        cache.add_dependency(ids[2], ids[1]);
+        cache.add_dependency(ids[1], ids[0]);
+        cache.add_dependency(ids[0], ids[2]);
+
+        match cache.next_task(&db) {
+            Err(Error::CycleDetected {
+                from_id,
+                to_id,
+                cycle_path,
+            }) => {
+                assert_eq!(cycle_path, vec![ids[0], ids[2], ids[1], ids[0]]);
+                assert_eq!((from_id, to_id), (ids[1], ids[0]));
+            }
+            other => panic!("expected a cycle, got {:?}", other),
+        }
+    }
+}
@@ -78,8 +78,8 @@ pub fn detect_all_cycles(db: &Db) -> Result<Vec<Vec<i64>>> {
         let mut path: Vec<i64> = Vec::new();
 
         if dfs_detect_cycle(&adj, start, &mut visited, &mut rec_stack, &mut path) {
-            // Found a cycle starting from this node
-            if let Some(cycle_path) = extract_cycle_path(&path, start) {
+            // Found a cycle reachable from this node
+            if let Some(cycle_path) = extract_cycle_path(&path) {
                 if !cycles.contains(&cycle_path) {
                     cycles.push(cycle_path);
                 }
@@ -110,7 +110,7 @@ fn build_adjacency_list(db: &Db) -> Result<HashMap<i64, Vec<i64>>> {
 
     for row in rows {
         let (task_id, dep_id) = row?;
-        adj.entry(dep_id).or_insert_with(Vec::new).push(task_id);
+        adj.entry(dep_id).or_default().push(task_id);
     }
 
     Ok(adj)
@@ -147,16 +147,20 @@ fn dfs_detect_cycle(
     false
 }
 
-/// Extract the cycle path from a DFS path
-fn extract_cycle_path(path: &[i64], start: i64) -> Option<Vec<i64>> {
-    // Find the last occurrence of start in the path
-    if let Some(pos) = path.iter().rposition(|&x| x == start) {
-        let cycle: Vec<i64> = path[pos..].to_vec();
-        if cycle.len() > 1 {
-            return Some(cycle);
-        }
+/// Extract the cycle from a DFS path that ends by revisiting one of its nodes.
+/// The cycle is rotated to start at its smallest ID so each one is reported once.
+fn extract_cycle_path(path: &[i64]) -> Option<Vec<i64>> {
+    let &last = path.last()?;
+    let pos = path.iter().position(|&x| x == last)?;
+    let mut cycle = path[pos..path.len() - 1].to_vec();
+    if cycle.len() < 2 {
+        return None;
     }
-    None
+
+    let min_pos = (0..cycle.len()).min_by_key(|&i| cycle[i])?;
+    cycle.rotate_left(min_pos);
+    cycle.push(cycle[0]);
+    Some(cycle)
 }
 
 #[cfg(test)]
@@ -165,7 +169,42 @@ mod tests {
 
     #[test]
     fn test_no_cycle_simple() {
-        // This would need a full test setup with database
-        // For now, we'll test the logic separately
+        let db = Db::in_memory().unwrap();
+        for title in ["A", "B", "C"] {
+            db.conn
+                .execute(
+                    "INSERT INTO tasks (title, status) VALUES (?1, 'pending')",
+                    [title],
+                )
+                .unwrap();
+        }
+        db.conn
+            .execute("INSERT INTO dependencies VALUES (2, 1), (3, 2)", [])
+            .unwrap();
+
+        assert!(check_cycle(&db, 3, 1).is_ok());
+        assert!(matches!(
+            check_cycle(&db, 1, 3),
+            Err(Error::CycleDetected { cycle_path, .. }) if cycle_path == vec![3, 2, 1, 3]
+        ));
+    }
+
+    #[test]
+    fn test_detect_all_cycles() {
+        let db = Db::in_memory().unwrap();
+        for title in ["A", "B", "C", "D"] {
+            db.conn
+                .execute(
+                    "INSERT INTO tasks (title, status) VALUES (?1, 'pending')",
+                    [title],
+                )
+                .unwrap();
+        }
+        // 1 leads into the cycle 2 → 3 → 2 without being part of it
+        db.conn
+            .execute("INSERT INTO dependencies VALUES (2, 1), (3, 2), (2, 3)", [])
+            .unwrap();
+
+        assert_eq!(detect_all_cycles(&db).unwrap(), vec![vec![2, 3, 2]]);
     }
 }
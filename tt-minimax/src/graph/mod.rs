@@ -1,5 +1,7 @@
+pub mod cache;
 pub mod cycle;
 pub mod sort;
 
+pub use cache::*;
 pub use cycle::*;
 pub use sort::*;
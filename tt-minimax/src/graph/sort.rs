@@ -6,9 +6,9 @@ use std::collections::{BinaryHeap, HashMap, HashSet};
 /// Wrapper for BinaryHeap to make it a min-heap based on manual_order
 /// Note: f64 doesn't implement Eq, so we use ordered_float for comparison
 #[derive(Debug, Clone)]
-struct MinHeapItem {
-    task_id: i64,
-    manual_order: f64,
+pub(super) struct MinHeapItem {
+    pub(super) task_id: i64,
+    pub(super) manual_order: f64,
 }
 
 impl PartialEq for MinHeapItem {
@@ -257,7 +257,7 @@ pub fn get_next_task(db: &Db, target_id: i64) -> Result<Task> {
 }
 
 /// Get a task by ID
-fn get_task(db: &Db, id: i64) -> Result<Task> {
+pub(super) fn get_task(db: &Db, id: i64) -> Result<Task> {
     let mut stmt = db.conn.prepare(
         "SELECT id, title, description, dod, status, manual_order,
                 created_at, started_at, completed_at, last_touched_at
@@ -1,7 +1,9 @@
+pub mod cli;
 pub mod core;
 pub mod db;
 pub mod error;
 pub mod graph;
+pub mod mcp;
 
 pub use db::{Artifact, Db, Task, TaskStatus};
 pub use error::{Error, Result};
@@ -1,11 +1,5 @@
-mod cli;
-mod core;
-mod db;
-mod error;
-mod graph;
-mod mcp;
-
 use std::process::ExitCode;
+use tt::{cli, mcp};
 
 #[tokio::main]
 async fn main() -> ExitCode {
@@ -1,31 +1,59 @@
 use crate::core::*;
 use crate::db::Db;
 use crate::error::{Error, Result};
+use crate::graph::GraphCache;
+use rmcp::{
+    handler::server::{router::tool::ToolRouter, wrapper::Parameters},
+    model::*,
+    schemars, tool, tool_router,
+    transport::stdio,
+    ErrorData as McpError, ServiceExt,
+};
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 
 /// Start the MCP server over stdio
 pub async fn run_mcp() -> Result<()> {
-    // TODO: Implement full MCP server using rmcp
-    // For now, just return an error indicating it's not yet implemented
-    Err(Error::Io(std::io::Error::new(
-        std::io::ErrorKind::Unsupported,
-        "MCP server not yet implemented. The rmcp crate requires async runtime setup.",
-    )))
+    let db_path = PathBuf::from("tt.db");
+    if !db_path.exists() {
+        return Err(Error::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "Not initialized. Run `tt init` first.",
+        )));
+    }
+
+    let server = McpServer::new(Db::open(&db_path)?)?;
+    let service = TtMcp::new(server)
+        .serve(stdio())
+        .await
+        .map_err(|e| Error::Io(std::io::Error::other(e)))?;
+    service
+        .waiting()
+        .await
+        .map_err(|e| Error::Io(std::io::Error::other(e)))?;
+    Ok(())
 }
 
 /// MCP tool definitions and handlers
-/// This is a placeholder for future implementation
+///
+/// Owns the database connection and a [`GraphCache`] for the lifetime of the
+/// `tt mcp` process.
 pub struct McpServer {
     db: Db,
+    /// Kept in step with every mutation below
+    graph: GraphCache,
 }
 
 impl McpServer {
-    pub fn new(db: Db) -> Self {
-        Self { db }
+    pub fn new(db: Db) -> Result<Self> {
+        let graph = GraphCache::load(&db)?;
+        Ok(Self { db, graph })
     }
 
     /// Get the next task to work on
-    pub fn get_next_task(&self) -> Result<McpResponse> {
-        match get_next(&self.db, None) {
+    pub fn get_next_task(&mut self) -> Result<McpResponse> {
+        match self.graph.next_task(&self.db) {
             Ok(task) => Ok(McpResponse::Task(task.into())),
             Err(Error::TargetReached { id, title }) => Ok(McpResponse::TargetReached { id, title }),
             Err(Error::AllBlocked { blocked_ids }) => Ok(McpResponse::AllBlocked { blocked_ids }),
@@ -46,18 +74,21 @@ impl McpServer {
     /// Start a task
     pub fn start_task(&mut self, id: i64) -> Result<McpResponse> {
         let task = workflow::start_task(&mut self.db, id)?;
+        self.graph.update_task(&task);
         Ok(McpResponse::Task(task.into()))
     }
 
     /// Complete the current task
     pub fn complete_task(&mut self) -> Result<McpResponse> {
         let task = workflow::complete_task(&mut self.db)?;
+        self.graph.update_task(&task);
         Ok(McpResponse::Task(task.into()))
     }
 
     /// Stop the current task
     pub fn stop_task(&mut self) -> Result<McpResponse> {
         let task = workflow::stop_task(&mut self.db)?;
+        self.graph.update_task(&task);
         Ok(McpResponse::Task(task.into()))
     }
 
@@ -71,6 +102,7 @@ impl McpServer {
         before_id: Option<i64>,
     ) -> Result<McpResponse> {
         let task = add_task(&mut self.db, title, description, dod, after_id, before_id)?;
+        self.graph.update_task(&task);
         Ok(McpResponse::Task(task.into()))
     }
 
@@ -103,6 +135,7 @@ impl McpServer {
     /// Add a dependency
     pub fn add_dependency(&mut self, task_id: i64, depends_on: i64) -> Result<McpResponse> {
         workflow::add_dependency(&mut self.db, task_id, depends_on)?;
+        self.graph.add_dependency(task_id, depends_on);
         Ok(McpResponse::Success {
             message: format!("Task #{} now depends on #{}", task_id, depends_on),
         })
@@ -111,6 +144,7 @@ impl McpServer {
     /// Remove a dependency
     pub fn remove_dependency(&mut self, task_id: i64, depends_on: i64) -> Result<McpResponse> {
         workflow::remove_dependency(&mut self.db, task_id, depends_on)?;
+        self.graph.remove_dependency(task_id, depends_on);
         Ok(McpResponse::Success {
             message: format!("Task #{} no longer depends on #{}", task_id, depends_on),
         })
@@ -119,12 +153,14 @@ impl McpServer {
     /// Block a task
     pub fn block_task(&mut self, id: i64) -> Result<McpResponse> {
         let task = workflow::block_task(&mut self.db, id)?;
+        self.graph.update_task(&task);
         Ok(McpResponse::Task(task.into()))
     }
 
     /// Unblock a task
     pub fn unblock_task(&mut self, id: i64) -> Result<McpResponse> {
         let task = workflow::unblock_task(&mut self.db, id)?;
+        self.graph.update_task(&task);
         Ok(McpResponse::Task(task.into()))
     }
 
@@ -145,6 +181,7 @@ impl McpServer {
     /// Set target
     pub fn set_target(&mut self, id: i64) -> Result<McpResponse> {
         target::set_target(&mut self.db, id)?;
+        self.graph.set_target(id);
         Ok(McpResponse::Success {
             message: format!("Set target to #{}", id),
         })
@@ -158,10 +195,293 @@ impl McpServer {
         before_id: Option<i64>,
     ) -> Result<McpResponse> {
         let new_order = reorder_task(&mut self.db, id, after_id, before_id)?;
+        self.graph.set_order(id, new_order);
         Ok(McpResponse::Reorder { id, new_order })
     }
 }
 
+/// rmcp handler exposing [`McpServer`] as MCP tools
+#[derive(Clone)]
+pub struct TtMcp {
+    server: Arc<Mutex<McpServer>>,
+    tool_router: ToolRouter<Self>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct IdInput {
+    pub id: i64,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct CreateTaskInput {
+    pub title: String,
+    pub description: Option<String>,
+    pub dod: Option<String>,
+    pub after_id: Option<i64>,
+    pub before_id: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct EditTaskInput {
+    pub id: i64,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub dod: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct DependencyInput {
+    pub task_id: i64,
+    pub depends_on: i64,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ListTasksInput {
+    /// List every task instead of only the target's subgraph
+    #[serde(default)]
+    pub all: bool,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct LogArtifactInput {
+    pub name: String,
+    pub file_path: String,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct GetArtifactsInput {
+    /// Defaults to the active task
+    pub task_id: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ReorderTaskInput {
+    pub id: i64,
+    pub after_id: Option<i64>,
+    pub before_id: Option<i64>,
+}
+
+/// Wrap a result in the `{ "status": ... }` envelope from the spec
+fn respond(result: Result<McpResponse>) -> std::result::Result<CallToolResult, McpError> {
+    let (content, is_error) = match result {
+        Ok(data) => (serde_json::json!({ "status": "ok", "data": data }), false),
+        Err(e) => (
+            serde_json::json!({
+                "status": "error",
+                "error_code": e.error_code(),
+                "message": e.to_string(),
+            }),
+            true,
+        ),
+    };
+    Ok(if is_error {
+        CallToolResult::structured_error(content)
+    } else {
+        CallToolResult::structured(content)
+    })
+}
+
+#[tool_router]
+impl TtMcp {
+    pub fn new(server: McpServer) -> Self {
+        Self {
+            server: Arc::new(Mutex::new(server)),
+            tool_router: Self::tool_router(),
+        }
+    }
+
+    /// Run `f` against the server and wrap its result
+    fn with_server(
+        &self,
+        f: impl FnOnce(&mut McpServer) -> Result<McpResponse>,
+    ) -> std::result::Result<CallToolResult, McpError> {
+        let mut server = self.server.lock().expect("MCP server lock poisoned");
+        respond(f(&mut server))
+    }
+
+    #[tool(
+        description = "Returns the next task to work on toward the current target. Call this after completing a task. If the response is TargetReached, stop working and report to the user."
+    )]
+    fn get_next_task(&self) -> std::result::Result<CallToolResult, McpError> {
+        self.with_server(|s| s.get_next_task())
+    }
+
+    #[tool(
+        description = "Returns the task you are working on and its artifacts. Call this when resuming work to see where you left off."
+    )]
+    fn get_current_task(&self) -> std::result::Result<CallToolResult, McpError> {
+        self.with_server(|s| s.get_current_task())
+    }
+
+    #[tool(
+        description = "Starts working on a task. Only one task can be in progress; if AnotherTaskActive is returned, complete or stop that task first."
+    )]
+    fn start_task(
+        &self,
+        Parameters(input): Parameters<IdInput>,
+    ) -> std::result::Result<CallToolResult, McpError> {
+        self.with_server(|s| s.start_task(input.id))
+    }
+
+    #[tool(
+        description = "Marks the task in progress as done. Only call this once its definition of done is met, then call get_next_task."
+    )]
+    fn complete_task(&self) -> std::result::Result<CallToolResult, McpError> {
+        self.with_server(|s| s.complete_task())
+    }
+
+    #[tool(
+        description = "Stops working on the task in progress without completing it. Use this before switching to another task."
+    )]
+    fn stop_task(&self) -> std::result::Result<CallToolResult, McpError> {
+        self.with_server(|s| s.stop_task())
+    }
+
+    #[tool(
+        description = "Creates a new task. If you discover during implementation that a task needs to be broken into smaller pieces, create subtasks and add dependencies."
+    )]
+    fn create_task(
+        &self,
+        Parameters(input): Parameters<CreateTaskInput>,
+    ) -> std::result::Result<CallToolResult, McpError> {
+        self.with_server(|s| {
+            s.create_task(
+                input.title,
+                input.description,
+                input.dod,
+                input.after_id,
+                input.before_id,
+            )
+        })
+    }
+
+    #[tool(
+        description = "Changes a task's title, description or definition of done. Omitted fields are left as they are."
+    )]
+    fn edit_task(
+        &self,
+        Parameters(input): Parameters<EditTaskInput>,
+    ) -> std::result::Result<CallToolResult, McpError> {
+        self.with_server(|s| s.edit_task(input.id, input.title, input.description, input.dod))
+    }
+
+    #[tool(
+        description = "Records that task_id cannot start until depends_on is completed. Fails with CycleDetected if that would create a cycle."
+    )]
+    fn add_dependency(
+        &self,
+        Parameters(input): Parameters<DependencyInput>,
+    ) -> std::result::Result<CallToolResult, McpError> {
+        self.with_server(|s| s.add_dependency(input.task_id, input.depends_on))
+    }
+
+    #[tool(description = "Removes a dependency added with add_dependency.")]
+    fn remove_dependency(
+        &self,
+        Parameters(input): Parameters<DependencyInput>,
+    ) -> std::result::Result<CallToolResult, McpError> {
+        self.with_server(|s| s.remove_dependency(input.task_id, input.depends_on))
+    }
+
+    #[tool(
+        description = "Marks a task as blocked by something outside the task graph. Blocked tasks are skipped by get_next_task until unblocked."
+    )]
+    fn block_task(
+        &self,
+        Parameters(input): Parameters<IdInput>,
+    ) -> std::result::Result<CallToolResult, McpError> {
+        self.with_server(|s| s.block_task(input.id))
+    }
+
+    #[tool(description = "Marks a blocked task as ready to work on again.")]
+    fn unblock_task(
+        &self,
+        Parameters(input): Parameters<IdInput>,
+    ) -> std::result::Result<CallToolResult, McpError> {
+        self.with_server(|s| s.unblock_task(input.id))
+    }
+
+    #[tool(
+        description = "Lists the tasks toward the current target in the order they will be worked on. Pass all to list every task."
+    )]
+    fn list_tasks(
+        &self,
+        Parameters(input): Parameters<ListTasksInput>,
+    ) -> std::result::Result<CallToolResult, McpError> {
+        self.with_server(|s| s.list_tasks(input.all))
+    }
+
+    #[tool(
+        description = "Returns a task with its dependencies, dependents and artifacts. Use this to read a task's details before starting it."
+    )]
+    fn show_task(
+        &self,
+        Parameters(input): Parameters<IdInput>,
+    ) -> std::result::Result<CallToolResult, McpError> {
+        self.with_server(|s| s.show_task(input.id))
+    }
+
+    #[tool(
+        description = "Records a file you have created as an artifact of the current task. Create the file first, then call this. Use descriptive names like 'research', 'plan', 'implementation-notes', 'test-report'."
+    )]
+    fn log_artifact(
+        &self,
+        Parameters(input): Parameters<LogArtifactInput>,
+    ) -> std::result::Result<CallToolResult, McpError> {
+        self.with_server(|s| s.log_artifact(input.name, input.file_path))
+    }
+
+    #[tool(
+        description = "Lists the artifacts recorded for a task, or for the current task if task_id is omitted. Read these to pick up earlier research and plans."
+    )]
+    fn get_artifacts(
+        &self,
+        Parameters(input): Parameters<GetArtifactsInput>,
+    ) -> std::result::Result<CallToolResult, McpError> {
+        self.with_server(|s| s.get_artifacts(input.task_id))
+    }
+
+    #[tool(
+        description = "Sets the task that get_next_task works toward. Only do this when the user asks for a new goal."
+    )]
+    fn set_target(
+        &self,
+        Parameters(input): Parameters<IdInput>,
+    ) -> std::result::Result<CallToolResult, McpError> {
+        self.with_server(|s| s.set_target(input.id))
+    }
+
+    #[tool(
+        description = "Moves a task after or before another task in the work order. Dependencies still take precedence over this order."
+    )]
+    fn reorder_task(
+        &self,
+        Parameters(input): Parameters<ReorderTaskInput>,
+    ) -> std::result::Result<CallToolResult, McpError> {
+        self.with_server(|s| s.reorder_task(input.id, input.after_id, input.before_id))
+    }
+}
+
+/// Kept apart from `crate::error::Result`, which `#[tool_handler]`'s
+/// generated code would otherwise pick up
+mod handler {
+    use super::TtMcp;
+    use rmcp::{model::*, tool_handler, ServerHandler};
+
+    #[tool_handler]
+    impl ServerHandler for TtMcp {
+        fn get_info(&self) -> ServerInfo {
+            ServerInfo {
+                instructions: Some(
+                    "DAG-based task tracker. Call get_next_task, start_task it, do the work, log artifacts with log_artifact, then complete_task and repeat.".to_string(),
+                ),
+                capabilities: ServerCapabilities::builder().enable_tools().build(),
+                ..Default::default()
+            }
+        }
+    }
+}
+
 /// MCP response types
 #[derive(Debug, Clone, serde::Serialize)]
 #[serde(untagged)]
@@ -288,3 +608,327 @@ impl From<crate::db::Artifact> for McpArtifact {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn next_id(server: &mut McpServer) -> i64 {
+        match server.get_next_task().unwrap() {
+            McpResponse::Task(task) => task.id,
+            other => panic!("unexpected response: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_next_follows_server_mutations() {
+        let mut server = McpServer::new(Db::in_memory().unwrap()).unwrap();
+        for title in ["Schema", "API", "Docs"] {
+            server
+                .create_task(
+                    title.to_string(),
+                    None,
+                    Some("Done".to_string()),
+                    None,
+                    None,
+                )
+                .unwrap();
+        }
+        server.set_target(2).unwrap();
+        assert_eq!(next_id(&mut server), 2);
+
+        server.add_dependency(2, 1).unwrap();
+        assert_eq!(next_id(&mut server), 1);
+
+        server.start_task(1).unwrap();
+        server.complete_task().unwrap();
+        assert_eq!(next_id(&mut server), 2);
+
+        server.add_dependency(2, 3).unwrap();
+        server.block_task(3).unwrap();
+        assert!(matches!(
+            server.get_next_task().unwrap(),
+            McpResponse::AllBlocked { blocked_ids } if blocked_ids == vec![3]
+        ));
+
+        server.unblock_task(3).unwrap();
+        server.remove_dependency(2, 3).unwrap();
+        assert_eq!(next_id(&mut server), 2);
+    }
+
+    #[test]
+    fn test_tool_results_use_status_envelope() {
+        let mcp = TtMcp::new(McpServer::new(Db::in_memory().unwrap()).unwrap());
+
+        let created = mcp
+            .create_task(Parameters(CreateTaskInput {
+                title: "Schema".to_string(),
+                description: None,
+                dod: None,
+                after_id: None,
+                before_id: None,
+            }))
+            .unwrap();
+        let content = created.structured_content.unwrap();
+        assert_eq!(content["status"], "ok");
+        assert_eq!(content["data"]["title"], "Schema");
+
+        let failed = mcp.get_next_task().unwrap();
+        assert_eq!(failed.is_error, Some(true));
+        let content = failed.structured_content.unwrap();
+        assert_eq!(content["status"], "error");
+        assert_eq!(content["error_code"], "NoTarget");
+    }
+
+    fn setup_mcp() -> TtMcp {
+        TtMcp::new(McpServer::new(Db::in_memory().unwrap()).unwrap())
+    }
+
+    /// Deserialize tool arguments the way rmcp does for a `tools/call`
+    fn args<T: serde::de::DeserializeOwned>(value: serde_json::Value) -> Parameters<T> {
+        Parameters(serde_json::from_value(value).unwrap())
+    }
+
+    fn data(result: CallToolResult) -> serde_json::Value {
+        let content = result.structured_content.unwrap();
+        assert_eq!(content["status"], "ok", "{}", content);
+        content["data"].clone()
+    }
+
+    fn error_code(result: CallToolResult) -> String {
+        assert_eq!(result.is_error, Some(true));
+        let content = result.structured_content.unwrap();
+        assert_eq!(content["status"], "error");
+        content["error_code"].as_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_router_lists_every_tool() {
+        let mcp = setup_mcp();
+        let mut names: Vec<String> = mcp
+            .tool_router
+            .list_all()
+            .into_iter()
+            .map(|tool| tool.name.to_string())
+            .collect();
+        names.sort();
+        assert_eq!(
+            names,
+            [
+                "add_dependency",
+                "block_task",
+                "complete_task",
+                "create_task",
+                "edit_task",
+                "get_artifacts",
+                "get_current_task",
+                "get_next_task",
+                "list_tasks",
+                "log_artifact",
+                "remove_dependency",
+                "reorder_task",
+                "set_target",
+                "show_task",
+                "start_task",
+                "stop_task",
+                "unblock_task",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tool_arguments_map_onto_server_calls() {
+        let mcp = setup_mcp();
+        let schema = data(
+            mcp.create_task(args(serde_json::json!({ "title": "Schema" })))
+                .unwrap(),
+        );
+        assert_eq!(schema["id"], 1);
+        assert!(schema.get("dod").is_none());
+        let api = data(
+            mcp.create_task(args(serde_json::json!({
+                "title": "API",
+                "description": "REST endpoints",
+                "dod": "Endpoints respond",
+                "before_id": 1,
+            })))
+            .unwrap(),
+        );
+        assert_eq!(api["description"], "REST endpoints");
+        assert_eq!(api["dod"], "Endpoints respond");
+        assert!(api["manual_order"].as_f64() < schema["manual_order"].as_f64());
+
+        // Omitted fields are left alone
+        let edited = data(
+            mcp.edit_task(args(serde_json::json!({ "id": 1, "dod": "Tables exist" })))
+                .unwrap(),
+        );
+        assert_eq!(edited["title"], "Schema");
+        assert_eq!(edited["dod"], "Tables exist");
+
+        // task_id waits on depends_on, not the other way round
+        data(
+            mcp.add_dependency(args(serde_json::json!({ "task_id": 2, "depends_on": 1 })))
+                .unwrap(),
+        );
+        let detail = data(mcp.show_task(args(serde_json::json!({ "id": 2 }))).unwrap());
+        assert_eq!(detail["dependencies"][0]["id"], 1);
+        assert_eq!(detail["dependents"], serde_json::json!([]));
+
+        let reordered = data(
+            mcp.reorder_task(args(serde_json::json!({ "id": 1, "before_id": 2 })))
+                .unwrap(),
+        );
+        assert_eq!(reordered["id"], 1);
+        assert!(reordered["new_order"].as_f64() < api["manual_order"].as_f64());
+
+        // `all` defaults to false, which needs a target
+        assert_eq!(
+            error_code(mcp.list_tasks(args(serde_json::json!({}))).unwrap()),
+            "NoTarget"
+        );
+        let all = data(
+            mcp.list_tasks(args(serde_json::json!({ "all": true })))
+                .unwrap(),
+        );
+        assert_eq!(all.as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_tool_errors_carry_error_codes() {
+        let mcp = setup_mcp();
+        for title in ["Schema", "API"] {
+            data(
+                mcp.create_task(args(serde_json::json!({ "title": title })))
+                    .unwrap(),
+            );
+        }
+        data(
+            mcp.add_dependency(args(serde_json::json!({ "task_id": 2, "depends_on": 1 })))
+                .unwrap(),
+        );
+
+        let cases = [
+            (
+                mcp.set_target(args(serde_json::json!({ "id": 9 }))),
+                "TaskNotFound",
+            ),
+            (mcp.get_current_task(), "NoActiveTask"),
+            (mcp.complete_task(), "NoActiveTask"),
+            (mcp.stop_task(), "NoActiveTask"),
+            (
+                mcp.start_task(args(serde_json::json!({ "id": 2 }))),
+                "UnmetDependencies",
+            ),
+            (
+                mcp.add_dependency(args(serde_json::json!({ "task_id": 1, "depends_on": 2 }))),
+                "CycleDetected",
+            ),
+            (
+                mcp.unblock_task(args(serde_json::json!({ "id": 1 }))),
+                "InvalidTaskStatus",
+            ),
+        ];
+        for (result, expected) in cases {
+            assert_eq!(error_code(result.unwrap()), expected);
+        }
+
+        data(
+            mcp.start_task(args(serde_json::json!({ "id": 1 })))
+                .unwrap(),
+        );
+        assert_eq!(error_code(mcp.complete_task().unwrap()), "NoDod");
+        data(
+            mcp.create_task(args(serde_json::json!({ "title": "Docs" })))
+                .unwrap(),
+        );
+        assert_eq!(
+            error_code(
+                mcp.start_task(args(serde_json::json!({ "id": 3 })))
+                    .unwrap()
+            ),
+            "AnotherTaskActive"
+        );
+    }
+
+    #[test]
+    fn test_mutation_tools_drive_the_workflow() {
+        let mcp = setup_mcp();
+        for title in ["Schema", "API", "Docs"] {
+            data(
+                mcp.create_task(args(serde_json::json!({ "title": title, "dod": "Done" })))
+                    .unwrap(),
+            );
+        }
+        data(
+            mcp.add_dependency(args(serde_json::json!({ "task_id": 2, "depends_on": 1 })))
+                .unwrap(),
+        );
+        data(
+            mcp.set_target(args(serde_json::json!({ "id": 2 })))
+                .unwrap(),
+        );
+        assert_eq!(data(mcp.get_next_task().unwrap())["id"], 1);
+
+        let started = data(
+            mcp.start_task(args(serde_json::json!({ "id": 1 })))
+                .unwrap(),
+        );
+        assert_eq!(started["status"], "in_progress");
+        let artifact = data(
+            mcp.log_artifact(args(serde_json::json!({
+                "name": "plan",
+                "file_path": "notes/plan.md",
+            })))
+            .unwrap(),
+        );
+        assert_eq!(artifact["task_id"], 1);
+        let current = data(mcp.get_current_task().unwrap());
+        assert_eq!(current["task"]["id"], 1);
+        assert_eq!(current["artifacts"][0]["name"], "plan");
+
+        let stopped = data(mcp.stop_task().unwrap());
+        assert_eq!(stopped["status"], "pending");
+        data(
+            mcp.start_task(args(serde_json::json!({ "id": 1 })))
+                .unwrap(),
+        );
+        let completed = data(mcp.complete_task().unwrap());
+        assert_eq!(completed["status"], "completed");
+        let artifacts = data(
+            mcp.get_artifacts(args(serde_json::json!({ "task_id": 1 })))
+                .unwrap(),
+        );
+        assert_eq!(artifacts[0]["file_path"], "notes/plan.md");
+
+        let blocked = data(
+            mcp.block_task(args(serde_json::json!({ "id": 2 })))
+                .unwrap(),
+        );
+        assert_eq!(blocked["status"], "blocked");
+        assert_eq!(
+            data(mcp.get_next_task().unwrap())["blocked_ids"],
+            serde_json::json!([2])
+        );
+        data(
+            mcp.unblock_task(args(serde_json::json!({ "id": 2 })))
+                .unwrap(),
+        );
+
+        data(
+            mcp.remove_dependency(args(serde_json::json!({ "task_id": 2, "depends_on": 1 })))
+                .unwrap(),
+        );
+        let detail = data(mcp.show_task(args(serde_json::json!({ "id": 2 }))).unwrap());
+        assert_eq!(detail["dependencies"], serde_json::json!([]));
+
+        data(
+            mcp.start_task(args(serde_json::json!({ "id": 2 })))
+                .unwrap(),
+        );
+        data(mcp.complete_task().unwrap());
+        let reached = data(mcp.get_next_task().unwrap());
+        assert_eq!(reached["id"], 2);
+        assert_eq!(reached["title"], "API");
+    }
+}